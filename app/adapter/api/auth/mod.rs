@@ -22,7 +22,7 @@ use crate::{
     WebState,
     shared::{
         error::WebError,
-        extractor::inject::Inject,
+        extractor::{client::Client, inject::Inject},
         middleware::rate_limit_ext::RateLimitRouterExt as _,
         response::{JsonResponse, JsonResponseType},
     },
@@ -42,10 +42,11 @@ const CAPTCHA_CONTENT_TYPE: HeaderValue = HeaderValue::from_static("image/png");
 )]
 #[tracing::instrument]
 async fn sign_in(
+    Client(client): Client,
     Inject(command_handler): Inject<SignInCommandHandler>,
     Json(command): Json<SignInCommand>,
 ) -> JsonResponseType<response::SignInResponse> {
-    let output = command_handler.handle(command).await?;
+    let output = command_handler.handle(command.with_client(client)).await?;
     JsonResponse::ok(response::SignInResponse {
         access_token: output.access_token,
         refresh_token: output.refresh_token,
@@ -63,10 +64,11 @@ async fn sign_in(
 )]
 #[tracing::instrument]
 async fn refresh_token(
+    Client(client): Client,
     Inject(command_handler): Inject<RefreshTokenCommandHandler>,
     Json(command): Json<RefreshTokenCommand>,
 ) -> JsonResponseType<response::SignInResponse> {
-    let output = command_handler.handle(command).await?;
+    let output = command_handler.handle(command.with_client(client)).await?;
     JsonResponse::ok(response::SignInResponse {
         access_token: output.access_token,
         refresh_token: output.refresh_token,
@@ -20,6 +20,7 @@ mod option;
 mod organization;
 mod profile;
 mod system;
+mod task;
 mod upload;
 
 pub fn routing(state: WebState) -> OpenApiRouter<WebState> {
@@ -27,7 +28,8 @@ pub fn routing(state: WebState) -> OpenApiRouter<WebState> {
         .nest("/profile", profile::routing())
         .nest("/organization", organization::routing())
         .nest("/system", system::routing())
-        .nest("/uploads", upload::routing())
+        .nest("/uploads", upload::routing(&state))
+        .nest("/tasks", task::routing())
         .nest("/options", option::routing())
         .layer(axum::middleware::from_fn_with_state(
             state.clone(),
@@ -9,17 +9,32 @@ use application::{
         },
         query::retrieve_user::{RetrieveUserQuery, RetrieveUserQueryHandler},
     },
-    shared::{command_handler::CommandHandler, query_handler::QueryHandler as _},
+    shared::{
+        command_handler::CommandHandler, paging_result::PagingResult,
+        query_handler::QueryHandler as _,
+    },
+    system::{
+        dto::security_event::SecurityEventDto,
+        query::search_security_events::{
+            SearchSecurityEventsQuery, SearchSecurityEventsQueryHandler,
+        },
+        service::upload_service::UploadService,
+    },
 };
 
-use axum::Json;
+use axum::{Json, extract::Query};
 use utoipa_axum::{router::OpenApiRouter, routes};
 
 use crate::{
     WebState,
     shared::{
-        extractor::{accept_language::AcceptLanguage, inject::Inject, valid_user::ValidUser},
-        response::{JsonResponse, JsonResponseEmpty, JsonResponseType},
+        extractor::{
+            accept_language::AcceptLanguage, client::Client, inject::Inject, valid_user::ValidUser,
+        },
+        response::{
+            JsonResponse, JsonResponseEmpty, JsonResponsePagingType, JsonResponseType,
+            PagingResponse,
+        },
         translation::tranlate_menus,
     },
 };
@@ -36,9 +51,10 @@ use crate::{
 #[tracing::instrument]
 async fn sign_out(
     ValidUser(id): ValidUser,
+    Client(client): Client,
     Inject(command_handler): Inject<SignOutCommandHandler>,
 ) -> JsonResponseType<()> {
-    let command = SignOutCommand::builder().id(id).build();
+    let command = SignOutCommand::builder().id(id).client(client).build();
     command_handler.handle(command).await?;
     JsonResponse::ok(())
 }
@@ -58,11 +74,17 @@ async fn current(
     ValidUser(id): ValidUser,
     Inject(service): Inject<AuthService>,
     Inject(query_handler): Inject<RetrieveUserQueryHandler>,
+    Inject(upload_service): Inject<UploadService>,
 ) -> JsonResponseType<response::CurrentResponse> {
-    let (mut user, pages, permissions) = tokio::try_join!(
-        query_handler.query(RetrieveUserQuery::builder().id(id.clone()).build()),
+    let (mut user, pages, permissions, storage_usage) = tokio::try_join!(
+        async {
+            Ok(query_handler
+                .query(RetrieveUserQuery::builder().id(id.clone()).build())
+                .await?)
+        },
         async { Ok(service.get_available_pages(&id).await) },
-        async { Ok(service.get_available_permissions(&id).await) }
+        async { Ok(service.get_available_permissions(&id).await) },
+        upload_service.usage(&id)
     )?;
     service
         .replenish_user_portrait(std::slice::from_mut(&mut user))
@@ -73,6 +95,7 @@ async fn current(
         pages: tranlate_menus(pages.to_vec(), lang_id),
         permissions,
         lang_id: lang_id.to_string(),
+        storage_usage,
     })
 }
 
@@ -117,6 +140,26 @@ async fn language(language: AcceptLanguage) -> JsonResponseType<response::Curren
     })
 }
 
+#[utoipa::path(
+    get,
+    params(SearchSecurityEventsQuery),
+    path = "/activities",
+    summary = "Current user login activities",
+    tag = "Profile",
+    responses(
+        (status = 200, body = inline(JsonResponse<PagingResponse<SecurityEventDto>>))
+    )
+)]
+#[tracing::instrument]
+async fn activities(
+    ValidUser(id): ValidUser,
+    Inject(query_handler): Inject<SearchSecurityEventsQueryHandler>,
+    Query(query): Query<SearchSecurityEventsQuery>,
+) -> JsonResponsePagingType<SecurityEventDto> {
+    let PagingResult { total, items } = query_handler.query(query.with_user_id(&id)).await?;
+    JsonResponse::ok(PagingResponse { total, items })
+}
+
 mod request {
     use serde::Deserialize;
     use utoipa::ToSchema;
@@ -130,7 +173,9 @@ mod request {
 }
 
 mod response {
-    use application::organization::dto::user::UserDto;
+    use application::{
+        organization::dto::user::UserDto, system::service::upload_service::StorageUsage,
+    };
     use domain::auth::value_object::permission::Permission;
     use serde::Serialize;
     use utoipa::ToSchema;
@@ -143,6 +188,7 @@ mod response {
         pub pages: Vec<TranslatedMenuTree>,
         pub permissions: Vec<&'static Permission>,
         pub lang_id: String,
+        pub storage_usage: StorageUsage,
     }
 
     #[derive(Serialize, ToSchema)]
@@ -156,4 +202,5 @@ pub fn routing() -> OpenApiRouter<WebState> {
         .routes(routes!(sign_out))
         .routes(routes!(password))
         .routes(routes!(language))
+        .routes(routes!(activities))
 }
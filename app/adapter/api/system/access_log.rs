@@ -1,14 +1,21 @@
 use application::{
-    shared::{paging_result::PagingResult, query_handler::QueryHandler as _},
+    shared::{
+        command_handler::CommandHandler as _, paging_result::PagingResult,
+        query_handler::QueryHandler as _,
+    },
     system::{
-        dto::access_log::AccessLogDto,
+        command::export_access_logs::{ExportAccessLogsCommand, ExportAccessLogsCommandHandler},
+        dto::access_log::{AccessLogDetailDto, AccessLogDto},
         query::{
             retrieve_access_log::{RetrieveAccessLogQuery, RetrieveAccessLogQueryHandler},
             search_access_logs::{SearchAccessLogsQuery, SearchAccessLogsQueryHandler},
         },
     },
 };
-use axum::extract::{Path, Query};
+use axum::{
+    Json,
+    extract::{Path, Query},
+};
 use domain::auth::value_object::permission::SYSTEM_ACCESS_LOG_READ;
 use domain::system::value_object::access_log_id::AccessLogId;
 use utoipa_axum::{router::OpenApiRouter, routes};
@@ -16,7 +23,7 @@ use utoipa_axum::{router::OpenApiRouter, routes};
 use crate::{
     WebState, perms,
     shared::{
-        extractor::inject::Inject,
+        extractor::{inject::Inject, valid_user::ValidUser},
         middleware::perm_router_ext::PermissonRouteExt as _,
         response::{JsonResponse, JsonResponsePagingType, JsonResponseType, PagingResponse},
     },
@@ -47,22 +54,44 @@ async fn search(
     summary = "Retrieve access_log",
     tag = "System",
     responses(
-        (status = 200, body = inline(JsonResponse<AccessLogDto>))
+        (status = 200, body = inline(JsonResponse<AccessLogDetailDto>))
     )
 )]
 #[tracing::instrument]
 async fn retrieve(
     Inject(query_handler): Inject<RetrieveAccessLogQueryHandler>,
     Path(id): Path<AccessLogId>,
-) -> JsonResponseType<AccessLogDto> {
+) -> JsonResponseType<AccessLogDetailDto> {
     let access_log = query_handler
         .query(RetrieveAccessLogQuery::builder().id(id).build())
         .await?;
     JsonResponse::ok(access_log)
 }
 
+#[utoipa::path(
+    post,
+    path = "/export",
+    summary = "Export access_logs to CSV in the background",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponse<String>))
+    )
+)]
+#[tracing::instrument]
+async fn export(
+    ValidUser(id): ValidUser,
+    Inject(command_handler): Inject<ExportAccessLogsCommandHandler>,
+    Json(command): Json<ExportAccessLogsCommand>,
+) -> JsonResponseType<String> {
+    let task_id = command_handler
+        .handle(command.with_requested_by(id))
+        .await?;
+    JsonResponse::ok(task_id)
+}
+
 pub fn routing() -> OpenApiRouter<WebState> {
     OpenApiRouter::new()
         .routes(routes!(search).permit_all(perms!(SYSTEM_ACCESS_LOG_READ)))
         .routes(routes!(retrieve).permit_all(perms!(SYSTEM_ACCESS_LOG_READ)))
+        .routes(routes!(export).permit_all(perms!(SYSTEM_ACCESS_LOG_READ)))
 }
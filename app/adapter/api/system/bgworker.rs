@@ -1,8 +1,12 @@
 use std::str::FromStr as _;
 
-use application::{re_export::State, shared::bgworker_impl::WorkerRegistry};
-use axum::extract::Path;
-use domain::auth::value_object::permission::SYSTEM_BGWORKER_READ;
+use application::{
+    error::ApplicationError, re_export::State, shared::bgworker_impl::WorkerRegistry,
+};
+use axum::{Json, extract::Path};
+use domain::auth::value_object::permission::{
+    SYSTEM_BGWORKER_DELETE, SYSTEM_BGWORKER_READ, SYSTEM_BGWORKER_RETRY, SYSTEM_BGWORKER_UPDATE,
+};
 use futures_util::{StreamExt as _, stream};
 use utoipa_axum::{router::OpenApiRouter, routes};
 
@@ -10,7 +14,10 @@ use crate::{
     WebState, perms,
     shared::{
         middleware::perm_router_ext::PermissonRouteExt as _,
-        response::{JsonResponse, JsonResponseType},
+        response::{
+            JsonResponse, JsonResponseEmpty, JsonResponsePagingType, JsonResponseType,
+            PagingResponse,
+        },
     },
 };
 
@@ -84,32 +91,184 @@ async fn stat(Path(ns): Path<String>) -> JsonResponseType<response::Stat> {
     summary = "List bgworker jobs by namespace",
     tag = "System",
     responses(
-        (status = 200, body = inline(JsonResponse<response::JobsResponse>))
+        (status = 200, body = inline(JsonResponse<PagingResponse<response::Job>>))
     )
 )]
 #[tracing::instrument]
 async fn jobs(
-    Path((ns, state, page)): Path<(String, String, i32)>,
-) -> JsonResponseType<response::JobsResponse> {
+    Path((ns, state, page)): Path<(String, String, i64)>,
+) -> JsonResponsePagingType<response::Job> {
     let Ok(state) = State::from_str(&state) else {
         return JsonResponse::err("invalid state");
     };
-    let items = WorkerRegistry::list_jobs(&ns, &state, page).await;
-    let items = items
-        .into_iter()
-        .map(|item| response::Job {
-            args: item.args,
-            parts: item.parts,
-        })
-        .collect::<Vec<_>>();
-    let len = items.len();
-    JsonResponse::ok(response::JobsResponse {
-        items,
-        has_next: len == 10,
+    let (total, items) = WorkerRegistry::list_jobs(&ns, &state, page)
+        .await
+        .map_err(ApplicationError::from)?;
+    let items = items.into_iter().map(Into::into).collect();
+    JsonResponse::ok(PagingResponse { total, items })
+}
+
+#[utoipa::path(
+    get,
+    path = "/{ns}/job/{id}",
+    summary = "Retrieve bgworker job",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponse<response::Job>))
+    )
+)]
+#[tracing::instrument]
+async fn job(Path((ns, id)): Path<(String, String)>) -> JsonResponseType<response::Job> {
+    let job = WorkerRegistry::get_job(&ns, &id)
+        .await
+        .map_err(ApplicationError::from)?;
+    match job {
+        Some(job) => JsonResponse::ok(job.into()),
+        None => JsonResponse::err("job not found"),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/{ns}/jobs/batch/retry",
+    summary = "Batch retry failed or killed bgworker jobs",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponse<response::Affected>))
+    )
+)]
+#[tracing::instrument]
+async fn batch_retry(
+    Path(ns): Path<String>,
+    Json(request): Json<request::JobIdsRequest>,
+) -> JsonResponseType<response::Affected> {
+    if request.ids.is_empty() {
+        return JsonResponse::ok(response::Affected { affected: 0 });
+    }
+    let affected = WorkerRegistry::retry_jobs(&ns, &request.ids)
+        .await
+        .map_err(ApplicationError::from)?;
+    JsonResponse::ok(response::Affected { affected })
+}
+
+#[utoipa::path(
+    post,
+    path = "/{ns}/retry",
+    summary = "Retry every failed or killed job of a bgworker namespace",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponse<response::Affected>))
+    )
+)]
+#[tracing::instrument]
+async fn retry_all(Path(ns): Path<String>) -> JsonResponseType<response::Affected> {
+    let affected = WorkerRegistry::retry_jobs(&ns, &[])
+        .await
+        .map_err(ApplicationError::from)?;
+    JsonResponse::ok(response::Affected { affected })
+}
+
+#[utoipa::path(
+    post,
+    path = "/{ns}/jobs/batch/delete",
+    summary = "Batch delete bgworker jobs that are not running",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponse<response::Affected>))
+    )
+)]
+#[tracing::instrument]
+async fn batch_delete(
+    Path(ns): Path<String>,
+    Json(request): Json<request::JobIdsRequest>,
+) -> JsonResponseType<response::Affected> {
+    let affected = WorkerRegistry::delete_jobs(&ns, &request.ids)
+        .await
+        .map_err(ApplicationError::from)?;
+    JsonResponse::ok(response::Affected { affected })
+}
+
+#[utoipa::path(
+    put,
+    path = "/{ns}/job/{id}/payload",
+    summary = "Replace the payload of a bgworker job that is not running",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponseEmpty))
+    )
+)]
+#[tracing::instrument]
+async fn update_payload(
+    Path((ns, id)): Path<(String, String)>,
+    Json(request): Json<request::UpdatePayloadRequest>,
+) -> JsonResponseType<()> {
+    let updated = WorkerRegistry::update_job_payload(&ns, &id, request.payload)
+        .await
+        .map_err(ApplicationError::from)?;
+    if !updated {
+        return JsonResponse::err("job not found or running");
+    }
+    JsonResponse::ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/workflows/{page}",
+    summary = "List bgworker workflows",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponse<PagingResponse<response::Workflow>>))
+    )
+)]
+#[tracing::instrument]
+async fn workflows(Path(page): Path<i64>) -> JsonResponsePagingType<response::Workflow> {
+    let (total, items) = WorkerRegistry::list_workflows(page)
+        .await
+        .map_err(ApplicationError::from)?;
+    let items = items.into_iter().map(Into::into).collect();
+    JsonResponse::ok(PagingResponse { total, items })
+}
+
+#[utoipa::path(
+    get,
+    path = "/workflow/{id}",
+    summary = "Retrieve bgworker workflow with its jobs",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponse<response::WorkflowDetail>))
+    )
+)]
+#[tracing::instrument]
+async fn workflow(Path(id): Path<String>) -> JsonResponseType<response::WorkflowDetail> {
+    let workflow = WorkerRegistry::get_workflow(&id)
+        .await
+        .map_err(ApplicationError::from)?;
+    let Some((workflow, jobs)) = workflow else {
+        return JsonResponse::err("workflow not found");
+    };
+    JsonResponse::ok(response::WorkflowDetail {
+        workflow: workflow.into(),
+        jobs: jobs.into_iter().map(Into::into).collect(),
     })
 }
 
+mod request {
+    use serde::Deserialize;
+    use utoipa::ToSchema;
+
+    #[derive(Debug, Deserialize, ToSchema)]
+    pub struct JobIdsRequest {
+        pub ids: Vec<String>,
+    }
+
+    #[derive(Debug, Deserialize, ToSchema)]
+    pub struct UpdatePayloadRequest {
+        pub payload: serde_json::Value,
+    }
+}
+
 mod response {
+    use application::re_export::{JobRecord, WorkflowJobRecord, WorkflowRecord};
     use serde::Serialize;
     use utoipa::ToSchema;
 
@@ -136,22 +295,115 @@ mod response {
     }
 
     #[derive(Serialize, ToSchema)]
-    pub struct JobsResponse {
-        pub items: Vec<Job>,
-        #[serde(rename = "hasNext")]
-        pub has_next: bool,
+    pub struct Job {
+        pub id: String,
+        pub status: String,
+        pub payload: serde_json::Value,
+        pub attempts: i32,
+        pub max_attempts: i32,
+        pub last_error: Option<String>,
+        pub run_at: Option<i64>,
+        pub lock_at: Option<i64>,
+        pub done_at: Option<i64>,
+    }
+
+    impl From<JobRecord> for Job {
+        fn from(record: JobRecord) -> Self {
+            Self {
+                id: record.id,
+                status: record.status,
+                payload: record.payload,
+                attempts: record.attempts,
+                max_attempts: record.max_attempts,
+                last_error: record.last_error,
+                run_at: record.run_at,
+                lock_at: record.lock_at,
+                done_at: record.done_at,
+            }
+        }
+    }
+
+    #[derive(Serialize, ToSchema)]
+    pub struct Affected {
+        pub affected: u64,
     }
 
     #[derive(Serialize, ToSchema)]
-    pub struct Job {
-        pub args: serde_json::Value,
-        pub parts: serde_json::Value,
+    pub struct Workflow {
+        pub id: String,
+        pub name: String,
+        pub status: String,
+        pub on_failure: String,
+        pub stage: i32,
+        pub stage_count: i32,
+        pub error: Option<String>,
+        pub created_at: i64,
+        pub updated_at: i64,
+    }
+
+    impl From<WorkflowRecord> for Workflow {
+        fn from(record: WorkflowRecord) -> Self {
+            Self {
+                id: record.id,
+                name: record.name,
+                status: record.status,
+                on_failure: record.on_failure,
+                stage: record.stage,
+                stage_count: record.stage_count,
+                error: record.error,
+                created_at: record.created_at,
+                updated_at: record.updated_at,
+            }
+        }
+    }
+
+    #[derive(Serialize, ToSchema)]
+    pub struct WorkflowJob {
+        pub stage: i32,
+        pub idx: i32,
+        pub job_id: String,
+        /// Namespace of the job
+        pub job_type: String,
+        pub status: String,
+        pub job_status: String,
+        pub last_error: Option<String>,
+        pub output: Option<serde_json::Value>,
+    }
+
+    impl From<WorkflowJobRecord> for WorkflowJob {
+        fn from(record: WorkflowJobRecord) -> Self {
+            Self {
+                stage: record.stage,
+                idx: record.idx,
+                job_id: record.job_id,
+                job_type: record.job_type,
+                status: record.status,
+                job_status: record.job_status,
+                last_error: record.last_error,
+                output: record
+                    .output
+                    .and_then(|output| serde_json::from_str(&output).ok()),
+            }
+        }
+    }
+
+    #[derive(Serialize, ToSchema)]
+    pub struct WorkflowDetail {
+        pub workflow: Workflow,
+        pub jobs: Vec<WorkflowJob>,
     }
 }
 
 pub fn routing() -> OpenApiRouter<WebState> {
     OpenApiRouter::new()
         .routes(routes!(namespaces).permit_all(perms!(SYSTEM_BGWORKER_READ)))
+        .routes(routes!(workflows).permit_all(perms!(SYSTEM_BGWORKER_READ)))
+        .routes(routes!(workflow).permit_all(perms!(SYSTEM_BGWORKER_READ)))
         .routes(routes!(stat).permit_all(perms!(SYSTEM_BGWORKER_READ)))
         .routes(routes!(jobs).permit_all(perms!(SYSTEM_BGWORKER_READ)))
+        .routes(routes!(job).permit_all(perms!(SYSTEM_BGWORKER_READ)))
+        .routes(routes!(batch_retry).permit_all(perms!(SYSTEM_BGWORKER_RETRY)))
+        .routes(routes!(retry_all).permit_all(perms!(SYSTEM_BGWORKER_RETRY)))
+        .routes(routes!(batch_delete).permit_all(perms!(SYSTEM_BGWORKER_DELETE)))
+        .routes(routes!(update_payload).permit_all(perms!(SYSTEM_BGWORKER_UPDATE)))
 }
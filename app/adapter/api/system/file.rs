@@ -1,25 +1,61 @@
 use application::{
-    shared::{paging_result::PagingResult, query_handler::QueryHandler as _},
+    shared::{
+        command_handler::CommandHandler, paging_result::PagingResult,
+        query_handler::QueryHandler as _,
+    },
     system::{
-        dto::file::FileDto,
-        query::search_files::{SearchFilesQuery, SearchFilesQueryHandler},
+        command::{
+            batch_delete_file_folders::{
+                BatchDeleteFileFoldersCommand, BatchDeleteFileFoldersCommandHandler,
+            },
+            batch_delete_files::{BatchDeleteFilesCommand, BatchDeleteFilesCommandHandler},
+            batch_move_files::{BatchMoveFilesCommand, BatchMoveFilesCommandHandler},
+            batch_tag_files::{BatchTagFilesCommand, BatchTagFilesCommandHandler},
+            create_file_folder::{CreateFileFolderCommand, CreateFileFolderCommandHandler},
+            create_file_share::{CreateFileShareCommand, CreateFileShareCommandHandler},
+            move_file_folder::{MoveFileFolderCommand, MoveFileFolderCommandHandler},
+            release_file::{ReleaseFileCommand, ReleaseFileCommandHandler},
+            rescan_file::{RescanFileCommand, RescanFileCommandHandler},
+            revoke_file_shares::{RevokeFileSharesCommand, RevokeFileSharesCommandHandler},
+            update_file::{UpdateFileCommand, UpdateFileCommandHandler},
+            update_file_folder::{UpdateFileFolderCommand, UpdateFileFolderCommandHandler},
+        },
+        dto::file::{FileDto, FileFolderWithChildren, FileShareDto, FileUsageDto},
+        query::{
+            search_file_shares::{SearchFileSharesQuery, SearchFileSharesQueryHandler},
+            search_file_usages::{SearchFileUsagesQuery, SearchFileUsagesQueryHandler},
+            search_files::{SearchFilesQuery, SearchFilesQueryHandler},
+            tree_file_folders::{TreeFileFoldersQuery, TreeFileFoldersQueryHandler},
+        },
         service::upload_service::UploadService,
     },
 };
 use axum::{
+    Json,
     extract::{Path, Query},
     http::StatusCode,
     response::{IntoResponse, Redirect},
 };
-use domain::auth::value_object::permission::{SYSTEM_FILE_DOWNLOAD, SYSTEM_FILE_READ};
+use domain::{
+    auth::value_object::permission::{
+        SYSTEM_FILE_DELETE, SYSTEM_FILE_DOWNLOAD, SYSTEM_FILE_READ, SYSTEM_FILE_SHARE,
+        SYSTEM_FILE_UPDATE,
+    },
+    system::value_object::{
+        file_folder_id::FileFolderId, file_id::FileId, scan_status::ScanStatus,
+    },
+};
 use utoipa_axum::{router::OpenApiRouter, routes};
 
 use crate::{
     WebState, perms,
     shared::{
-        extractor::inject::Inject,
+        extractor::{inject::Inject, valid_user::ValidUser},
         middleware::perm_router_ext::PermissonRouteExt as _,
-        response::{JsonResponse, JsonResponsePagingType, PagingResponse},
+        response::{
+            JsonResponse, JsonResponseEmpty, JsonResponsePagingType, JsonResponseType,
+            PagingResponse,
+        },
     },
 };
 
@@ -42,6 +78,25 @@ async fn search(
     JsonResponse::ok(PagingResponse { total, items })
 }
 
+#[utoipa::path(
+    get,
+    params(SearchFileUsagesQuery),
+    path = "/usages",
+    summary = "Search storage usage per uploader",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponse<PagingResponse<FileUsageDto>>))
+    )
+)]
+#[tracing::instrument]
+async fn usages(
+    Inject(query_handler): Inject<SearchFileUsagesQueryHandler>,
+    Query(query): Query<SearchFileUsagesQuery>,
+) -> JsonResponsePagingType<FileUsageDto> {
+    let PagingResult { total, items } = query_handler.query(query).await?;
+    JsonResponse::ok(PagingResponse { total, items })
+}
+
 #[utoipa::path(
     get,
     path = "/download/{*path}",
@@ -62,8 +117,285 @@ async fn download(
     StatusCode::BAD_REQUEST.into_response()
 }
 
+#[utoipa::path(
+    put,
+    path = "/{id}",
+    summary = "Update file name, description and tags",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponseEmpty))
+    )
+)]
+#[tracing::instrument]
+async fn update(
+    Inject(command_handler): Inject<UpdateFileCommandHandler>,
+    Path(_id): Path<FileId>,
+    Json(command): Json<UpdateFileCommand>,
+) -> JsonResponseType<()> {
+    let _ = command_handler.handle(command).await?;
+    JsonResponse::ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/rescan",
+    summary = "Scan file content again, clean content leaves quarantine",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponse<ScanStatus>))
+    )
+)]
+#[tracing::instrument]
+async fn rescan(
+    Inject(command_handler): Inject<RescanFileCommandHandler>,
+    Path(id): Path<FileId>,
+) -> JsonResponseType<ScanStatus> {
+    let status = command_handler
+        .handle(RescanFileCommand::builder().id(id).build())
+        .await?;
+    JsonResponse::ok(status)
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/release",
+    summary = "Release quarantined file without a clean scan",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponseEmpty))
+    )
+)]
+#[tracing::instrument]
+async fn release(
+    Inject(command_handler): Inject<ReleaseFileCommandHandler>,
+    Path(id): Path<FileId>,
+) -> JsonResponseType<()> {
+    command_handler
+        .handle(ReleaseFileCommand::builder().id(id).build())
+        .await?;
+    JsonResponse::ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/batch/move",
+    summary = "Batch move files into a folder",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponseEmpty))
+    )
+)]
+#[tracing::instrument]
+async fn batch_move(
+    Inject(command_handler): Inject<BatchMoveFilesCommandHandler>,
+    Json(command): Json<BatchMoveFilesCommand>,
+) -> JsonResponseType<()> {
+    command_handler.handle(command).await?;
+    JsonResponse::ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/batch/delete",
+    summary = "Batch delete files into the recycle bin",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponseEmpty))
+    )
+)]
+#[tracing::instrument]
+async fn batch_delete(
+    Inject(command_handler): Inject<BatchDeleteFilesCommandHandler>,
+    Json(command): Json<BatchDeleteFilesCommand>,
+) -> JsonResponseType<()> {
+    command_handler.handle(command).await?;
+    JsonResponse::ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/batch/tag",
+    summary = "Batch add and remove file tags",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponseEmpty))
+    )
+)]
+#[tracing::instrument]
+async fn batch_tag(
+    Inject(command_handler): Inject<BatchTagFilesCommandHandler>,
+    Json(command): Json<BatchTagFilesCommand>,
+) -> JsonResponseType<()> {
+    command_handler.handle(command).await?;
+    JsonResponse::ok(())
+}
+
+#[utoipa::path(
+    get,
+    params(TreeFileFoldersQuery),
+    path = "/folders",
+    summary = "Tree file folders",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponse<Vec<FileFolderWithChildren>>))
+    )
+)]
+#[tracing::instrument]
+async fn tree_folders(
+    Inject(query_handler): Inject<TreeFileFoldersQueryHandler>,
+    Query(query): Query<TreeFileFoldersQuery>,
+) -> JsonResponseType<Vec<FileFolderWithChildren>> {
+    let items = query_handler.query(query).await?;
+    JsonResponse::ok(items)
+}
+
+#[utoipa::path(
+    post,
+    path = "/folders",
+    summary = "Create file folder",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponseEmpty))
+    )
+)]
+#[tracing::instrument]
+async fn create_folder(
+    Inject(command_handler): Inject<CreateFileFolderCommandHandler>,
+    Json(command): Json<CreateFileFolderCommand>,
+) -> JsonResponseType<()> {
+    let _ = command_handler.handle(command).await?;
+    JsonResponse::ok(())
+}
+
+#[utoipa::path(
+    put,
+    path = "/folders/{id}",
+    summary = "Rename file folder",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponseEmpty))
+    )
+)]
+#[tracing::instrument]
+async fn update_folder(
+    Inject(command_handler): Inject<UpdateFileFolderCommandHandler>,
+    Path(_id): Path<FileFolderId>,
+    Json(command): Json<UpdateFileFolderCommand>,
+) -> JsonResponseType<()> {
+    let _ = command_handler.handle(command).await?;
+    JsonResponse::ok(())
+}
+
+#[utoipa::path(
+    put,
+    path = "/folders/{id}/move",
+    summary = "Move file folder",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponseEmpty))
+    )
+)]
+#[tracing::instrument]
+async fn move_folder(
+    Inject(command_handler): Inject<MoveFileFolderCommandHandler>,
+    Path(_id): Path<FileFolderId>,
+    Json(command): Json<MoveFileFolderCommand>,
+) -> JsonResponseType<()> {
+    let _ = command_handler.handle(command).await?;
+    JsonResponse::ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/folders/batch/delete",
+    summary = "Batch delete file folders, files inside move to the root",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponseEmpty))
+    )
+)]
+#[tracing::instrument]
+async fn batch_delete_folders(
+    Inject(command_handler): Inject<BatchDeleteFileFoldersCommandHandler>,
+    Json(command): Json<BatchDeleteFileFoldersCommand>,
+) -> JsonResponseType<()> {
+    command_handler.handle(command).await?;
+    JsonResponse::ok(())
+}
+
+#[utoipa::path(
+    get,
+    params(SearchFileSharesQuery),
+    path = "/shares",
+    summary = "Search file share links",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponse<PagingResponse<FileShareDto>>))
+    )
+)]
+#[tracing::instrument]
+async fn search_shares(
+    Inject(query_handler): Inject<SearchFileSharesQueryHandler>,
+    Query(query): Query<SearchFileSharesQuery>,
+) -> JsonResponsePagingType<FileShareDto> {
+    let PagingResult { total, items } = query_handler.query(query).await?;
+    JsonResponse::ok(PagingResponse { total, items })
+}
+
+#[utoipa::path(
+    post,
+    path = "/shares",
+    summary = "Create file share link",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponseEmpty))
+    )
+)]
+#[tracing::instrument]
+async fn create_share(
+    ValidUser(id): ValidUser,
+    Inject(command_handler): Inject<CreateFileShareCommandHandler>,
+    Json(command): Json<CreateFileShareCommand>,
+) -> JsonResponseType<()> {
+    let _ = command_handler.handle(command.with_created_by(id)).await?;
+    JsonResponse::ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/shares/batch/revoke",
+    summary = "Batch revoke file share links",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponseEmpty))
+    )
+)]
+#[tracing::instrument]
+async fn batch_revoke_shares(
+    Inject(command_handler): Inject<RevokeFileSharesCommandHandler>,
+    Json(command): Json<RevokeFileSharesCommand>,
+) -> JsonResponseType<()> {
+    command_handler.handle(command).await?;
+    JsonResponse::ok(())
+}
+
 pub fn routing() -> OpenApiRouter<WebState> {
     OpenApiRouter::new()
         .routes(routes!(search).permit_all(perms!(SYSTEM_FILE_READ)))
+        .routes(routes!(usages).permit_all(perms!(SYSTEM_FILE_READ)))
         .routes(routes!(download).permit_all(perms!(SYSTEM_FILE_DOWNLOAD)))
+        .routes(routes!(update).permit_all(perms!(SYSTEM_FILE_UPDATE)))
+        .routes(routes!(rescan).permit_all(perms!(SYSTEM_FILE_UPDATE)))
+        .routes(routes!(release).permit_all(perms!(SYSTEM_FILE_UPDATE)))
+        .routes(routes!(batch_move).permit_all(perms!(SYSTEM_FILE_UPDATE)))
+        .routes(routes!(batch_tag).permit_all(perms!(SYSTEM_FILE_UPDATE)))
+        .routes(routes!(batch_delete).permit_all(perms!(SYSTEM_FILE_DELETE)))
+        .routes(routes!(tree_folders).permit_all(perms!(SYSTEM_FILE_READ)))
+        .routes(routes!(create_folder).permit_all(perms!(SYSTEM_FILE_UPDATE)))
+        .routes(routes!(update_folder).permit_all(perms!(SYSTEM_FILE_UPDATE)))
+        .routes(routes!(move_folder).permit_all(perms!(SYSTEM_FILE_UPDATE)))
+        .routes(routes!(batch_delete_folders).permit_all(perms!(SYSTEM_FILE_UPDATE)))
+        .routes(routes!(search_shares).permit_all(perms!(SYSTEM_FILE_SHARE)))
+        .routes(routes!(create_share).permit_all(perms!(SYSTEM_FILE_SHARE)))
+        .routes(routes!(batch_revoke_shares).permit_all(perms!(SYSTEM_FILE_SHARE)))
 }
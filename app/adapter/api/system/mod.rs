@@ -6,7 +6,9 @@ mod access_log;
 mod bgworker;
 mod cache;
 mod file;
+mod recycle_bin;
 mod sched;
+mod security_event;
 mod stat;
 
 pub fn routing() -> OpenApiRouter<WebState> {
@@ -17,4 +19,6 @@ pub fn routing() -> OpenApiRouter<WebState> {
         .nest("/bgworkers", bgworker::routing())
         .nest("/caches", cache::routing())
         .nest("/access_logs", access_log::routing())
+        .nest("/security_events", security_event::routing())
+        .nest("/recycle_bin", recycle_bin::routing())
 }
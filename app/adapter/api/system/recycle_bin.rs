@@ -0,0 +1,93 @@
+use application::{
+    shared::{
+        command_handler::CommandHandler, paging_result::PagingResult,
+        query_handler::QueryHandler as _,
+    },
+    system::{
+        command::{
+            purge_recycle_bin::{PurgeRecycleBinCommand, PurgeRecycleBinCommandHandler},
+            restore_recycle_bin::{RestoreRecycleBinCommand, RestoreRecycleBinCommandHandler},
+        },
+        dto::recycle_bin::RecycleBinItemDto,
+        query::search_recycle_bin::{SearchRecycleBinQuery, SearchRecycleBinQueryHandler},
+    },
+};
+use axum::{Json, extract::Query};
+use domain::auth::value_object::permission::{
+    SYSTEM_RECYCLE_BIN_PURGE, SYSTEM_RECYCLE_BIN_READ, SYSTEM_RECYCLE_BIN_RESTORE,
+};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{
+    WebState, perms,
+    shared::{
+        extractor::inject::Inject,
+        middleware::perm_router_ext::PermissonRouteExt as _,
+        response::{
+            JsonResponse, JsonResponseEmpty, JsonResponsePagingType, JsonResponseType,
+            PagingResponse,
+        },
+    },
+};
+
+#[utoipa::path(
+    get,
+    params(SearchRecycleBinQuery),
+    path = "/",
+    summary = "Search deleted users, roles, departments and files",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponse<PagingResponse<RecycleBinItemDto>>))
+    )
+)]
+#[tracing::instrument]
+async fn search(
+    Inject(query_handler): Inject<SearchRecycleBinQueryHandler>,
+    Query(query): Query<SearchRecycleBinQuery>,
+) -> JsonResponsePagingType<RecycleBinItemDto> {
+    let PagingResult { total, items } = query_handler.query(query).await?;
+    JsonResponse::ok(PagingResponse { total, items })
+}
+
+#[utoipa::path(
+    post,
+    path = "/batch/restore",
+    summary = "Batch restore deleted items of one kind",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponseEmpty))
+    )
+)]
+#[tracing::instrument]
+async fn batch_restore(
+    Inject(command_handler): Inject<RestoreRecycleBinCommandHandler>,
+    Json(command): Json<RestoreRecycleBinCommand>,
+) -> JsonResponseType<()> {
+    command_handler.handle(command).await?;
+    JsonResponse::ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/batch/purge",
+    summary = "Batch purge deleted items of one kind",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponseEmpty))
+    )
+)]
+#[tracing::instrument]
+async fn batch_purge(
+    Inject(command_handler): Inject<PurgeRecycleBinCommandHandler>,
+    Json(command): Json<PurgeRecycleBinCommand>,
+) -> JsonResponseType<()> {
+    command_handler.handle(command).await?;
+    JsonResponse::ok(())
+}
+
+pub fn routing() -> OpenApiRouter<WebState> {
+    OpenApiRouter::new()
+        .routes(routes!(search).permit_all(perms!(SYSTEM_RECYCLE_BIN_READ)))
+        .routes(routes!(batch_restore).permit_all(perms!(SYSTEM_RECYCLE_BIN_RESTORE)))
+        .routes(routes!(batch_purge).permit_all(perms!(SYSTEM_RECYCLE_BIN_PURGE)))
+}
@@ -4,22 +4,35 @@ use application::{
         query_handler::QueryHandler as _,
     },
     system::{
-        command::batch_delete_scheds::{BatchDeleteSchedsCommand, BatchDeleteSchedsCommandHandler},
-        dto::sched::{SchedDto, SchedRecordDto},
+        command::{
+            batch_delete_scheds::{BatchDeleteSchedsCommand, BatchDeleteSchedsCommandHandler},
+            pause_sched_job::{PauseSchedJobCommand, PauseSchedJobCommandHandler},
+            resume_sched_job::{ResumeSchedJobCommand, ResumeSchedJobCommandHandler},
+            trigger_sched_job::{TriggerSchedJobCommand, TriggerSchedJobCommandHandler},
+            update_sched_job::{UpdateSchedJobCommand, UpdateSchedJobCommandHandler},
+        },
+        dto::sched::{SchedDto, SchedLeaderDto, SchedNextRunDto, SchedRecordDto},
         query::{
+            next_sched_runs::{NextSchedRunsQuery, NextSchedRunsQueryHandler},
             paging_sched_records::{PagingSchedRecordsQuery, PagingSchedRecordsQueryHandler},
+            retrieve_sched_leader::{RetrieveSchedLeaderQuery, RetrieveSchedLeaderQueryHandler},
             search_scheds::{SearchSchedsQuery, SearchSchedsQueryHandler},
         },
     },
 };
-use axum::{Json, extract::Query};
-use domain::auth::value_object::permission::{SYSTEM_SCHED_DELETE, SYSTEM_SCHED_READ};
+use axum::{
+    Json,
+    extract::{Path, Query},
+};
+use domain::auth::value_object::permission::{
+    SYSTEM_SCHED_DELETE, SYSTEM_SCHED_READ, SYSTEM_SCHED_RUN, SYSTEM_SCHED_UPDATE,
+};
 use utoipa_axum::{router::OpenApiRouter, routes};
 
 use crate::{
     WebState, perms,
     shared::{
-        extractor::inject::Inject,
+        extractor::{inject::Inject, valid_user::ValidUser},
         middleware::perm_router_ext::PermissonRouteExt as _,
         response::{
             JsonResponse, JsonResponseEmpty, JsonResponsePagingType, JsonResponseType,
@@ -47,6 +60,134 @@ async fn search(
     JsonResponse::ok(PagingResponse { total, items })
 }
 
+#[utoipa::path(
+    put,
+    path = "/{key}",
+    summary = "Update the schedule of a scheduled job",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponseEmpty))
+    )
+)]
+#[tracing::instrument]
+async fn update(
+    Inject(command_handler): Inject<UpdateSchedJobCommandHandler>,
+    Path(key): Path<String>,
+    Json(request): Json<request::UpdateSchedJobRequest>,
+) -> JsonResponseType<()> {
+    let command = UpdateSchedJobCommand::builder()
+        .key(key)
+        .maybe_expr(request.expr)
+        .maybe_timezone(request.timezone)
+        .enabled(request.enabled)
+        .maybe_params(request.params)
+        .build();
+    let _ = command_handler.handle(command).await?;
+    JsonResponse::ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/{key}/run",
+    summary = "Run a scheduled job right away",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponseEmpty))
+    )
+)]
+#[tracing::instrument]
+async fn run(
+    ValidUser(id): ValidUser,
+    Inject(command_handler): Inject<TriggerSchedJobCommandHandler>,
+    Path(key): Path<String>,
+) -> JsonResponseType<()> {
+    let command = TriggerSchedJobCommand::builder()
+        .key(key)
+        .triggered_by(id)
+        .build();
+    command_handler.handle(command).await?;
+    JsonResponse::ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/{key}/pause",
+    summary = "Pause a scheduled job",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponseEmpty))
+    )
+)]
+#[tracing::instrument]
+async fn pause(
+    Inject(command_handler): Inject<PauseSchedJobCommandHandler>,
+    Path(key): Path<String>,
+) -> JsonResponseType<()> {
+    command_handler
+        .handle(PauseSchedJobCommand::builder().key(key).build())
+        .await?;
+    JsonResponse::ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/{key}/resume",
+    summary = "Resume a paused scheduled job",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponseEmpty))
+    )
+)]
+#[tracing::instrument]
+async fn resume(
+    Inject(command_handler): Inject<ResumeSchedJobCommandHandler>,
+    Path(key): Path<String>,
+) -> JsonResponseType<()> {
+    command_handler
+        .handle(ResumeSchedJobCommand::builder().key(key).build())
+        .await?;
+    JsonResponse::ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/{key}/next",
+    params(request::NextRunsRequest),
+    summary = "Next fire times of a scheduled job",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponse<Vec<SchedNextRunDto>>))
+    )
+)]
+#[tracing::instrument]
+async fn next_runs(
+    Inject(query_handler): Inject<NextSchedRunsQueryHandler>,
+    Path(key): Path<String>,
+    Query(request): Query<request::NextRunsRequest>,
+) -> JsonResponseType<Vec<SchedNextRunDto>> {
+    let query = NextSchedRunsQuery::builder()
+        .key(key)
+        .maybe_count(request.count)
+        .build();
+    JsonResponse::ok(query_handler.query(query).await?)
+}
+
+#[utoipa::path(
+    get,
+    path = "/leader",
+    summary = "Process currently running the scheduled jobs",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponse<Option<SchedLeaderDto>>))
+    )
+)]
+#[tracing::instrument]
+async fn leader(
+    Inject(query_handler): Inject<RetrieveSchedLeaderQueryHandler>,
+) -> JsonResponseType<Option<SchedLeaderDto>> {
+    JsonResponse::ok(query_handler.query(RetrieveSchedLeaderQuery).await?)
+}
+
 #[utoipa::path(
     get,
     path = "/records",
@@ -86,6 +227,33 @@ async fn batch_delete_records(
 pub fn routing() -> OpenApiRouter<WebState> {
     OpenApiRouter::new()
         .routes(routes!(search).permit_all(perms!(SYSTEM_SCHED_READ)))
+        .routes(routes!(update).permit_all(perms!(SYSTEM_SCHED_UPDATE)))
+        .routes(routes!(run).permit_all(perms!(SYSTEM_SCHED_RUN)))
+        .routes(routes!(pause).permit_all(perms!(SYSTEM_SCHED_UPDATE)))
+        .routes(routes!(resume).permit_all(perms!(SYSTEM_SCHED_UPDATE)))
+        .routes(routes!(next_runs).permit_all(perms!(SYSTEM_SCHED_READ)))
+        .routes(routes!(leader).permit_all(perms!(SYSTEM_SCHED_READ)))
         .routes(routes!(records).permit_all(perms!(SYSTEM_SCHED_READ)))
         .routes(routes!(batch_delete_records).permit_all(perms!(SYSTEM_SCHED_DELETE)))
 }
+
+mod request {
+    use serde::Deserialize;
+    use utoipa::{IntoParams, ToSchema};
+
+    #[derive(Debug, Deserialize, IntoParams)]
+    pub struct NextRunsRequest {
+        /// How many fire times to list, 5 by default and 50 at most
+        pub count: Option<usize>,
+    }
+
+    /// Blank fields fall back to what the job is compiled with
+    #[derive(Debug, Deserialize, ToSchema)]
+    pub struct UpdateSchedJobRequest {
+        pub expr: Option<String>,
+        pub timezone: Option<String>,
+        pub enabled: bool,
+        /// JSON handed to the job when it runs
+        pub params: Option<String>,
+    }
+}
@@ -0,0 +1,44 @@
+use application::{
+    shared::{paging_result::PagingResult, query_handler::QueryHandler as _},
+    system::{
+        dto::security_event::SecurityEventDto,
+        query::search_security_events::{
+            SearchSecurityEventsQuery, SearchSecurityEventsQueryHandler,
+        },
+    },
+};
+use axum::extract::Query;
+use domain::auth::value_object::permission::SYSTEM_SECURITY_EVENT_READ;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{
+    WebState, perms,
+    shared::{
+        extractor::inject::Inject,
+        middleware::perm_router_ext::PermissonRouteExt as _,
+        response::{JsonResponse, JsonResponsePagingType, PagingResponse},
+    },
+};
+
+#[utoipa::path(
+    get,
+    params(SearchSecurityEventsQuery),
+    path = "/",
+    summary = "Search security_events",
+    tag = "System",
+    responses(
+        (status = 200, body = inline(JsonResponse<PagingResponse<SecurityEventDto>>))
+    )
+)]
+#[tracing::instrument]
+async fn search(
+    Inject(query_handler): Inject<SearchSecurityEventsQueryHandler>,
+    Query(query): Query<SearchSecurityEventsQuery>,
+) -> JsonResponsePagingType<SecurityEventDto> {
+    let PagingResult { total, items } = query_handler.query(query).await?;
+    JsonResponse::ok(PagingResponse { total, items })
+}
+
+pub fn routing() -> OpenApiRouter<WebState> {
+    OpenApiRouter::new().routes(routes!(search).permit_all(perms!(SYSTEM_SECURITY_EVENT_READ)))
+}
@@ -0,0 +1,216 @@
+use std::{convert::Infallible, time::Duration};
+
+use application::{
+    error::ApplicationError, re_export::TaskRecord, shared::bgworker_impl::WorkerRegistry,
+    shared::paging_query::PagingQuery, system::service::upload_service::UploadService,
+};
+use axum::{
+    extract::{Path, Query},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::{Stream, stream};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{
+    WebState,
+    shared::{
+        extractor::{inject::Inject, valid_user::ValidUser},
+        response::{
+            JsonResponse, JsonResponseEmpty, JsonResponsePagingType, JsonResponseType,
+            PagingResponse,
+        },
+    },
+};
+
+/// How often the event stream looks for progress
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[utoipa::path(
+    get,
+    path = "/",
+    summary = "List my background tasks",
+    tag = "Task",
+    responses(
+        (status = 200, body = inline(JsonResponse<PagingResponse<response::Task>>))
+    )
+)]
+#[tracing::instrument]
+async fn list(
+    ValidUser(id): ValidUser,
+    Query(query): Query<PagingQuery>,
+) -> JsonResponsePagingType<response::Task> {
+    let (total, items) = WorkerRegistry::list_tasks(&id, query.page(), query.page_size())
+        .await
+        .map_err(ApplicationError::from)?;
+    let items = items.into_iter().map(Into::into).collect();
+    JsonResponse::ok(PagingResponse { total, items })
+}
+
+#[utoipa::path(
+    get,
+    path = "/events",
+    summary = "Stream my latest background tasks whenever they change",
+    tag = "Task",
+    responses(
+        (status = 200, content_type = "text/event-stream", body = Vec<response::Task>)
+    )
+)]
+#[tracing::instrument]
+async fn events(ValidUser(id): ValidUser) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = stream::unfold(
+        (id.to_string(), None::<String>),
+        |(owner, last)| async move {
+            loop {
+                let tasks = match WorkerRegistry::list_tasks(&owner, 1, 10).await {
+                    Ok((_, items)) => items
+                        .into_iter()
+                        .map(response::Task::from)
+                        .collect::<Vec<_>>(),
+                    Err(err) => {
+                        tracing::error!(%err, "Failed to list tasks");
+                        return None;
+                    }
+                };
+                let data = serde_json::to_string(&tasks).unwrap_or_default();
+                if last.as_ref() != Some(&data) {
+                    let event = Event::default().event("tasks").data(&data);
+                    return Some((Ok(event), (owner, Some(data))));
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        },
+    );
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    summary = "Retrieve my background task",
+    tag = "Task",
+    responses(
+        (status = 200, body = inline(JsonResponse<response::Task>))
+    )
+)]
+#[tracing::instrument]
+async fn retrieve(
+    ValidUser(user_id): ValidUser,
+    Path(id): Path<String>,
+) -> JsonResponseType<response::Task> {
+    let task = WorkerRegistry::get_task(&user_id, &id)
+        .await
+        .map_err(ApplicationError::from)?;
+    match task {
+        Some(task) => JsonResponse::ok(task.into()),
+        None => JsonResponse::err("task not found"),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/cancel",
+    summary = "Cancel my background task",
+    tag = "Task",
+    responses(
+        (status = 200, body = inline(JsonResponseEmpty))
+    )
+)]
+#[tracing::instrument]
+async fn cancel(ValidUser(user_id): ValidUser, Path(id): Path<String>) -> JsonResponseType<()> {
+    let cancelled = WorkerRegistry::cancel_task(&user_id, &id)
+        .await
+        .map_err(ApplicationError::from)?;
+    if !cancelled {
+        return JsonResponse::err("task not found");
+    }
+    JsonResponse::ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/download",
+    summary = "Presign the result file of my finished background task",
+    tag = "Task",
+    responses(
+        (status = 200, body = inline(JsonResponse<response::Download>))
+    )
+)]
+#[tracing::instrument]
+async fn download(
+    ValidUser(user_id): ValidUser,
+    Inject(upload_service): Inject<UploadService>,
+    Path(id): Path<String>,
+) -> JsonResponseType<response::Download> {
+    let task = WorkerRegistry::get_task(&user_id, &id)
+        .await
+        .map_err(ApplicationError::from)?;
+    let Some(path) = task
+        .filter(|task| task.status == "Done")
+        .and_then(|task| task.result)
+        .and_then(|result| serde_json::from_str::<serde_json::Value>(&result).ok())
+        .and_then(|result| result["path"].as_str().map(str::to_string))
+    else {
+        return JsonResponse::err("task has no result file");
+    };
+    let url = upload_service.presign_url(&path).await?;
+    JsonResponse::ok(response::Download { url })
+}
+
+mod response {
+    use super::TaskRecord;
+    use serde::Serialize;
+    use utoipa::ToSchema;
+
+    #[derive(Serialize, ToSchema)]
+    pub struct Task {
+        pub id: String,
+        pub job_type: String,
+        pub title: Option<String>,
+        pub status: String,
+        pub percent: i32,
+        pub message: Option<String>,
+        pub result: Option<serde_json::Value>,
+        pub cancel_requested: bool,
+        pub last_error: Option<String>,
+        pub run_at: Option<i64>,
+        pub done_at: Option<i64>,
+        pub created_at: i64,
+        pub updated_at: i64,
+    }
+
+    impl From<TaskRecord> for Task {
+        fn from(record: TaskRecord) -> Self {
+            Self {
+                id: record.id,
+                job_type: record.job_type,
+                title: record.title,
+                status: record.status,
+                percent: record.percent,
+                message: record.message,
+                result: record
+                    .result
+                    .and_then(|result| serde_json::from_str(&result).ok()),
+                cancel_requested: record.cancel_requested,
+                last_error: record.last_error,
+                run_at: record.run_at,
+                done_at: record.done_at,
+                created_at: record.created_at,
+                updated_at: record.updated_at,
+            }
+        }
+    }
+
+    #[derive(Serialize, ToSchema)]
+    pub struct Download {
+        pub url: String,
+    }
+}
+
+pub fn routing() -> OpenApiRouter<WebState> {
+    OpenApiRouter::new()
+        .routes(routes!(list))
+        .routes(routes!(events))
+        .routes(routes!(retrieve))
+        .routes(routes!(cancel))
+        .routes(routes!(download))
+}
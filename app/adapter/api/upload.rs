@@ -1,7 +1,12 @@
+use application::re_export::ConfigRef;
 use application::system::service::upload_service::{
-    ChunkResponse, FinishResponse, StartChunkResponse, UploadService,
+    ChunkResponse, FinishResponse, PresignResponse, StartChunkResponse, UploadService,
+    UploadedPartResponse,
+};
+use axum::{
+    Json,
+    extract::{DefaultBodyLimit, Query},
 };
-use axum::{Json, extract::DefaultBodyLimit};
 use axum_typed_multipart::TypedMultipart;
 use domain::auth::value_object::permission::SYSTEM_FILE_UPLOAD;
 use utoipa_axum::{
@@ -12,7 +17,7 @@ use utoipa_axum::{
 use crate::{
     WebState, perms,
     shared::{
-        extractor::inject::Inject,
+        extractor::{inject::Inject, valid_user::ValidUser},
         middleware::perm_router_ext::PermissonRouteExt,
         response::{JsonResponse, JsonResponseType},
     },
@@ -33,11 +38,12 @@ use crate::{
 )]
 #[tracing::instrument]
 async fn single(
+    ValidUser(id): ValidUser,
     Inject(service): Inject<UploadService>,
     TypedMultipart(request): TypedMultipart<request::UploadRequest>,
 ) -> JsonResponseType<FinishResponse> {
     let resp = service
-        .single(request.file.metadata.file_name, request.file.contents)
+        .single(&id, request.file.metadata.file_name, request.file.contents)
         .await?;
     JsonResponse::ok(resp)
 }
@@ -57,11 +63,12 @@ async fn single(
 )]
 #[tracing::instrument]
 async fn image(
+    ValidUser(id): ValidUser,
     Inject(service): Inject<UploadService>,
     TypedMultipart(request): TypedMultipart<request::UploadRequest>,
 ) -> JsonResponseType<FinishResponse> {
     let resp = service
-        .image(request.file.metadata.file_name, request.file.contents)
+        .image(&id, request.file.metadata.file_name, request.file.contents)
         .await?;
     JsonResponse::ok(resp)
 }
@@ -77,10 +84,13 @@ async fn image(
 )]
 #[tracing::instrument]
 async fn start_chunk(
+    ValidUser(id): ValidUser,
     Inject(service): Inject<UploadService>,
     Json(request): Json<request::StartChunkRequest>,
 ) -> JsonResponseType<StartChunkResponse> {
-    let resp = service.start_chunk(request.filename).await?;
+    let resp = service
+        .start_chunk(&id, request.filename, request.size)
+        .await?;
     JsonResponse::ok(resp)
 }
 
@@ -103,11 +113,35 @@ async fn chunk(
     TypedMultipart(request): TypedMultipart<request::ChunkRequest>,
 ) -> JsonResponseType<ChunkResponse> {
     let resp = service
-        .chunk(request.key, request.part_number, request.file.contents)
+        .chunk(
+            request.key,
+            request.upload_id,
+            request.part_number,
+            request.file.contents,
+        )
         .await?;
     JsonResponse::ok(resp)
 }
 
+#[utoipa::path(
+    get,
+    params(request::ListChunksRequest),
+    path = "/chunks",
+    summary = "List uploaded chunks",
+    tag = "Upload",
+    responses(
+        (status = 200, body = inline(JsonResponse<Vec<UploadedPartResponse>>))
+    )
+)]
+#[tracing::instrument]
+async fn list_chunks(
+    Inject(service): Inject<UploadService>,
+    Query(request): Query<request::ListChunksRequest>,
+) -> JsonResponseType<Vec<UploadedPartResponse>> {
+    let resp = service.list_chunks(request.key, request.upload_id).await?;
+    JsonResponse::ok(resp)
+}
+
 #[utoipa::path(
     post,
     path = "/finish_chunk",
@@ -119,11 +153,13 @@ async fn chunk(
 )]
 #[tracing::instrument]
 async fn finish_chunk(
+    ValidUser(id): ValidUser,
     Inject(service): Inject<UploadService>,
     Json(request): Json<request::FinishChunkRequest>,
 ) -> JsonResponseType<FinishResponse> {
     let resp = service
         .finish_chunk(
+            &id,
             request.filename,
             request.key,
             request.upload_id,
@@ -133,12 +169,52 @@ async fn finish_chunk(
     JsonResponse::ok(resp)
 }
 
+#[utoipa::path(
+    post,
+    path = "/presign",
+    summary = "Presign a direct upload to storage",
+    tag = "Upload",
+    responses(
+        (status = 200, body = inline(JsonResponse<PresignResponse>))
+    )
+)]
+#[tracing::instrument]
+async fn presign(
+    ValidUser(id): ValidUser,
+    Inject(service): Inject<UploadService>,
+    Json(request): Json<request::PresignRequest>,
+) -> JsonResponseType<PresignResponse> {
+    let resp = service
+        .presign(&id, request.filename, request.content_type, request.size)
+        .await?;
+    JsonResponse::ok(resp)
+}
+
+#[utoipa::path(
+    post,
+    path = "/confirm",
+    summary = "Confirm a direct upload",
+    tag = "Upload",
+    responses(
+        (status = 200, body = inline(JsonResponse<FinishResponse>))
+    )
+)]
+#[tracing::instrument]
+async fn confirm(
+    ValidUser(id): ValidUser,
+    Inject(service): Inject<UploadService>,
+    Json(request): Json<request::ConfirmRequest>,
+) -> JsonResponseType<FinishResponse> {
+    let resp = service.confirm(&id, request.key, request.hash).await?;
+    JsonResponse::ok(resp)
+}
+
 mod request {
     use application::system::service::upload_service::PartItem;
     use axum_typed_multipart::{FieldData, TryFromMultipart};
     use serde::Deserialize;
     use tempfile::NamedTempFile;
-    use utoipa::ToSchema;
+    use utoipa::{IntoParams, ToSchema};
 
     #[derive(TryFromMultipart, Debug, ToSchema)]
     pub(crate) struct UploadRequest {
@@ -151,20 +227,32 @@ mod request {
     #[derive(Debug, Deserialize, ToSchema)]
     pub(crate) struct StartChunkRequest {
         pub filename: String,
+        /// size of the whole file in bytes, lets an oversized upload fail before any part is sent
+        pub size: u64,
     }
 
     #[derive(TryFromMultipart, Debug, ToSchema)]
     pub(crate) struct ChunkRequest {
         pub key: String,
+        #[form_data(field_name = "uploadId")]
+        #[schema(rename = "uploadId")]
+        pub upload_id: String,
         #[form_data(field_name = "partNumber")]
         #[schema(rename = "partNumber")]
         pub part_number: u32,
-        #[form_data(limit = "2MiB")]
+        #[form_data(limit = "6MiB")]
         #[schema(value_type = Vec<u8>)]
-        /// max size: 2MiB
+        /// max size: 6MiB, S3 requires every part but the last to be at least 5MiB
         pub file: FieldData<NamedTempFile>,
     }
 
+    #[derive(Debug, Deserialize, IntoParams)]
+    pub(crate) struct ListChunksRequest {
+        pub key: String,
+        #[serde(rename = "uploadId")]
+        pub upload_id: String,
+    }
+
     #[derive(Debug, Deserialize, ToSchema)]
     pub(crate) struct FinishChunkRequest {
         pub filename: String,
@@ -174,14 +262,37 @@ mod request {
         #[serde(rename = "partList")]
         pub part_list: Vec<PartItem>,
     }
+
+    #[derive(Debug, Deserialize, ToSchema)]
+    pub(crate) struct PresignRequest {
+        pub filename: String,
+        #[serde(rename = "contentType")]
+        pub content_type: String,
+        pub size: u64,
+    }
+
+    #[derive(Debug, Deserialize, ToSchema)]
+    pub(crate) struct ConfirmRequest {
+        pub key: String,
+        /// blake3 hash of the content, checked when given
+        pub hash: Option<String>,
+    }
 }
 
-pub fn routing() -> OpenApiRouter<WebState> {
+/// Room for the form fields around the file of a chunk
+const CHUNK_FORM_OVERHEAD: usize = 1024 * 1024;
+
+pub fn routing(state: &WebState) -> OpenApiRouter<WebState> {
+    let config = state.provider().provide::<ConfigRef>();
+    let chunk_body_limit = config.upload.chunk_part_max_size as usize + CHUNK_FORM_OVERHEAD;
     OpenApiRouter::new()
         .routes(routes!(single).layer(DefaultBodyLimit::max(3 * 1024 * 1024)))
         .routes(routes!(image).layer(DefaultBodyLimit::max(3 * 1024 * 1024)))
-        .routes(routes!(chunk).layer(DefaultBodyLimit::max(3 * 1024 * 1024)))
+        .routes(routes!(chunk).layer(DefaultBodyLimit::max(chunk_body_limit)))
+        .routes(routes!(list_chunks))
         .routes(routes!(start_chunk))
         .routes(routes!(finish_chunk))
+        .routes(routes!(presign))
+        .routes(routes!(confirm))
         .permit_all(perms!(SYSTEM_FILE_UPLOAD))
 }
@@ -1,5 +1,12 @@
 database_error = Database error
 unsupported_image_format = Unsupported image format
+upload_not_found = Upload not found or expired
+upload_mismatch = Uploaded content does not match the announced size or hash
+upload_too_large = Upload is too large
+unsupported_file_type = Unsupported file type
+quota_exceeded = Storage quota exceeded
+file_quarantined = File was flagged by the virus scanner and quarantined
+file_scan_failed = File could not be scanned, please try again later
 illegal_header_value = Illegal header value
 authorized_user_not_found = Authorized user not found
 illegal_token = Illegal token
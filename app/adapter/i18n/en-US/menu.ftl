@@ -8,7 +8,11 @@ menu_file = Files
 menu_sched = Schedulers
 menu_bgworker = Bgworkers
 menu_access_log = Access Logs
+menu_security_event = Security Events
+menu_recycle_bin = Recycle Bin
 menu_stat = Stats
 menu_example = Examples
 
-menu_change_password = Change Password
\ No newline at end of file
+menu_change_password = Change Password
+menu_account_activity = Account Activity
+menu_my_tasks = My Tasks
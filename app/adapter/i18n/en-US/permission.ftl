@@ -14,6 +14,12 @@ perm_bgworker = Bgworker
 perm_department = Department  
 perm_cache = Cache
 perm_access_log = Access Log
+perm_security_event = Security Event
+perm_recycle_bin = Recycle Bin
+perm_restore = Restore
+perm_purge = Purge
+perm_retry = Retry
+perm_run = Run
 perm_upload = Upload
 perm_download = Download
 perm_update_password = Update Password
\ No newline at end of file
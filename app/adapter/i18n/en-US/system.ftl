@@ -1,3 +1,18 @@
 file_not_found = File not found
+fileFolder_not_found = Folder not found
+fileFolder_invalid_name = Folder names must be 1 to 64 characters long
+fileFolder_name_conflict = A folder with this name already exists here
+fileFolder_invalid_parent = A folder cannot be moved into itself or its subfolders
+fileShare_not_found = Share link not found
+fileShare_revoked = Share link has been revoked
+fileShare_expired = Share link has expired
+fileShare_exhausted = Share link has reached its download limit
+fileShare_password_required = Share link requires a password
+fileShare_password_incorrect = Incorrect share link password
 sched_not_found = Sched not found
-accessLog_not_found = Access log not found
\ No newline at end of file
+schedJob_not_found = Scheduled job not found
+schedJob_invalid_expr = Invalid schedule expression
+schedJob_invalid_timezone = Unknown timezone
+schedJob_invalid_params = Parameters must be valid JSON
+accessLog_not_found = Access log not found
+securityEvent_not_found = Security event not found
\ No newline at end of file
@@ -1,5 +1,12 @@
 database_error = 数据库错误
 unsupported_image_format = 不支持的图片格式 
+upload_not_found = 上传任务不存在或已过期
+upload_mismatch = 上传内容与声明的大小或哈希不一致
+upload_too_large = 上传文件过大
+unsupported_file_type = 不支持的文件类型
+quota_exceeded = 存储空间配额已用尽
+file_quarantined = 文件未通过病毒扫描，已被隔离
+file_scan_failed = 文件扫描失败，请稍后重试
 illegal_header_value = 非法的header值
 authorized_user_not_found = 未找到授权用户
 illegal_token = 非法的token
@@ -8,7 +8,11 @@ menu_file = 文件管理
 menu_sched = 定时任务
 menu_bgworker = 后台任务
 menu_access_log = 访问日志
+menu_security_event = 安全事件
+menu_recycle_bin = 回收站
 menu_stat = 系统信息
 menu_example = 示例页面
 
 menu_change_password = 修改密码
+menu_account_activity = 账号动态
+menu_my_tasks = 我的任务
@@ -14,6 +14,12 @@ perm_bgworker = 后台任务
 perm_department = 部门  
 perm_cache = 缓存
 perm_access_log = 访问日志
+perm_security_event = 安全事件
+perm_recycle_bin = 回收站
+perm_restore = 恢复
+perm_purge = 彻底删除
+perm_retry = 重试
+perm_run = 运行
 perm_upload = 上传
 perm_download = 下载
 perm_update_password = 更新密码
\ No newline at end of file
@@ -1,3 +1,18 @@
 file_not_found = 文件不存在
+fileFolder_not_found = 文件夹不存在
+fileFolder_invalid_name = 文件夹名称长度须为 1 到 64 个字符
+fileFolder_name_conflict = 同级目录下已存在同名文件夹
+fileFolder_invalid_parent = 不能将文件夹移动到自身或其子文件夹中
+fileShare_not_found = 分享链接不存在
+fileShare_revoked = 分享链接已撤销
+fileShare_expired = 分享链接已过期
+fileShare_exhausted = 分享链接下载次数已用完
+fileShare_password_required = 分享链接需要密码
+fileShare_password_incorrect = 分享链接密码错误
 sched_not_found = 定时任务不存在
-accessLog_not_found = 访问日志不存在
\ No newline at end of file
+schedJob_not_found = 定时任务不存在
+schedJob_invalid_expr = 运行周期格式错误
+schedJob_invalid_timezone = 未知的时区
+schedJob_invalid_params = 运行参数必须是合法的 JSON
+accessLog_not_found = 访问日志不存在
+securityEvent_not_found = 安全事件不存在
\ No newline at end of file
@@ -2,6 +2,7 @@ mod api;
 #[cfg(not(debug_assertions))]
 mod frontend;
 mod i18n;
+mod share;
 mod shared;
 mod upload;
 use axum::Router;
@@ -39,6 +40,7 @@ impl utoipa::Modify for SecurityAddon {
 pub fn routing(state: WebState, with_openapi: bool) -> Router {
     let (mut router, open_api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
         .nest("/api", api::routing(state.clone()))
+        .merge(share::routing())
         .with_state(state.clone())
         .merge(upload::routing(state))
         .route("/health", get(health))
@@ -0,0 +1,88 @@
+use application::{
+    shared::command_handler::CommandHandler as _,
+    system::command::download_file_share::{
+        DownloadFileShareCommand, DownloadFileShareCommandHandler,
+    },
+};
+use std::time::Duration;
+
+use axum::{Form, extract::Path, response::Redirect};
+use serde::Deserialize;
+use utoipa::ToSchema;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{
+    SHARE_PATH, WebState,
+    shared::{
+        error::WebError,
+        extractor::{client::Client, inject::Inject},
+        middleware::{api_error::api_error, rate_limit_ext::RateLimitRouterExt as _},
+    },
+};
+
+/// Share links are public, they sit outside `/api` and its authentication
+pub fn routing() -> OpenApiRouter<WebState> {
+    let router = OpenApiRouter::new()
+        .routes(routes!(download))
+        // passwords are guessed one request at a time, so the guesses are slowed down
+        .routes(routes!(unlock).rate_limit_layer(Duration::from_secs(3), 5))
+        .layer(axum::middleware::from_fn(api_error));
+    OpenApiRouter::new().nest(SHARE_PATH, router)
+}
+
+#[utoipa::path(
+    get,
+    params(("code" = String, Path)),
+    path = "/{code}",
+    summary = "Download a shared file",
+    tag = "System",
+    responses(
+        (status = 307, description = "Redirects to a short-lived storage link")
+    )
+)]
+#[tracing::instrument]
+async fn download(
+    Inject(command_handler): Inject<DownloadFileShareCommandHandler>,
+    Client(client): Client,
+    Path(code): Path<String>,
+) -> Result<Redirect, WebError> {
+    let command = DownloadFileShareCommand::builder()
+        .code(code)
+        .client(client)
+        .build();
+    let url = command_handler.handle(command).await?;
+    Ok(Redirect::temporary(&url))
+}
+
+/// Sent as a form so the password stays out of URLs, logs and browser history
+#[derive(Deserialize, ToSchema)]
+struct UnlockForm {
+    password: String,
+}
+
+#[utoipa::path(
+    post,
+    params(("code" = String, Path)),
+    path = "/{code}",
+    request_body(content = UnlockForm, content_type = "application/x-www-form-urlencoded"),
+    summary = "Download a password protected shared file",
+    tag = "System",
+    responses(
+        (status = 303, description = "Redirects to a short-lived storage link")
+    )
+)]
+#[tracing::instrument(skip(form))]
+async fn unlock(
+    Inject(command_handler): Inject<DownloadFileShareCommandHandler>,
+    Client(client): Client,
+    Path(code): Path<String>,
+    Form(form): Form<UnlockForm>,
+) -> Result<Redirect, WebError> {
+    let command = DownloadFileShareCommand::builder()
+        .code(code)
+        .password(form.password)
+        .client(client)
+        .build();
+    let url = command_handler.handle(command).await?;
+    Ok(Redirect::to(&url))
+}
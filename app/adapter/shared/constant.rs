@@ -1 +1,2 @@
 pub const UPLOAD_PATH: &str = "/uploads";
+pub const SHARE_PATH: &str = "/s";
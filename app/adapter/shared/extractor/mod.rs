@@ -1,3 +1,4 @@
 pub mod accept_language;
+pub mod client;
 pub mod inject;
 pub mod valid_user;
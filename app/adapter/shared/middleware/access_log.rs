@@ -1,7 +1,7 @@
 use std::net::SocketAddr;
 
 use application::{
-    re_export::ChronoTz,
+    re_export::{ChronoTz, ConfigRef},
     shared::{bgworker::record_access_log::RecordAccessLog, bgworker_impl::WorkerRegistry},
 };
 use axum::{
@@ -11,7 +11,13 @@ use axum::{
 };
 use tokio::time::Instant;
 
-use crate::{WebState, shared::extractor::valid_user::ValidUser};
+use crate::{
+    WebState,
+    shared::{
+        error::WebErrorData, extractor::valid_user::ValidUser,
+        middleware::body_capture::BodyCapture,
+    },
+};
 
 pub async fn access_log(State(state): State<WebState>, request: Request, next: Next) -> Response {
     let now = Instant::now();
@@ -33,9 +39,39 @@ pub async fn access_log(State(state): State<WebState>, request: Request, next: N
         .get::<ValidUser>()
         .cloned()
         .expect("Failed to get valid user");
+    let config = state.provider().provide::<ConfigRef>();
+    let access_log_config = &config.access_log;
+    let body_capture = (access_log_config.capture_body
+        && BodyCapture::matches_route(&access_log_config.capture_routes, &uri))
+    .then(|| {
+        BodyCapture::new(
+            access_log_config.capture_body_max_size,
+            &access_log_config.capture_statuses,
+            &access_log_config.redact_paths,
+        )
+    });
+    let (request, request_body) = match &body_capture {
+        Some(body_capture) => body_capture.buffer_request(request).await,
+        None => (request, None),
+    };
     let response = next.run(request).await;
     let status = response.status();
     let elapsed = now.elapsed();
+    let (response, request_body, response_body) =
+        match body_capture.filter(|body_capture| body_capture.matches_status(status)) {
+            Some(body_capture) => {
+                let (response, response_body) = body_capture.buffer_response(response).await;
+                // web errors are rendered by the outer api_error middleware, keep the error code instead
+                let response_body = response_body.or_else(|| {
+                    response
+                        .extensions()
+                        .get::<WebErrorData>()
+                        .map(|data| data.code.clone())
+                });
+                (response, request_body, response_body)
+            }
+            None => (response, None, None),
+        };
     let ct = state.provider().provide::<ChronoTz>();
     let task = RecordAccessLog::builder()
         .user_id(valid_user.0.to_string())
@@ -46,6 +82,8 @@ pub async fn access_log(State(state): State<WebState>, request: Request, next: N
         .status(status.as_u16() as i16)
         .elapsed(elapsed.as_millis() as i64)
         .occurred_at(ct.now())
+        .maybe_request_body(request_body)
+        .maybe_response_body(response_body)
         .build();
     if let Err(err) = WorkerRegistry::enqueue_record_access_log(task).await {
         tracing::error!(error = %err, "Failed to enqueue record_access_log");
@@ -0,0 +1,378 @@
+use axum::{
+    body::{Body, Bytes, HttpBody as _, to_bytes},
+    extract::Request,
+    http::{HeaderMap, StatusCode, header},
+    response::Response,
+};
+use serde_json::Value;
+
+const REDACTED: &str = "******";
+const TRUNCATED: &str = "...(truncated)";
+/// Keys matched after lowercasing and removing `_` and `-`
+const SENSITIVE_KEYS: [&str; 3] = ["password", "captchavalue", "token"];
+/// Bodies larger than this are never buffered, whatever the configured max size is
+const MAX_BUFFER_SIZE: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BodyKind {
+    Json,
+    Form,
+    Text,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+#[derive(Debug, Clone)]
+pub struct BodyCapture {
+    max_size: usize,
+    statuses: Vec<String>,
+    redact_paths: Vec<Vec<PathSegment>>,
+}
+
+impl BodyCapture {
+    pub fn new(max_size: usize, statuses: &[String], redact_paths: &[String]) -> Self {
+        let statuses = statuses
+            .iter()
+            .map(|status| status.trim().to_ascii_lowercase())
+            .filter(|status| !status.is_empty())
+            .collect();
+        let redact_paths = redact_paths
+            .iter()
+            .filter(|path| !path.trim().is_empty())
+            .filter_map(|path| {
+                let segments = parse_path(path);
+                if segments.is_none() {
+                    tracing::warn!(path, "Ignore invalid access log redact path");
+                }
+                segments
+            })
+            .collect();
+        Self {
+            max_size,
+            statuses,
+            redact_paths,
+        }
+    }
+
+    pub fn matches_route(routes: &[String], uri: &str) -> bool {
+        let mut routes = routes
+            .iter()
+            .map(|route| route.trim())
+            .filter(|route| !route.is_empty())
+            .peekable();
+        routes.peek().is_none() || routes.any(|route| uri.starts_with(route))
+    }
+
+    pub fn matches_status(&self, status: StatusCode) -> bool {
+        let status = status.as_u16();
+        self.statuses.is_empty()
+            || self
+                .statuses
+                .iter()
+                .any(|spec| match spec.strip_suffix("xx") {
+                    Some(class) => class
+                        .parse::<u16>()
+                        .is_ok_and(|class| status / 100 == class),
+                    None => spec.parse::<u16>().is_ok_and(|spec| spec == status),
+                })
+    }
+
+    pub async fn buffer_request(&self, request: Request) -> (Request, Option<String>) {
+        let Some(kind) = bufferable_kind(request.headers(), request.body()) else {
+            return (request, None);
+        };
+        let (parts, body) = request.into_parts();
+        match to_bytes(body, MAX_BUFFER_SIZE as usize).await {
+            Ok(bytes) => {
+                let captured = self.render(kind, &bytes);
+                (
+                    Request::from_parts(parts, Body::from(bytes)),
+                    Some(captured),
+                )
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, "Failed to buffer request body");
+                (Request::from_parts(parts, Body::empty()), None)
+            }
+        }
+    }
+
+    pub async fn buffer_response(&self, response: Response) -> (Response, Option<String>) {
+        let Some(kind) = bufferable_kind(response.headers(), response.body()) else {
+            return (response, None);
+        };
+        let (parts, body) = response.into_parts();
+        match to_bytes(body, MAX_BUFFER_SIZE as usize).await {
+            Ok(bytes) => {
+                let captured = self.render(kind, &bytes);
+                (
+                    Response::from_parts(parts, Body::from(bytes)),
+                    Some(captured),
+                )
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, "Failed to buffer response body");
+                (Response::from_parts(parts, Body::empty()), None)
+            }
+        }
+    }
+
+    fn render(&self, kind: BodyKind, bytes: &Bytes) -> String {
+        let body = match kind {
+            BodyKind::Json => self.redact_json(bytes),
+            BodyKind::Form => self.redact_form(bytes),
+            BodyKind::Text => None,
+        }
+        .unwrap_or_else(|| String::from_utf8_lossy(bytes).to_string());
+        truncate(body, self.max_size)
+    }
+
+    fn redact_json(&self, bytes: &[u8]) -> Option<String> {
+        let mut value = serde_json::from_slice::<Value>(bytes).ok()?;
+        redact_sensitive_keys(&mut value);
+        for path in &self.redact_paths {
+            redact_path(&mut value, path);
+        }
+        serde_json::to_string(&value).ok()
+    }
+
+    fn redact_form(&self, bytes: &[u8]) -> Option<String> {
+        let pairs = serde_urlencoded::from_bytes::<Vec<(String, String)>>(bytes).ok()?;
+        let pairs: Vec<(String, String)> = pairs
+            .into_iter()
+            .map(|(key, value)| {
+                let redacted = is_sensitive_key(&key)
+                    || self
+                        .redact_paths
+                        .iter()
+                        .any(|path| matches!(path.as_slice(), [PathSegment::Key(k)] if *k == key));
+                if redacted {
+                    (key, REDACTED.to_string())
+                } else {
+                    (key, value)
+                }
+            })
+            .collect();
+        serde_urlencoded::to_string(pairs).ok()
+    }
+}
+
+fn bufferable_kind(headers: &HeaderMap, body: &Body) -> Option<BodyKind> {
+    let size = body.size_hint().upper()?;
+    if size == 0 || size > MAX_BUFFER_SIZE {
+        return None;
+    }
+    let content_type = headers.get(header::CONTENT_TYPE)?.to_str().ok()?;
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    if mime == "application/json" || mime.ends_with("+json") {
+        Some(BodyKind::Json)
+    } else if mime == "application/x-www-form-urlencoded" {
+        Some(BodyKind::Form)
+    } else if mime.starts_with("text/") || mime == "application/xml" || mime.ends_with("+xml") {
+        Some(BodyKind::Text)
+    } else {
+        None
+    }
+}
+
+fn is_sensitive_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase().replace(['_', '-'], "");
+    SENSITIVE_KEYS
+        .iter()
+        .any(|sensitive| key.contains(sensitive))
+}
+
+fn redact_sensitive_keys(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_sensitive_key(key) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_sensitive_keys(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_sensitive_keys),
+        _ => {}
+    }
+}
+
+/// Parses a JSON path such as `$.user.id_card`, `$.items[*].secret` or `$.items[0]`
+fn parse_path(path: &str) -> Option<Vec<PathSegment>> {
+    let mut chars = path.trim().strip_prefix('$')?.chars().peekable();
+    let mut segments = Vec::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                let mut key = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c == '.' || c == '[' {
+                        break;
+                    }
+                    key.push(c);
+                    chars.next();
+                }
+                match key.as_str() {
+                    "" => return None,
+                    "*" => segments.push(PathSegment::Wildcard),
+                    _ => segments.push(PathSegment::Key(key)),
+                }
+            }
+            '[' => {
+                let mut index = String::new();
+                loop {
+                    match chars.next()? {
+                        ']' => break,
+                        c => index.push(c),
+                    }
+                }
+                match index.trim() {
+                    "*" => segments.push(PathSegment::Wildcard),
+                    index => segments.push(PathSegment::Index(index.parse().ok()?)),
+                }
+            }
+            _ => return None,
+        }
+    }
+    (!segments.is_empty()).then_some(segments)
+}
+
+fn redact_path(value: &mut Value, segments: &[PathSegment]) {
+    let Some((first, rest)) = segments.split_first() else {
+        *value = Value::String(REDACTED.to_string());
+        return;
+    };
+    match (first, value) {
+        (PathSegment::Key(key), Value::Object(map)) => {
+            if let Some(value) = map.get_mut(key) {
+                redact_path(value, rest);
+            }
+        }
+        (PathSegment::Index(index), Value::Array(items)) => {
+            if let Some(value) = items.get_mut(*index) {
+                redact_path(value, rest);
+            }
+        }
+        (PathSegment::Wildcard, Value::Object(map)) => {
+            map.values_mut().for_each(|value| redact_path(value, rest));
+        }
+        (PathSegment::Wildcard, Value::Array(items)) => {
+            items.iter_mut().for_each(|value| redact_path(value, rest));
+        }
+        _ => {}
+    }
+}
+
+fn truncate(mut body: String, max_size: usize) -> String {
+    if body.len() <= max_size {
+        return body;
+    }
+    let mut end = max_size;
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    body.truncate(end);
+    body.push_str(TRUNCATED);
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture(redact_paths: &[&str]) -> BodyCapture {
+        let redact_paths: Vec<String> = redact_paths.iter().map(|p| p.to_string()).collect();
+        BodyCapture::new(4096, &[], &redact_paths)
+    }
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(
+            parse_path("$.items[*].secret"),
+            Some(vec![
+                PathSegment::Key("items".to_string()),
+                PathSegment::Wildcard,
+                PathSegment::Key("secret".to_string()),
+            ])
+        );
+        assert_eq!(
+            parse_path("$.items[1]"),
+            Some(vec![
+                PathSegment::Key("items".to_string()),
+                PathSegment::Index(1)
+            ])
+        );
+        assert_eq!(parse_path("items.secret"), None);
+        assert_eq!(parse_path("$"), None);
+        assert_eq!(parse_path("$.items[x]"), None);
+    }
+
+    #[test]
+    fn test_redact_json_sensitive_keys() {
+        let body = br#"{"account":"admin","password":"123456","captcha_value":"42","nested":{"refresh_token":"abc","accessToken":"def"}}"#;
+        let redacted = capture(&[]).redact_json(body).unwrap();
+        let value: Value = serde_json::from_str(&redacted).unwrap();
+        assert_eq!(value["account"], "admin");
+        assert_eq!(value["password"], REDACTED);
+        assert_eq!(value["captcha_value"], REDACTED);
+        assert_eq!(value["nested"]["refresh_token"], REDACTED);
+        assert_eq!(value["nested"]["accessToken"], REDACTED);
+    }
+
+    #[test]
+    fn test_redact_json_paths() {
+        let body = br#"{"user":{"id_card":"110101","name":"Tom"},"items":[{"secret":"a"},{"secret":"b"}]}"#;
+        let redacted = capture(&["$.user.id_card", "$.items[*].secret"])
+            .redact_json(body)
+            .unwrap();
+        let value: Value = serde_json::from_str(&redacted).unwrap();
+        assert_eq!(value["user"]["id_card"], REDACTED);
+        assert_eq!(value["user"]["name"], "Tom");
+        assert_eq!(value["items"][0]["secret"], REDACTED);
+        assert_eq!(value["items"][1]["secret"], REDACTED);
+    }
+
+    #[test]
+    fn test_redact_form() {
+        let redacted = capture(&["$.phone"])
+            .redact_form(b"account=admin&password=123456&phone=10086")
+            .unwrap();
+        assert_eq!(redacted, "account=admin&password=******&phone=******");
+    }
+
+    #[test]
+    fn test_matches_status() {
+        let statuses = vec!["5xx".to_string(), "401".to_string()];
+        let capture = BodyCapture::new(4096, &statuses, &[]);
+        assert!(capture.matches_status(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(capture.matches_status(StatusCode::UNAUTHORIZED));
+        assert!(!capture.matches_status(StatusCode::OK));
+        assert!(BodyCapture::new(4096, &[], &[]).matches_status(StatusCode::OK));
+    }
+
+    #[test]
+    fn test_matches_route() {
+        let routes = vec!["/organization".to_string()];
+        assert!(BodyCapture::matches_route(&routes, "/organization/users"));
+        assert!(!BodyCapture::matches_route(&routes, "/system/files"));
+        assert!(BodyCapture::matches_route(&[], "/system/files"));
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("hello".to_string(), 10), "hello");
+        assert_eq!(truncate("hello".to_string(), 3), format!("hel{TRUNCATED}"));
+        assert_eq!(truncate("你好".to_string(), 4), format!("你{TRUNCATED}"));
+    }
+}
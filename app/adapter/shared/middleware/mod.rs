@@ -1,5 +1,6 @@
 pub mod access_log;
 pub mod api_error;
+mod body_capture;
 mod common;
 pub mod perm_router_ext;
 pub mod rate_limit_ext;
@@ -1,30 +1,30 @@
-use application::{re_export::WorkspaceRef, system::service::upload_service::UploadService};
+use std::ops::Range;
+
+use application::system::service::upload_service::UploadService;
 use axum::{
+    body::Body,
     extract::{OriginalUri, Request, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode, header},
     middleware::{self, Next},
     response::{IntoResponse as _, Response},
-    routing::get,
+    routing::{get, put},
 };
 
-use tower::util::ServiceExt as _;
-use tower_http::services::ServeDir;
 use utoipa_axum::router::OpenApiRouter;
 
 use crate::{UPLOAD_PATH, WebState};
 
 pub fn routing(state: WebState) -> OpenApiRouter {
-    let workspace = state.provider().provide::<WorkspaceRef>();
-    let serve_dir = ServeDir::new(workspace.upload_dir());
-    let router = OpenApiRouter::new()
-        .route(
-            "/{*path}",
-            get(move |req: Request<axum::body::Body>| async move { serve_dir.oneshot(req).await }),
-        )
-        .layer(middleware::from_fn_with_state(
-            state,
-            sign_required_middleware,
-        ));
+    let router = OpenApiRouter::new().route(
+        "/{*path}",
+        get(serve_object)
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                sign_required_middleware,
+            ))
+            .merge(put(presigned_write))
+            .with_state(state),
+    );
 
     let router = OpenApiRouter::new().nest(UPLOAD_PATH, router);
     #[cfg(feature = "trace_otlp")]
@@ -45,5 +45,166 @@ async fn sign_required_middleware(
     if !verified {
         return StatusCode::UNAUTHORIZED.into_response();
     }
-    next.run(request).await
+    // unscanned and quarantined content is never served, even with a valid link
+    let path = request.uri().path().trim_start_matches('/');
+    match service.servable(path).await {
+        Ok(true) => next.run(request).await,
+        Ok(false) => StatusCode::FORBIDDEN.into_response(),
+        Err(e) => {
+            tracing::error!(%e, "Failed to look up scan status");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Objects are read through the storage instead of straight from disk, so content
+/// encrypted at rest goes out decrypted. A single byte range is honoured
+async fn serve_object(
+    State(state): State<WebState>,
+    headers: HeaderMap,
+    request: Request,
+) -> Response {
+    let path = request.uri().path().trim_start_matches('/').to_string();
+    let service = state.provider().provide::<UploadService>();
+    let size = match service.object_size(&path).await {
+        Ok(Some(size)) => size,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            tracing::error!(%e, "Failed to look up object");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let requested = headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| parse_range(value, size))
+        .unwrap_or(ByteRange::Full);
+    let (status, range) = match requested {
+        ByteRange::Full => (StatusCode::OK, 0..size),
+        ByteRange::Partial(range) => (StatusCode::PARTIAL_CONTENT, range),
+        ByteRange::Unsatisfiable => {
+            return (
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                [(header::CONTENT_RANGE, format!("bytes */{size}"))],
+            )
+                .into_response();
+        }
+    };
+    let body = if range.is_empty() {
+        Body::empty()
+    } else {
+        match service.read_range(&path, range.clone()).await {
+            Ok(stream) => Body::from_stream(stream),
+            Err(e) => {
+                tracing::error!(%e, "Failed to read object");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        }
+    };
+    let mime = mime_guess::from_path(&path).first_or_octet_stream();
+    let mut response = (
+        status,
+        [
+            (header::CONTENT_TYPE, mime.to_string()),
+            (header::ACCEPT_RANGES, "bytes".to_string()),
+            (
+                header::CONTENT_LENGTH,
+                (range.end - range.start).to_string(),
+            ),
+        ],
+        body,
+    )
+        .into_response();
+    if status == StatusCode::PARTIAL_CONTENT
+        && let Ok(value) = format!("bytes {}-{}/{size}", range.start, range.end - 1).parse()
+    {
+        response.headers_mut().insert(header::CONTENT_RANGE, value);
+    }
+    response
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ByteRange {
+    Full,
+    Partial(Range<u64>),
+    Unsatisfiable,
+}
+
+/// Understands `bytes=start-end`, `bytes=start-` and `bytes=-suffix`, anything else
+/// (multiple ranges included) is answered with the whole object
+fn parse_range(value: &str, size: u64) -> ByteRange {
+    let Some(spec) = value.trim().strip_prefix("bytes=") else {
+        return ByteRange::Full;
+    };
+    let Some((start, end)) = spec.split_once('-') else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let (start, end) = (start.trim(), end.trim());
+    let range = match (start.parse::<u64>(), end.parse::<u64>()) {
+        (Ok(start), Ok(end)) if start <= end => start..(end + 1).min(size),
+        (Ok(start), Err(_)) if end.is_empty() => start..size,
+        (Err(_), Ok(suffix)) if start.is_empty() && suffix > 0 => size.saturating_sub(suffix)..size,
+        _ => return ByteRange::Full,
+    };
+    if range.start >= size {
+        return ByteRange::Unsatisfiable;
+    }
+    ByteRange::Partial(range)
+}
+
+/// Target of the presigned PUT urls of storages without an endpoint of their own
+async fn presigned_write(
+    State(state): State<WebState>,
+    uri: OriginalUri,
+    headers: HeaderMap,
+    request: Request,
+) -> Response {
+    let Some(size) = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok())
+    else {
+        return StatusCode::LENGTH_REQUIRED.into_response();
+    };
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let service = state.provider().provide::<UploadService>();
+    if !service.verify_write_url(uri.0, content_type, size) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    let path = request.uri().path().trim_start_matches('/').to_string();
+    let body = request.into_body().into_data_stream();
+    match service.write_presigned(&path, size, body).await {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(e) => {
+            tracing::error!(%e, "Failed to write presigned upload");
+            StatusCode::BAD_REQUEST.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-9", 100), ByteRange::Partial(0..10));
+        assert_eq!(parse_range("bytes=90-", 100), ByteRange::Partial(90..100));
+        assert_eq!(parse_range("bytes=-10", 100), ByteRange::Partial(90..100));
+        assert_eq!(parse_range("bytes=-200", 100), ByteRange::Partial(0..100));
+        assert_eq!(
+            parse_range("bytes=50-500", 100),
+            ByteRange::Partial(50..100)
+        );
+        assert_eq!(parse_range("bytes=100-", 100), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range("bytes=0-1,5-6", 100), ByteRange::Full);
+        assert_eq!(parse_range("bytes=9-0", 100), ByteRange::Full);
+        assert_eq!(parse_range("items=0-9", 100), ByteRange::Full);
+    }
 }
@@ -5,6 +5,7 @@ use domain::auth::event::AuthEvent;
 use domain::auth::port::token_issuer::TokenIssuerOutput;
 use domain::auth::port::token_issuer::TokenIssuerTrait;
 use domain::auth::port::token_store::TokenStoreTrait;
+use domain::auth::value_object::client_info::ClientInfo;
 use domain::organization::port::user_repository::UserRepository;
 use domain::shared::port::domain_repository::DomainRepository;
 use futures_util::TryFutureExt;
@@ -18,6 +19,16 @@ use utoipa::ToSchema;
 #[derive(Debug, Deserialize, Builder, ToSchema)]
 pub struct RefreshTokenCommand {
     token: String,
+    #[serde(skip)]
+    #[builder(default)]
+    client: ClientInfo,
+}
+
+impl RefreshTokenCommand {
+    pub fn with_client(mut self, client: ClientInfo) -> Self {
+        self.client = client;
+        self
+    }
 }
 
 #[derive(Debug, Builder)]
@@ -56,7 +67,10 @@ impl CommandHandler for RefreshTokenCommandHandler {
         )?;
         Ok(CommandResult::with_event(
             token_output,
-            AuthEvent::UserRefreshTokenSucceeded { id },
+            AuthEvent::UserRefreshTokenSucceeded {
+                id,
+                client: cmd.client,
+            },
         ))
     }
 }
@@ -2,12 +2,16 @@ use std::fmt::Debug;
 
 use crate::error::ApplicationError;
 use crate::shared::command_handler::{CommandHandler, CommandResult};
+use crate::shared::event::EVENT_BUS;
 use bon::Builder;
 use domain::auth::event::AuthEvent;
 use domain::auth::port::captcha_issuer::CaptchaIssuerTrait as _;
 use domain::auth::port::token_issuer::{TokenIssuerOutput, TokenIssuerTrait};
 use domain::auth::port::token_store::TokenStoreTrait;
+use domain::auth::value_object::client_info::ClientInfo;
+use domain::organization::entity::user::User;
 use domain::organization::port::user_repository::UserRepository;
+use domain::organization::value_object::user_id::UserId;
 use domain::shared::port::domain_repository::DomainRepository;
 use futures_util::TryFutureExt;
 use infrastructure::port::captcha_issuer_impl::CaptchaIssuerImpl;
@@ -24,6 +28,16 @@ pub struct SignInCommand {
     password: String,
     captcha_key: String,
     captcha_value: String,
+    #[serde(skip)]
+    #[builder(default)]
+    client: ClientInfo,
+}
+
+impl SignInCommand {
+    pub fn with_client(mut self, client: ClientInfo) -> Self {
+        self.client = client;
+        self
+    }
 }
 
 impl Debug for SignInCommand {
@@ -33,6 +47,7 @@ impl Debug for SignInCommand {
             .field("password", &"<RESERVED>")
             .field("captcha_key", &self.captcha_key)
             .field("captcha_value", &self.captcha_value)
+            .field("client", &self.client)
             .finish()
     }
 }
@@ -56,18 +71,62 @@ impl CommandHandler for SignInCommandHandler {
         &self,
         cmd: Self::Command,
     ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        let account = cmd.account.clone();
+        let client = cmd.client.clone();
+        // failures never reach `handle`, so the event is published here
+        let user = match self.load_user(&cmd).await {
+            Ok(user) => user,
+            Err(err) => return Err(login_failed(account, None, err, client)),
+        };
+        let id = user.id.clone();
+        match self.sign_in(user, &cmd.password).await {
+            Ok(token_output) => Ok(CommandResult::with_event(
+                token_output,
+                AuthEvent::UserLoginSucceeded { id, client },
+            )),
+            Err(err) => Err(login_failed(account, Some(id), err, client)),
+        }
+    }
+}
+
+fn login_failed(
+    account: String,
+    id: Option<UserId>,
+    err: ApplicationError,
+    client: ClientInfo,
+) -> ApplicationError {
+    EVENT_BUS.publish(
+        AuthEvent::UserLoginFailed {
+            account,
+            id,
+            reason: err.to_string(),
+            client,
+        }
+        .into(),
+    );
+    err
+}
+
+impl SignInCommandHandler {
+    async fn load_user(&self, cmd: &SignInCommand) -> Result<User, ApplicationError> {
         self.captcha_issuer
             .verify(&cmd.captcha_key, &cmd.captcha_value)
             .await?;
-        let mut user = self.user_repository.by_account(cmd.account).await?;
+        Ok(self.user_repository.by_account(cmd.account.clone()).await?)
+    }
+
+    async fn sign_in(
+        &self,
+        mut user: User,
+        password: &str,
+    ) -> Result<TokenIssuerOutput, ApplicationError> {
         user.assert_activated()?;
-        user.password.verify(&cmd.password)?;
+        user.password.verify(password)?;
         let token_output = self.token_issuer.generate(user.id.to_string())?;
         user.update_refresh_token(
             Some(token_output.refresh_token.clone()),
             Some(token_output.refresh_token_expires_at.naive_utc()),
         );
-        let id = user.id.clone();
         tokio::try_join!(
             self.token_store.store(
                 user.id.to_string(),
@@ -76,9 +135,6 @@ impl CommandHandler for SignInCommandHandler {
             ),
             self.user_repository.save(user).map_err(Into::into),
         )?;
-        Ok(CommandResult::with_event(
-            token_output,
-            AuthEvent::UserLoginSucceeded { id },
-        ))
+        Ok(token_output)
     }
 }
@@ -3,6 +3,7 @@ use crate::shared::command_handler::{CommandHandler, CommandResult};
 use bon::Builder;
 use domain::auth::event::AuthEvent;
 use domain::auth::port::token_store::TokenStoreTrait;
+use domain::auth::value_object::client_info::ClientInfo;
 use domain::organization::value_object::user_id::UserId;
 use domain::shared::port::domain_repository::DomainRepository;
 use futures_util::TryFutureExt;
@@ -14,6 +15,9 @@ use serde::Deserialize;
 #[derive(Debug, Deserialize, Builder)]
 pub struct SignOutCommand {
     id: UserId,
+    #[serde(skip)]
+    #[builder(default)]
+    client: ClientInfo,
 }
 
 #[derive(Debug, Builder)]
@@ -44,7 +48,10 @@ impl CommandHandler for SignOutCommandHandler {
         }
         Ok(CommandResult::with_event(
             (),
-            AuthEvent::UserLogoutSucceeded { id },
+            AuthEvent::UserLogoutSucceeded {
+                id,
+                client: cmd.client,
+            },
         ))
     }
 }
@@ -21,6 +21,9 @@ use infrastructure::port::token_store_impl::TokenStoreImpl;
 use infrastructure::shared::config::ConfigRef;
 use nject::injectable;
 
+/// Image variant shown wherever the portrait is rendered as an avatar
+const PORTRAIT_VARIANT: &str = "thumb";
+
 #[derive(Debug, Clone, Builder)]
 #[injectable]
 pub struct AuthService {
@@ -137,6 +140,10 @@ impl AuthService {
                 let upload_service = &self.upload_service;
 
                 if let Some(portrait) = &dto.portrait {
+                    dto.portrait_thumb = upload_service
+                        .presign_variant_url(portrait, PORTRAIT_VARIANT)
+                        .await
+                        .ok();
                     if let Ok(url) = upload_service.presign_url(portrait).await {
                         dto.portrait = Some(url);
                     } else {
@@ -142,21 +142,25 @@ pub struct JobContext {
 }
 
 const BGWORKER_TEMPLATE: &str = r#"use bg_worker_kit::WorkerManager;
-use bg_worker_kit::{Storage, StorageBackend};
+use bg_worker_kit::{EnqueueOptions, Queue};
 use infrastructure::shared::provider::Provider;
 use bg_worker_kit::error::WorkerError;
 use std::sync::OnceLock;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
 use bg_worker_kit::Stat;
 use bg_worker_kit::BackendExpose as _;
 use bg_worker_kit::WorkerTrait as _;
 use bg_worker_kit::State;
+use bg_worker_kit::{JobRecord, JobStore, TaskRecord};
+use bg_worker_kit::{Workflow, WorkflowJobRecord, WorkflowRecord, Workflows};
 
 {%- for job in jobs %}
 use crate::shared::bgworker::{{job}}::{{job | pascal_case}};
 {%- endfor %}
 
 {%- for job in jobs %}
-static {{job | screaming_snake_case}}_BACKEND: OnceLock<StorageBackend<{{job | pascal_case}}>> = OnceLock::new();
+static {{job | screaming_snake_case}}_QUEUE: OnceLock<Queue<{{job | pascal_case}}>> = OnceLock::new();
 {%- endfor %}
 
 const BGWORKER_NAMESPACES: &[Namespace] = &[
@@ -170,18 +174,45 @@ const BGWORKER_NAMESPACES: &[Namespace] = &[
 {%- endfor %}
 ];
 
+const JOBS_PAGE_SIZE: i64 = 10;
+
+static JOB_STORE: OnceLock<JobStore> = OnceLock::new();
+
+static WORKFLOWS: OnceLock<Workflows> = OnceLock::new();
+
 pub struct WorkerRegistry;
 
 impl WorkerRegistry {
 
     {%- for job in jobs %}
     pub async fn enqueue_{{job | snake_case}}(worker: {{job | pascal_case}}) -> Result<(), WorkerError> {
-        if let Some(backend) = {{job | screaming_snake_case}}_BACKEND.get() {
-            let mut backend = backend.to_owned();
-            backend.push(worker).await?;
+        if let Some(queue) = {{job | screaming_snake_case}}_QUEUE.get() {
+            queue.push(worker).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn enqueue_{{job | snake_case}}_at(worker: {{job | pascal_case}}, at: DateTime<Utc>) -> Result<(), WorkerError> {
+        if let Some(queue) = {{job | screaming_snake_case}}_QUEUE.get() {
+            queue.push_at(worker, at).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn enqueue_{{job | snake_case}}_in(worker: {{job | pascal_case}}, delay: Duration) -> Result<(), WorkerError> {
+        if let Some(queue) = {{job | screaming_snake_case}}_QUEUE.get() {
+            queue.push_in(worker, delay).await?;
         }
         Ok(())
     }
+
+    /// Returns the job id, `None` when a pending job with the same unique key swallowed this one
+    pub async fn enqueue_{{job | snake_case}}_with(worker: {{job | pascal_case}}, options: EnqueueOptions) -> Result<Option<String>, WorkerError> {
+        if let Some(queue) = {{job | screaming_snake_case}}_QUEUE.get() {
+            return queue.enqueue(worker, options).await;
+        }
+        Ok(None)
+    }
     {%- endfor %}
 
     pub fn list_namespaces() -> &'static [Namespace] {
@@ -192,8 +223,8 @@ impl WorkerRegistry {
         match ns {
             {%- for job in jobs %}
             {{job | pascal_case}}::NAME => {
-                if let Some(backend) = {{job | screaming_snake_case}}_BACKEND.get() {
-                    let backend = backend.to_owned();
+                if let Some(queue) = {{job | screaming_snake_case}}_QUEUE.get() {
+                    let backend = queue.backend().to_owned();
                     backend.stats().await.unwrap_or_default()
                 } else {
                     Stat::default()
@@ -204,39 +235,118 @@ impl WorkerRegistry {
         }
     }
 
-     pub async fn list_jobs(ns: &str, state: &State, page: i32) -> Vec<WorkerJob> {
+    /// One page of jobs in `state` with their total count
+    pub async fn list_jobs(ns: &str, state: &State, page: i64) -> Result<(i64, Vec<JobRecord>), WorkerError> {
+        let store = job_store()?;
         match ns {
             {%- for job in jobs %}
-            {{job | pascal_case}}::NAME => {
-                if let Some(backend) = {{job | screaming_snake_case}}_BACKEND.get() {
-                    let backend = backend.to_owned();
-                    let items = backend.list_jobs(state, page).await.unwrap_or_default();
-                    items.into_iter().filter_map(|item| {
-                        if let (Ok(args), Ok(parts)) = (
-                            serde_json::to_value(item.args),
-                            serde_json::to_value(item.parts.context),
-                        ) {
-                            Some(WorkerJob { args, parts })
-                        } else {
-                            None
-                        }
-                    }).collect()
-                } else {
-                   vec![]
-                }
-            }
+            {{job | pascal_case}}::NAME => store.list::<{{job | pascal_case}}>(state, page, JOBS_PAGE_SIZE).await,
+            {%- endfor %}
+            _ => Err(unknown_namespace(ns)),
+        }
+    }
+
+    pub async fn get_job(ns: &str, id: &str) -> Result<Option<JobRecord>, WorkerError> {
+        let store = job_store()?;
+        match ns {
+            {%- for job in jobs %}
+            {{job | pascal_case}}::NAME => store.get::<{{job | pascal_case}}>(id).await,
+            {%- endfor %}
+            _ => Err(unknown_namespace(ns)),
+        }
+    }
+
+    /// Requeues failed or killed jobs, all of them in the namespace when `ids` is empty
+    pub async fn retry_jobs(ns: &str, ids: &[String]) -> Result<u64, WorkerError> {
+        let store = job_store()?;
+        match ns {
+            {%- for job in jobs %}
+            {{job | pascal_case}}::NAME => store.retry::<{{job | pascal_case}}>(ids).await,
+            {%- endfor %}
+            _ => Err(unknown_namespace(ns)),
+        }
+    }
+
+    pub async fn delete_jobs(ns: &str, ids: &[String]) -> Result<u64, WorkerError> {
+        let store = job_store()?;
+        match ns {
+            {%- for job in jobs %}
+            {{job | pascal_case}}::NAME => store.delete::<{{job | pascal_case}}>(ids).await,
+            {%- endfor %}
+            _ => Err(unknown_namespace(ns)),
+        }
+    }
+
+    pub async fn update_job_payload(ns: &str, id: &str, payload: serde_json::Value) -> Result<bool, WorkerError> {
+        let store = job_store()?;
+        match ns {
+            {%- for job in jobs %}
+            {{job | pascal_case}}::NAME => store.update_payload::<{{job | pascal_case}}>(id, payload).await,
             {%- endfor %}
-            _ => vec![],
+            _ => Err(unknown_namespace(ns)),
         }
     }
 }
 
+impl WorkerRegistry {
+    pub async fn list_tasks(owner: &str, page: i64, page_size: i64) -> Result<(i64, Vec<TaskRecord>), WorkerError> {
+        job_store()?.list_tasks(owner, page, page_size).await
+    }
+
+    pub async fn get_task(owner: &str, id: &str) -> Result<Option<TaskRecord>, WorkerError> {
+        job_store()?.get_task(owner, id).await
+    }
+
+    pub async fn cancel_task(owner: &str, id: &str) -> Result<bool, WorkerError> {
+        job_store()?.cancel_task(owner, id).await
+    }
+}
+
+impl WorkerRegistry {
+    /// Returns the workflow id
+    pub async fn start_workflow(workflow: Workflow) -> Result<String, WorkerError> {
+        workflows()?.start(workflow).await
+    }
+
+    pub async fn list_workflows(page: i64) -> Result<(i64, Vec<WorkflowRecord>), WorkerError> {
+        workflows()?.list(page, JOBS_PAGE_SIZE).await
+    }
+
+    /// The workflow with the jobs it pushed so far
+    pub async fn get_workflow(id: &str) -> Result<Option<(WorkflowRecord, Vec<WorkflowJobRecord>)>, WorkerError> {
+        let workflows = workflows()?;
+        let Some(workflow) = workflows.get(id).await? else {
+            return Ok(None);
+        };
+        let jobs = workflows.jobs(id).await?;
+        Ok(Some((workflow, jobs)))
+    }
+}
+
+fn job_store() -> Result<&'static JobStore, WorkerError> {
+    JOB_STORE
+        .get()
+        .ok_or_else(|| WorkerError::Custom("bgworkers are not registered".to_string()))
+}
+
+fn workflows() -> Result<&'static Workflows, WorkerError> {
+    WORKFLOWS
+        .get()
+        .ok_or_else(|| WorkerError::Custom("bgworkers are not registered".to_string()))
+}
+
+fn unknown_namespace(ns: &str) -> WorkerError {
+    WorkerError::Custom(format!("unknown bgworker namespace: {ns}"))
+}
+
 pub fn register_bgworkers(manager: WorkerManager, provider: Provider) -> WorkerManager {
+    JOB_STORE.set(manager.job_store()).expect("Failed to set job store");
+    WORKFLOWS.set(manager.workflows()).expect("Failed to set workflows");
     {%- for job in jobs %}
-    let (manager, backend) = manager.register::<{{job | pascal_case}}>(
+    let (manager, queue) = manager.register::<{{job | pascal_case}}>(
         provider.clone(),
     );
-    {{job | screaming_snake_case}}_BACKEND.set(backend).expect("Failed to set backend");
+    {{job | screaming_snake_case}}_QUEUE.set(queue).expect("Failed to set queue");
     tracing::info!("Worker [{{job | pascal_case}}] has been registered");
     {%- endfor %}
    
@@ -257,6 +367,10 @@ use sched_kit::ScheduledJob;
 use infrastructure::port::sched_receiver_impl::SchedReceiverImpl;
 #[allow(unused_imports)]
 use infrastructure::shared::config::ConfigRef;
+#[allow(unused_imports)]
+use nject::injectable;
+#[allow(unused_imports)]
+use sched_kit::JobSetting;
 
 pub const SCHEDULER_JOBS: &[SchedulerJob] = &[
 {%- for job in jobs %}
@@ -282,4 +396,46 @@ pub async fn register_scheduled_jobs(
     {%- endfor %}
     Ok(())
 }
+
+/// Every scheduled job, to run one by hand
+#[derive(Clone)]
+#[injectable]
+pub struct ScheduledJobRunner {
+    receiver: SchedReceiverImpl,
+    config: ConfigRef,
+    {%- for job in jobs %}
+    {{job}}: crate::shared::scheduler_job::{{job}}::{{job | pascal_case}},
+    {%- endfor %}
+}
+
+impl std::fmt::Debug for ScheduledJobRunner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScheduledJobRunner").finish_non_exhaustive()
+    }
+}
+
+impl ScheduledJobRunner {
+    /// Runs the job `key` in the background on behalf of `triggered_by`, false
+    /// when no job has that key
+    pub fn run(&self, key: &str, setting: JobSetting, triggered_by: &str) -> bool {
+        #[allow(unused)]
+        let receiver = self.receiver.clone();
+        #[allow(unused)]
+        let timezone = self.config.timezone;
+        #[allow(unused)]
+        let triggered_by = triggered_by.to_string();
+        match key {
+            {%- for job in jobs %}
+            "{{job}}" => {
+                let job = self.{{job}}.clone();
+                tokio::spawn(async move {
+                    sched_kit::cron_tab::run_now(&receiver, "{{job}}", &job, &setting, timezone, &triggered_by).await;
+                });
+            }
+            {%- endfor %}
+            _ => return false,
+        }
+        true
+    }
+}
 "#;
@@ -1,3 +1,4 @@
+use bg_worker_kit::error::WorkerError;
 use cache_kit::error::CacheError;
 use domain::{
     auth::error::AuthError,
@@ -17,6 +18,27 @@ pub enum ApplicationError {
     #[error("unsupported_image_format")]
     UnsupportedImageFormat,
 
+    #[error("upload_not_found")]
+    UploadNotFound,
+
+    #[error("upload_mismatch")]
+    UploadMismatch,
+
+    #[error("upload_too_large")]
+    UploadTooLarge,
+
+    #[error("unsupported_file_type")]
+    UnsupportedFileType,
+
+    #[error("quota_exceeded")]
+    QuotaExceeded,
+
+    #[error("file_quarantined")]
+    FileQuarantined,
+
+    #[error("file_scan_failed")]
+    FileScanFailed,
+
     #[error("illegal_token")]
     IllegalToken,
 
@@ -67,4 +89,7 @@ pub enum ApplicationError {
 
     #[error(transparent)]
     Password(#[from] PasswordError),
+
+    #[error(transparent)]
+    Worker(#[from] WorkerError),
 }
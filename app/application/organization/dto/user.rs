@@ -8,6 +8,8 @@ pub struct UserDto {
     pub id: String,
     pub account: String,
     pub portrait: Option<String>,
+    /// Thumbnail of the portrait for avatars, the full resolution one is only needed for editing
+    pub portrait_thumb: Option<String>,
     pub name: String,
     pub role_ids: Vec<RoleId>,
     pub role_names: Vec<String>,
@@ -23,7 +23,7 @@ impl QueryHandler for OptionRolesQueryHandler {
         let options = sqlx::query_as!(
             OptionStringDto,
             r#"
-        SELECT name as label, id as value FROM _roles ORDER BY updated_at DESC
+        SELECT name as label, id as value FROM _roles WHERE deleted_at IS NULL ORDER BY updated_at DESC
         "#
         )
         .fetch_all(&self.pool)
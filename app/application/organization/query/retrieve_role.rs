@@ -33,7 +33,7 @@ impl QueryHandler for RetrieveRoleQueryHandler {
             r#"
             SELECT id, name, menus as "menus: Vec<Menu>", permissions as "permissions: Vec<Permission>", privileged, enabled, created_at, updated_at
             FROM _roles
-            WHERE id = $1
+            WHERE id = $1 AND deleted_at IS NULL
             LIMIT 1
         "#,
             &query.id,
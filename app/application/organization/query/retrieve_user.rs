@@ -34,6 +34,7 @@ impl QueryHandler for RetrieveUserQueryHandler {
             u.id as id,
             u.account as account,
             u.portrait as portrait,
+            u.portrait as portrait_thumb,
             u.name as name,
             u.role_ids as "role_ids: Vec<RoleId>",
             u.privileged as privileged,
@@ -42,8 +43,8 @@ impl QueryHandler for RetrieveUserQueryHandler {
             u.updated_at as updated_at,
             COALESCE(array_agg(r.name) FILTER (WHERE r.name IS NOT NULL), '{}') as "role_names!: Vec<String>"
         FROM _users as u
-        LEFT JOIN _roles as r ON r.id = ANY(u.role_ids)
-        WHERE u.id = $1
+        LEFT JOIN _roles as r ON r.id = ANY(u.role_ids) AND r.deleted_at IS NULL
+        WHERE u.id = $1 AND u.deleted_at IS NULL
         GROUP BY u.id
         LIMIT 1
         "#,
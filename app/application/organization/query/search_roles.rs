@@ -63,7 +63,8 @@ impl QueryHandler for SearchRolesQueryHandler {
             r#"
             SELECT COUNT(*) AS "count!"
             FROM _roles
-            WHERE ($1::text IS NULL OR name LIKE CONCAT('%', $1, '%'))
+            WHERE deleted_at IS NULL
+                AND ($1::text IS NULL OR name LIKE CONCAT('%', $1, '%'))
                 AND ($2::boolean IS NULL OR privileged = $2)
                 AND ($3::boolean IS NULL OR enabled = $3)
                 AND ($4::integer IS NULL OR $4 = ANY(menus))
@@ -84,7 +85,8 @@ impl QueryHandler for SearchRolesQueryHandler {
             r#"
         SELECT id, name, menus as "menus: Vec<Menu>", permissions as "permissions: Vec<Permission>", privileged, enabled, created_at, updated_at
         FROM _roles
-        WHERE ($1::text IS NULL OR name LIKE CONCAT('%', $1, '%'))
+        WHERE deleted_at IS NULL
+            AND ($1::text IS NULL OR name LIKE CONCAT('%', $1, '%'))
             AND ($2::boolean IS NULL OR privileged = $2)
             AND ($3::boolean IS NULL OR enabled = $3)
             AND ($4::integer IS NULL OR $4 = ANY(menus))
@@ -60,7 +60,8 @@ impl QueryHandler for SearchUsersQueryHandler {
             r#"
             SELECT COUNT(*) as "count!"
             FROM _users
-            WHERE ($1::text IS NULL OR account LIKE CONCAT('%', $1, '%'))
+            WHERE deleted_at IS NULL
+                AND ($1::text IS NULL OR account LIKE CONCAT('%', $1, '%'))
                 AND ($2::text IS NULL OR name LIKE CONCAT('%', $2, '%'))
                 AND ($3::boolean IS NULL OR privileged = $3)
                 AND ($4::boolean IS NULL OR enabled = $4)
@@ -83,6 +84,7 @@ impl QueryHandler for SearchUsersQueryHandler {
             u.id as id,
             u.account as account,
             u.portrait as portrait,
+            u.portrait as portrait_thumb,
             u.name as name,
             u.role_ids as "role_ids: Vec<RoleId>",
             u.privileged as privileged,
@@ -91,8 +93,9 @@ impl QueryHandler for SearchUsersQueryHandler {
             u.updated_at as updated_at,
             COALESCE(array_agg(r.name) FILTER (WHERE r.name IS NOT NULL), '{}') as "role_names!: Vec<String>"
         FROM _users as u
-        LEFT JOIN _roles as r ON r.id = ANY(u.role_ids)
-        WHERE ($1::text IS NULL OR u.account LIKE CONCAT('%', $1, '%'))
+        LEFT JOIN _roles as r ON r.id = ANY(u.role_ids) AND r.deleted_at IS NULL
+        WHERE u.deleted_at IS NULL
+            AND ($1::text IS NULL OR u.account LIKE CONCAT('%', $1, '%'))
             AND ($2::text IS NULL OR u.name LIKE CONCAT('%', $2, '%'))
             AND ($3::boolean IS NULL OR u.privileged = $3)
             AND ($4::boolean IS NULL OR u.enabled = $4)
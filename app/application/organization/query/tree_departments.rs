@@ -37,6 +37,7 @@ impl QueryHandler for TreeDepartmentsQueryHandler {
             r#"
         SELECT id, name, code, parent_code
         FROM _departments
+        WHERE deleted_at IS NULL
         "#,
         )
         .fetch_all(&self.pool)
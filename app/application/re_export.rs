@@ -1,4 +1,8 @@
-pub use bg_worker_kit::State;
+pub use bg_worker_kit::{
+    EnqueueOptions, FailurePolicy, JobRecord, State, TaskRecord, Workflow, WorkflowJobRecord,
+    WorkflowRecord,
+};
 pub use infrastructure::shared::chrono_tz::ChronoTz;
+pub use infrastructure::shared::config::ConfigRef;
 pub use infrastructure::shared::provider;
 pub use infrastructure::shared::workspace::WorkspaceRef;
@@ -0,0 +1,47 @@
+use bg_worker_kit::error::{Result, WorkerError};
+use bg_worker_kit::{Progress, WorkerTrait};
+use bon::Builder;
+use infrastructure::shared::provider::Provider;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    error::ApplicationError,
+    system::service::{export_service::ExportService, upload_service::UploadService},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Builder)]
+pub struct ExportAccessLogs {
+    from: Option<chrono::NaiveDateTime>,
+    to: Option<chrono::NaiveDateTime>,
+}
+
+impl WorkerTrait for ExportAccessLogs {
+    type State = Provider;
+
+    const NAME: &'static str = "export_access_logs";
+
+    const CONCURRENCY: usize = 1;
+
+    const RETRIES: usize = 1;
+
+    const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30 * 60);
+
+    async fn execute(params: Self, state: &Self::State, progress: &Progress) -> Result<()> {
+        let export_service = state.provide::<ExportService>();
+        let path = state
+            .provide::<UploadService>()
+            .export_path(&format!("access_logs_{}.csv", progress.job_id()));
+        let rows = export_service
+            .export_access_logs(&path, params.from, params.to, progress)
+            .await
+            .map_err(|e| match e {
+                ApplicationError::Worker(err) => err,
+                err => WorkerError::Custom(err.to_string()),
+            })?;
+        progress
+            .result(&json!({ "path": path, "name": "access_logs.csv", "rows": rows }))
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use bg_worker_kit::error::{Result, WorkerError};
+use bg_worker_kit::{Backoff, Progress, WorkerTrait};
+use bon::Builder;
+use infrastructure::shared::provider::Provider;
+use serde::{Deserialize, Serialize};
+
+use crate::system::service::upload_service::UploadService;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Builder)]
+pub struct GenerateImageVariants {
+    path: String,
+}
+
+impl WorkerTrait for GenerateImageVariants {
+    type State = Provider;
+
+    const NAME: &'static str = "generate_image_variants";
+
+    const CONCURRENCY: usize = 2;
+
+    const RETRIES: usize = 3;
+
+    const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+    const BACKOFF: Backoff = Backoff::Exponential {
+        base: std::time::Duration::from_secs(2),
+        max: std::time::Duration::from_secs(60),
+        jitter: true,
+    };
+
+    async fn execute(params: Self, state: &Self::State, _progress: &Progress) -> Result<()> {
+        let upload_service = state.provide::<UploadService>();
+        upload_service
+            .generate_variants(&params.path)
+            .await
+            .map_err(|e| WorkerError::Custom(e.to_string()))?;
+        Ok(())
+    }
+}
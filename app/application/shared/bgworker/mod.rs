@@ -1 +1,3 @@
+pub mod export_access_logs;
+pub mod generate_image_variants;
 pub mod record_access_log;
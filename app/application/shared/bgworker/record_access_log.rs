@@ -1,5 +1,5 @@
-use bg_worker_kit::WorkerTrait;
 use bg_worker_kit::error::{Result, WorkerError};
+use bg_worker_kit::{Progress, WorkerTrait};
 use bon::Builder;
 use infrastructure::shared::provider::Provider;
 use serde::{Deserialize, Serialize};
@@ -19,6 +19,8 @@ pub struct RecordAccessLog {
     status: i16,
     elapsed: i64,
     occurred_at: chrono::NaiveDateTime,
+    request_body: Option<String>,
+    response_body: Option<String>,
 }
 
 impl WorkerTrait for RecordAccessLog {
@@ -32,7 +34,7 @@ impl WorkerTrait for RecordAccessLog {
 
     const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
 
-    async fn execute(params: Self, state: &Self::State) -> Result<()> {
+    async fn execute(params: Self, state: &Self::State, _progress: &Progress) -> Result<()> {
         let command = CreateAccessLogCommand::builder()
             .user_id(params.user_id)
             .method(params.method)
@@ -42,6 +44,8 @@ impl WorkerTrait for RecordAccessLog {
             .status(params.status)
             .elapsed(params.elapsed)
             .occurred_at(params.occurred_at)
+            .maybe_request_body(params.request_body)
+            .maybe_response_body(params.response_body)
             .build();
         let command_handler = state.provide::<CreateAccessLogCommandHandler>();
         command_handler
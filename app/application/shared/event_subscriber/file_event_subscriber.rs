@@ -0,0 +1,99 @@
+use crate::{
+    shared::event::Event,
+    system::service::file_service::{FileOwner, FileService},
+};
+use domain::system::{entity::file::File, event::SystemEvent};
+use event_kit::{EventSubscriber, error::Result};
+use nject::injectable;
+
+const FILE_OWNER: &str = "file";
+/// A file filed into a folder or tagged is kept by the library itself
+const LIBRARY_FIELD: &str = "library";
+const FILE_SHARE_OWNER: &str = "file_share";
+const FILE_SHARE_FIELD: &str = "file";
+
+/// Keeps the file references of the file library and share links, a file
+/// nothing refers to is cleaned up
+#[derive(Clone)]
+#[injectable]
+pub struct FileEventSubscriber {
+    file_service: FileService,
+}
+
+impl FileEventSubscriber {
+    async fn set_library_references(&self, file: &File) {
+        let owner = FileOwner::builder()
+            .owner_type(FILE_OWNER)
+            .owner_id(file.id.to_string())
+            .field(LIBRARY_FIELD)
+            .build();
+        let ids = if file.folder_id.is_some() || !file.tags.is_empty() {
+            Vec::from([file.id.to_string()])
+        } else {
+            Vec::with_capacity(0)
+        };
+        if let Err(err) = self.file_service.set_file_references(&owner, &ids).await {
+            tracing::error!(id = ?file.id, error = %err, "Failed to set library file references");
+        }
+    }
+}
+
+impl EventSubscriber<Event> for FileEventSubscriber {
+    async fn on_received(&self, event: Event) -> Result<()> {
+        let Event::System(e) = event else {
+            return Ok(());
+        };
+        match e {
+            SystemEvent::FilesUpdated { items } => {
+                for item in items {
+                    if item.before.folder_id != item.after.folder_id
+                        || item.before.tags != item.after.tags
+                    {
+                        self.set_library_references(&item.after).await;
+                    }
+                }
+            }
+            SystemEvent::FilesPurged { items } => {
+                let ids = items
+                    .into_iter()
+                    .map(|item| item.id.to_string())
+                    .collect::<Vec<_>>();
+                if let Err(err) = self.file_service.remove_references(FILE_OWNER, &ids).await {
+                    tracing::error!(error = %err, "FilesPurged: failed to remove file references");
+                }
+                if let Err(err) = self.file_service.remove_file_references(&ids).await {
+                    tracing::error!(error = %err, "FilesPurged: failed to remove references to files");
+                }
+            }
+            SystemEvent::FileSharesCreated { items } => {
+                for item in items {
+                    let owner = FileOwner::builder()
+                        .owner_type(FILE_SHARE_OWNER)
+                        .owner_id(item.id.to_string())
+                        .field(FILE_SHARE_FIELD)
+                        .build();
+                    let ids = Vec::from([item.file_id.to_string()]);
+                    if let Err(err) = self.file_service.set_file_references(&owner, &ids).await {
+                        tracing::error!(id = ?item.id, error = %err, "Failed to set share file references");
+                    }
+                }
+            }
+            // a revoked link can't be opened anymore, its file may go
+            SystemEvent::FileSharesRevoked { items } => {
+                let ids = items
+                    .into_iter()
+                    .map(|item| item.after.id.to_string())
+                    .collect::<Vec<_>>();
+                if let Err(err) = self
+                    .file_service
+                    .remove_references(FILE_SHARE_OWNER, &ids)
+                    .await
+                {
+                    tracing::error!(error = %err, "FileSharesRevoked: failed to remove file references");
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
@@ -1,2 +1,5 @@
+pub mod file_event_subscriber;
 pub mod log_event_subscriber;
 pub mod organization_event_subscriber;
+pub mod sched_event_subscriber;
+pub mod security_event_subscriber;
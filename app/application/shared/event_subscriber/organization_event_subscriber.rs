@@ -3,18 +3,22 @@ use crate::{
         search_roles::SearchRolesQueryHandler, search_users::SearchUsersQueryHandler,
     },
     shared::event::Event,
-    system::service::file_service::FileService,
+    system::service::file_service::{FileOwner, FileService},
 };
 use bon::Builder;
 use domain::auth::port::menu_resolver::MenuResolver;
 use domain::{
-    auth::port::permission_resolver::PermissionResolver, organization::event::OrganizationEvent,
+    auth::port::permission_resolver::PermissionResolver,
+    organization::{entity::user::User, event::OrganizationEvent},
 };
 use event_kit::{EventSubscriber, error::Result};
 use infrastructure::port::menu_resolver_impl::MenuResolverImpl;
 use infrastructure::port::permission_resolver_impl::PermissionResolverImpl;
 use nject::injectable;
 
+const USER_OWNER: &str = "user";
+const PORTRAIT_FIELD: &str = "portrait";
+
 #[derive(Clone, Builder)]
 #[injectable]
 pub struct OrganizationEventSubscriber {
@@ -26,12 +30,25 @@ pub struct OrganizationEventSubscriber {
 }
 
 impl OrganizationEventSubscriber {
+    async fn set_portrait_references(&self, user: &User) {
+        let owner = FileOwner::builder()
+            .owner_type(USER_OWNER)
+            .owner_id(user.id.to_string())
+            .field(PORTRAIT_FIELD)
+            .build();
+        let paths = user.portrait.iter().cloned().collect::<Vec<_>>();
+        if let Err(err) = self.file_service.set_references(&owner, &paths).await {
+            tracing::error!(id = ?user.id, error = %err, "Failed to set portrait file references");
+        }
+    }
+
     fn is_users_changed(event: &OrganizationEvent) -> bool {
         matches!(
             event,
             OrganizationEvent::UsersCreated { .. }
                 | OrganizationEvent::UsersUpdated { .. }
                 | OrganizationEvent::UsersDeleted { .. }
+                | OrganizationEvent::UsersRestored { .. }
         )
     }
     fn is_roles_changed(event: &OrganizationEvent) -> bool {
@@ -40,6 +57,7 @@ impl OrganizationEventSubscriber {
             OrganizationEvent::RolesCreated { .. }
                 | OrganizationEvent::RolesUpdated { .. }
                 | OrganizationEvent::RolesDeleted { .. }
+                | OrganizationEvent::RolesRestored { .. }
         )
     }
     fn is_permission_changed(event: &OrganizationEvent) -> bool {
@@ -47,8 +65,10 @@ impl OrganizationEventSubscriber {
             event,
             OrganizationEvent::UsersUpdated { .. }
                 | OrganizationEvent::UsersDeleted { .. }
+                | OrganizationEvent::UsersRestored { .. }
                 | OrganizationEvent::RolesUpdated { .. }
                 | OrganizationEvent::RolesDeleted { .. }
+                | OrganizationEvent::RolesRestored { .. }
         )
     }
 }
@@ -72,47 +92,25 @@ impl EventSubscriber<Event> for OrganizationEventSubscriber {
             }
             match e {
                 OrganizationEvent::UsersCreated { items } => {
-                    let paths = items
-                        .into_iter()
-                        .filter_map(|item| item.portrait.clone())
-                        .collect::<Vec<_>>();
-                    if let Err(err) = self.file_service.set_files_used(&paths).await {
-                        tracing::error!(error = %err, "UsersCreated: failed to set files used");
+                    for item in items {
+                        self.set_portrait_references(&item).await;
                     }
                 }
                 OrganizationEvent::UsersUpdated { items } => {
-                    let (mut unused_paths, mut used_paths) = (Vec::new(), Vec::new());
-
                     for item in items {
-                        match (&item.before.portrait, &item.after.portrait) {
-                            (Some(before), Some(after)) if before != after => {
-                                // User changed portrait
-                                unused_paths.push(before.clone());
-                                used_paths.push(after.clone());
-                            }
-                            (Some(before), None) => {
-                                unused_paths.push(before.clone()); // User deleted portrait
-                            }
-                            (None, Some(after)) => {
-                                used_paths.push(after.clone()); // User added portrait
-                            }
-                            _ => {} // No change
+                        if item.before.portrait != item.after.portrait {
+                            self.set_portrait_references(&item.after).await;
                         }
                     }
-                    if let Err(err) = self.file_service.set_files_unused(&unused_paths).await {
-                        tracing::error!(error = %err, "UsersUpdated: failed to set files unused");
-                    }
-                    if let Err(err) = self.file_service.set_files_used(&used_paths).await {
-                        tracing::error!(error = %err, "UsersUpdated: failed to set files used");
-                    }
                 }
-                OrganizationEvent::UsersDeleted { items } => {
-                    let paths = items
+                // a deleted user keeps its portrait until it leaves the recycle bin
+                OrganizationEvent::UsersPurged { items } => {
+                    let ids = items
                         .into_iter()
-                        .filter_map(|item| item.portrait.clone())
+                        .map(|item| item.id.to_string())
                         .collect::<Vec<_>>();
-                    if let Err(err) = self.file_service.set_files_unused(&paths).await {
-                        tracing::error!(error = %err, "UsersDeleted: failed to set files unused");
+                    if let Err(err) = self.file_service.remove_references(USER_OWNER, &ids).await {
+                        tracing::error!(error = %err, "UsersPurged: failed to remove file references");
                     }
                 }
                 _ => {}
@@ -0,0 +1,24 @@
+use std::sync::{Arc, LazyLock};
+
+use crate::shared::event::Event;
+use domain::system::event::SystemEvent;
+use event_kit::{EventSubscriber, error::Result};
+use nject::injectable;
+use tokio::sync::Notify;
+
+/// Reloads the scheduled job settings of this instance, other instances pick a
+/// change up on their next periodic reload
+pub static SCHED_RELOAD: LazyLock<Arc<Notify>> = LazyLock::new(|| Arc::new(Notify::new()));
+
+#[derive(Clone)]
+#[injectable]
+pub struct SchedEventSubscriber;
+
+impl EventSubscriber<Event> for SchedEventSubscriber {
+    async fn on_received(&self, event: Event) -> Result<()> {
+        if let Event::System(SystemEvent::SchedJobsUpdated { .. }) = event {
+            SCHED_RELOAD.notify_one();
+        }
+        Ok(())
+    }
+}
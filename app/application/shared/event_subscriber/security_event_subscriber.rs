@@ -0,0 +1,76 @@
+use crate::{
+    shared::{command_handler::CommandHandler as _, event::Event},
+    system::command::create_security_event::{
+        CreateSecurityEventCommand, CreateSecurityEventCommandHandler,
+    },
+};
+use domain::{
+    auth::event::AuthEvent,
+    system::{event::SystemEvent, value_object::security_event_kind::SecurityEventKind},
+};
+use event_kit::{EventSubscriber, error::Result};
+use infrastructure::shared::chrono_tz::ChronoTz;
+use nject::injectable;
+
+#[derive(Clone)]
+#[injectable]
+pub struct SecurityEventSubscriber {
+    command_handler: CreateSecurityEventCommandHandler,
+    ct: ChronoTz,
+}
+
+impl EventSubscriber<Event> for SecurityEventSubscriber {
+    async fn on_received(&self, event: Event) -> Result<()> {
+        let builder = CreateSecurityEventCommand::builder().occurred_at(self.ct.now());
+        let command = match event {
+            Event::Auth(AuthEvent::UserLoginSucceeded { id, client }) => builder
+                .user_id(id)
+                .kind(SecurityEventKind::LoginSucceeded)
+                .succeed(true)
+                .client(client)
+                .build(),
+            Event::Auth(AuthEvent::UserLoginFailed {
+                account,
+                id,
+                reason,
+                client,
+            }) => builder
+                .maybe_user_id(id)
+                .account(account)
+                .kind(SecurityEventKind::LoginFailed)
+                .succeed(false)
+                .reason(reason)
+                .client(client)
+                .build(),
+            Event::Auth(AuthEvent::UserRefreshTokenSucceeded { id, client }) => builder
+                .user_id(id)
+                .kind(SecurityEventKind::TokenRefreshed)
+                .succeed(true)
+                .client(client)
+                .build(),
+            Event::Auth(AuthEvent::UserLogoutSucceeded { id, client }) => builder
+                .user_id(id)
+                .kind(SecurityEventKind::LogoutSucceeded)
+                .succeed(true)
+                .client(client)
+                .build(),
+            // share links are opened anonymously, the code stands in for the account
+            Event::System(SystemEvent::FileShareAccessed {
+                code,
+                client,
+                reason,
+            }) => builder
+                .account(code)
+                .kind(SecurityEventKind::FileShareDownloaded)
+                .succeed(reason.is_none())
+                .maybe_reason(reason)
+                .client(client)
+                .build(),
+            _ => return Ok(()),
+        };
+        if let Err(err) = self.command_handler.handle(command).await {
+            tracing::error!(error = %err, "Failed to record security event");
+        }
+        Ok(())
+    }
+}
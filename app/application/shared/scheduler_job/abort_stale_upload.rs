@@ -0,0 +1,34 @@
+use bon::Builder;
+use nject::injectable;
+use sched_kit::{
+    Misfire, Overlap, ScheduledJob,
+    error::{Result, SchedError},
+};
+use std::time::Duration;
+
+use crate::system::service::upload_service::UploadService;
+
+/// Chunked uploads nobody finished keep their parts in the storage until aborted
+#[derive(Clone, Builder)]
+#[injectable]
+pub struct AbortStaleUpload {
+    upload_service: UploadService,
+}
+
+impl ScheduledJob for AbortStaleUpload {
+    const EXPR: &'static str = "at 00:31";
+    const NAME: &'static str = "AbortStaleUpload";
+    const MISFIRE: Misfire = Misfire::RunOnce;
+    const OVERLAP: Overlap = Overlap::Skip;
+    const TIMEOUT: Option<Duration> = Some(Duration::from_secs(3600));
+
+    async fn run(&self) -> Result<()> {
+        let aborted = self
+            .upload_service
+            .abort_stale_multiparts()
+            .await
+            .map_err(|e| SchedError::Custom(e.to_string()))?;
+        tracing::info!("Aborted {} stale chunked uploads", aborted);
+        Ok(())
+    }
+}
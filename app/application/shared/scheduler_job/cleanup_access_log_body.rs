@@ -0,0 +1,35 @@
+use infrastructure::shared::chrono_tz::{ChronoTz, Duration};
+use infrastructure::shared::config::ConfigRef;
+use infrastructure::shared::pg_pool::PgPool;
+use nject::injectable;
+use sched_kit::error::{Result, SchedError};
+use sched_kit::{Misfire, Overlap, ScheduledJob};
+
+#[derive(Clone)]
+#[injectable]
+pub struct CleanupAccessLogBody {
+    pool: PgPool,
+    config: ConfigRef,
+    ct: ChronoTz,
+}
+
+impl ScheduledJob for CleanupAccessLogBody {
+    const EXPR: &'static str = "at 02:11";
+    const NAME: &'static str = "CleanupAccessLogBody";
+    const MISFIRE: Misfire = Misfire::RunOnce;
+    const OVERLAP: Overlap = Overlap::Skip;
+
+    async fn run(&self) -> Result<()> {
+        let retention = Duration::from_std(self.config.access_log.body_retention)
+            .map_err(|e| SchedError::Custom(e.to_string()))?;
+        let before = self.ct.now() - retention;
+        sqlx::query!(
+            "DELETE FROM _access_log_bodies WHERE occurred_at < $1",
+            before
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| SchedError::Custom(e.to_string()))?;
+        Ok(())
+    }
+}
@@ -2,7 +2,11 @@ use bon::Builder;
 use futures_util::StreamExt as _;
 use infrastructure::shared::workspace::WorkspaceRef;
 use nject::injectable;
-use sched_kit::{ScheduledJob, error::Result};
+use sched_kit::{
+    Misfire, Overlap, ScheduledJob,
+    error::{Result, SchedError},
+};
+use serde::Deserialize;
 use std::time::{Duration, SystemTime};
 use tokio::fs;
 use tracing::warn;
@@ -13,11 +17,37 @@ pub struct CleanupTempDir {
     workspace: WorkspaceRef,
 }
 
+/// Parameters an admin can set on the job
+#[derive(Deserialize)]
+struct Params {
+    /// Dirs untouched for longer are removed
+    max_age_hours: u64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self { max_age_hours: 48 }
+    }
+}
+
 impl ScheduledJob for CleanupTempDir {
     const EXPR: &'static str = "at 00:01";
     const NAME: &'static str = "CleanupTempDir";
+    const MISFIRE: Misfire = Misfire::RunOnce;
+    const OVERLAP: Overlap = Overlap::Skip;
+    const TIMEOUT: Option<Duration> = Some(Duration::from_secs(3600));
 
     async fn run(&self) -> Result<()> {
+        self.run_with(&serde_json::Value::Null).await
+    }
+
+    async fn run_with(&self, params: &serde_json::Value) -> Result<()> {
+        let params = if params.is_null() {
+            Params::default()
+        } else {
+            Params::deserialize(params).map_err(|e| SchedError::Custom(e.to_string()))?
+        };
+        let max_age = params.max_age_hours * 3600;
         let now = SystemTime::now();
 
         if let Ok(dir) = fs::read_dir(self.workspace.temp_dir()).await {
@@ -36,7 +66,7 @@ impl ScheduledJob for CleanupTempDir {
                                 && let Ok(modified) = meta.modified()
                             {
                                 let age = now.duration_since(modified).unwrap_or(Duration::ZERO);
-                                if age.as_secs() > 2 * 24 * 3600
+                                if age.as_secs() > max_age
                                     && let Err(err) = fs::remove_dir_all(&path).await
                                 {
                                     warn!(
@@ -80,4 +110,23 @@ mod tests {
         assert!(job.run().await.is_ok());
         assert!(!path.exists());
     }
+
+    #[tokio::test]
+    async fn test_cleanup_temp_dir_with_params() {
+        let workspace = WorkspaceRef::default();
+        let temp_subdir = workspace.temp_dir().join("recent_dir");
+        std::fs::create_dir_all(&temp_subdir).unwrap();
+        let five_hours_ago = SystemTime::now() - Duration::from_secs(5 * 3600);
+        let ft = filetime::FileTime::from_system_time(five_hours_ago);
+        filetime::set_file_mtime(&temp_subdir, ft).unwrap();
+
+        let job = CleanupTempDir::builder().workspace(workspace).build();
+        assert!(job.run().await.is_ok());
+        assert!(temp_subdir.exists());
+        let params = serde_json::json!({ "max_age_hours": 4 });
+        assert!(job.run_with(&params).await.is_ok());
+        assert!(!temp_subdir.exists());
+        let params = serde_json::json!({ "max_age_hours": "4" });
+        assert!(job.run_with(&params).await.is_err());
+    }
 }
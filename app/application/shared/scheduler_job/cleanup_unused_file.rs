@@ -1,7 +1,8 @@
 use bon::Builder;
 use futures_util::StreamExt as _;
 use nject::injectable;
-use sched_kit::{ScheduledJob, error::Result};
+use sched_kit::{Misfire, Overlap, ScheduledJob, error::Result};
+use std::time::Duration;
 
 use crate::system::service::{file_service::FileService, upload_service::UploadService};
 
@@ -15,25 +16,35 @@ pub struct CleanupUnusedFile {
 impl ScheduledJob for CleanupUnusedFile {
     const EXPR: &'static str = "at 01:01";
     const NAME: &'static str = "CleanupUnusedFile";
+    const MISFIRE: Misfire = Misfire::RunOnce;
+    const OVERLAP: Overlap = Overlap::Skip;
+    const TIMEOUT: Option<Duration> = Some(Duration::from_secs(3600));
 
     async fn run(&self) -> Result<()> {
         let file_service = &self.file_service;
         let upload_service = &self.upload_service;
 
-        let mut paths = Vec::new();
+        let mut ids = Vec::new();
         let mut stream = file_service.unused_2days_ago();
         while let Some(Ok(row)) = stream.next().await {
-            paths.push(row.path);
+            ids.push(row.id);
         }
 
-        if paths.is_empty() {
+        if ids.is_empty() {
             tracing::info!("No unused files found");
             return Ok(());
         }
 
-        match file_service.delete_files(&paths).await {
-            Ok(_) => {
-                tracing::info!("Deleting {} unused files", paths.len());
+        match file_service.delete_files(&ids).await {
+            Ok(paths) => {
+                tracing::info!(
+                    "Deleting {} unused files, {} objects released",
+                    ids.len(),
+                    paths.len()
+                );
+                if paths.is_empty() {
+                    return Ok(());
+                }
                 if let Err(err) = upload_service.delete_many(paths).await {
                     tracing::error!(%err, "Delete file failed");
                 }
@@ -49,18 +60,20 @@ impl ScheduledJob for CleanupUnusedFile {
 #[cfg(test)]
 mod tests {
 
-    use domain::shared::id_generator::IdGenerator;
+    use domain::{shared::id_generator::IdGenerator, system::port::file_scanner::ScanVerdict};
     use infrastructure::{
+        port::file_scanner_impl::FileScannerImpl,
         shared::{
             chrono_tz::{ChronoTz, Duration},
+            config::ConfigRef,
             pg_pool::PgPool,
-            workspace::WorkspaceRef,
         },
-        test_utils::{setup_database, setup_object_storage},
+        test_utils::{setup_database, setup_kvdb, setup_object_storage},
     };
     use sqlx::types::chrono::Utc;
 
     use super::*;
+    use crate::system::service::file_service::{ContentMeta, FileOwner, NewObject, Quota};
 
     async fn build_job(pool: PgPool) -> CleanupUnusedFile {
         setup_database(pool.clone()).await;
@@ -76,11 +89,17 @@ mod tests {
                     .ct(ChronoTz::default())
                     .build()
             };
+            let file_scanner = FileScannerImpl::builder()
+                .config(ConfigRef::default())
+                .object_storage(object_storage.clone())
+                .build();
             UploadService::builder()
                 .ct(ChronoTz::default())
+                .config(ConfigRef::default())
                 .object_storage(object_storage)
                 .file_service(file_service)
-                .workspace(WorkspaceRef::default())
+                .file_scanner(file_scanner)
+                .kvdb(setup_kvdb().await)
                 .build()
         };
         CleanupUnusedFile::builder()
@@ -96,12 +115,11 @@ mod tests {
         // Insert a 2-day-old file
         let now = Utc::now() - Duration::days(3);
         let id = IdGenerator::primary_id();
-        let insert = sqlx::query("INSERT INTO _files (id, name, size, path, used, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7)")
+        let insert = sqlx::query("INSERT INTO _files (id, name, size, path, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6)")
             .bind(id)
             .bind("test3.txt")
             .bind(125)
             .bind("/test/test3.txt")
-            .bind(false)
             .bind(now)
             .bind(now)
             .execute(&pool)
@@ -109,4 +127,164 @@ mod tests {
         assert!(insert.is_ok());
         assert!(job.run().await.is_ok());
     }
+
+    #[sqlx::test]
+    async fn test_cleanup_keeps_shared_object(pool: PgPool) {
+        let job = build_job(pool.clone()).await;
+        let file_service = FileService::builder()
+            .pool(pool.clone())
+            .ct(ChronoTz::default())
+            .build();
+        let hash = "0".repeat(64);
+        let object = file_service
+            .create(
+                "a.txt",
+                "uploader",
+                NewObject::builder()
+                    .hash(&hash)
+                    .size(3)
+                    .path("test/shared.txt")
+                    .verdict(&ScanVerdict::Clean)
+                    .meta(ContentMeta::default())
+                    .build(),
+                Quota::default(),
+            )
+            .await
+            .unwrap();
+        let reused = file_service
+            .create_from_object("b.txt", &hash, "uploader", Quota::default())
+            .await
+            .unwrap();
+        assert_eq!(reused.map(|o| o.path), Some(object.path.clone()));
+
+        let three_days_ago = Utc::now() - Duration::days(3);
+        let update = sqlx::query("UPDATE _files SET created_at = $1 WHERE name = 'a.txt'")
+            .bind(three_days_ago)
+            .execute(&pool)
+            .await;
+        assert!(update.is_ok());
+        assert!(job.run().await.is_ok());
+
+        let ref_count: i32 =
+            sqlx::query_scalar("SELECT ref_count FROM _file_objects WHERE hash = $1")
+                .bind(&hash)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(ref_count, 1);
+    }
+
+    #[sqlx::test]
+    async fn test_cleanup_keeps_referenced_files(pool: PgPool) {
+        let job = build_job(pool.clone()).await;
+        let file_service = FileService::builder()
+            .pool(pool.clone())
+            .ct(ChronoTz::default())
+            .build();
+        let hash = "1".repeat(64);
+        let object = file_service
+            .create(
+                "portrait.webp",
+                "uploader",
+                NewObject::builder()
+                    .hash(&hash)
+                    .size(3)
+                    .path("test/portrait.webp")
+                    .verdict(&ScanVerdict::Clean)
+                    .meta(ContentMeta::default())
+                    .build(),
+                Quota::default(),
+            )
+            .await
+            .unwrap();
+        let paths = Vec::from([object.path]);
+        for id in ["user_a", "user_b"] {
+            let owner = FileOwner::builder()
+                .owner_type("user")
+                .owner_id(id)
+                .field("portrait")
+                .build();
+            assert!(file_service.set_references(&owner, &paths).await.is_ok());
+        }
+        // deleting one of the owners leaves the file referenced by the other
+        let removed = file_service
+            .remove_references("user", &[String::from("user_a")])
+            .await;
+        assert!(removed.is_ok());
+
+        let three_days_ago = Utc::now() - Duration::days(3);
+        let update = sqlx::query("UPDATE _files SET created_at = $1")
+            .bind(three_days_ago)
+            .execute(&pool)
+            .await;
+        assert!(update.is_ok());
+        assert!(job.run().await.is_ok());
+
+        let ref_count: i32 =
+            sqlx::query_scalar("SELECT ref_count FROM _file_objects WHERE hash = $1")
+                .bind(&hash)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(ref_count, 1);
+    }
+
+    #[sqlx::test]
+    async fn test_cleanup_releases_unreferenced_duplicates(pool: PgPool) {
+        let job = build_job(pool.clone()).await;
+        let file_service = FileService::builder()
+            .pool(pool.clone())
+            .ct(ChronoTz::default())
+            .build();
+        let hash = "2".repeat(64);
+        let object = file_service
+            .create(
+                "a.webp",
+                "uploader_a",
+                NewObject::builder()
+                    .hash(&hash)
+                    .size(3)
+                    .path("test/duplicate.webp")
+                    .verdict(&ScanVerdict::Clean)
+                    .meta(ContentMeta::default())
+                    .build(),
+                Quota::default(),
+            )
+            .await
+            .unwrap();
+        let duplicate = file_service
+            .create_from_object("b.webp", &hash, "uploader_b", Quota::default())
+            .await
+            .unwrap();
+        assert!(duplicate.is_some());
+        // the path is shared, only one of the two files is referenced through it
+        let owner = FileOwner::builder()
+            .owner_type("user")
+            .owner_id("user_a")
+            .field("portrait")
+            .build();
+        let paths = Vec::from([object.path]);
+        assert!(file_service.set_references(&owner, &paths).await.is_ok());
+
+        let three_days_ago = Utc::now() - Duration::days(3);
+        let update = sqlx::query("UPDATE _files SET created_at = $1")
+            .bind(three_days_ago)
+            .execute(&pool)
+            .await;
+        assert!(update.is_ok());
+        assert!(job.run().await.is_ok());
+
+        let names: Vec<String> = sqlx::query_scalar("SELECT name FROM _files")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(names, ["a.webp"]);
+        let ref_count: i32 =
+            sqlx::query_scalar("SELECT ref_count FROM _file_objects WHERE hash = $1")
+                .bind(&hash)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(ref_count, 1);
+    }
 }
@@ -1,3 +1,7 @@
-pub mod cleanup_access_log;
+pub mod abort_stale_upload;
+pub mod cleanup_access_log_body;
 pub mod cleanup_temp_dir;
 pub mod cleanup_unused_file;
+pub mod purge_export;
+pub mod purge_recycle_bin;
+pub mod rotate_access_log_partition;
@@ -0,0 +1,57 @@
+use bon::Builder;
+use nject::injectable;
+use sched_kit::{
+    Misfire, Overlap, ScheduledJob,
+    error::{Result, SchedError},
+};
+use serde::Deserialize;
+use std::time::Duration;
+
+use crate::system::service::upload_service::UploadService;
+
+/// Exports are handed out once through their task, they are not kept for good
+#[derive(Clone, Builder)]
+#[injectable]
+pub struct PurgeExport {
+    upload_service: UploadService,
+}
+
+/// Parameters an admin can set on the job
+#[derive(Deserialize)]
+struct Params {
+    /// Exports made more days ago are removed
+    max_age_days: u32,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self { max_age_days: 7 }
+    }
+}
+
+impl ScheduledJob for PurgeExport {
+    const EXPR: &'static str = "at 00:41";
+    const NAME: &'static str = "PurgeExport";
+    const MISFIRE: Misfire = Misfire::RunOnce;
+    const OVERLAP: Overlap = Overlap::Skip;
+    const TIMEOUT: Option<Duration> = Some(Duration::from_secs(3600));
+
+    async fn run(&self) -> Result<()> {
+        self.run_with(&serde_json::Value::Null).await
+    }
+
+    async fn run_with(&self, params: &serde_json::Value) -> Result<()> {
+        let params = if params.is_null() {
+            Params::default()
+        } else {
+            Params::deserialize(params).map_err(|e| SchedError::Custom(e.to_string()))?
+        };
+        let purged = self
+            .upload_service
+            .purge_exports(params.max_age_days)
+            .await
+            .map_err(|e| SchedError::Custom(e.to_string()))?;
+        tracing::info!("Purged exports of {} days", purged);
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use bon::Builder;
+use infrastructure::shared::chrono_tz::{ChronoTz, Duration};
+use infrastructure::shared::config::ConfigRef;
+use nject::injectable;
+use sched_kit::error::{Result, SchedError};
+use sched_kit::{Misfire, Overlap, ScheduledJob};
+
+use crate::shared::event::EVENT_BUS;
+use crate::system::service::recycle_bin_service::RecycleBinService;
+
+#[derive(Clone, Builder)]
+#[injectable]
+pub struct PurgeRecycleBin {
+    recycle_bin_service: RecycleBinService,
+    config: ConfigRef,
+    ct: ChronoTz,
+}
+
+impl ScheduledJob for PurgeRecycleBin {
+    const EXPR: &'static str = "at 01:31";
+    const NAME: &'static str = "PurgeRecycleBin";
+    const MISFIRE: Misfire = Misfire::RunOnce;
+    const OVERLAP: Overlap = Overlap::Skip;
+    const TIMEOUT: Option<std::time::Duration> = Some(std::time::Duration::from_secs(3600));
+
+    async fn run(&self) -> Result<()> {
+        let retention = Duration::from_std(self.config.recycle_bin.retention)
+            .map_err(|e| SchedError::Custom(e.to_string()))?;
+        let before = self.ct.now() - retention;
+        let events = self
+            .recycle_bin_service
+            .purge_expired(before)
+            .await
+            .map_err(|e| SchedError::Custom(e.to_string()))?;
+        for event in events {
+            EVENT_BUS.publish(event);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use infrastructure::shared::chrono_tz::Duration;
+use infrastructure::shared::config::ConfigRef;
+use nject::injectable;
+use sched_kit::error::{Result, SchedError};
+use sched_kit::{Misfire, Overlap, ScheduledJob};
+
+use crate::system::service::access_log_partition_service::AccessLogPartitionService;
+
+/// Number of days whose partitions are created ahead of time
+const PREMAKE_DAYS: i64 = 7;
+
+#[derive(Clone)]
+#[injectable]
+pub struct RotateAccessLogPartition {
+    partition_service: AccessLogPartitionService,
+    config: ConfigRef,
+}
+
+impl ScheduledJob for RotateAccessLogPartition {
+    const EXPR: &'static str = "at 02:01";
+    const NAME: &'static str = "RotateAccessLogPartition";
+    const MISFIRE: Misfire = Misfire::RunOnce;
+    const OVERLAP: Overlap = Overlap::Skip;
+
+    async fn run(&self) -> Result<()> {
+        self.partition_service
+            .create_ahead(PREMAKE_DAYS)
+            .await
+            .map_err(|e| SchedError::Custom(e.to_string()))?;
+        let retention = Duration::from_std(self.config.access_log.retention)
+            .map_err(|e| SchedError::Custom(e.to_string()))?;
+        self.partition_service
+            .rotate(retention)
+            .await
+            .map_err(|e| SchedError::Custom(e.to_string()))?;
+        Ok(())
+    }
+}
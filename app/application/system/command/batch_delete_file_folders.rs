@@ -0,0 +1,44 @@
+use bon::Builder;
+use domain::{
+    shared::port::domain_repository::DomainRepository,
+    system::{event::SystemEvent, value_object::file_folder_id::FileFolderId},
+};
+use infrastructure::repository::system::file_folder_repository::FileFolderRepositoryImpl;
+use nject::injectable;
+use serde::Deserialize;
+
+use crate::{
+    error::ApplicationError,
+    shared::command_handler::{CommandHandler, CommandResult},
+};
+use utoipa::ToSchema;
+
+/// Deletes the folders with everything below them, their files move back to the root
+#[derive(Debug, Deserialize, Builder, ToSchema)]
+pub struct BatchDeleteFileFoldersCommand {
+    ids: Vec<FileFolderId>,
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct BatchDeleteFileFoldersCommandHandler {
+    file_folder_repo: FileFolderRepositoryImpl,
+}
+
+impl CommandHandler for BatchDeleteFileFoldersCommandHandler {
+    type Command = BatchDeleteFileFoldersCommand;
+    type Output = ();
+    type Event = SystemEvent;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        let items = self.file_folder_repo.batch_delete(&cmd.ids).await?;
+        Ok(CommandResult::with_event(
+            (),
+            SystemEvent::FileFoldersDeleted { items },
+        ))
+    }
+}
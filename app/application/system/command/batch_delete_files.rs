@@ -0,0 +1,43 @@
+use bon::Builder;
+use domain::shared::port::domain_repository::DomainRepository;
+use domain::system::event::SystemEvent;
+use domain::system::value_object::file_id::FileId;
+use infrastructure::repository::system::file_repository::FileRepositoryImpl;
+use nject::injectable;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    error::ApplicationError,
+    shared::command_handler::{CommandHandler, CommandResult},
+};
+
+/// Moves the files into the recycle bin
+#[derive(Debug, Deserialize, Builder, ToSchema)]
+pub struct BatchDeleteFilesCommand {
+    ids: Vec<FileId>,
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct BatchDeleteFilesCommandHandler {
+    file_repo: FileRepositoryImpl,
+}
+
+impl CommandHandler for BatchDeleteFilesCommandHandler {
+    type Command = BatchDeleteFilesCommand;
+    type Output = ();
+    type Event = SystemEvent;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        let items = self.file_repo.batch_delete(&cmd.ids).await?;
+        Ok(CommandResult::with_event(
+            (),
+            SystemEvent::FilesDeleted { items },
+        ))
+    }
+}
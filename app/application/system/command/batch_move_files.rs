@@ -0,0 +1,54 @@
+use bon::Builder;
+use domain::shared::port::domain_repository::DomainRepository;
+use domain::system::event::SystemEvent;
+use domain::system::port::file_repository::FileRepository;
+use domain::system::value_object::{file_folder_id::FileFolderId, file_id::FileId};
+use infrastructure::repository::system::{
+    file_folder_repository::FileFolderRepositoryImpl, file_repository::FileRepositoryImpl,
+};
+use nject::injectable;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    error::ApplicationError,
+    shared::command_handler::{CommandHandler, CommandResult},
+};
+
+/// Moves the files into a folder, or to the root without `folder_id`
+#[derive(Debug, Deserialize, Builder, ToSchema)]
+pub struct BatchMoveFilesCommand {
+    ids: Vec<FileId>,
+    folder_id: Option<FileFolderId>,
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct BatchMoveFilesCommandHandler {
+    file_repo: FileRepositoryImpl,
+    file_folder_repo: FileFolderRepositoryImpl,
+}
+
+impl CommandHandler for BatchMoveFilesCommandHandler {
+    type Command = BatchMoveFilesCommand;
+    type Output = ();
+    type Event = SystemEvent;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        if let Some(folder_id) = &cmd.folder_id {
+            self.file_folder_repo.by_id(folder_id).await?;
+        }
+        let items = self
+            .file_repo
+            .move_to_folder(&cmd.ids, cmd.folder_id.as_ref())
+            .await?;
+        Ok(CommandResult::with_event(
+            (),
+            SystemEvent::FilesUpdated { items },
+        ))
+    }
+}
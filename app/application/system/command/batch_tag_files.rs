@@ -0,0 +1,55 @@
+use bon::Builder;
+use domain::system::entity::file::normalize_tags;
+use domain::system::event::SystemEvent;
+use domain::system::port::file_repository::FileRepository;
+use domain::system::value_object::file_id::FileId;
+use infrastructure::repository::system::file_repository::FileRepositoryImpl;
+use nject::injectable;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    error::ApplicationError,
+    shared::command_handler::{CommandHandler, CommandResult},
+};
+
+/// Adds and removes tags on many files at once, other tags are left alone
+#[derive(Debug, Deserialize, Builder, ToSchema)]
+pub struct BatchTagFilesCommand {
+    ids: Vec<FileId>,
+    #[serde(default)]
+    add_tags: Vec<String>,
+    #[serde(default)]
+    remove_tags: Vec<String>,
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct BatchTagFilesCommandHandler {
+    file_repo: FileRepositoryImpl,
+}
+
+impl CommandHandler for BatchTagFilesCommandHandler {
+    type Command = BatchTagFilesCommand;
+    type Output = ();
+    type Event = SystemEvent;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        let items = self
+            .file_repo
+            .update_tags(
+                &cmd.ids,
+                &normalize_tags(cmd.add_tags),
+                &normalize_tags(cmd.remove_tags),
+            )
+            .await?;
+        Ok(CommandResult::with_event(
+            (),
+            SystemEvent::FilesUpdated { items },
+        ))
+    }
+}
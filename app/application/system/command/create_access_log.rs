@@ -20,6 +20,8 @@ pub struct CreateAccessLogCommand {
     status: i16,
     elapsed: i64,
     occurred_at: chrono::NaiveDateTime,
+    request_body: Option<String>,
+    response_body: Option<String>,
 }
 
 #[derive(Debug)]
@@ -48,6 +50,8 @@ impl CommandHandler for CreateAccessLogCommandHandler {
             .status(cmd.status)
             .elapsed(cmd.elapsed)
             .occurred_at(cmd.occurred_at)
+            .maybe_request_body(cmd.request_body)
+            .maybe_response_body(cmd.response_body)
             .build();
         let access_log = self.access_log_repo.save(access_log).await?;
         Ok(CommandResult::with_event(
@@ -0,0 +1,55 @@
+use bon::Builder;
+use domain::shared::port::domain_repository::DomainRepository;
+use domain::system::entity::file_folder::FileFolder;
+use domain::system::event::SystemEvent;
+use domain::system::value_object::file_folder_id::FileFolderId;
+use infrastructure::repository::system::file_folder_repository::FileFolderRepositoryImpl;
+use nject::injectable;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    error::ApplicationError,
+    shared::command_handler::{CommandHandler, CommandResult},
+};
+
+#[derive(Debug, Deserialize, Builder, ToSchema)]
+pub struct CreateFileFolderCommand {
+    name: String,
+    parent_id: Option<FileFolderId>,
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct CreateFileFolderCommandHandler {
+    file_folder_repo: FileFolderRepositoryImpl,
+}
+
+impl CommandHandler for CreateFileFolderCommandHandler {
+    type Command = CreateFileFolderCommand;
+    type Output = FileFolder;
+    type Event = SystemEvent;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        let name = FileFolder::check_name(&cmd.name)?;
+        if let Some(parent_id) = &cmd.parent_id {
+            self.file_folder_repo.by_id(parent_id).await?;
+        }
+        let folder = FileFolder::builder()
+            .id(FileFolderId::generate())
+            .name(name)
+            .maybe_parent_id(cmd.parent_id)
+            .build();
+        let folder = self.file_folder_repo.save(folder).await?;
+        Ok(CommandResult::with_event(
+            folder.clone(),
+            SystemEvent::FileFoldersCreated {
+                items: vec![folder],
+            },
+        ))
+    }
+}
@@ -0,0 +1,94 @@
+use bon::Builder;
+use domain::organization::value_object::hashed_password::HashedPassword;
+use domain::organization::value_object::user_id::UserId;
+use domain::shared::id_generator::IdGenerator;
+use domain::shared::port::domain_repository::DomainRepository;
+use domain::system::entity::file_share::FileShare;
+use domain::system::event::SystemEvent;
+use domain::system::value_object::file_id::FileId;
+use domain::system::value_object::file_share_id::FileShareId;
+use infrastructure::repository::system::file_repository::FileRepositoryImpl;
+use infrastructure::repository::system::file_share_repository::FileShareRepositoryImpl;
+use infrastructure::shared::chrono_tz::ChronoTz;
+use nject::injectable;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    error::ApplicationError,
+    shared::command_handler::{CommandHandler, CommandResult},
+};
+
+#[derive(Deserialize, Builder, ToSchema)]
+pub struct CreateFileShareCommand {
+    file_id: FileId,
+    /// Seconds the link stays valid, it never expires when absent
+    expires_in: Option<u32>,
+    password: Option<String>,
+    max_downloads: Option<i32>,
+    #[serde(skip)]
+    created_by: Option<UserId>,
+}
+
+impl CreateFileShareCommand {
+    pub fn with_created_by(mut self, created_by: UserId) -> Self {
+        self.created_by = Some(created_by);
+        self
+    }
+}
+
+impl std::fmt::Debug for CreateFileShareCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CreateFileShareCommand")
+            .field("file_id", &self.file_id)
+            .field("expires_in", &self.expires_in)
+            .field("password", &self.password.as_ref().map(|_| "<RESERVED>"))
+            .field("max_downloads", &self.max_downloads)
+            .field("created_by", &self.created_by)
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct CreateFileShareCommandHandler {
+    file_repo: FileRepositoryImpl,
+    file_share_repo: FileShareRepositoryImpl,
+    ct: ChronoTz,
+}
+
+impl CommandHandler for CreateFileShareCommandHandler {
+    type Command = CreateFileShareCommand;
+    type Output = FileShare;
+    type Event = SystemEvent;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        self.file_repo.by_id(&cmd.file_id).await?;
+        let password = cmd
+            .password
+            .filter(|password| !password.trim().is_empty())
+            .map(HashedPassword::try_new)
+            .transpose()?;
+        let expires_at = cmd
+            .expires_in
+            .map(|secs| self.ct.now() + chrono::Duration::seconds(secs.into()));
+        let share = FileShare::builder()
+            .id(FileShareId::generate())
+            .code(IdGenerator::share_code())
+            .file_id(cmd.file_id)
+            .maybe_password(password)
+            .maybe_expires_at(expires_at)
+            .maybe_max_downloads(cmd.max_downloads.filter(|max| *max > 0))
+            .maybe_created_by(cmd.created_by)
+            .build();
+        let share = self.file_share_repo.save(share).await?;
+        Ok(CommandResult::with_event(
+            share.clone(),
+            SystemEvent::FileSharesCreated { items: vec![share] },
+        ))
+    }
+}
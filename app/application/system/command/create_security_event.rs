@@ -0,0 +1,94 @@
+use crate::error::ApplicationError;
+use crate::shared::command_handler::{CommandHandler, CommandResult};
+use bon::Builder;
+use domain::auth::value_object::client_info::ClientInfo;
+use domain::organization::value_object::user_id::UserId;
+use domain::shared::port::domain_repository::DomainRepository;
+use domain::system::entity::security_event::SecurityEvent;
+use domain::system::event::SystemEvent;
+use domain::system::port::geo_ip_resolver::GeoIpResolver as _;
+use domain::system::port::security_event_repository::SecurityEventRepository as _;
+use domain::system::value_object::security_event_id::SecurityEventId;
+use domain::system::value_object::security_event_kind::SecurityEventKind;
+use infrastructure::port::geo_ip_resolver_impl::GeoIpResolverImpl;
+use infrastructure::repository::system::security_event_repository::SecurityEventRepositoryImpl;
+use nject::injectable;
+
+/// Number of previous logins the anomaly detection compares against
+const RECENT_LOGIN_LIMIT: i64 = 50;
+const MAX_TEXT_LENGTH: usize = 64;
+
+#[derive(Debug, Builder)]
+pub struct CreateSecurityEventCommand {
+    user_id: Option<UserId>,
+    account: Option<String>,
+    kind: SecurityEventKind,
+    succeed: bool,
+    reason: Option<String>,
+    client: ClientInfo,
+    occurred_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Clone)]
+#[injectable]
+pub struct CreateSecurityEventCommandHandler {
+    security_event_repo: SecurityEventRepositoryImpl,
+    geo_ip_resolver: GeoIpResolverImpl,
+}
+
+impl CommandHandler for CreateSecurityEventCommandHandler {
+    type Command = CreateSecurityEventCommand;
+    type Output = SecurityEvent;
+    type Event = SystemEvent;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        let truncate = |value: String| value.chars().take(MAX_TEXT_LENGTH).collect::<String>();
+        let mut security_event = SecurityEvent::builder()
+            .id(SecurityEventId::generate())
+            .maybe_user_id(cmd.user_id)
+            .maybe_account(cmd.account.map(truncate))
+            .kind(cmd.kind)
+            .succeed(cmd.succeed)
+            .maybe_reason(cmd.reason.map(truncate))
+            .maybe_ip(cmd.client.ip)
+            .maybe_user_agent(cmd.client.user_agent)
+            .occurred_at(cmd.occurred_at)
+            .build();
+        // only logins are compared by location, other events skip the lookup
+        let is_login = matches!(
+            security_event.kind,
+            SecurityEventKind::LoginSucceeded | SecurityEventKind::LoginFailed
+        );
+        if let (true, Some(ip)) = (is_login, &security_event.ip)
+            && let Some(location) = self.geo_ip_resolver.resolve(ip).await
+        {
+            security_event.update_location(location);
+        }
+        if let (SecurityEventKind::LoginSucceeded, Some(user_id)) =
+            (security_event.kind, &security_event.user_id)
+        {
+            let previous_logins = self
+                .security_event_repo
+                .recent_succeeded_logins(user_id, RECENT_LOGIN_LIMIT)
+                .await?;
+            security_event.flag_anomalies(&previous_logins);
+        }
+        if security_event.new_device
+            || security_event.new_country
+            || security_event.impossible_travel
+        {
+            tracing::warn!(?security_event, "Anomalous login detected");
+        }
+        let security_event = self.security_event_repo.save(security_event).await?;
+        Ok(CommandResult::with_event(
+            security_event.clone(),
+            SystemEvent::SecurityEventsCreated {
+                items: vec![security_event],
+            },
+        ))
+    }
+}
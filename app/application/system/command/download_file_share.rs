@@ -0,0 +1,107 @@
+use std::fmt::Debug;
+use std::time::Duration;
+
+use bon::Builder;
+use domain::auth::value_object::client_info::ClientInfo;
+use domain::shared::port::domain_repository::DomainRepository;
+use domain::system::error::SystemError;
+use domain::system::event::SystemEvent;
+use domain::system::port::file_share_repository::FileShareRepository as _;
+use infrastructure::repository::system::file_repository::FileRepositoryImpl;
+use infrastructure::repository::system::file_share_repository::FileShareRepositoryImpl;
+use infrastructure::shared::chrono_tz::ChronoTz;
+use nject::injectable;
+
+use crate::{
+    error::ApplicationError,
+    shared::{
+        command_handler::{CommandHandler, CommandResult},
+        event::EVENT_BUS,
+    },
+    system::service::upload_service::UploadService,
+};
+
+/// The storage link a share redirects to is followed right away, it only has to outlive the redirect
+const DOWNLOAD_LINK_PERIOD: Duration = Duration::from_secs(60);
+
+#[derive(Builder)]
+pub struct DownloadFileShareCommand {
+    code: String,
+    password: Option<String>,
+    #[builder(default)]
+    client: ClientInfo,
+}
+
+impl Debug for DownloadFileShareCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DownloadFileShareCommand")
+            .field("code", &self.code)
+            .field("password", &self.password.as_ref().map(|_| "<RESERVED>"))
+            .field("client", &self.client)
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct DownloadFileShareCommandHandler {
+    file_repo: FileRepositoryImpl,
+    file_share_repo: FileShareRepositoryImpl,
+    upload_service: UploadService,
+    ct: ChronoTz,
+}
+
+impl CommandHandler for DownloadFileShareCommandHandler {
+    type Command = DownloadFileShareCommand;
+    /// Presigned storage URL of the shared file
+    type Output = String;
+    type Event = SystemEvent;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        let code = cmd.code.clone();
+        let client = cmd.client.clone();
+        match self.download(cmd).await {
+            Ok(url) => Ok(CommandResult::with_event(
+                url,
+                SystemEvent::FileShareAccessed {
+                    code,
+                    client,
+                    reason: None,
+                },
+            )),
+            Err(err) => {
+                // failures never reach `handle`, so the event is published here
+                EVENT_BUS.publish(
+                    SystemEvent::FileShareAccessed {
+                        code,
+                        client,
+                        reason: Some(err.to_string()),
+                    }
+                    .into(),
+                );
+                Err(err)
+            }
+        }
+    }
+}
+
+impl DownloadFileShareCommandHandler {
+    async fn download(&self, cmd: DownloadFileShareCommand) -> Result<String, ApplicationError> {
+        let share = self.file_share_repo.by_code(&cmd.code).await?;
+        share.check_access(self.ct.now(), cmd.password.as_deref())?;
+        let file = self.file_repo.by_id(&share.file_id).await?;
+        if !self.upload_service.servable(&file.path).await? {
+            return Err(ApplicationError::FileQuarantined);
+        }
+        if !self.file_share_repo.count_download(&share).await? {
+            return Err(SystemError::FileShareExhausted.into());
+        }
+        self.upload_service
+            .presign_url_with(&file.path, DOWNLOAD_LINK_PERIOD)
+            .await
+    }
+}
@@ -0,0 +1,66 @@
+use bg_worker_kit::{EnqueueOptions, error::WorkerError};
+use bon::Builder;
+use domain::organization::value_object::user_id::UserId;
+use domain::system::event::SystemEvent;
+use nject::injectable;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    error::ApplicationError,
+    shared::{
+        bgworker::export_access_logs::ExportAccessLogs,
+        bgworker_impl::WorkerRegistry,
+        command_handler::{CommandHandler, CommandResult},
+    },
+};
+
+/// Starts exporting access logs in the background, the CSV shows up in the
+/// requester's task list
+#[derive(Debug, Deserialize, Builder, ToSchema)]
+pub struct ExportAccessLogsCommand {
+    from: Option<chrono::NaiveDateTime>,
+    to: Option<chrono::NaiveDateTime>,
+    #[serde(skip)]
+    requested_by: Option<UserId>,
+}
+
+impl ExportAccessLogsCommand {
+    pub fn with_requested_by(mut self, requested_by: UserId) -> Self {
+        self.requested_by = Some(requested_by);
+        self
+    }
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct ExportAccessLogsCommandHandler;
+
+impl CommandHandler for ExportAccessLogsCommandHandler {
+    type Command = ExportAccessLogsCommand;
+    /// Id of the export task
+    type Output = String;
+    type Event = SystemEvent;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        let Some(requested_by) = cmd.requested_by else {
+            return Err(ApplicationError::PermissionDenied);
+        };
+        let job = ExportAccessLogs::builder()
+            .maybe_from(cmd.from)
+            .maybe_to(cmd.to)
+            .build();
+        let options =
+            EnqueueOptions::default().owner(requested_by.to_string(), "Export access logs");
+        let id = WorkerRegistry::enqueue_export_access_logs_with(job, options)
+            .await?
+            .ok_or_else(|| {
+                WorkerError::Custom("export_access_logs is not registered".to_string())
+            })?;
+        Ok(CommandResult::without_events(id))
+    }
+}
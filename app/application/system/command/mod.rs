@@ -1,2 +1,23 @@
+pub mod batch_delete_file_folders;
+pub mod batch_delete_files;
 pub mod batch_delete_scheds;
+pub mod batch_move_files;
+pub mod batch_tag_files;
 pub mod create_access_log;
+pub mod create_file_folder;
+pub mod create_file_share;
+pub mod create_security_event;
+pub mod download_file_share;
+pub mod export_access_logs;
+pub mod move_file_folder;
+pub mod pause_sched_job;
+pub mod purge_recycle_bin;
+pub mod release_file;
+pub mod rescan_file;
+pub mod restore_recycle_bin;
+pub mod resume_sched_job;
+pub mod revoke_file_shares;
+pub mod trigger_sched_job;
+pub mod update_file;
+pub mod update_file_folder;
+pub mod update_sched_job;
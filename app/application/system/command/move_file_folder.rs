@@ -0,0 +1,119 @@
+use bon::Builder;
+use domain::shared::event_util::UpdatedEvent;
+use domain::shared::port::domain_repository::DomainRepository;
+use domain::system::entity::file_folder::FileFolder;
+use domain::system::error::SystemError;
+use domain::system::event::SystemEvent;
+use domain::system::port::file_folder_repository::FileFolderRepository;
+use domain::system::value_object::file_folder_id::FileFolderId;
+use infrastructure::repository::system::file_folder_repository::FileFolderRepositoryImpl;
+use nject::injectable;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    error::ApplicationError,
+    shared::command_handler::{CommandHandler, CommandResult},
+};
+
+/// Moves a folder with everything inside below another one, or to the root without `parent_id`
+#[derive(Debug, Deserialize, Builder, ToSchema)]
+pub struct MoveFileFolderCommand {
+    id: FileFolderId,
+    parent_id: Option<FileFolderId>,
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct MoveFileFolderCommandHandler {
+    file_folder_repo: FileFolderRepositoryImpl,
+}
+
+impl CommandHandler for MoveFileFolderCommandHandler {
+    type Command = MoveFileFolderCommand;
+    type Output = FileFolder;
+    type Event = SystemEvent;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        let mut folder = self.file_folder_repo.by_id(&cmd.id).await?;
+        let before = folder.clone();
+        if let Some(parent_id) = &cmd.parent_id {
+            self.file_folder_repo.by_id(parent_id).await?;
+            let subtree = self.file_folder_repo.subtree_ids(&folder.id).await?;
+            if subtree.contains(parent_id) {
+                return Err(SystemError::FileFolderInvalidParent.into());
+            }
+        }
+        folder.update_parent_id(cmd.parent_id);
+        let folder = self.file_folder_repo.save(folder).await?;
+        Ok(CommandResult::with_event(
+            folder.clone(),
+            SystemEvent::FileFoldersUpdated {
+                items: vec![UpdatedEvent {
+                    before,
+                    after: folder,
+                }],
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use infrastructure::{
+        shared::{chrono_tz::ChronoTz, pg_pool::PgPool},
+        test_utils::setup_database,
+    };
+
+    use super::*;
+
+    #[sqlx::test]
+    async fn test_execute_return_err_given_parent_inside_folder(pool: PgPool) {
+        setup_database(pool.clone()).await;
+        let file_folder_repo = FileFolderRepositoryImpl::builder()
+            .pool(pool)
+            .ct(ChronoTz::default())
+            .build();
+        let mut ids = Vec::new();
+        for name in ["root", "child", "grandchild"] {
+            let folder = FileFolder::builder()
+                .id(FileFolderId::generate())
+                .name(name.to_string())
+                .maybe_parent_id(ids.last().cloned())
+                .build();
+            ids.push(file_folder_repo.save(folder).await.unwrap().id.clone());
+        }
+        let handler = MoveFileFolderCommandHandler { file_folder_repo };
+        let move_to = |id: &FileFolderId, parent_id: Option<&FileFolderId>| {
+            MoveFileFolderCommand::builder()
+                .id(id.clone())
+                .maybe_parent_id(parent_id.cloned())
+                .build()
+        };
+
+        for parent_id in [&ids[0], &ids[2]] {
+            assert!(matches!(
+                handler.execute(move_to(&ids[0], Some(parent_id))).await,
+                Err(ApplicationError::System(
+                    SystemError::FileFolderInvalidParent
+                ))
+            ));
+        }
+        let moved = handler
+            .execute(move_to(&ids[2], Some(&ids[0])))
+            .await
+            .unwrap()
+            .output;
+        assert_eq!(moved.parent_id.as_ref(), Some(&ids[0]));
+        let moved = handler
+            .execute(move_to(&ids[1], None))
+            .await
+            .unwrap()
+            .output;
+        assert_eq!(moved.parent_id, None);
+    }
+}
@@ -0,0 +1,42 @@
+use bon::Builder;
+use domain::system::value_object::recycle_bin_kind::RecycleBinKind;
+use nject::injectable;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    error::ApplicationError,
+    shared::{
+        command_handler::{CommandHandler, CommandResult},
+        event::Event,
+    },
+    system::service::recycle_bin_service::RecycleBinService,
+};
+
+/// Removes deleted entities of one kind before their retention ends
+#[derive(Debug, Deserialize, Builder, ToSchema)]
+pub struct PurgeRecycleBinCommand {
+    kind: RecycleBinKind,
+    ids: Vec<String>,
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct PurgeRecycleBinCommandHandler {
+    recycle_bin_service: RecycleBinService,
+}
+
+impl CommandHandler for PurgeRecycleBinCommandHandler {
+    type Command = PurgeRecycleBinCommand;
+    type Output = ();
+    type Event = Event;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        let event = self.recycle_bin_service.purge(cmd.kind, &cmd.ids).await?;
+        Ok(CommandResult::with_event((), event))
+    }
+}
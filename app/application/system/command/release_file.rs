@@ -0,0 +1,37 @@
+use bon::Builder;
+use domain::system::event::SystemEvent;
+use domain::system::value_object::file_id::FileId;
+use nject::injectable;
+
+use crate::{
+    error::ApplicationError,
+    shared::command_handler::{CommandHandler, CommandResult},
+    system::service::upload_service::UploadService,
+};
+
+/// Takes a file the scanner objected to wrongly out of quarantine without a clean scan
+#[derive(Debug, Builder)]
+pub struct ReleaseFileCommand {
+    id: FileId,
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct ReleaseFileCommandHandler {
+    upload_service: UploadService,
+}
+
+impl CommandHandler for ReleaseFileCommandHandler {
+    type Command = ReleaseFileCommand;
+    type Output = ();
+    type Event = SystemEvent;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        self.upload_service.release(&cmd.id).await?;
+        Ok(CommandResult::without_events(()))
+    }
+}
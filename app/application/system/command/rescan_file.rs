@@ -0,0 +1,37 @@
+use bon::Builder;
+use domain::system::event::SystemEvent;
+use domain::system::value_object::{file_id::FileId, scan_status::ScanStatus};
+use nject::injectable;
+
+use crate::{
+    error::ApplicationError,
+    shared::command_handler::{CommandHandler, CommandResult},
+    system::service::upload_service::UploadService,
+};
+
+/// Scans the stored content of a file again, a quarantined file found clean becomes servable
+#[derive(Debug, Builder)]
+pub struct RescanFileCommand {
+    id: FileId,
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct RescanFileCommandHandler {
+    upload_service: UploadService,
+}
+
+impl CommandHandler for RescanFileCommandHandler {
+    type Command = RescanFileCommand;
+    type Output = ScanStatus;
+    type Event = SystemEvent;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        let status = self.upload_service.rescan(&cmd.id).await?;
+        Ok(CommandResult::without_events(status))
+    }
+}
@@ -0,0 +1,43 @@
+use bon::Builder;
+use domain::system::value_object::recycle_bin_kind::RecycleBinKind;
+use nject::injectable;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    error::ApplicationError,
+    shared::{
+        command_handler::{CommandHandler, CommandResult},
+        event::Event,
+    },
+    system::service::recycle_bin_service::RecycleBinService,
+};
+
+/// Brings deleted entities of one kind back, a user whose account was taken
+/// in the meantime is refused as duplicated
+#[derive(Debug, Deserialize, Builder, ToSchema)]
+pub struct RestoreRecycleBinCommand {
+    kind: RecycleBinKind,
+    ids: Vec<String>,
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct RestoreRecycleBinCommandHandler {
+    recycle_bin_service: RecycleBinService,
+}
+
+impl CommandHandler for RestoreRecycleBinCommandHandler {
+    type Command = RestoreRecycleBinCommand;
+    type Output = ();
+    type Event = Event;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        let event = self.recycle_bin_service.restore(cmd.kind, &cmd.ids).await?;
+        Ok(CommandResult::with_event((), event))
+    }
+}
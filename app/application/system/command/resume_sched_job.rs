@@ -0,0 +1,59 @@
+use bon::Builder;
+use domain::shared::event_util::UpdatedEvent;
+use domain::system::error::SystemError;
+use domain::system::event::SystemEvent;
+use domain::system::port::sched_job_repository::SchedJobRepository as _;
+use infrastructure::repository::system::sched_job_repository::SchedJobRepositoryImpl;
+use nject::injectable;
+
+use crate::{
+    error::ApplicationError,
+    shared::{
+        command_handler::{CommandHandler, CommandResult},
+        scheduler_job_impl::SCHEDULER_JOBS,
+    },
+};
+
+/// Lets a paused job fire on its schedule again
+#[derive(Debug, Builder)]
+pub struct ResumeSchedJobCommand {
+    key: String,
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct ResumeSchedJobCommandHandler {
+    sched_job_repo: SchedJobRepositoryImpl,
+}
+
+impl CommandHandler for ResumeSchedJobCommandHandler {
+    type Command = ResumeSchedJobCommand;
+    type Output = ();
+    type Event = SystemEvent;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        if !SCHEDULER_JOBS.iter().any(|job| job.key == cmd.key) {
+            return Err(SystemError::SchedJobNotFound.into());
+        }
+        let mut sched_job = self.sched_job_repo.by_key(&cmd.key).await?;
+        if sched_job.enabled {
+            return Ok(CommandResult::without_events(()));
+        }
+        let before = sched_job.clone();
+        sched_job.update_enabled(true);
+        let sched_job = self.sched_job_repo.save(sched_job).await?;
+        Ok(CommandResult::with_event(
+            (),
+            SystemEvent::SchedJobsUpdated {
+                items: vec![UpdatedEvent {
+                    before,
+                    after: sched_job,
+                }],
+            },
+        ))
+    }
+}
@@ -0,0 +1,42 @@
+use bon::Builder;
+use domain::system::event::SystemEvent;
+use domain::system::port::file_share_repository::FileShareRepository as _;
+use domain::system::value_object::file_share_id::FileShareId;
+use infrastructure::repository::system::file_share_repository::FileShareRepositoryImpl;
+use nject::injectable;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    error::ApplicationError,
+    shared::command_handler::{CommandHandler, CommandResult},
+};
+
+#[derive(Debug, Deserialize, Builder, ToSchema)]
+pub struct RevokeFileSharesCommand {
+    ids: Vec<FileShareId>,
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct RevokeFileSharesCommandHandler {
+    file_share_repo: FileShareRepositoryImpl,
+}
+
+impl CommandHandler for RevokeFileSharesCommandHandler {
+    type Command = RevokeFileSharesCommand;
+    type Output = ();
+    type Event = SystemEvent;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        let items = self.file_share_repo.revoke(&cmd.ids).await?;
+        Ok(CommandResult::with_event(
+            (),
+            SystemEvent::FileSharesRevoked { items },
+        ))
+    }
+}
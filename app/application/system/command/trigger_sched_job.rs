@@ -0,0 +1,52 @@
+use bon::Builder;
+use domain::organization::value_object::user_id::UserId;
+use domain::system::error::SystemError;
+use domain::system::event::SystemEvent;
+use domain::system::port::sched_job_repository::SchedJobRepository as _;
+use infrastructure::port::sched_receiver_impl::job_setting;
+use infrastructure::repository::system::sched_job_repository::SchedJobRepositoryImpl;
+use nject::injectable;
+
+use crate::{
+    error::ApplicationError,
+    shared::{
+        command_handler::{CommandHandler, CommandResult},
+        scheduler_job_impl::ScheduledJobRunner,
+    },
+};
+
+/// Runs the job right away with its current parameters, paused or not. The run
+/// is recorded as manual once it finishes
+#[derive(Debug, Builder)]
+pub struct TriggerSchedJobCommand {
+    key: String,
+    triggered_by: UserId,
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct TriggerSchedJobCommandHandler {
+    sched_job_repo: SchedJobRepositoryImpl,
+    runner: ScheduledJobRunner,
+}
+
+impl CommandHandler for TriggerSchedJobCommandHandler {
+    type Command = TriggerSchedJobCommand;
+    type Output = ();
+    type Event = SystemEvent;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        let sched_job = self.sched_job_repo.by_key(&cmd.key).await?;
+        if !self
+            .runner
+            .run(&cmd.key, job_setting(&sched_job), &cmd.triggered_by)
+        {
+            return Err(SystemError::SchedJobNotFound.into());
+        }
+        Ok(CommandResult::without_events(()))
+    }
+}
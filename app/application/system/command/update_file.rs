@@ -0,0 +1,64 @@
+use bon::Builder;
+use domain::shared::event_util::UpdatedEvent;
+use domain::shared::port::domain_repository::DomainRepository;
+use domain::system::entity::file::File;
+use domain::system::event::SystemEvent;
+use domain::system::value_object::file_id::FileId;
+use infrastructure::repository::system::file_repository::FileRepositoryImpl;
+use nject::injectable;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    error::ApplicationError,
+    shared::command_handler::{CommandHandler, CommandResult},
+};
+
+/// Updates the library attributes of a file, the stored content stays as it is
+#[derive(Debug, Deserialize, Builder, ToSchema)]
+pub struct UpdateFileCommand {
+    id: FileId,
+    name: Option<String>,
+    description: Option<String>,
+    tags: Option<Vec<String>>,
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct UpdateFileCommandHandler {
+    file_repo: FileRepositoryImpl,
+}
+
+impl CommandHandler for UpdateFileCommandHandler {
+    type Command = UpdateFileCommand;
+    type Output = File;
+    type Event = SystemEvent;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        let mut file = self.file_repo.by_id(&cmd.id).await?;
+        let before = file.clone();
+        if let Some(name) = cmd.name {
+            file.update_name(name);
+        }
+        if let Some(description) = cmd.description {
+            file.update_description(Some(description));
+        }
+        if let Some(tags) = cmd.tags {
+            file.update_tags(tags);
+        }
+        let file = self.file_repo.save(file).await?;
+        Ok(CommandResult::with_event(
+            file.clone(),
+            SystemEvent::FilesUpdated {
+                items: vec![UpdatedEvent {
+                    before,
+                    after: file,
+                }],
+            },
+        ))
+    }
+}
@@ -0,0 +1,60 @@
+use bon::Builder;
+use domain::shared::event_util::UpdatedEvent;
+use domain::shared::port::domain_repository::DomainRepository;
+use domain::system::entity::file_folder::FileFolder;
+use domain::system::event::SystemEvent;
+use domain::system::value_object::file_folder_id::FileFolderId;
+use infrastructure::repository::system::file_folder_repository::FileFolderRepositoryImpl;
+use nject::injectable;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    error::ApplicationError,
+    shared::command_handler::{CommandHandler, CommandResult},
+};
+
+#[derive(Debug, Deserialize, Builder, ToSchema)]
+pub struct UpdateFileFolderCommand {
+    id: FileFolderId,
+    name: Option<String>,
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct UpdateFileFolderCommandHandler {
+    file_folder_repo: FileFolderRepositoryImpl,
+}
+
+impl CommandHandler for UpdateFileFolderCommandHandler {
+    type Command = UpdateFileFolderCommand;
+    type Output = FileFolder;
+    type Event = SystemEvent;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        let name = cmd
+            .name
+            .as_deref()
+            .map(FileFolder::check_name)
+            .transpose()?;
+        let mut folder = self.file_folder_repo.by_id(&cmd.id).await?;
+        let before = folder.clone();
+        if let Some(name) = name {
+            folder.update_name(name);
+        }
+        let folder = self.file_folder_repo.save(folder).await?;
+        Ok(CommandResult::with_event(
+            folder.clone(),
+            SystemEvent::FileFoldersUpdated {
+                items: vec![UpdatedEvent {
+                    before,
+                    after: folder,
+                }],
+            },
+        ))
+    }
+}
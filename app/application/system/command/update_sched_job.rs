@@ -0,0 +1,96 @@
+use std::str::FromStr as _;
+
+use bon::Builder;
+use domain::shared::event_util::UpdatedEvent;
+use domain::system::entity::sched_job::SchedJob;
+use domain::system::error::SystemError;
+use domain::system::event::SystemEvent;
+use domain::system::port::sched_job_repository::SchedJobRepository as _;
+use infrastructure::repository::system::sched_job_repository::SchedJobRepositoryImpl;
+use nject::injectable;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    error::ApplicationError,
+    shared::{
+        command_handler::{CommandHandler, CommandResult},
+        scheduler_job_impl::SCHEDULER_JOBS,
+    },
+};
+
+/// Blank fields fall back to what the job is compiled with
+#[derive(Debug, Deserialize, Builder, ToSchema)]
+pub struct UpdateSchedJobCommand {
+    key: String,
+    expr: Option<String>,
+    timezone: Option<String>,
+    enabled: bool,
+    /// JSON handed to the job when it runs
+    params: Option<String>,
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct UpdateSchedJobCommandHandler {
+    sched_job_repo: SchedJobRepositoryImpl,
+}
+
+impl CommandHandler for UpdateSchedJobCommandHandler {
+    type Command = UpdateSchedJobCommand;
+    type Output = SchedJob;
+    type Event = SystemEvent;
+
+    #[tracing::instrument]
+    async fn execute(
+        &self,
+        cmd: Self::Command,
+    ) -> Result<CommandResult<Self::Output, Self::Event>, ApplicationError> {
+        if !SCHEDULER_JOBS.iter().any(|job| job.key == cmd.key) {
+            return Err(SystemError::SchedJobNotFound.into());
+        }
+        let expr = not_blank(cmd.expr);
+        if let Some(expr) = &expr
+            && sched_kit::cron_tab::to_cron(expr).is_err()
+        {
+            return Err(SystemError::SchedJobInvalidExpr.into());
+        }
+        let timezone = not_blank(cmd.timezone);
+        if let Some(timezone) = &timezone
+            && chrono_tz::Tz::from_str(timezone).is_err()
+        {
+            return Err(SystemError::SchedJobInvalidTimezone.into());
+        }
+        let params = not_blank(cmd.params);
+        if let Some(params) = &params
+            && serde_json::from_str::<serde_json::Value>(params).is_err()
+        {
+            return Err(SystemError::SchedJobInvalidParams.into());
+        }
+
+        let before = self.sched_job_repo.by_key(&cmd.key).await?;
+        let sched_job = SchedJob::builder()
+            .key(cmd.key)
+            .maybe_expr(expr)
+            .maybe_timezone(timezone)
+            .enabled(cmd.enabled)
+            .maybe_params(params)
+            .build();
+        let sched_job = self.sched_job_repo.save(sched_job).await?;
+        Ok(CommandResult::with_event(
+            sched_job.clone(),
+            SystemEvent::SchedJobsUpdated {
+                items: vec![UpdatedEvent {
+                    before,
+                    after: sched_job,
+                }],
+            },
+        ))
+    }
+}
+
+fn not_blank(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
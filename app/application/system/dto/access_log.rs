@@ -17,3 +17,21 @@ pub struct AccessLogDto {
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, FromRow)]
+pub struct AccessLogDetailDto {
+    pub id: String,
+    pub user_id: String,
+    pub user_name: Option<String>,
+    pub method: String,
+    pub uri: String,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub status: i16,
+    pub elapsed: i64,
+    pub request_body: Option<String>,
+    pub response_body: Option<String>,
+    pub occurred_at: chrono::NaiveDateTime,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
@@ -1,3 +1,4 @@
+use domain::system::value_object::scan_status::ScanStatus;
 use serde::{Deserialize, Serialize};
 use sqlx::{prelude::FromRow, types::chrono};
 use utoipa::ToSchema;
@@ -8,7 +9,60 @@ pub struct FileDto {
     pub name: String,
     pub path: String,
     pub size: i64,
-    pub used: bool,
+    pub hash: Option<String>,
+    pub reference_count: i64,
+    pub uploader_id: Option<String>,
+    pub uploader_name: Option<String>,
+    pub scan_status: ScanStatus,
+    /// Signature or reason reported by the scanner
+    pub scan_result: Option<String>,
+    pub folder_id: Option<String>,
+    pub folder_name: Option<String>,
+    pub tags: Vec<String>,
+    pub description: Option<String>,
+    pub mime_type: Option<String>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, FromRow)]
+pub struct FileUsageDto {
+    pub uploader_id: Option<String>,
+    pub uploader_name: Option<String>,
+    pub file_count: i64,
+    pub size: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, FromRow)]
+pub struct FileFolderDto {
+    pub id: String,
+    pub name: String,
+    pub parent_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FileFolderWithChildren {
+    pub id: String,
+    pub label: String,
+    pub value: String,
+    #[schema(no_recursion)]
+    pub children: Vec<FileFolderWithChildren>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, FromRow)]
+pub struct FileShareDto {
+    pub id: String,
+    pub code: String,
+    pub file_id: String,
+    pub file_name: Option<String>,
+    pub has_password: bool,
+    pub expires_at: Option<chrono::NaiveDateTime>,
+    pub max_downloads: Option<i32>,
+    pub download_count: i32,
+    pub last_downloaded_at: Option<chrono::NaiveDateTime>,
+    pub revoked: bool,
+    pub creator_name: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+}
@@ -2,6 +2,8 @@ pub mod access_log;
 pub mod cpu;
 pub mod file;
 pub mod process_info;
+pub mod recycle_bin;
 pub mod sched;
+pub mod security_event;
 pub mod system_info;
 pub mod system_snapshot;
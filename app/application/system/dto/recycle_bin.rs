@@ -0,0 +1,14 @@
+use domain::system::value_object::recycle_bin_kind::RecycleBinKind;
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, types::chrono};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, FromRow)]
+pub struct RecycleBinItemDto {
+    pub id: String,
+    pub kind: RecycleBinKind,
+    pub name: String,
+    /// Account of a user, code of a department or path of a file
+    pub detail: Option<String>,
+    pub deleted_at: chrono::NaiveDateTime,
+}
@@ -6,7 +6,13 @@ use utoipa::ToSchema;
 pub struct SchedDto {
     pub key: String,
     pub name: String,
+    /// Expression the job runs on, the edited one when set
     pub expr: String,
+    pub default_expr: String,
+    /// `None` runs on the configured timezone
+    pub timezone: Option<String>,
+    pub enabled: bool,
+    pub params: Option<String>,
     pub last_succeed: Option<bool>,
     pub last_result: Option<String>,
     pub last_run_at: Option<chrono::NaiveDateTime>,
@@ -24,6 +30,27 @@ pub struct SchedRecordDto {
     pub result: Option<String>,
     pub run_at: Option<chrono::NaiveDateTime>,
     pub duration_ms: Option<i64>,
+    pub manual: bool,
+    pub triggered_by: Option<String>,
+    pub triggered_by_name: Option<String>,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SchedNextRunDto {
+    pub run_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, FromRow)]
+pub struct SchedLeaderDto {
+    /// `hostname:pid:started` of the process running the jobs
+    pub holder: String,
+    /// Goes up on every failover
+    pub token: i64,
+    pub acquired_at: chrono::NaiveDateTime,
+    pub renewed_at: chrono::NaiveDateTime,
+    pub expires_at: chrono::NaiveDateTime,
+    /// `false` once the lease expired without anyone taking it over
+    pub alive: bool,
+}
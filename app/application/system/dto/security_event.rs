@@ -0,0 +1,26 @@
+use domain::system::value_object::security_event_kind::SecurityEventKind;
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, types::chrono};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, FromRow)]
+pub struct SecurityEventDto {
+    pub id: String,
+    pub user_id: Option<String>,
+    pub user_name: Option<String>,
+    pub account: Option<String>,
+    pub kind: SecurityEventKind,
+    pub succeed: bool,
+    pub reason: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub country: Option<String>,
+    pub region: Option<String>,
+    pub city: Option<String>,
+    pub new_device: bool,
+    pub new_country: bool,
+    pub impossible_travel: bool,
+    pub occurred_at: chrono::NaiveDateTime,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
@@ -1,5 +1,12 @@
+pub mod next_sched_runs;
 pub mod paging_sched_records;
 pub mod retrieve_access_log;
+pub mod retrieve_sched_leader;
 pub mod search_access_logs;
+pub mod search_file_shares;
+pub mod search_file_usages;
 pub mod search_files;
+pub mod search_recycle_bin;
 pub mod search_scheds;
+pub mod search_security_events;
+pub mod tree_file_folders;
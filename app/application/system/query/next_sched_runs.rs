@@ -0,0 +1,63 @@
+use std::str::FromStr as _;
+
+use crate::shared::query_handler::QueryHandler;
+use crate::shared::scheduler_job_impl::SCHEDULER_JOBS;
+use crate::system::dto::sched::SchedNextRunDto;
+use bon::Builder;
+use domain::system::error::SystemError;
+use domain::system::port::sched_job_repository::SchedJobRepository as _;
+use infrastructure::repository::system::sched_job_repository::SchedJobRepositoryImpl;
+use infrastructure::shared::config::ConfigRef;
+use nject::injectable;
+use sched_kit::cron_tab::next_ticks;
+use serde::Deserialize;
+
+const DEFAULT_COUNT: usize = 5;
+
+const MAX_COUNT: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Builder)]
+pub struct NextSchedRunsQuery {
+    key: String,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+#[injectable]
+pub struct NextSchedRunsQueryHandler {
+    config: ConfigRef,
+    sched_job_repo: SchedJobRepositoryImpl,
+}
+
+impl QueryHandler for NextSchedRunsQueryHandler {
+    type Query = NextSchedRunsQuery;
+    type Output = Vec<SchedNextRunDto>;
+    type Error = SystemError;
+
+    /// Fire times in the configured timezone, none while the job is paused
+    #[tracing::instrument]
+    async fn query(&self, query: NextSchedRunsQuery) -> Result<Vec<SchedNextRunDto>, SystemError> {
+        let job = SCHEDULER_JOBS
+            .iter()
+            .find(|job| job.key == query.key)
+            .ok_or(SystemError::SchedJobNotFound)?;
+        let setting = self.sched_job_repo.by_key(job.key).await?;
+        if !setting.enabled {
+            return Ok(Vec::new());
+        }
+        let expr = setting.expr.as_deref().unwrap_or(job.expr);
+        let timezone = setting
+            .timezone
+            .as_deref()
+            .and_then(|timezone| chrono_tz::Tz::from_str(timezone).ok())
+            .unwrap_or(self.config.timezone);
+        let count = query.count.unwrap_or(DEFAULT_COUNT).clamp(1, MAX_COUNT);
+        let items = next_ticks(expr, timezone, count)
+            .into_iter()
+            .map(|d| SchedNextRunDto {
+                run_at: d.with_timezone(&self.config.timezone).naive_local(),
+            })
+            .collect();
+        Ok(items)
+    }
+}
@@ -47,18 +47,19 @@ impl QueryHandler for PagingSchedRecordsQueryHandler {
         let page = query.paging.page();
         let page_size = query.paging.page_size();
         let offset = (page - 1) * page_size;
-        let rows_future = sqlx::query_as!(
-            SchedRecordDto,
+        let rows_future = sqlx::query_as::<_, SchedRecordDto>(
             r#"
-        SELECT id, key, name, expr, succeed, result, run_at, duration_ms, created_at, updated_at
-        FROM _scheds
-        WHERE ($1::text IS NULL OR key = $1)
-        ORDER BY run_at DESC LIMIT $2 OFFSET $3
+        SELECT s.id, s.key, s.name, s.expr, s.succeed, s.result, s.run_at, s.duration_ms,
+            s.manual, s.triggered_by, u.name AS triggered_by_name, s.created_at, s.updated_at
+        FROM _scheds AS s
+        LEFT JOIN _users AS u ON u.id = s.triggered_by
+        WHERE ($1::text IS NULL OR s.key = $1)
+        ORDER BY s.run_at DESC LIMIT $2 OFFSET $3
         "#,
-            query.key,
-            page_size,
-            offset,
         )
+        .bind(&query.key)
+        .bind(page_size)
+        .bind(offset)
         .fetch_all(&self.pool);
         let (total, rows) = tokio::try_join!(total_future, rows_future)?;
         Ok(PagingResult { total, items: rows })
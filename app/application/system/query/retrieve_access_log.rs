@@ -1,5 +1,5 @@
 use crate::shared::query_handler::QueryHandler;
-use crate::system::dto::access_log::AccessLogDto;
+use crate::system::dto::access_log::AccessLogDetailDto;
 use bon::Builder;
 use domain::system::error::SystemError;
 use domain::system::value_object::access_log_id::AccessLogId;
@@ -21,14 +21,17 @@ pub struct RetrieveAccessLogQueryHandler {
 
 impl QueryHandler for RetrieveAccessLogQueryHandler {
     type Query = RetrieveAccessLogQuery;
-    type Output = AccessLogDto;
+    type Output = AccessLogDetailDto;
     type Error = SystemError;
 
     #[single_flight]
     #[tracing::instrument]
-    async fn query(&self, query: RetrieveAccessLogQuery) -> Result<AccessLogDto, SystemError> {
+    async fn query(
+        &self,
+        query: RetrieveAccessLogQuery,
+    ) -> Result<AccessLogDetailDto, SystemError> {
         let row_opt = sqlx::query_as!(
-            AccessLogDto,
+            AccessLogDetailDto,
             r#"
             SELECT a.id as id,
                 a.user_id as user_id,
@@ -38,12 +41,15 @@ impl QueryHandler for RetrieveAccessLogQueryHandler {
                 a.ip as ip,
                 a.status as status,
                 a.elapsed as elapsed, 
+                b.request_body as "request_body?",
+                b.response_body as "response_body?",
                 a.occurred_at as occurred_at, 
                 a.created_at as created_at, 
                 a.updated_at as updated_at,
                 u.name as "user_name?"
             FROM _access_logs as a
             LEFT JOIN _users as u ON u.id = a.user_id
+            LEFT JOIN _access_log_bodies as b ON b.access_log_id = a.id
             WHERE a.id = $1
         "#,
             &query.id,
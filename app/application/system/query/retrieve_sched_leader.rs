@@ -0,0 +1,43 @@
+use crate::shared::query_handler::QueryHandler;
+use crate::system::dto::sched::SchedLeaderDto;
+use domain::system::error::SystemError;
+use infrastructure::port::sched_receiver_impl::SCHED_LEASE;
+use infrastructure::shared::pg_pool::PgPool;
+use nject::injectable;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+pub struct RetrieveSchedLeaderQuery;
+
+#[derive(Debug, Clone)]
+#[injectable]
+pub struct RetrieveSchedLeaderQueryHandler {
+    pool: PgPool,
+}
+
+impl QueryHandler for RetrieveSchedLeaderQueryHandler {
+    type Query = RetrieveSchedLeaderQuery;
+    type Output = Option<SchedLeaderDto>;
+    type Error = SystemError;
+
+    /// `None` until a scheduler process has started once
+    #[tracing::instrument]
+    async fn query(
+        &self,
+        _query: RetrieveSchedLeaderQuery,
+    ) -> Result<Option<SchedLeaderDto>, SystemError> {
+        let leader = sqlx::query_as::<_, SchedLeaderDto>(
+            r#"
+            SELECT holder, token, acquired_at::timestamp AS acquired_at,
+                renewed_at::timestamp AS renewed_at, expires_at::timestamp AS expires_at,
+                expires_at > now() AS alive
+            FROM _sched_leader
+            WHERE name = $1
+            "#,
+        )
+        .bind(SCHED_LEASE)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(leader)
+    }
+}
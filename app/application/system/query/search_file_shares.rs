@@ -0,0 +1,97 @@
+use crate::shared::query_handler::QueryHandler;
+use crate::{
+    shared::{paging_query::PagingQuery, paging_result::PagingResult},
+    system::dto::file::FileShareDto,
+};
+use bon::Builder;
+use domain::system::error::SystemError;
+use infrastructure::shared::pg_pool::PgPool;
+use nject::injectable;
+use serde::Deserialize;
+use serde_with::{NoneAsEmptyString, serde_as};
+use single_flight::single_flight;
+use utoipa::IntoParams;
+
+#[serde_as]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, IntoParams, Builder)]
+pub struct SearchFileSharesQuery {
+    #[serde(flatten)]
+    #[param(inline)]
+    paging: PagingQuery,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    file_id: Option<String>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    file_name: Option<String>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    code: Option<String>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    revoked: Option<bool>,
+}
+
+#[derive(Debug, Clone)]
+#[injectable]
+pub struct SearchFileSharesQueryHandler {
+    pool: PgPool,
+}
+
+impl QueryHandler for SearchFileSharesQueryHandler {
+    type Query = SearchFileSharesQuery;
+    type Output = PagingResult<FileShareDto>;
+    type Error = SystemError;
+
+    #[single_flight]
+    #[tracing::instrument]
+    async fn query(
+        &self,
+        query: SearchFileSharesQuery,
+    ) -> Result<PagingResult<FileShareDto>, SystemError> {
+        let total_future = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM _file_shares AS s
+            LEFT JOIN _files AS f ON f.id = s.file_id
+            WHERE ($1::text IS NULL OR s.file_id = $1)
+            AND ($2::text IS NULL OR f.name LIKE CONCAT('%', $2, '%'))
+            AND ($3::text IS NULL OR s.code = $3)
+            AND ($4::boolean IS NULL OR s.revoked = $4)
+            "#,
+            query.file_id,
+            query.file_name,
+            query.code,
+            query.revoked,
+        )
+        .fetch_one(&self.pool);
+        let page = query.paging.page();
+        let page_size = query.paging.page_size();
+        let offset = (page - 1) * page_size;
+        let rows_future = sqlx::query_as!(
+            FileShareDto,
+            r#"
+        SELECT s.id, s.code, s.file_id, f.name AS "file_name?", s.password IS NOT NULL AS "has_password!",
+            s.expires_at, s.max_downloads, s.download_count, s.last_downloaded_at, s.revoked,
+            u.name AS "creator_name?", s.created_at
+        FROM _file_shares AS s
+        LEFT JOIN _files AS f ON f.id = s.file_id
+        LEFT JOIN _users AS u ON u.id = s.created_by
+        WHERE ($1::text IS NULL OR s.file_id = $1)
+        AND ($2::text IS NULL OR f.name LIKE CONCAT('%', $2, '%'))
+        AND ($3::text IS NULL OR s.code = $3)
+        AND ($4::boolean IS NULL OR s.revoked = $4)
+        ORDER BY s.created_at DESC LIMIT $5 OFFSET $6
+        "#,
+            query.file_id,
+            query.file_name,
+            query.code,
+            query.revoked,
+            page_size,
+            offset,
+        )
+        .fetch_all(&self.pool);
+        let (total, rows) = tokio::try_join!(total_future, rows_future)?;
+        Ok(PagingResult { total, items: rows })
+    }
+}
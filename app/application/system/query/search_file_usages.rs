@@ -0,0 +1,78 @@
+use crate::shared::query_handler::QueryHandler;
+use crate::{
+    shared::{paging_query::PagingQuery, paging_result::PagingResult},
+    system::dto::file::FileUsageDto,
+};
+use bon::Builder;
+use domain::system::error::SystemError;
+use infrastructure::shared::pg_pool::PgPool;
+use nject::injectable;
+use serde::Deserialize;
+use serde_with::{NoneAsEmptyString, serde_as};
+use single_flight::single_flight;
+use utoipa::IntoParams;
+
+#[serde_as]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, IntoParams, Builder)]
+pub struct SearchFileUsagesQuery {
+    #[serde(flatten)]
+    #[param(inline)]
+    paging: PagingQuery,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    uploader_name: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+#[injectable]
+pub struct SearchFileUsagesQueryHandler {
+    pool: PgPool,
+}
+
+impl QueryHandler for SearchFileUsagesQueryHandler {
+    type Query = SearchFileUsagesQuery;
+    type Output = PagingResult<FileUsageDto>;
+    type Error = SystemError;
+
+    #[single_flight]
+    #[tracing::instrument]
+    async fn query(
+        &self,
+        query: SearchFileUsagesQuery,
+    ) -> Result<PagingResult<FileUsageDto>, SystemError> {
+        let total_future = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!" FROM (
+                SELECT f.uploader_id
+                FROM _files AS f
+                LEFT JOIN _users AS u ON u.id = f.uploader_id
+                WHERE ($1::text IS NULL OR u.name LIKE CONCAT('%', $1, '%'))
+                GROUP BY f.uploader_id
+            ) AS g
+            "#,
+            query.uploader_name,
+        )
+        .fetch_one(&self.pool);
+        let page = query.paging.page();
+        let page_size = query.paging.page_size();
+        let offset = (page - 1) * page_size;
+        let rows_future = sqlx::query_as!(
+            FileUsageDto,
+            r#"
+        SELECT f.uploader_id, MAX(u.name) AS "uploader_name?",
+            COUNT(*) AS "file_count!", COALESCE(SUM(f.size), 0)::BIGINT AS "size!"
+        FROM _files AS f
+        LEFT JOIN _users AS u ON u.id = f.uploader_id
+        WHERE ($1::text IS NULL OR u.name LIKE CONCAT('%', $1, '%'))
+        GROUP BY f.uploader_id
+        ORDER BY SUM(f.size) DESC LIMIT $2 OFFSET $3
+        "#,
+            query.uploader_name,
+            page_size,
+            offset,
+        )
+        .fetch_all(&self.pool);
+        let (total, rows) = tokio::try_join!(total_future, rows_future)?;
+        Ok(PagingResult { total, items: rows })
+    }
+}
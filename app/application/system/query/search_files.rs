@@ -4,7 +4,7 @@ use crate::{
     system::dto::file::FileDto,
 };
 use bon::Builder;
-use domain::system::error::SystemError;
+use domain::system::{error::SystemError, value_object::scan_status::ScanStatus};
 use infrastructure::shared::pg_pool::PgPool;
 use nject::injectable;
 use serde::Deserialize;
@@ -24,6 +24,28 @@ pub struct SearchFilesQuery {
     #[serde_as(as = "NoneAsEmptyString")]
     #[serde(default)]
     used: Option<bool>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    folder_id: Option<String>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    tag: Option<String>,
+    /// Matches by prefix, `image/` finds every image
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    mime_type: Option<String>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    uploader_name: Option<String>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    description: Option<String>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    min_width: Option<i32>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    min_height: Option<i32>,
 }
 
 #[derive(Debug, Clone)]
@@ -43,12 +65,28 @@ impl QueryHandler for SearchFilesQueryHandler {
         let total_future = sqlx::query_scalar!(
             r#"
             SELECT COUNT(*) AS "count!"
-            FROM _files
-            WHERE ($1::text IS NULL OR name LIKE CONCAT('%', $1, '%'))
-            AND ($2::boolean IS NULL OR used = $2)
+            FROM _files AS f
+            LEFT JOIN _users AS u ON u.id = f.uploader_id
+            WHERE f.deleted_at IS NULL
+            AND ($1::text IS NULL OR f.name LIKE CONCAT('%', $1, '%'))
+            AND ($2::boolean IS NULL OR EXISTS (SELECT 1 FROM _file_references AS r WHERE r.file_id = f.id) = $2)
+            AND ($3::text IS NULL OR f.folder_id = $3)
+            AND ($4::text IS NULL OR $4 = ANY(f.tags))
+            AND ($5::text IS NULL OR f.mime_type LIKE CONCAT($5, '%'))
+            AND ($6::text IS NULL OR u.name LIKE CONCAT('%', $6, '%'))
+            AND ($7::text IS NULL OR f.description LIKE CONCAT('%', $7, '%'))
+            AND ($8::integer IS NULL OR f.width >= $8)
+            AND ($9::integer IS NULL OR f.height >= $9)
             "#,
             query.name,
             query.used,
+            query.folder_id,
+            query.tag,
+            query.mime_type,
+            query.uploader_name,
+            query.description,
+            query.min_width,
+            query.min_height,
         )
         .fetch_one(&self.pool);
         let page = query.paging.page();
@@ -57,14 +95,36 @@ impl QueryHandler for SearchFilesQueryHandler {
         let rows_future = sqlx::query_as!(
             FileDto,
             r#"
-        SELECT id, name, path, size, used, created_at, updated_at
-        FROM _files
-        WHERE ($1::text IS NULL OR name LIKE CONCAT('%', $1, '%'))
-        AND ($2::boolean IS NULL OR used = $2)
-        ORDER BY created_at DESC LIMIT $3 OFFSET $4 
+        SELECT f.id, f.name, f.path, f.size, f.hash,
+            (SELECT COUNT(*) FROM _file_references AS r WHERE r.file_id = f.id) AS "reference_count!",
+            f.uploader_id, u.name AS "uploader_name?",
+            f.scan_status AS "scan_status: ScanStatus", f.scan_result,
+            f.folder_id, d.name AS "folder_name?", f.tags, f.description, f.mime_type, f.width, f.height,
+            f.created_at, f.updated_at
+        FROM _files AS f
+        LEFT JOIN _users AS u ON u.id = f.uploader_id
+        LEFT JOIN _file_folders AS d ON d.id = f.folder_id
+        WHERE f.deleted_at IS NULL
+        AND ($1::text IS NULL OR f.name LIKE CONCAT('%', $1, '%'))
+        AND ($2::boolean IS NULL OR EXISTS (SELECT 1 FROM _file_references AS r WHERE r.file_id = f.id) = $2)
+        AND ($3::text IS NULL OR f.folder_id = $3)
+        AND ($4::text IS NULL OR $4 = ANY(f.tags))
+        AND ($5::text IS NULL OR f.mime_type LIKE CONCAT($5, '%'))
+        AND ($6::text IS NULL OR u.name LIKE CONCAT('%', $6, '%'))
+        AND ($7::text IS NULL OR f.description LIKE CONCAT('%', $7, '%'))
+        AND ($8::integer IS NULL OR f.width >= $8)
+        AND ($9::integer IS NULL OR f.height >= $9)
+        ORDER BY f.created_at DESC LIMIT $10 OFFSET $11
         "#,
             query.name,
             query.used,
+            query.folder_id,
+            query.tag,
+            query.mime_type,
+            query.uploader_name,
+            query.description,
+            query.min_width,
+            query.min_height,
             page_size,
             offset,
         )
@@ -73,3 +133,122 @@ impl QueryHandler for SearchFilesQueryHandler {
         Ok(PagingResult { total, items: rows })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use infrastructure::{shared::chrono_tz::ChronoTz, test_utils::setup_database};
+    use serde_json::json;
+
+    use super::*;
+
+    async fn search(handler: &SearchFilesQueryHandler, query: serde_json::Value) -> Vec<String> {
+        let query = serde_json::from_value(query).unwrap();
+        let mut names: Vec<String> = handler
+            .query(query)
+            .await
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|file| file.name)
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[sqlx::test]
+    async fn test_query_filter_files(pool: PgPool) {
+        setup_database(pool.clone()).await;
+        let now = ChronoTz::default().now();
+        let folder_id = "f".repeat(24);
+        sqlx::query!(
+            r#"
+            INSERT INTO _file_folders (id, name, created_at, updated_at) VALUES ($1, 'photos', $2, $2)
+            "#,
+            folder_id,
+            now,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query!(
+            r#"
+            INSERT INTO _files (id, name, size, path, folder_id, tags, description, mime_type,
+                width, height, deleted_at, created_at, updated_at)
+            VALUES
+                ('a', 'beach.png', 1, 'test/a.png', $1, '{holiday,sea}', 'summer holiday',
+                    'image/png', 200, 100, NULL, $2, $2),
+                ('b', 'report.pdf', 1, 'test/b.pdf', NULL, '{sea}', NULL,
+                    'application/pdf', NULL, NULL, NULL, $2, $2),
+                ('c', 'deleted.png', 1, 'test/c.png', $1, '{holiday}', 'summer holiday',
+                    'image/png', 200, 200, $2, $2, $2)
+            "#,
+            folder_id,
+            now,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query!(
+            r#"
+            INSERT INTO _file_references (owner_type, owner_id, field, file_id, created_at)
+            VALUES ('user', 'u', 'portrait', 'b', $1)
+            "#,
+            now,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let handler = SearchFilesQueryHandler { pool };
+
+        assert_eq!(
+            search(&handler, json!({})).await,
+            ["beach.png", "report.pdf"]
+        );
+        assert_eq!(
+            search(&handler, json!({"name": "port"})).await,
+            ["report.pdf"]
+        );
+        assert_eq!(
+            search(&handler, json!({"used": "true"})).await,
+            ["report.pdf"]
+        );
+        assert_eq!(
+            search(&handler, json!({"used": "false"})).await,
+            ["beach.png"]
+        );
+        assert_eq!(
+            search(&handler, json!({"folder_id": folder_id})).await,
+            ["beach.png"]
+        );
+        assert_eq!(
+            search(&handler, json!({"tag": "holiday"})).await,
+            ["beach.png"]
+        );
+        assert_eq!(
+            search(&handler, json!({"tag": "sea"})).await,
+            ["beach.png", "report.pdf"]
+        );
+        assert_eq!(
+            search(&handler, json!({"mime_type": "image/"})).await,
+            ["beach.png"]
+        );
+        assert_eq!(
+            search(&handler, json!({"description": "holiday"})).await,
+            ["beach.png"]
+        );
+        assert_eq!(
+            search(&handler, json!({"min_width": "200"})).await,
+            ["beach.png"]
+        );
+        assert!(
+            search(&handler, json!({"min_width": "200", "min_height": "200"}))
+                .await
+                .is_empty()
+        );
+        // empty parameters of a form leave the filter out
+        assert_eq!(
+            search(&handler, json!({"tag": "", "min_width": ""})).await,
+            ["beach.png", "report.pdf"]
+        );
+    }
+}
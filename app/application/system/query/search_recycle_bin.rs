@@ -0,0 +1,95 @@
+use crate::shared::query_handler::QueryHandler;
+use crate::{
+    shared::{paging_query::PagingQuery, paging_result::PagingResult},
+    system::dto::recycle_bin::RecycleBinItemDto,
+};
+use bon::Builder;
+use domain::system::{error::SystemError, value_object::recycle_bin_kind::RecycleBinKind};
+use infrastructure::shared::pg_pool::PgPool;
+use nject::injectable;
+use serde::Deserialize;
+use serde_with::{NoneAsEmptyString, serde_as};
+use single_flight::single_flight;
+use utoipa::IntoParams;
+
+#[serde_as]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, IntoParams, Builder)]
+pub struct SearchRecycleBinQuery {
+    #[serde(flatten)]
+    #[param(inline)]
+    paging: PagingQuery,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    kind: Option<RecycleBinKind>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    name: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+#[injectable]
+pub struct SearchRecycleBinQueryHandler {
+    pool: PgPool,
+}
+
+impl QueryHandler for SearchRecycleBinQueryHandler {
+    type Query = SearchRecycleBinQuery;
+    type Output = PagingResult<RecycleBinItemDto>;
+    type Error = SystemError;
+
+    #[single_flight]
+    #[tracing::instrument]
+    async fn query(
+        &self,
+        query: SearchRecycleBinQuery,
+    ) -> Result<PagingResult<RecycleBinItemDto>, SystemError> {
+        let total_future = sqlx::query_scalar!(
+            r#"
+            WITH bin AS (
+                SELECT 'user' AS kind, name FROM _users WHERE deleted_at IS NOT NULL
+                UNION ALL
+                SELECT 'role' AS kind, name FROM _roles WHERE deleted_at IS NOT NULL
+                UNION ALL
+                SELECT 'department' AS kind, name FROM _departments WHERE deleted_at IS NOT NULL
+                UNION ALL
+                SELECT 'file' AS kind, name FROM _files WHERE deleted_at IS NOT NULL
+            )
+            SELECT COUNT(*) AS "count!" FROM bin
+            WHERE ($1::text IS NULL OR kind = $1)
+            AND ($2::text IS NULL OR name LIKE CONCAT('%', $2, '%'))
+            "#,
+            query.kind as Option<RecycleBinKind>,
+            query.name,
+        )
+        .fetch_one(&self.pool);
+        let page = query.paging.page();
+        let page_size = query.paging.page_size();
+        let offset = (page - 1) * page_size;
+        let rows_future = sqlx::query_as!(
+            RecycleBinItemDto,
+            r#"
+        WITH bin AS (
+            SELECT id, 'user' AS kind, name, account AS detail, deleted_at FROM _users WHERE deleted_at IS NOT NULL
+            UNION ALL
+            SELECT id, 'role' AS kind, name, NULL AS detail, deleted_at FROM _roles WHERE deleted_at IS NOT NULL
+            UNION ALL
+            SELECT id, 'department' AS kind, name, code AS detail, deleted_at FROM _departments WHERE deleted_at IS NOT NULL
+            UNION ALL
+            SELECT id, 'file' AS kind, name, path AS detail, deleted_at FROM _files WHERE deleted_at IS NOT NULL
+        )
+        SELECT id AS "id!", kind AS "kind!: RecycleBinKind", name AS "name!", detail, deleted_at AS "deleted_at!"
+        FROM bin
+        WHERE ($1::text IS NULL OR kind = $1)
+        AND ($2::text IS NULL OR name LIKE CONCAT('%', $2, '%'))
+        ORDER BY deleted_at DESC LIMIT $3 OFFSET $4
+        "#,
+            query.kind as Option<RecycleBinKind>,
+            query.name,
+            page_size,
+            offset,
+        )
+        .fetch_all(&self.pool);
+        let (total, rows) = tokio::try_join!(total_future, rows_future)?;
+        Ok(PagingResult { total, items: rows })
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::str::FromStr as _;
 
 use crate::shared::paging_query::PagingQuery;
 use crate::shared::paging_result::PagingResult;
@@ -6,7 +7,10 @@ use crate::shared::query_handler::QueryHandler;
 use crate::shared::scheduler_job_impl::SCHEDULER_JOBS;
 use crate::system::dto::sched::SchedDto;
 use bon::Builder;
+use domain::system::entity::sched_job::SchedJob;
 use domain::system::error::SystemError;
+use domain::system::port::sched_job_repository::SchedJobRepository as _;
+use infrastructure::repository::system::sched_job_repository::SchedJobRepositoryImpl;
 use infrastructure::shared::config::ConfigRef;
 use infrastructure::shared::pg_pool::PgPool;
 use nject::injectable;
@@ -28,6 +32,7 @@ pub struct SearchSchedsQuery {
 pub struct SearchSchedsQueryHandler {
     pool: PgPool,
     config: ConfigRef,
+    sched_job_repo: SchedJobRepositoryImpl,
 }
 
 impl QueryHandler for SearchSchedsQueryHandler {
@@ -64,17 +69,42 @@ impl QueryHandler for SearchSchedsQueryHandler {
             .into_iter()
             .map(|row| (row.key.to_string(), row))
             .collect::<HashMap<_, _>>();
+        let mut setting_map = self
+            .sched_job_repo
+            .all()
+            .await?
+            .into_iter()
+            .map(|item| (item.key.clone(), item))
+            .collect::<HashMap<_, _>>();
         let items = page_jobs
             .iter()
-            .map(|job| SchedDto {
-                key: job.key.to_string(),
-                name: job.name.to_string(),
-                expr: job.expr.to_string(),
-                last_succeed: record_map.get(job.key).map(|row| row.succeed),
-                last_result: record_map.get(job.key).map(|row| row.result.clone()),
-                last_run_at: record_map.get(job.key).map(|row| row.run_at),
-                next_run_at: next_tick(job.expr, self.config.timezone).map(|d| d.naive_local()),
-                last_duration_ms: record_map.get(job.key).map(|row| row.duration_ms),
+            .map(|job| {
+                let setting = setting_map
+                    .remove(job.key)
+                    .unwrap_or_else(|| SchedJob::defaults(job.key));
+                let expr = setting.expr.clone().unwrap_or(job.expr.to_string());
+                let timezone = setting
+                    .timezone
+                    .as_deref()
+                    .and_then(|timezone| chrono_tz::Tz::from_str(timezone).ok())
+                    .unwrap_or(self.config.timezone);
+                let next_run_at = next_tick(&expr, timezone)
+                    .filter(|_| setting.enabled)
+                    .map(|d| d.with_timezone(&self.config.timezone).naive_local());
+                SchedDto {
+                    key: job.key.to_string(),
+                    name: job.name.to_string(),
+                    default_expr: job.expr.to_string(),
+                    timezone: setting.timezone.clone(),
+                    enabled: setting.enabled,
+                    params: setting.params.clone(),
+                    expr,
+                    last_succeed: record_map.get(job.key).and_then(|row| row.succeed),
+                    last_result: record_map.get(job.key).and_then(|row| row.result.clone()),
+                    last_run_at: record_map.get(job.key).map(|row| row.run_at),
+                    next_run_at,
+                    last_duration_ms: record_map.get(job.key).and_then(|row| row.duration_ms),
+                }
             })
             .collect();
         Ok(PagingResult {
@@ -0,0 +1,128 @@
+use crate::shared::query_handler::QueryHandler;
+use crate::{
+    shared::{paging_query::PagingQuery, paging_result::PagingResult},
+    system::dto::security_event::SecurityEventDto,
+};
+use bon::Builder;
+use domain::organization::value_object::user_id::UserId;
+use domain::system::error::SystemError;
+use domain::system::value_object::security_event_kind::SecurityEventKind;
+use infrastructure::shared::pg_pool::PgPool;
+use nject::injectable;
+use serde::Deserialize;
+use serde_with::{NoneAsEmptyString, serde_as};
+use single_flight::single_flight;
+use utoipa::IntoParams;
+
+#[serde_as]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, IntoParams, Builder)]
+pub struct SearchSecurityEventsQuery {
+    #[serde(flatten)]
+    #[param(inline)]
+    paging: PagingQuery,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    user_id: Option<String>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    account: Option<String>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    kind: Option<SecurityEventKind>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    succeed: Option<bool>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    anomalous: Option<bool>,
+}
+
+impl SearchSecurityEventsQuery {
+    /// Restricts the search to the given user, regardless of the requested filter
+    pub fn with_user_id(mut self, user_id: &UserId) -> Self {
+        self.user_id = Some(user_id.to_string());
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+#[injectable]
+pub struct SearchSecurityEventsQueryHandler {
+    pool: PgPool,
+}
+
+impl QueryHandler for SearchSecurityEventsQueryHandler {
+    type Query = SearchSecurityEventsQuery;
+    type Output = PagingResult<SecurityEventDto>;
+    type Error = SystemError;
+
+    #[single_flight]
+    #[tracing::instrument]
+    async fn query(
+        &self,
+        query: SearchSecurityEventsQuery,
+    ) -> Result<PagingResult<SecurityEventDto>, SystemError> {
+        let total_future = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM _security_events
+            WHERE ($1::text IS NULL OR user_id = $1)
+                AND ($2::text IS NULL OR account LIKE CONCAT('%', $2, '%'))
+                AND ($3::text IS NULL OR kind = $3)
+                AND ($4::boolean IS NULL OR succeed = $4)
+                AND ($5::boolean IS NULL OR (new_device OR new_country OR impossible_travel) = $5)
+            "#,
+            query.user_id,
+            query.account,
+            query.kind as Option<SecurityEventKind>,
+            query.succeed,
+            query.anomalous,
+        )
+        .fetch_one(&self.pool);
+        let page = query.paging.page();
+        let page_size = query.paging.page_size();
+        let offset = (page - 1) * page_size;
+        let rows_future = sqlx::query_as!(
+            SecurityEventDto,
+            r#"
+        SELECT e.id as id,
+            e.user_id as user_id,
+            u.name as "user_name?",
+            e.account as account,
+            e.kind as "kind: SecurityEventKind",
+            e.succeed as succeed,
+            e.reason as reason,
+            e.ip as ip,
+            e.user_agent as user_agent,
+            e.country as country,
+            e.region as region,
+            e.city as city,
+            e.new_device as new_device,
+            e.new_country as new_country,
+            e.impossible_travel as impossible_travel,
+            e.occurred_at as occurred_at,
+            e.created_at as created_at,
+            e.updated_at as updated_at
+        FROM _security_events as e
+        LEFT JOIN _users as u ON u.id = e.user_id
+        WHERE ($1::text IS NULL OR e.user_id = $1)
+            AND ($2::text IS NULL OR e.account LIKE CONCAT('%', $2, '%'))
+            AND ($3::text IS NULL OR e.kind = $3)
+            AND ($4::boolean IS NULL OR e.succeed = $4)
+            AND ($5::boolean IS NULL OR (e.new_device OR e.new_country OR e.impossible_travel) = $5)
+        ORDER BY e.occurred_at DESC
+        LIMIT $6 OFFSET $7
+        "#,
+            query.user_id,
+            query.account,
+            query.kind as Option<SecurityEventKind>,
+            query.succeed,
+            query.anomalous,
+            page_size,
+            offset,
+        )
+        .fetch_all(&self.pool);
+        let (total, rows) = tokio::try_join!(total_future, rows_future)?;
+        Ok(PagingResult { total, items: rows })
+    }
+}
@@ -0,0 +1,86 @@
+use crate::shared::query_handler::QueryHandler;
+use crate::system::dto::file::FileFolderDto;
+use crate::system::dto::file::FileFolderWithChildren;
+use bon::Builder;
+use domain::system::error::SystemError;
+use infrastructure::shared::pg_pool::PgPool;
+use nject::injectable;
+use serde::Deserialize;
+use serde_with::serde_as;
+use single_flight::single_flight;
+use std::collections::HashMap;
+use utoipa::IntoParams;
+
+#[serde_as]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, IntoParams, Builder)]
+pub struct TreeFileFoldersQuery {}
+
+#[derive(Debug, Clone)]
+#[injectable]
+pub struct TreeFileFoldersQueryHandler {
+    pool: PgPool,
+}
+
+impl QueryHandler for TreeFileFoldersQueryHandler {
+    type Query = TreeFileFoldersQuery;
+    type Output = Vec<FileFolderWithChildren>;
+    type Error = SystemError;
+
+    #[single_flight]
+    #[tracing::instrument]
+    async fn query(
+        &self,
+        query: TreeFileFoldersQuery,
+    ) -> Result<Vec<FileFolderWithChildren>, SystemError> {
+        let folders = sqlx::query_as!(
+            FileFolderDto,
+            r#"
+        SELECT id, name, parent_id
+        FROM _file_folders
+        ORDER BY name
+        "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(Self::build_folder_tree(folders))
+    }
+}
+
+impl TreeFileFoldersQueryHandler {
+    pub fn build_folder_tree(folders: Vec<FileFolderDto>) -> Vec<FileFolderWithChildren> {
+        let mut children_map: HashMap<Option<String>, Vec<FileFolderDto>> = HashMap::new();
+        for folder in folders {
+            children_map
+                .entry(folder.parent_id.clone())
+                .or_default()
+                .push(folder);
+        }
+
+        fn build_node(
+            folder: &FileFolderDto,
+            children_map: &HashMap<Option<String>, Vec<FileFolderDto>>,
+        ) -> FileFolderWithChildren {
+            let children = children_map.get(&Some(folder.id.clone())).map(|children| {
+                children
+                    .iter()
+                    .map(|child| build_node(child, children_map))
+                    .collect()
+            });
+
+            FileFolderWithChildren {
+                id: folder.id.clone(),
+                label: folder.name.clone(),
+                value: folder.id.clone(),
+                children: children.unwrap_or_default(),
+            }
+        }
+
+        children_map
+            .get(&None)
+            .unwrap_or(&vec![])
+            .iter()
+            .map(|folder| build_node(folder, &children_map))
+            .collect()
+    }
+}
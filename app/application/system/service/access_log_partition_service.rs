@@ -0,0 +1,338 @@
+use crate::error::ApplicationResult;
+use bon::Builder;
+use chrono::{NaiveDate, NaiveTime};
+use flate2::{Compression, write::GzEncoder};
+use futures_util::{StreamExt as _, stream};
+use infrastructure::shared::{
+    chrono_tz::{ChronoTz, Duration},
+    pg_pool::PgPool,
+    workspace::WorkspaceRef,
+};
+use nject::injectable;
+use object_storage_kit::{ObjectStorage, ObjectStorageWriter as _, error::ObjectStorageError};
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use std::{
+    io::{BufWriter, Write as _},
+    pin,
+};
+use tempfile::NamedTempFile;
+use tokio::{fs::File, sync::mpsc, task};
+use tokio_util::io::ReaderStream;
+
+const PARENT_TABLE: &str = "_access_logs";
+const DEFAULT_PARTITION: &str = "_access_logs_default";
+const PARTITION_PREFIX: &str = "_access_logs_p";
+const PARTITION_DATE_FORMAT: &str = "%Y%m%d";
+const ARCHIVE_DIR: &str = "archives/access_logs";
+const ARCHIVE_BUFFER: usize = 256;
+
+#[derive(Debug, Clone, Builder)]
+#[injectable]
+pub struct AccessLogPartitionService {
+    ct: ChronoTz,
+    pool: PgPool,
+    object_storage: ObjectStorage,
+    workspace: WorkspaceRef,
+}
+
+impl AccessLogPartitionService {
+    /// Creates daily partitions from today up to `days` ahead, and moves rows that
+    /// fell into the default partition into partitions of their own
+    #[tracing::instrument]
+    pub async fn create_ahead(&self, days: i64) -> ApplicationResult<()> {
+        let today = self.ct.now().date();
+        let stray_days = sqlx::query_scalar!(
+            r#"SELECT DISTINCT occurred_at::DATE AS "day!" FROM _access_logs_default"#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let upcoming_days = (0..=days).map(|offset| today + Duration::days(offset));
+        for day in stray_days.into_iter().chain(upcoming_days) {
+            self.ensure_partition(day).await?;
+        }
+        Ok(())
+    }
+
+    /// Detaches partitions older than the retention, archives them to object storage and drops them
+    #[tracing::instrument]
+    pub async fn rotate(&self, retention: Duration) -> ApplicationResult<()> {
+        let cutoff = (self.ct.now() - retention).date();
+        let names = sqlx::query_scalar!(
+            r#"SELECT relname::TEXT AS "name!" FROM pg_class WHERE relkind = 'r' AND relname LIKE '\_access\_logs\_p%'"#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let mut expired: Vec<(String, NaiveDate)> = names
+            .into_iter()
+            .filter_map(|name| partition_day(&name).map(|day| (name, day)))
+            .filter(|(_, day)| *day < cutoff)
+            .collect();
+        expired.sort_by_key(|(_, day)| *day);
+        for (name, day) in expired {
+            self.detach_partition(&name).await?;
+            let path = self.archive_partition(&name, day).await?;
+            sqlx::query(&format!("DROP TABLE {name}"))
+                .execute(&self.pool)
+                .await?;
+            tracing::info!(partition = name, path, "Access log partition archived");
+        }
+        Ok(())
+    }
+
+    async fn ensure_partition(&self, day: NaiveDate) -> ApplicationResult<()> {
+        let name = partition_name(day);
+        let exists =
+            sqlx::query_scalar!(r#"SELECT to_regclass($1) IS NOT NULL AS "exists!""#, &name)
+                .fetch_one(&self.pool)
+                .await?;
+        if exists {
+            return Ok(());
+        }
+        let from = day.and_time(NaiveTime::MIN);
+        let to = from + Duration::days(1);
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(&format!(
+            "LOCK TABLE {DEFAULT_PARTITION} IN ACCESS EXCLUSIVE MODE"
+        ))
+        .execute(&mut *tx)
+        .await?;
+        // another run may have created it while we were waiting for the lock
+        let exists =
+            sqlx::query_scalar!(r#"SELECT to_regclass($1) IS NOT NULL AS "exists!""#, &name)
+                .fetch_one(&mut *tx)
+                .await?;
+        if exists {
+            return Ok(());
+        }
+        sqlx::query(&format!(
+            "CREATE TABLE {name} (LIKE {PARENT_TABLE} INCLUDING DEFAULTS INCLUDING CONSTRAINTS)"
+        ))
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(&format!(
+            r#"
+            WITH moved AS (
+                DELETE FROM {DEFAULT_PARTITION} WHERE occurred_at >= $1 AND occurred_at < $2 RETURNING *
+            )
+            INSERT INTO {name} SELECT * FROM moved
+            "#
+        ))
+        .bind(from)
+        .bind(to)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(&format!(
+            "ALTER TABLE {PARENT_TABLE} ATTACH PARTITION {name} FOR VALUES FROM ('{from}') TO ('{to}')"
+        ))
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn detach_partition(&self, name: &str) -> ApplicationResult<()> {
+        let attached = sqlx::query_scalar!(
+            r#"SELECT EXISTS (SELECT 1 FROM pg_inherits WHERE inhrelid = to_regclass($1)) AS "attached!""#,
+            name
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        if attached {
+            sqlx::query(&format!(
+                "ALTER TABLE {PARENT_TABLE} DETACH PARTITION {name}"
+            ))
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Writes the partition as gzip compressed NDJSON, returns the object path
+    async fn archive_partition(&self, name: &str, day: NaiveDate) -> ApplicationResult<String> {
+        let path = format!("{ARCHIVE_DIR}/{}/{name}.ndjson.gz", day.format("%Y/%m"));
+        let temp_file = NamedTempFile::new_in(self.workspace.temp_dir())?;
+        let writer = BufWriter::new(temp_file.reopen()?);
+        let (tx, mut rx) = mpsc::channel::<ArchivedAccessLog>(ARCHIVE_BUFFER);
+        let encoding = task::spawn_blocking(move || -> std::io::Result<()> {
+            let mut encoder = GzEncoder::new(writer, Compression::default());
+            while let Some(row) = rx.blocking_recv() {
+                serde_json::to_writer(&mut encoder, &row)?;
+                encoder.write_all(b"\n")?;
+            }
+            encoder.finish()?.flush()
+        });
+
+        let sql = format!(
+            "SELECT id, user_id, method, uri, user_agent, ip, status, elapsed, occurred_at, created_at, updated_at FROM {name} ORDER BY occurred_at"
+        );
+        let mut rows = sqlx::query_as::<_, ArchivedAccessLog>(&sql).fetch(&self.pool);
+        while let Some(row) = rows.next().await {
+            // the encoder only hangs up on error, which is reported below
+            if tx.send(row?).await.is_err() {
+                break;
+            }
+        }
+        drop(tx);
+        encoding.await??;
+
+        let file = File::open(temp_file.path()).await?;
+        let stream =
+            stream::once(async move { Ok::<_, ObjectStorageError>(ReaderStream::new(file)) });
+        self.object_storage
+            .write_stream(&path, pin::pin!(stream))
+            .await?;
+        Ok(path)
+    }
+}
+
+fn partition_name(day: NaiveDate) -> String {
+    format!("{PARTITION_PREFIX}{}", day.format(PARTITION_DATE_FORMAT))
+}
+
+fn partition_day(name: &str) -> Option<NaiveDate> {
+    let suffix = name.strip_prefix(PARTITION_PREFIX)?;
+    NaiveDate::parse_from_str(suffix, PARTITION_DATE_FORMAT).ok()
+}
+
+#[derive(Debug, FromRow, Serialize)]
+struct ArchivedAccessLog {
+    id: String,
+    user_id: String,
+    method: String,
+    uri: String,
+    user_agent: Option<String>,
+    ip: Option<String>,
+    status: i16,
+    elapsed: i64,
+    occurred_at: chrono::NaiveDateTime,
+    created_at: chrono::NaiveDateTime,
+    updated_at: chrono::NaiveDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDateTime;
+    use flate2::read::GzDecoder;
+    use infrastructure::test_utils::{setup_database, setup_object_storage};
+    use object_storage_kit::ObjectStorageStreamReader as _;
+    use std::io::Read as _;
+
+    use super::*;
+
+    async fn build_service(pool: PgPool) -> AccessLogPartitionService {
+        setup_database(pool.clone()).await;
+        AccessLogPartitionService::builder()
+            .ct(ChronoTz::default())
+            .pool(pool)
+            .object_storage(setup_object_storage().await)
+            .workspace(WorkspaceRef::default())
+            .build()
+    }
+
+    async fn insert_log(pool: &PgPool, id: &str, occurred_at: NaiveDateTime) {
+        sqlx::query(
+            r#"
+            INSERT INTO _access_logs (id, user_id, method, uri, status, elapsed, occurred_at, created_at, updated_at)
+            VALUES ($1, $2, 'GET', '/api/ping', 200, 1, $3, $3, $3)
+            "#,
+        )
+        .bind(id)
+        .bind("u".repeat(24))
+        .bind(occurred_at)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    async fn partition_exists(pool: &PgPool, name: &str) -> bool {
+        sqlx::query_scalar::<_, bool>("SELECT to_regclass($1) IS NOT NULL")
+            .bind(name)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn count_rows(pool: &PgPool, table: &str) -> i64 {
+        sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM {table}"))
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_create_ahead_create_partitions_from_today(pool: PgPool) {
+        let service = build_service(pool.clone()).await;
+        let today = ChronoTz::default().now().date();
+
+        service.create_ahead(2).await.unwrap();
+        service.create_ahead(2).await.unwrap();
+
+        for offset in 0..=2 {
+            let name = partition_name(today + Duration::days(offset));
+            assert!(partition_exists(&pool, &name).await, "{name}");
+        }
+        let name = partition_name(today + Duration::days(3));
+        assert!(!partition_exists(&pool, &name).await);
+    }
+
+    #[sqlx::test]
+    async fn test_create_ahead_move_rows_out_of_default_partition(pool: PgPool) {
+        let service = build_service(pool.clone()).await;
+        let occurred_at = day(2020, 1, 2).and_hms_opt(23, 59, 59).unwrap();
+        insert_log(&pool, &"a".repeat(24), occurred_at).await;
+        assert_eq!(count_rows(&pool, DEFAULT_PARTITION).await, 1);
+
+        service.create_ahead(0).await.unwrap();
+
+        assert_eq!(count_rows(&pool, DEFAULT_PARTITION).await, 0);
+        assert_eq!(count_rows(&pool, "_access_logs_p20200102").await, 1);
+        assert_eq!(count_rows(&pool, PARENT_TABLE).await, 1);
+    }
+
+    #[sqlx::test]
+    async fn test_rotate_archive_and_drop_expired_partitions(pool: PgPool) {
+        let service = build_service(pool.clone()).await;
+        let expired = day(2020, 1, 2);
+        let id = "b".repeat(24);
+        insert_log(&pool, &id, expired.and_hms_opt(8, 0, 0).unwrap()).await;
+        service.create_ahead(0).await.unwrap();
+
+        service.rotate(Duration::days(30)).await.unwrap();
+
+        assert!(!partition_exists(&pool, &partition_name(expired)).await);
+        let today = ChronoTz::default().now().date();
+        assert!(partition_exists(&pool, &partition_name(today)).await);
+        assert_eq!(count_rows(&pool, PARENT_TABLE).await, 0);
+
+        let mut data = Vec::new();
+        let mut stream = service
+            .object_storage
+            .read_stream("archives/access_logs/2020/01/_access_logs_p20200102.ndjson.gz")
+            .await
+            .unwrap();
+        while let Some(chunk) = stream.next().await {
+            data.extend_from_slice(chunk.unwrap().as_ref());
+        }
+        let mut ndjson = String::new();
+        GzDecoder::new(data.as_slice())
+            .read_to_string(&mut ndjson)
+            .unwrap();
+        let lines: Vec<&str> = ndjson.lines().collect();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains(&id));
+    }
+
+    #[test]
+    fn test_partition_name() {
+        let day = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+        assert_eq!(partition_name(day), "_access_logs_p20250102");
+        assert_eq!(partition_day(&partition_name(day)), Some(day));
+        assert_eq!(partition_day("_access_logs_default"), None);
+        assert_eq!(partition_day("_access_logs_p2025"), None);
+    }
+}
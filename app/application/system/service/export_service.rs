@@ -0,0 +1,164 @@
+use bg_worker_kit::Progress;
+use bon::Builder;
+use infrastructure::shared::pg_pool::PgPool;
+use nject::injectable;
+use object_storage_kit::ObjectWriter;
+use sqlx::{FromRow, types::chrono::NaiveDateTime};
+
+use crate::{error::ApplicationResult, system::service::upload_service::UploadService};
+
+const EXPORT_BATCH_SIZE: i64 = 1000;
+
+#[derive(Debug, FromRow)]
+struct AccessLogRow {
+    id: String,
+    occurred_at: NaiveDateTime,
+    user_name: Option<String>,
+    method: String,
+    uri: String,
+    status: i16,
+    elapsed: i64,
+    ip: Option<String>,
+    user_agent: Option<String>,
+}
+
+#[derive(Debug, Clone, Builder)]
+#[injectable]
+pub struct ExportService {
+    pool: PgPool,
+    upload_service: UploadService,
+}
+
+impl ExportService {
+    /// Writes the access logs that occurred in `[from, to)` to a CSV object at `path`,
+    /// returns the number of rows. Rows go out batch by batch, nothing is left at `path`
+    /// when the export fails or is cancelled
+    #[tracing::instrument(skip(progress))]
+    pub async fn export_access_logs(
+        &self,
+        path: &str,
+        from: Option<NaiveDateTime>,
+        to: Option<NaiveDateTime>,
+        progress: &Progress,
+    ) -> ApplicationResult<i64> {
+        let mut writer = self.upload_service.export_writer(path).await?;
+        match self
+            .write_access_logs(&mut writer, from, to, progress)
+            .await
+        {
+            Ok(done) => {
+                writer.close().await?;
+                progress.report(100, format!("{done} / {done}")).await?;
+                Ok(done)
+            }
+            Err(e) => {
+                if let Err(err) = writer.abort().await {
+                    tracing::warn!(%err, "Failed to abort export");
+                }
+                Err(e)
+            }
+        }
+    }
+
+    async fn write_access_logs(
+        &self,
+        writer: &mut ObjectWriter,
+        from: Option<NaiveDateTime>,
+        to: Option<NaiveDateTime>,
+        progress: &Progress,
+    ) -> ApplicationResult<i64> {
+        let total: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM _access_logs
+            WHERE ($1::TIMESTAMP IS NULL OR occurred_at >= $1)
+                AND ($2::TIMESTAMP IS NULL OR occurred_at < $2)
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_one(&self.pool)
+        .await?;
+        writer
+            .write("occurred_at,user,method,uri,status,elapsed,ip,user_agent\n")
+            .await?;
+        let mut cursor: Option<(NaiveDateTime, String)> = None;
+        let mut done = 0;
+        loop {
+            progress.check_cancelled().await?;
+            let rows: Vec<AccessLogRow> = sqlx::query_as(
+                r#"
+                SELECT a.id, a.occurred_at, u.name AS user_name, a.method, a.uri,
+                    a.status, a.elapsed, a.ip, a.user_agent
+                FROM _access_logs AS a
+                LEFT JOIN _users AS u ON u.id = a.user_id
+                WHERE ($1::TIMESTAMP IS NULL OR a.occurred_at >= $1)
+                    AND ($2::TIMESTAMP IS NULL OR a.occurred_at < $2)
+                    AND ($3::TIMESTAMP IS NULL OR (a.occurred_at, a.id) > ($3, $4))
+                ORDER BY a.occurred_at, a.id
+                LIMIT $5
+                "#,
+            )
+            .bind(from)
+            .bind(to)
+            .bind(cursor.as_ref().map(|(occurred_at, _)| *occurred_at))
+            .bind(cursor.as_ref().map(|(_, id)| id.as_str()))
+            .bind(EXPORT_BATCH_SIZE)
+            .fetch_all(&self.pool)
+            .await?;
+            let Some(last) = rows.last() else {
+                break;
+            };
+            cursor = Some((last.occurred_at, last.id.clone()));
+            done += rows.len() as i64;
+            let mut csv = String::new();
+            for row in rows {
+                let fields = [
+                    row.occurred_at.to_string(),
+                    row.user_name.unwrap_or_default(),
+                    row.method,
+                    row.uri,
+                    row.status.to_string(),
+                    row.elapsed.to_string(),
+                    row.ip.unwrap_or_default(),
+                    row.user_agent.unwrap_or_default(),
+                ];
+                let line: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+                csv.push_str(&line.join(","));
+                csv.push('\n');
+            }
+            writer.write(csv).await?;
+            let percent = (done * 100 / total.max(1)).min(99) as u8;
+            progress
+                .report(percent, format!("{done} / {total}"))
+                .await?;
+        }
+        Ok(done)
+    }
+}
+
+/// Quotes fields that would break the row, formula prefixes are defused for spreadsheets
+fn csv_field(field: &str) -> String {
+    let field = if field.starts_with(['=', '+', '-', '@']) {
+        format!("'{field}")
+    } else {
+        field.to_string()
+    };
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("GET"), "GET");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("=SUM(A1)"), "'=SUM(A1)");
+    }
+}
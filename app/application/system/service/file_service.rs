@@ -1,13 +1,19 @@
-use crate::error::ApplicationResult;
+use crate::error::{ApplicationError, ApplicationResult};
 use bon::Builder;
-use domain::shared::id_generator::IdGenerator;
+use domain::{
+    shared::id_generator::IdGenerator,
+    system::{port::file_scanner::ScanVerdict, value_object::scan_status::ScanStatus},
+};
 use futures_util::stream::BoxStream;
 use infrastructure::shared::{
     chrono_tz::{ChronoTz, Duration},
     pg_pool::PgPool,
 };
 use nject::injectable;
-use sqlx::prelude::FromRow;
+use sqlx::{Postgres, Transaction, prelude::FromRow, types::chrono::NaiveDateTime};
+
+/// Class of the advisory locks uploads are charged against a quota under
+const QUOTA_LOCK: i32 = 0x7175_6f74;
 
 #[derive(Debug, Clone, Builder)]
 #[injectable]
@@ -24,83 +30,807 @@ impl FileService {
 
         (sqlx::query_as!(
             File,
-            "SELECT path, used from _files WHERE used = false AND created_at < $1",
+            r#"
+            SELECT id, path FROM _files AS f
+            WHERE f.created_at < $1
+                AND f.deleted_at IS NULL
+                AND NOT EXISTS (SELECT 1 FROM _file_references AS r WHERE r.file_id = f.id)
+            "#,
             two_days_ago
         )
         .fetch(&self.pool)) as _
     }
 
+    /// Records an upload against an already stored object with identical content,
+    /// returns `None` when there is no such object and the content has to be written first
     #[tracing::instrument]
-    pub async fn create(&self, name: &str, size: u64, path: &str) -> ApplicationResult<()> {
+    pub async fn create_from_object(
+        &self,
+        name: &str,
+        hash: &str,
+        uploader_id: &str,
+        quota: Quota,
+    ) -> ApplicationResult<Option<FileObject>> {
         let now = self.ct.now();
+        let mut tx = self.pool.begin().await?;
+        let object = sqlx::query_as!(
+            FileObject,
+            r#"
+            UPDATE _file_objects SET ref_count = ref_count + 1, updated_at = $2
+            WHERE hash = $1
+            RETURNING path, size, scan_status AS "scan_status: ScanStatus", scan_result,
+                mime_type, width, height
+            "#,
+            hash,
+            now,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(object) = object else {
+            return Ok(None);
+        };
+        // the content is stored already, only the user's share grows
+        Self::charge_quota(&mut tx, uploader_id, object.size, quota, false).await?;
+        Self::insert_file(&mut tx, name, hash, uploader_id, &object, now).await?;
+        tx.commit().await?;
+        Ok(Some(object))
+    }
+
+    /// Records an upload whose content was written to storage and scanned. When a concurrent
+    /// upload of the same content got there first, the returned object points to its path instead
+    #[tracing::instrument]
+    pub async fn create(
+        &self,
+        name: &str,
+        uploader_id: &str,
+        new_object: NewObject<'_>,
+        quota: Quota,
+    ) -> ApplicationResult<FileObject> {
+        let now = self.ct.now();
+        let NewObject {
+            hash,
+            size,
+            path,
+            verdict,
+            meta,
+        } = new_object;
+        let mut tx = self.pool.begin().await?;
+        Self::charge_quota(&mut tx, uploader_id, size as i64, quota, true).await?;
+        let object = sqlx::query_as!(
+            FileObject,
+            r#"
+            INSERT INTO _file_objects (hash, path, size, ref_count, scan_status, scan_result,
+                mime_type, width, height, created_at, updated_at)
+            VALUES ($1, $2, $3, 1, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (hash) DO UPDATE SET
+                ref_count = _file_objects.ref_count + 1,
+                updated_at = EXCLUDED.updated_at
+            RETURNING path, size, scan_status AS "scan_status: ScanStatus", scan_result,
+                mime_type, width, height
+            "#,
+            hash,
+            path,
+            size as i64,
+            verdict.status() as ScanStatus,
+            verdict.result(),
+            meta.mime_type,
+            meta.width,
+            meta.height,
+            now,
+            now,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        Self::insert_file(&mut tx, name, hash, uploader_id, &object, now).await?;
+        tx.commit().await?;
+        Ok(object)
+    }
+
+    /// Bytes uploaded by the user, content shared with other uploads counts for each of them
+    #[tracing::instrument]
+    pub async fn usage(&self, uploader_id: &str) -> ApplicationResult<u64> {
+        let usage = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(SUM(size), 0)::BIGINT AS "usage!" FROM _files WHERE uploader_id = $1
+            "#,
+            uploader_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(usage as u64)
+    }
+
+    /// Bytes actually held by the storage, every distinct content counts once
+    #[tracing::instrument]
+    pub async fn total_usage(&self) -> ApplicationResult<u64> {
+        let usage = sqlx::query_scalar!(
+            r#"
+            SELECT (
+                (SELECT COALESCE(SUM(size), 0) FROM _file_objects)
+                + (SELECT COALESCE(SUM(size), 0) FROM _files WHERE hash IS NULL)
+            )::BIGINT AS "usage!"
+            "#
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(usage as u64)
+    }
+
+    /// Scan status of the live file stored at `path`, `None` when no file was recorded there
+    /// or it went to the recycle bin
+    #[tracing::instrument]
+    pub async fn scan_status(&self, path: &str) -> ApplicationResult<Option<ScanStatus>> {
+        let status = sqlx::query_scalar!(
+            r#"
+            SELECT scan_status AS "scan_status: ScanStatus" FROM _files
+            WHERE path = $1 AND deleted_at IS NULL
+            LIMIT 1
+            "#,
+            path
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(status)
+    }
+
+    /// The hash and stored object of a live file, `None` for files recorded before
+    /// identical content was shared between uploads
+    #[tracing::instrument]
+    pub async fn object_of(&self, id: &str) -> ApplicationResult<Option<(String, FileObject)>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT o.hash, o.path, o.size, o.scan_status AS "scan_status: ScanStatus",
+                o.scan_result, o.mime_type, o.width, o.height
+            FROM _files AS f
+            JOIN _file_objects AS o ON o.hash = f.hash
+            WHERE f.id = $1 AND f.deleted_at IS NULL
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|row| {
+            (
+                row.hash,
+                FileObject {
+                    path: row.path,
+                    size: row.size,
+                    scan_status: row.scan_status,
+                    scan_result: row.scan_result,
+                    mime_type: row.mime_type,
+                    width: row.width,
+                    height: row.height,
+                },
+            )
+        }))
+    }
+
+    /// Records a verdict reached again on stored content, the object and every file of it
+    /// now live at `path`
+    #[tracing::instrument]
+    pub async fn update_scan(
+        &self,
+        hash: &str,
+        path: &str,
+        verdict: &ScanVerdict,
+    ) -> ApplicationResult<()> {
+        let now = self.ct.now();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#"
+            UPDATE _file_objects SET path = $2, scan_status = $3, scan_result = $4, updated_at = $5
+            WHERE hash = $1
+            "#,
+            hash,
+            path,
+            verdict.status() as ScanStatus,
+            verdict.result(),
+            now,
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"
+            UPDATE _files SET path = $2, scan_status = $3, scan_result = $4, updated_at = $5
+            WHERE hash = $1
+            "#,
+            hash,
+            path,
+            verdict.status() as ScanStatus,
+            verdict.result(),
+            now,
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Checks that `size` more bytes fit the quota. The lock taken is held until the
+    /// transaction ends, so concurrent uploads are charged one after the other
+    async fn charge_quota(
+        tx: &mut Transaction<'_, Postgres>,
+        uploader_id: &str,
+        size: i64,
+        quota: Quota,
+        adds_content: bool,
+    ) -> ApplicationResult<()> {
+        match (quota.user, quota.total) {
+            (None, None) => return Ok(()),
+            // every upload counts toward the total, so they all take the same lock
+            (_, Some(_)) => {
+                sqlx::query_scalar!(
+                    r#"SELECT 1 AS "locked!" FROM pg_advisory_xact_lock($1, 0)"#,
+                    QUOTA_LOCK,
+                )
+                .fetch_one(&mut **tx)
+                .await?;
+            }
+            (Some(_), None) => {
+                sqlx::query_scalar!(
+                    r#"SELECT 1 AS "locked!" FROM pg_advisory_xact_lock($1, hashtext($2))"#,
+                    QUOTA_LOCK,
+                    uploader_id,
+                )
+                .fetch_one(&mut **tx)
+                .await?;
+            }
+        }
+        if let Some(quota) = quota.user {
+            let usage = sqlx::query_scalar!(
+                r#"
+                SELECT COALESCE(SUM(size), 0)::BIGINT AS "usage!" FROM _files WHERE uploader_id = $1
+                "#,
+                uploader_id
+            )
+            .fetch_one(&mut **tx)
+            .await?;
+            if (usage + size) as u64 > quota {
+                return Err(ApplicationError::QuotaExceeded);
+            }
+        }
+        if let Some(quota) = quota.total.filter(|_| adds_content) {
+            let usage = sqlx::query_scalar!(
+                r#"
+                SELECT (
+                    (SELECT COALESCE(SUM(size), 0) FROM _file_objects)
+                    + (SELECT COALESCE(SUM(size), 0) FROM _files WHERE hash IS NULL)
+                )::BIGINT AS "usage!"
+                "#
+            )
+            .fetch_one(&mut **tx)
+            .await?;
+            if (usage + size) as u64 > quota {
+                return Err(ApplicationError::QuotaExceeded);
+            }
+        }
+        Ok(())
+    }
+
+    async fn insert_file(
+        tx: &mut Transaction<'_, Postgres>,
+        name: &str,
+        hash: &str,
+        uploader_id: &str,
+        object: &FileObject,
+        now: NaiveDateTime,
+    ) -> ApplicationResult<()> {
         let id = IdGenerator::primary_id();
-        let _ = sqlx::query!(
-            "INSERT INTO _files (id, name, size, path, used, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        sqlx::query!(
+            r#"
+            INSERT INTO _files (id, name, size, path, hash, uploader_id, scan_status, scan_result,
+                mime_type, width, height, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            "#,
             id,
             name,
-            size as i64,
-            path,
-            false,
+            object.size,
+            object.path,
+            hash,
+            uploader_id,
+            object.scan_status as ScanStatus,
+            object.scan_result,
+            object.mime_type,
+            object.width,
+            object.height,
             now,
             now,
         )
-        .execute(&self.pool)
+        .execute(&mut **tx)
         .await?;
         Ok(())
     }
 
+    /// Makes the files stored at `paths` the complete set of files referenced by the
+    /// owner's field. Identical uploads share a path, of those the oldest live file is
+    /// referenced so the others can still be cleaned up
     #[tracing::instrument]
-    pub async fn set_files_unused(&self, relative_paths: &[String]) -> ApplicationResult<()> {
-        self.set_files_status(relative_paths, false).await
+    pub async fn set_references(
+        &self,
+        owner: &FileOwner,
+        paths: &[String],
+    ) -> ApplicationResult<()> {
+        let ids = sqlx::query_scalar!(
+            r#"
+            SELECT DISTINCT ON (path) id FROM _files
+            WHERE path = ANY($1)
+            ORDER BY path, deleted_at IS NOT NULL, created_at, id
+            "#,
+            paths,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        self.set_file_references(owner, &ids).await
     }
 
+    /// Makes `ids` the complete set of files referenced by the owner's field,
+    /// references to any other file are removed
     #[tracing::instrument]
-    pub async fn set_files_used(&self, relative_paths: &[String]) -> ApplicationResult<()> {
-        self.set_files_status(relative_paths, true).await
+    pub async fn set_file_references(
+        &self,
+        owner: &FileOwner,
+        ids: &[String],
+    ) -> ApplicationResult<()> {
+        let now = self.ct.now();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#"
+            DELETE FROM _file_references
+            WHERE owner_type = $1 AND owner_id = $2 AND field = $3 AND NOT (file_id = ANY($4))
+            "#,
+            owner.owner_type,
+            owner.owner_id,
+            owner.field,
+            ids,
+        )
+        .execute(&mut *tx)
+        .await?;
+        if !ids.is_empty() {
+            sqlx::query!(
+                r#"
+                INSERT INTO _file_references (owner_type, owner_id, field, file_id, created_at)
+                SELECT $1, $2, $3, file_id, $5 FROM UNNEST($4::text[]) AS file_id
+                ON CONFLICT DO NOTHING
+                "#,
+                owner.owner_type,
+                owner.owner_id,
+                owner.field,
+                ids,
+                now,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
     }
 
+    /// Removes every reference held by the deleted owners
     #[tracing::instrument]
-    async fn set_files_status(
+    pub async fn remove_references(
         &self,
-        relative_paths: &[String],
-        used: bool,
+        owner_type: &str,
+        owner_ids: &[String],
     ) -> ApplicationResult<()> {
-        if relative_paths.is_empty() {
+        if owner_ids.is_empty() {
             return Ok(());
         }
-        let now = self.ct.now();
         sqlx::query!(
             r#"
-            UPDATE _files SET used = $1, updated_at = $2 WHERE path = ANY($3)
+            DELETE FROM _file_references WHERE owner_type = $1 AND owner_id = ANY($2)
             "#,
-            used,
-            now,
-            relative_paths,
+            owner_type,
+            owner_ids,
         )
         .execute(&self.pool)
         .await?;
         Ok(())
     }
 
+    /// Drops the references to files that are gone for good
     #[tracing::instrument]
-    pub async fn delete_files(&self, relative_paths: &[String]) -> ApplicationResult<()> {
-        if relative_paths.is_empty() {
+    pub async fn remove_file_references(&self, ids: &[String]) -> ApplicationResult<()> {
+        if ids.is_empty() {
             return Ok(());
         }
         sqlx::query!(
             r#"
-            DELETE FROM _files WHERE path = ANY($1)
+            DELETE FROM _file_references WHERE file_id = ANY($1)
             "#,
-            relative_paths
+            ids,
         )
         .execute(&self.pool)
         .await?;
         Ok(())
     }
+
+    /// Deletes the files and releases their objects, returns the paths of the
+    /// objects whose last reference went away
+    #[tracing::instrument]
+    pub async fn delete_files(&self, ids: &[String]) -> ApplicationResult<Vec<String>> {
+        if ids.is_empty() {
+            return Ok(Vec::with_capacity(0));
+        }
+        let now = self.ct.now();
+        let mut tx = self.pool.begin().await?;
+        let deleted = sqlx::query!(
+            r#"
+            DELETE FROM _files WHERE id = ANY($1) RETURNING path, hash
+            "#,
+            ids
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"
+            DELETE FROM _file_references WHERE file_id = ANY($1)
+            "#,
+            ids
+        )
+        .execute(&mut *tx)
+        .await?;
+        let objects = deleted
+            .into_iter()
+            .map(|row| (row.path, row.hash))
+            .collect();
+        let released_paths = Self::release(&mut tx, objects, now).await?;
+        tx.commit().await?;
+        Ok(released_paths)
+    }
+
+    /// Releases the objects of files whose rows are already gone, given as
+    /// `(path, hash)`, returns the paths of the objects nothing refers to anymore
+    #[tracing::instrument]
+    pub async fn release_objects(
+        &self,
+        objects: Vec<(String, Option<String>)>,
+    ) -> ApplicationResult<Vec<String>> {
+        if objects.is_empty() {
+            return Ok(Vec::with_capacity(0));
+        }
+        let now = self.ct.now();
+        let mut tx = self.pool.begin().await?;
+        let released_paths = Self::release(&mut tx, objects, now).await?;
+        tx.commit().await?;
+        Ok(released_paths)
+    }
+
+    async fn release(
+        tx: &mut Transaction<'_, Postgres>,
+        objects: Vec<(String, Option<String>)>,
+        now: NaiveDateTime,
+    ) -> ApplicationResult<Vec<String>> {
+        let mut hashes = Vec::with_capacity(objects.len());
+        let mut released_paths = Vec::new();
+        for (path, hash) in objects {
+            match hash {
+                Some(hash) => hashes.push(hash),
+                // uploaded before deduplication, the object is owned by this file alone
+                None => released_paths.push(path),
+            }
+        }
+        if !hashes.is_empty() {
+            sqlx::query!(
+                r#"
+                UPDATE _file_objects AS o SET ref_count = o.ref_count - d.count, updated_at = $2
+                FROM (
+                    SELECT hash, COUNT(*)::INTEGER AS count FROM UNNEST($1::text[]) AS hash GROUP BY hash
+                ) AS d
+                WHERE o.hash = d.hash
+                "#,
+                &hashes,
+                now,
+            )
+            .execute(&mut **tx)
+            .await?;
+            let paths = sqlx::query_scalar!(
+                r#"
+                DELETE FROM _file_objects WHERE hash = ANY($1) AND ref_count <= 0 RETURNING path
+                "#,
+                &hashes,
+            )
+            .fetch_all(&mut **tx)
+            .await?;
+            released_paths.extend(paths);
+        }
+        released_paths.sort();
+        released_paths.dedup();
+        Ok(released_paths)
+    }
+
+    /// Prepends `prefix` to every stored path after the objects moved under it in a new
+    /// storage, paths already carrying the prefix are left alone so a rerun is harmless.
+    /// Paths are held by files, their objects and user portraits only. Share links and
+    /// file references point at files by id, variants are named and cached after the
+    /// path of their original and exports are never recorded, so those follow along.
+    /// Returns the number of files rewritten
+    #[tracing::instrument]
+    pub async fn prefix_paths(&self, prefix: &str) -> ApplicationResult<u64> {
+        let pattern = format!(
+            "{}%",
+            prefix
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        let mut tx = self.pool.begin().await?;
+        let rewritten = sqlx::query!(
+            r#"
+            UPDATE _files SET path = $1 || path WHERE path NOT LIKE $2
+            "#,
+            prefix,
+            pattern,
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        sqlx::query!(
+            r#"
+            UPDATE _file_objects SET path = $1 || path WHERE path NOT LIKE $2
+            "#,
+            prefix,
+            pattern,
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"
+            UPDATE _users SET portrait = $1 || portrait
+            WHERE portrait IS NOT NULL AND portrait <> '' AND portrait NOT LIKE $2
+            "#,
+            prefix,
+            pattern,
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(rewritten)
+    }
 }
 
 #[derive(Clone, FromRow)]
 pub struct File {
+    pub id: String,
     pub path: String,
-    pub used: bool,
+}
+
+/// The field of an aggregate that holds file paths, e.g. a user's portrait
+#[derive(Debug, Clone, Builder)]
+pub struct FileOwner {
+    owner_type: &'static str,
+    #[builder(into)]
+    owner_id: String,
+    field: &'static str,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct FileObject {
+    pub path: String,
+    pub size: i64,
+    pub scan_status: ScanStatus,
+    pub scan_result: Option<String>,
+    pub mime_type: Option<String>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+}
+
+/// Bytes uploads may take up, unlimited when absent
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Quota {
+    /// Bytes a single user may upload
+    pub user: Option<u64>,
+    /// Bytes the storage may hold in total
+    pub total: Option<u64>,
+}
+
+/// Content written to storage and scanned, about to become a file
+#[derive(Debug, Builder)]
+pub struct NewObject<'a> {
+    hash: &'a str,
+    size: u64,
+    path: &'a str,
+    verdict: &'a ScanVerdict,
+    meta: ContentMeta,
+}
+
+/// Properties of the content itself, shared by every file of an object
+#[derive(Debug, Clone, Default)]
+pub struct ContentMeta {
+    pub mime_type: Option<String>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use infrastructure::test_utils::setup_database;
+
+    use super::*;
+
+    #[sqlx::test]
+    async fn test_create_return_err_given_quota_exceeded(pool: PgPool) {
+        setup_database(pool.clone()).await;
+        let file_service = FileService::builder()
+            .pool(pool)
+            .ct(ChronoTz::default())
+            .build();
+        let quota = Quota {
+            user: Some(10),
+            total: None,
+        };
+        fn new_object<'a>(hash: &'a str, path: &'a str) -> NewObject<'a> {
+            NewObject::builder()
+                .hash(hash)
+                .size(6)
+                .path(path)
+                .verdict(&ScanVerdict::Clean)
+                .meta(ContentMeta::default())
+                .build()
+        }
+        let (hash_a, hash_b) = ("a".repeat(64), "b".repeat(64));
+        assert!(
+            file_service
+                .create(
+                    "a.txt",
+                    "uploader",
+                    new_object(&hash_a, "test/a.txt"),
+                    quota
+                )
+                .await
+                .is_ok()
+        );
+        assert!(matches!(
+            file_service
+                .create(
+                    "b.txt",
+                    "uploader",
+                    new_object(&hash_b, "test/b.txt"),
+                    quota
+                )
+                .await,
+            Err(ApplicationError::QuotaExceeded)
+        ));
+        assert!(matches!(
+            file_service
+                .create_from_object("c.txt", &hash_a, "uploader", quota)
+                .await,
+            Err(ApplicationError::QuotaExceeded)
+        ));
+        // another user's share is their own, the content is stored already
+        let quota = Quota {
+            user: Some(10),
+            total: Some(6),
+        };
+        assert!(
+            file_service
+                .create_from_object("c.txt", &hash_a, "other", quota)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert_eq!(file_service.usage("uploader").await.unwrap(), 6);
+    }
+
+    #[sqlx::test]
+    async fn test_scan_status_return_none_given_file_deleted(pool: PgPool) {
+        setup_database(pool.clone()).await;
+        let file_service = FileService::builder()
+            .pool(pool.clone())
+            .ct(ChronoTz::default())
+            .build();
+        let hash = "c".repeat(64);
+        let new_object = NewObject::builder()
+            .hash(&hash)
+            .size(5)
+            .path("test/c.txt")
+            .verdict(&ScanVerdict::Clean)
+            .meta(ContentMeta::default())
+            .build();
+        file_service
+            .create("c.txt", "uploader", new_object, Quota::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            file_service.scan_status("test/c.txt").await.unwrap(),
+            Some(ScanStatus::Clean)
+        );
+        sqlx::query!("UPDATE _files SET deleted_at = NOW() WHERE path = 'test/c.txt'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(file_service.scan_status("test/c.txt").await.unwrap(), None);
+    }
+
+    #[sqlx::test]
+    async fn test_prefix_paths_rewrite_every_path_column(pool: PgPool) {
+        setup_database(pool.clone()).await;
+        let file_service = FileService::builder()
+            .pool(pool.clone())
+            .ct(ChronoTz::default())
+            .build();
+        for (name, hash, path) in [
+            ("d.png", "d".repeat(64), "test/d.png"),
+            ("e.png", "e".repeat(64), "uploads/test/e.png"),
+        ] {
+            let new_object = NewObject::builder()
+                .hash(&hash)
+                .size(5)
+                .path(path)
+                .verdict(&ScanVerdict::Clean)
+                .meta(ContentMeta::default())
+                .build();
+            file_service
+                .create(name, "uploader", new_object, Quota::default())
+                .await
+                .unwrap();
+        }
+        let file_id = sqlx::query_scalar!("SELECT id FROM _files WHERE path = 'test/d.png'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        sqlx::query!("UPDATE _users SET portrait = 'test/d.png' WHERE account = 'admin'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        file_service
+            .set_references(
+                &FileOwner::builder()
+                    .owner_type("user")
+                    .owner_id("admin")
+                    .field("portrait")
+                    .build(),
+                &["test/d.png".to_string()],
+            )
+            .await
+            .unwrap();
+        sqlx::query!(
+            r#"
+            INSERT INTO _file_shares (id, code, file_id, created_at, updated_at)
+            VALUES ('share', 'code', $1, NOW(), NOW())
+            "#,
+            &file_id,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        assert_eq!(file_service.prefix_paths("uploads/").await.unwrap(), 1);
+        let files = sqlx::query_scalar!("SELECT path FROM _files ORDER BY path")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(files, ["uploads/test/d.png", "uploads/test/e.png"]);
+        let objects = sqlx::query_scalar!("SELECT path FROM _file_objects ORDER BY path")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(objects, files);
+        let portrait = sqlx::query_scalar!("SELECT portrait FROM _users WHERE account = 'admin'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(portrait.as_deref(), Some("uploads/test/d.png"));
+        // shares and references follow the file they point at
+        let shared = sqlx::query_scalar!(
+            r#"
+            SELECT f.path FROM _file_shares s JOIN _files f ON f.id = s.file_id
+            WHERE s.code = 'code'
+            "#
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(shared, "uploads/test/d.png");
+        let referenced = sqlx::query_scalar!(
+            r#"
+            SELECT f.path FROM _file_references r JOIN _files f ON f.id = r.file_id
+            WHERE r.owner_type = 'user' AND r.owner_id = 'admin' AND r.field = 'portrait'
+            "#
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(referenced, "uploads/test/d.png");
+        assert_eq!(file_service.prefix_paths("uploads/").await.unwrap(), 0);
+    }
 }
@@ -1,3 +1,6 @@
+pub mod access_log_partition_service;
+pub mod export_service;
 pub mod file_service;
+pub mod recycle_bin_service;
 pub mod system_service;
 pub mod upload_service;
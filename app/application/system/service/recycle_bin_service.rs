@@ -0,0 +1,287 @@
+use bon::Builder;
+use domain::{
+    organization::{
+        event::OrganizationEvent,
+        value_object::{department_id::DepartmentId, role_id::RoleId, user_id::UserId},
+    },
+    shared::port::recyclable_repository::RecyclableRepository,
+    system::{
+        event::SystemEvent,
+        value_object::{file_id::FileId, recycle_bin_kind::RecycleBinKind},
+    },
+};
+use infrastructure::repository::{
+    organization::{
+        department_repository::DepartmentRepositoryImpl, role_repository::RoleRepositoryImpl,
+        user_repository::UserRepositoryImpl,
+    },
+    system::file_repository::FileRepositoryImpl,
+};
+use nject::injectable;
+use sqlx::types::chrono::NaiveDateTime;
+
+use crate::{
+    error::ApplicationResult,
+    shared::event::Event,
+    system::service::{file_service::FileService, upload_service::UploadService},
+};
+
+#[derive(Debug, Clone, Builder)]
+#[injectable]
+pub struct RecycleBinService {
+    user_repository: UserRepositoryImpl,
+    role_repository: RoleRepositoryImpl,
+    department_repository: DepartmentRepositoryImpl,
+    file_repository: FileRepositoryImpl,
+    file_service: FileService,
+    upload_service: UploadService,
+}
+
+impl RecycleBinService {
+    #[tracing::instrument]
+    pub async fn restore(&self, kind: RecycleBinKind, ids: &[String]) -> ApplicationResult<Event> {
+        let event = match kind {
+            RecycleBinKind::User => {
+                let items = self
+                    .user_repository
+                    .restore(&typed(ids, UserId::new_unchecked))
+                    .await?;
+                OrganizationEvent::UsersRestored { items }.into()
+            }
+            RecycleBinKind::Role => {
+                let items = self
+                    .role_repository
+                    .restore(&typed(ids, RoleId::new_unchecked))
+                    .await?;
+                OrganizationEvent::RolesRestored { items }.into()
+            }
+            RecycleBinKind::Department => {
+                let items = self
+                    .department_repository
+                    .restore(&typed(ids, DepartmentId::new_unchecked))
+                    .await?;
+                OrganizationEvent::DepartmentsRestored { items }.into()
+            }
+            RecycleBinKind::File => {
+                let items = self
+                    .file_repository
+                    .restore(&typed(ids, FileId::new_unchecked))
+                    .await?;
+                SystemEvent::FilesRestored { items }.into()
+            }
+        };
+        Ok(event)
+    }
+
+    /// Removes deleted entities for good, purged files also release their stored objects
+    #[tracing::instrument]
+    pub async fn purge(&self, kind: RecycleBinKind, ids: &[String]) -> ApplicationResult<Event> {
+        let event = match kind {
+            RecycleBinKind::User => {
+                let items = self
+                    .user_repository
+                    .purge(&typed(ids, UserId::new_unchecked))
+                    .await?;
+                OrganizationEvent::UsersPurged { items }.into()
+            }
+            RecycleBinKind::Role => {
+                let items = self
+                    .role_repository
+                    .purge(&typed(ids, RoleId::new_unchecked))
+                    .await?;
+                OrganizationEvent::RolesPurged { items }.into()
+            }
+            RecycleBinKind::Department => {
+                let items = self
+                    .department_repository
+                    .purge(&typed(ids, DepartmentId::new_unchecked))
+                    .await?;
+                OrganizationEvent::DepartmentsPurged { items }.into()
+            }
+            RecycleBinKind::File => {
+                let items = self
+                    .file_repository
+                    .purge(&typed(ids, FileId::new_unchecked))
+                    .await?;
+                let objects = items
+                    .iter()
+                    .map(|item| (item.path.clone(), item.hash.clone()))
+                    .collect();
+                let paths = self.file_service.release_objects(objects).await?;
+                if !paths.is_empty()
+                    && let Err(err) = self.upload_service.delete_many(paths).await
+                {
+                    tracing::error!(%err, "Delete purged file objects failed");
+                }
+                SystemEvent::FilesPurged { items }.into()
+            }
+        };
+        Ok(event)
+    }
+
+    /// Purges every kind of entity deleted before `before`
+    #[tracing::instrument]
+    pub async fn purge_expired(&self, before: NaiveDateTime) -> ApplicationResult<Vec<Event>> {
+        let mut events = Vec::new();
+        for kind in RecycleBinKind::ALL {
+            let ids = self.deleted_before(kind, before).await?;
+            if ids.is_empty() {
+                continue;
+            }
+            tracing::info!(%kind, count = ids.len(), "Purging expired recycle bin items");
+            events.push(self.purge(kind, &ids).await?);
+        }
+        Ok(events)
+    }
+
+    async fn deleted_before(
+        &self,
+        kind: RecycleBinKind,
+        before: NaiveDateTime,
+    ) -> ApplicationResult<Vec<String>> {
+        let ids = match kind {
+            RecycleBinKind::User => untyped(self.user_repository.deleted_before(before).await?),
+            RecycleBinKind::Role => untyped(self.role_repository.deleted_before(before).await?),
+            RecycleBinKind::Department => {
+                untyped(self.department_repository.deleted_before(before).await?)
+            }
+            RecycleBinKind::File => untyped(self.file_repository.deleted_before(before).await?),
+        };
+        Ok(ids)
+    }
+}
+
+fn typed<T>(ids: &[String], new: fn(String) -> T) -> Vec<T> {
+    ids.iter().cloned().map(new).collect()
+}
+
+fn untyped<T: std::ops::Deref<Target = str>>(ids: Vec<T>) -> Vec<String> {
+    ids.iter().map(|id| id.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::{
+        shared::port::domain_repository::DomainRepository, system::port::file_scanner::ScanVerdict,
+    };
+    use infrastructure::{
+        port::file_scanner_impl::FileScannerImpl,
+        shared::{
+            chrono_tz::{ChronoTz, Duration},
+            config::ConfigRef,
+            pg_pool::PgPool,
+        },
+        test_utils::{setup_database, setup_kvdb, setup_object_storage},
+    };
+
+    use super::*;
+    use crate::system::service::file_service::{ContentMeta, NewObject, Quota};
+
+    async fn build_service(pool: PgPool) -> RecycleBinService {
+        setup_database(pool.clone()).await;
+        let ct = ChronoTz::default();
+        let object_storage = setup_object_storage().await;
+        let file_service = FileService::builder()
+            .pool(pool.clone())
+            .ct(ct.clone())
+            .build();
+        let file_scanner = FileScannerImpl::builder()
+            .config(ConfigRef::default())
+            .object_storage(object_storage.clone())
+            .build();
+        let upload_service = UploadService::builder()
+            .ct(ct.clone())
+            .config(ConfigRef::default())
+            .object_storage(object_storage)
+            .file_service(file_service.clone())
+            .file_scanner(file_scanner)
+            .kvdb(setup_kvdb().await)
+            .build();
+        RecycleBinService::builder()
+            .user_repository(
+                UserRepositoryImpl::builder()
+                    .pool(pool.clone())
+                    .ct(ct.clone())
+                    .build(),
+            )
+            .role_repository(
+                RoleRepositoryImpl::builder()
+                    .pool(pool.clone())
+                    .ct(ct.clone())
+                    .build(),
+            )
+            .department_repository(
+                DepartmentRepositoryImpl::builder()
+                    .pool(pool.clone())
+                    .ct(ct.clone())
+                    .build(),
+            )
+            .file_repository(FileRepositoryImpl::builder().pool(pool).ct(ct).build())
+            .file_service(file_service)
+            .upload_service(upload_service)
+            .build()
+    }
+
+    #[sqlx::test]
+    async fn test_restore_and_purge_files(pool: PgPool) {
+        let service = build_service(pool.clone()).await;
+        let file_service = FileService::builder()
+            .pool(pool.clone())
+            .ct(ChronoTz::default())
+            .build();
+        let hash = "2".repeat(64);
+        let object = NewObject::builder()
+            .hash(&hash)
+            .size(3)
+            .path("test/recycled.txt")
+            .verdict(&ScanVerdict::Clean)
+            .meta(ContentMeta::default())
+            .build();
+        assert!(
+            file_service
+                .create("a.txt", "uploader", object, Quota::default())
+                .await
+                .is_ok()
+        );
+        assert!(
+            file_service
+                .create_from_object("b.txt", &hash, "uploader", Quota::default())
+                .await
+                .is_ok()
+        );
+        let ids: Vec<FileId> = sqlx::query_scalar("SELECT id FROM _files ORDER BY name")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        let repository = FileRepositoryImpl::builder()
+            .pool(pool.clone())
+            .ct(ChronoTz::default())
+            .build();
+        assert_eq!(repository.batch_delete(&ids).await.unwrap().len(), 2);
+
+        let ids = untyped(ids);
+        assert!(
+            service
+                .restore(RecycleBinKind::File, &ids[..1])
+                .await
+                .is_ok()
+        );
+        assert!(
+            repository
+                .by_id(&FileId::new_unchecked(ids[0].clone()))
+                .await
+                .is_ok()
+        );
+
+        // the restored file still holds the shared object
+        let before = ChronoTz::default().now() + Duration::seconds(1);
+        assert_eq!(service.purge_expired(before).await.unwrap().len(), 1);
+        let ref_count: i32 =
+            sqlx::query_scalar("SELECT ref_count FROM _file_objects WHERE hash = $1")
+                .bind(&hash)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(ref_count, 1);
+    }
+}
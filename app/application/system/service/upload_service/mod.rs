@@ -0,0 +1,777 @@
+use crate::error::{ApplicationError, ApplicationResult};
+use axum::http::Uri;
+use bon::Builder;
+use domain::{organization::value_object::user_id::UserId, shared::id_generator::IdGenerator};
+use futures_util::StreamExt;
+use image::{DynamicImage, ImageDecoder as _, ImageFormat, ImageReader};
+use imageformat::detect_image_format;
+use infrastructure::{
+    port::file_scanner_impl::FileScannerImpl,
+    shared::{
+        chrono_tz::{ChronoTz, Datelike as _},
+        config::{ConfigRef, UploadRule},
+    },
+};
+use kvdb_kit::{Kvdb, KvdbTrait as _};
+use nject::injectable;
+use object_storage_kit::{
+    FuturesBytesStream, ObjectStorage, ObjectStorageReader, ObjectStorageStreamReader as _,
+    ObjectStorageWriter, ObjectWriter,
+};
+use serde::Serialize;
+use std::io::Cursor;
+use std::{
+    io::Seek,
+    ops::Range,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tempfile::NamedTempFile;
+use utoipa::ToSchema;
+
+use crate::system::service::file_service::{
+    ContentMeta, FileObject, FileService, NewObject, Quota,
+};
+
+mod multipart;
+mod presign;
+mod scan;
+mod variant;
+
+pub use multipart::{ChunkResponse, PartItem, StartChunkResponse, UploadedPartResponse};
+pub use presign::PresignResponse;
+
+/// Content generated for a user such as exports, grouped by the day it was made on
+const EXPORT_DIR: &str = "exports";
+const EXPORT_DAY_FORMAT: &str = "%Y%m%d";
+const JPEG_MIME: &str = "image/jpeg";
+/// Bytes read from the start of a file to tell its type by content
+const SNIFF_LEN: usize = 512;
+/// The metadata segments of a JPEG sit at its start, within this many bytes
+const JPEG_HEAD_LEN: usize = 256 * 1024;
+
+#[derive(Debug, Clone, Builder)]
+#[injectable]
+pub struct UploadService {
+    ct: ChronoTz,
+    config: ConfigRef,
+    file_service: FileService,
+    object_storage: ObjectStorage,
+    file_scanner: FileScannerImpl,
+    kvdb: Kvdb,
+}
+
+impl UploadService {
+    #[tracing::instrument(skip_all)]
+    pub async fn image(
+        &self,
+        uploader_id: &UserId,
+        file_name: Option<String>,
+        mut file_contents: NamedTempFile,
+    ) -> ApplicationResult<FinishResponse> {
+        let Some(format) = SupportedFormat::validate_image_type(&mut file_contents) else {
+            return Err(ApplicationError::UnsupportedImageFormat);
+        };
+        let size = file_contents.as_file().metadata()?.len();
+        self.check_rule(&self.config.upload.image, size, format.mime())?;
+        self.check_quota(uploader_id, size).await?;
+
+        let (reader, (width, height)) =
+            SupportedFormat::convert_to_webp(format, file_contents).await?;
+        let hash = blake3::hash(reader.get_ref()).to_hex().to_string();
+        let original_filename = file_name.unwrap_or_default();
+        let meta = ContentMeta {
+            mime_type: Some(SupportedFormat::Webp.mime().to_string()),
+            width: Some(width as i32),
+            height: Some(height as i32),
+        };
+        let resp = self
+            .store(
+                uploader_id,
+                &original_filename,
+                &hash,
+                ".webp",
+                meta,
+                |relative_path| async move {
+                    Ok(self.object_storage.write(&relative_path, reader).await?)
+                },
+            )
+            .await?;
+        self.enqueue_variants(&resp.value).await;
+        Ok(resp)
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn single(
+        &self,
+        uploader_id: &UserId,
+        file_name: Option<String>,
+        file_contents: NamedTempFile,
+    ) -> ApplicationResult<FinishResponse> {
+        let size = file_contents.as_file().metadata()?.len();
+        let head = read_file_head(file_contents.path().to_path_buf()).await?;
+        let mime_type = self.check_content_rule(
+            &self.config.upload.single,
+            size,
+            file_name.as_deref(),
+            &head,
+        )?;
+        self.check_quota(uploader_id, size).await?;
+        let extension = Self::extract_extension(file_name.clone());
+        if mime_type == JPEG_MIME {
+            scrub_jpeg_file(file_contents.path().to_path_buf()).await?;
+        }
+        let hash = hash_file(file_contents.path().to_path_buf()).await?;
+        let dimensions = if mime_type.starts_with("image/") {
+            image_dimensions(file_contents.path().to_path_buf()).await
+        } else {
+            None
+        };
+        let meta = ContentMeta {
+            mime_type: Some(mime_type),
+            width: dimensions.map(|(width, _)| width as i32),
+            height: dimensions.map(|(_, height)| height as i32),
+        };
+        let original_filename = file_name.unwrap_or_default();
+        self.store(
+            uploader_id,
+            &original_filename,
+            &hash,
+            &extension,
+            meta,
+            |relative_path| async move {
+                Ok(self
+                    .object_storage
+                    .write(&relative_path, file_contents)
+                    .await?)
+            },
+        )
+        .await
+    }
+
+    /// Records content that is already stored at `key`, dropping it in favour of an
+    /// existing object with the same hash
+    async fn register_object(
+        &self,
+        uploader_id: &str,
+        filename: &str,
+        key: &str,
+        hash: &str,
+        file_size: u64,
+        meta: ContentMeta,
+    ) -> ApplicationResult<FinishResponse> {
+        // the size was only announced up front, charge what actually arrived
+        let reused = self
+            .file_service
+            .create_from_object(filename, hash, uploader_id, self.quota())
+            .await;
+        let object = match reused {
+            Ok(Some(object)) => {
+                self.delete(key).await?;
+                object
+            }
+            Err(err) => {
+                self.delete(key).await?;
+                return Err(err);
+            }
+            Ok(None) => {
+                let (path, verdict) = self.scan(key).await?;
+                let new_object = NewObject::builder()
+                    .hash(hash)
+                    .size(file_size)
+                    .path(&path)
+                    .verdict(&verdict)
+                    .meta(meta)
+                    .build();
+                let object = self
+                    .file_service
+                    .create(filename, uploader_id, new_object, self.quota())
+                    .await;
+                let object = match object {
+                    Ok(object) => object,
+                    Err(err) => {
+                        self.delete(&path).await?;
+                        return Err(err);
+                    }
+                };
+                if object.path != path {
+                    self.delete(&path).await?;
+                }
+                object
+            }
+        };
+        self.finish(object).await
+    }
+
+    async fn hash_object(&self, path: &str) -> ApplicationResult<(String, u64)> {
+        let mut stream = self.object_storage.read_stream(path).await?;
+        let mut hasher = blake3::Hasher::new();
+        let mut size = 0;
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            size += chunk.as_ref().len() as u64;
+            hasher.update(chunk.as_ref());
+        }
+        Ok((hasher.finalize().to_hex().to_string(), size))
+    }
+
+    /// Stores the content once per hash, `write` is only called when no upload with
+    /// identical content exists yet
+    async fn store<F, Fut>(
+        &self,
+        uploader_id: &str,
+        original_filename: &str,
+        hash: &str,
+        extension: &str,
+        meta: ContentMeta,
+        write: F,
+    ) -> ApplicationResult<FinishResponse>
+    where
+        F: FnOnce(String) -> Fut,
+        Fut: Future<Output = ApplicationResult<u64>>,
+    {
+        let object = match self
+            .file_service
+            .create_from_object(original_filename, hash, uploader_id, self.quota())
+            .await?
+        {
+            Some(object) => object,
+            None => {
+                let filename = IdGenerator::filename().to_lowercase();
+                let relative_path = self.build_relative_path(format!("{filename}{extension}"));
+                let file_size = write(relative_path.clone()).await?;
+                let (path, verdict) = self.scan(&relative_path).await?;
+                let new_object = NewObject::builder()
+                    .hash(hash)
+                    .size(file_size)
+                    .path(&path)
+                    .verdict(&verdict)
+                    .meta(meta)
+                    .build();
+                let object = self
+                    .file_service
+                    .create(original_filename, uploader_id, new_object, self.quota())
+                    .await;
+                let object = match object {
+                    Ok(object) => object,
+                    Err(err) => {
+                        self.delete(&path).await?;
+                        return Err(err);
+                    }
+                };
+                if object.path != path {
+                    // a concurrent upload of the same content was stored first
+                    self.delete(&path).await?;
+                }
+                object
+            }
+        };
+        self.finish(object).await
+    }
+
+    /// Hands out the stored file, a quarantined one is recorded but refused
+    async fn finish(&self, object: FileObject) -> ApplicationResult<FinishResponse> {
+        if !object.scan_status.is_servable() {
+            return Err(ApplicationError::FileQuarantined);
+        }
+        Ok(FinishResponse {
+            url: self.object_storage.presign_url(&object.path).await?,
+            value: object.path,
+        })
+    }
+
+    /// Whether the object at `path` may be served. Only recorded files that passed the scan
+    /// qualify, objects of unfinished uploads are still unscanned
+    #[tracing::instrument]
+    pub async fn servable(&self, path: &str) -> ApplicationResult<bool> {
+        // generated by the server itself, nothing uploaded ends up there
+        if path.starts_with(&format!("{EXPORT_DIR}/")) {
+            return Ok(true);
+        }
+        let status = self
+            .file_service
+            .scan_status(&Self::original_path(path))
+            .await?;
+        Ok(status.is_some_and(|status| status.is_servable()))
+    }
+
+    /// Bytes the user has uploaded and may upload at most
+    #[tracing::instrument]
+    pub async fn usage(&self, uploader_id: &UserId) -> ApplicationResult<StorageUsage> {
+        Ok(StorageUsage {
+            used: self.file_service.usage(uploader_id).await?,
+            quota: self.config.upload.user_quota,
+        })
+    }
+
+    fn quota(&self) -> Quota {
+        Quota {
+            user: self.config.upload.user_quota,
+            total: self.config.upload.total_quota,
+        }
+    }
+
+    /// Fails early when `size` more bytes are sure not to fit, the upload is charged when recorded
+    async fn check_quota(&self, uploader_id: &str, size: u64) -> ApplicationResult<()> {
+        let upload = &self.config.upload;
+        if let Some(quota) = upload.user_quota
+            && self.file_service.usage(uploader_id).await? + size > quota
+        {
+            return Err(ApplicationError::QuotaExceeded);
+        }
+        if let Some(quota) = upload.total_quota
+            && self.file_service.total_usage().await? + size > quota
+        {
+            return Err(ApplicationError::QuotaExceeded);
+        }
+        Ok(())
+    }
+
+    fn check_rule(&self, rule: &UploadRule, size: u64, mime: &str) -> ApplicationResult<()> {
+        if !rule.permits_size(size) {
+            return Err(ApplicationError::UploadTooLarge);
+        }
+        if !rule.permits_mime(mime) {
+            return Err(ApplicationError::UnsupportedFileType);
+        }
+        Ok(())
+    }
+
+    /// Checks the type told by the name and, when the content is recognised, the type of
+    /// the content as well. Returns the type to record
+    fn check_content_rule(
+        &self,
+        rule: &UploadRule,
+        size: u64,
+        file_name: Option<&str>,
+        head: &[u8],
+    ) -> ApplicationResult<String> {
+        let guessed = Self::guess_mime(file_name);
+        self.check_rule(rule, size, &guessed)?;
+        match sniff_mime(head) {
+            Some(sniffed) => {
+                self.check_rule(rule, size, sniffed)?;
+                Ok(sniffed.to_string())
+            }
+            None => Ok(guessed),
+        }
+    }
+
+    /// The first bytes of a stored object of `size` bytes, enough to tell its type
+    async fn read_head(&self, path: &str, size: u64) -> ApplicationResult<Vec<u8>> {
+        let mut head = Vec::with_capacity(SNIFF_LEN);
+        if size == 0 {
+            return Ok(head);
+        }
+        let mut stream = self
+            .object_storage
+            .read_range_stream(path, 0..size.min(SNIFF_LEN as u64))
+            .await?;
+        while let Some(chunk) = stream.next().await {
+            head.extend_from_slice(chunk?.as_ref());
+        }
+        Ok(head)
+    }
+
+    fn guess_mime(file_name: Option<&str>) -> String {
+        file_name
+            .map(|name| mime_guess::from_path(name).first_or_octet_stream())
+            .unwrap_or(mime_guess::mime::APPLICATION_OCTET_STREAM)
+            .to_string()
+    }
+
+    /// Size of the stored object, `None` when it does not exist
+    #[tracing::instrument]
+    pub async fn object_size(&self, path: &str) -> ApplicationResult<Option<u64>> {
+        Ok(self.object_storage.size(path).await?)
+    }
+
+    /// Streams `range` of the stored object, decrypted when the storage encrypts at rest
+    #[tracing::instrument]
+    pub async fn read_range(
+        &self,
+        path: &str,
+        range: Range<u64>,
+    ) -> ApplicationResult<FuturesBytesStream> {
+        Ok(self.object_storage.read_range_stream(path, range).await?)
+    }
+
+    /// Where the export `name` goes, under the dir of the day it is made on
+    pub fn export_path(&self, name: &str) -> String {
+        format!(
+            "{EXPORT_DIR}/{}/{name}",
+            self.ct.now().format(EXPORT_DAY_FORMAT)
+        )
+    }
+
+    /// Opens an export to write it piece by piece
+    #[tracing::instrument]
+    pub async fn export_writer(&self, path: &str) -> ApplicationResult<ObjectWriter> {
+        Ok(self.object_storage.writer(path).await?)
+    }
+
+    /// Removes the exports made more than `max_age_days` days ago, returns how many
+    /// days worth of exports went
+    #[tracing::instrument]
+    pub async fn purge_exports(&self, max_age_days: u32) -> ApplicationResult<u64> {
+        let oldest = self.ct.now().date() - chrono::Duration::days(max_age_days as i64);
+        let mut purged = 0;
+        for day in self.object_storage.list_dir(EXPORT_DIR).await? {
+            let Ok(date) = chrono::NaiveDate::parse_from_str(&day, EXPORT_DAY_FORMAT) else {
+                continue;
+            };
+            if date < oldest {
+                self.object_storage
+                    .delete_dir(format!("{EXPORT_DIR}/{day}/"))
+                    .await?;
+                purged += 1;
+            }
+        }
+        Ok(purged)
+    }
+
+    #[tracing::instrument(skip(path))]
+    pub async fn presign_url(&self, path: impl AsRef<str>) -> ApplicationResult<String> {
+        let url = self.object_storage.presign_url(path).await?;
+        Ok(url)
+    }
+
+    #[tracing::instrument(skip(path))]
+    pub async fn presign_url_with(
+        &self,
+        path: impl AsRef<str>,
+        expires: Duration,
+    ) -> ApplicationResult<String> {
+        let url = self.object_storage.presign_url_with(path, expires).await?;
+        Ok(url)
+    }
+
+    #[tracing::instrument]
+    pub fn verify_url(&self, url: Uri) -> bool {
+        self.object_storage.verify_url(url)
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn delete(&self, path: impl AsRef<str>) -> ApplicationResult<()> {
+        self.delete_many(Vec::from([path.as_ref().to_string()]))
+            .await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn delete_many(&self, paths: Vec<String>) -> ApplicationResult<()> {
+        let variant_paths: Vec<String> = paths
+            .iter()
+            .flat_map(|path| {
+                self.config
+                    .upload
+                    .image_variants
+                    .iter()
+                    .filter_map(|variant| Self::variant_path(path, &variant.name))
+            })
+            .collect();
+        for variant_path in &variant_paths {
+            self.kvdb.delete(&Self::variant_key(variant_path)).await?;
+        }
+        let paths = paths.into_iter().chain(variant_paths).collect();
+        self.object_storage.delete_many(paths).await?;
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    fn build_relative_path(&self, filename: String) -> String {
+        let now = self.ct.now();
+        let year = now.year().to_string();
+        let month = format!("{:02}", now.month());
+        let relative_path = format!("{year}/{month}/{filename}");
+        relative_path
+    }
+
+    #[tracing::instrument]
+    fn extract_extension(path: Option<String>) -> String {
+        path.as_ref()
+            .and_then(|f| Path::new(f).extension())
+            .and_then(|ext| ext.to_str())
+            .map(|ext| format!(".{}", ext))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StorageUsage {
+    pub used: u64,
+    /// Unlimited when absent
+    pub quota: Option<u64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FinishResponse {
+    pub value: String,
+    pub url: String,
+}
+
+enum SupportedFormat {
+    Png,
+    Jpg,
+    Webp,
+}
+
+impl SupportedFormat {
+    fn mime(&self) -> &'static str {
+        match self {
+            SupportedFormat::Png => "image/png",
+            SupportedFormat::Jpg => "image/jpeg",
+            SupportedFormat::Webp => "image/webp",
+        }
+    }
+
+    fn validate_image_type<R: std::io::Read>(contents: &mut R) -> Option<SupportedFormat> {
+        let Ok(format) = detect_image_format(contents) else {
+            return None;
+        };
+        let extension = match format {
+            imageformat::ImageFormat::Jpeg | imageformat::ImageFormat::JpegXl => {
+                SupportedFormat::Jpg
+            }
+            imageformat::ImageFormat::Png => SupportedFormat::Png,
+            imageformat::ImageFormat::Webp => SupportedFormat::Webp,
+            _ => {
+                return None;
+            }
+        };
+        Some(extension)
+    }
+
+    /// Re-encodes the image as WebP with its EXIF orientation applied, which leaves
+    /// EXIF and GPS metadata behind
+    async fn convert_to_webp(
+        format: SupportedFormat,
+        mut file: NamedTempFile,
+    ) -> ApplicationResult<(Cursor<Vec<u8>>, (u32, u32))> {
+        tokio::task::spawn_blocking(move || {
+            file.rewind()?;
+            let reader = std::io::BufReader::new(file.into_file());
+            let mut decoder = ImageReader::with_format(
+                reader,
+                match format {
+                    SupportedFormat::Png => ImageFormat::Png,
+                    SupportedFormat::Jpg => ImageFormat::Jpeg,
+                    SupportedFormat::Webp => ImageFormat::WebP,
+                },
+            )
+            .into_decoder()?;
+            let orientation = decoder.orientation()?;
+            let mut img = DynamicImage::from_decoder(decoder)?;
+            img.apply_orientation(orientation);
+            let mut data = Cursor::new(Vec::new());
+            img.write_to(&mut data, ImageFormat::WebP)?;
+
+            Ok((data, (img.width(), img.height())))
+        })
+        .await?
+    }
+}
+
+/// Width and height of an image file, `None` for anything the decoder does not recognize
+async fn image_dimensions(path: PathBuf) -> Option<(u32, u32)> {
+    tokio::task::spawn_blocking(move || {
+        ImageReader::open(path)
+            .ok()?
+            .with_guessed_format()
+            .ok()?
+            .into_dimensions()
+            .ok()
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+/// Blanks the EXIF and XMP segments of a JPEG in place, they carry where and with what
+/// the photo was taken. Each becomes a comment of the same length so the size and the
+/// layout of chunked uploads don't change. The orientation goes with the EXIF segment.
+/// Returns whether anything was blanked
+fn scrub_jpeg_metadata(data: &mut [u8]) -> bool {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return false;
+    }
+    let mut scrubbed = false;
+    let mut offset = 2;
+    while offset + 4 <= data.len() && data[offset] == 0xFF {
+        let marker = data[offset + 1];
+        match marker {
+            // fill byte before a marker
+            0xFF => {
+                offset += 1;
+                continue;
+            }
+            // the image data follows the start of scan, no metadata after it
+            0xD9 | 0xDA => break,
+            // markers without a length
+            0x01 | 0xD0..=0xD7 => {
+                offset += 2;
+                continue;
+            }
+            _ => {}
+        }
+        let len = u16::from_be_bytes([data[offset + 2], data[offset + 3]]) as usize;
+        if len < 2 {
+            break;
+        }
+        if marker == 0xE1 {
+            let end = (offset + 2 + len).min(data.len());
+            data[offset + 1] = 0xFE;
+            data[offset + 4..end].fill(0);
+            scrubbed = true;
+        }
+        offset += 2 + len;
+    }
+    scrubbed
+}
+
+async fn scrub_jpeg_file(path: PathBuf) -> ApplicationResult<()> {
+    tokio::task::spawn_blocking(move || {
+        use std::io::{Read as _, Write as _};
+
+        let mut file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)?;
+        let mut head = Vec::with_capacity(JPEG_HEAD_LEN);
+        (&mut file)
+            .take(JPEG_HEAD_LEN as u64)
+            .read_to_end(&mut head)?;
+        if scrub_jpeg_metadata(&mut head) {
+            file.rewind()?;
+            file.write_all(&head)?;
+        }
+        Ok(())
+    })
+    .await?
+}
+
+/// Type of the content by its leading magic bytes, `None` when not recognised. Only
+/// types that tell a file apart from what its name may claim are listed, container
+/// formats like zip are left to the name
+fn sniff_mime(head: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (&[0xFF, 0xD8, 0xFF], JPEG_MIME),
+        (b"\x89PNG\r\n\x1A\n", "image/png"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"\x7FELF", "application/x-executable"),
+        (b"MZ", "application/x-msdownload"),
+        (&[0x1F, 0x8B], "application/gzip"),
+    ];
+    if head.len() >= 12 && head.starts_with(b"RIFF") && &head[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+    SIGNATURES
+        .iter()
+        .find(|(magic, _)| head.starts_with(magic))
+        .map(|(_, mime)| *mime)
+}
+
+async fn read_file_head(path: PathBuf) -> ApplicationResult<Vec<u8>> {
+    use tokio::io::AsyncReadExt as _;
+
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    tokio::fs::File::open(path)
+        .await?
+        .take(SNIFF_LEN as u64)
+        .read_to_end(&mut head)
+        .await?;
+    Ok(head)
+}
+
+async fn hash_file(path: PathBuf) -> ApplicationResult<String> {
+    tokio::task::spawn_blocking(move || {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update_reader(std::fs::File::open(path)?)?;
+        Ok(hasher.finalize().to_hex().to_string())
+    })
+    .await?
+}
+
+#[cfg(test)]
+mod tests {
+    use infrastructure::{
+        shared::pg_pool::PgPool,
+        test_utils::{setup_database, setup_kvdb, setup_object_storage},
+    };
+
+    use super::*;
+
+    pub(super) async fn build_service(pool: PgPool) -> UploadService {
+        setup_database(pool.clone()).await;
+        let object_storage = setup_object_storage().await;
+        let file_scanner = FileScannerImpl::builder()
+            .config(ConfigRef::default())
+            .object_storage(object_storage.clone())
+            .build();
+        UploadService::builder()
+            .ct(ChronoTz::default())
+            .config(ConfigRef::default())
+            .object_storage(object_storage)
+            .file_service(
+                FileService::builder()
+                    .pool(pool)
+                    .ct(ChronoTz::default())
+                    .build(),
+            )
+            .file_scanner(file_scanner)
+            .kvdb(setup_kvdb().await)
+            .build()
+    }
+
+    #[sqlx::test]
+    async fn test_servable_and_purge_given_export(pool: PgPool) {
+        let service = build_service(pool).await;
+        let name = format!("{}.csv", IdGenerator::primary_id());
+        let path = service.export_path(&name);
+        let mut writer = service.export_writer(&path).await.unwrap();
+        writer.write(b"id\n".to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+        // exports have no file record, yet their links work
+        assert!(service.servable(&path).await.unwrap());
+        assert!(!service.servable(&format!("test/{name}")).await.unwrap());
+
+        let stale = format!("{EXPORT_DIR}/20000101/{name}");
+        service
+            .object_storage
+            .write(&stale, Cursor::new(b"id\n"))
+            .await
+            .unwrap();
+        assert!(service.purge_exports(7).await.unwrap() >= 1);
+        assert!(service.object_storage.size(&stale).await.unwrap().is_none());
+        assert!(service.object_storage.size(&path).await.unwrap().is_some());
+    }
+
+    #[test]
+    fn test_sniff_mime() {
+        assert_eq!(sniff_mime(&[0xFF, 0xD8, 0xFF, 0xE0]), Some("image/jpeg"));
+        assert_eq!(sniff_mime(b"\x89PNG\r\n\x1A\n...."), Some("image/png"));
+        assert_eq!(sniff_mime(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff_mime(b"RIFF\0\0\0\0WAVEfmt "), None);
+        assert_eq!(sniff_mime(b"MZ\x90\0"), Some("application/x-msdownload"));
+        assert_eq!(sniff_mime(b"hello"), None);
+        assert_eq!(sniff_mime(b""), None);
+    }
+
+    #[test]
+    fn test_scrub_jpeg_metadata() {
+        let exif = [0xFF, 0xE1, 0x00, 0x08, b'E', b'x', b'i', b'f', 0x00, 0x00];
+        let quant = [0xFF, 0xDB, 0x00, 0x03, 0x07];
+        let scan = [0xFF, 0xDA, 0x00, 0x02, 0xFF, 0xE1, 0x01];
+        let mut data = [&[0xFF, 0xD8][..], &exif, &quant, &scan].concat();
+        let len = data.len();
+        assert!(scrub_jpeg_metadata(&mut data));
+        assert_eq!(data.len(), len);
+        assert_eq!(&data[2..12], &[0xFF, 0xFE, 0x00, 0x08, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&data[12..], &[&quant[..], &scan].concat());
+        assert!(!scrub_jpeg_metadata(&mut data));
+
+        let mut png = b"\x89PNG\xFF\xE1\x00\x08Exif".to_vec();
+        assert!(!scrub_jpeg_metadata(&mut png));
+    }
+}
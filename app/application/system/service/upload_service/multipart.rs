@@ -0,0 +1,461 @@
+use crate::error::{ApplicationError, ApplicationResult};
+use blake3::hazmat::{
+    ChainingValue, HasherExt as _, Mode, left_subtree_len, merge_subtrees_non_root,
+    merge_subtrees_root,
+};
+use domain::{organization::value_object::user_id::UserId, shared::id_generator::IdGenerator};
+use kvdb_kit::KvdbTrait as _;
+use object_storage_kit::ObjectStorageMultipart as _;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, time::Duration};
+use tempfile::NamedTempFile;
+use tokio_util::io::ReaderStream;
+use utoipa::ToSchema;
+
+use crate::system::service::file_service::ContentMeta;
+
+use super::{FinishResponse, JPEG_MIME, UploadService, scrub_jpeg_file};
+
+const MULTIPART_KEY_PREFIX: &str = "upload:multipart:";
+/// Chunked uploads not finished within this period have to start over
+const MULTIPART_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Parts of this size are hashed as they pass through, it is a power of two of
+/// blake3 chunks so every part is a whole subtree of the hash of the file, and
+/// above the 5 MiB S3 wants of every part but the last
+const MULTIPART_PART_SIZE: u64 = 8 * 1024 * 1024;
+
+impl UploadService {
+    #[tracing::instrument]
+    pub async fn start_chunk(
+        &self,
+        uploader_id: &UserId,
+        filename: String,
+        size: u64,
+    ) -> ApplicationResult<StartChunkResponse> {
+        self.check_rule(
+            &self.config.upload.chunk,
+            size,
+            &Self::guess_mime(Some(&filename)),
+        )?;
+        self.check_quota(uploader_id, size).await?;
+        let extension = Self::extract_extension(Some(filename));
+        let filename = IdGenerator::filename().to_lowercase();
+        let key = self.build_relative_path(format!("{filename}{extension}"));
+        let upload_id = self.object_storage.create_multipart(&key).await?;
+        self.kvdb
+            .set_with_ex(&Self::multipart_key(&upload_id), &key, MULTIPART_TTL)
+            .await?;
+        Ok(StartChunkResponse {
+            key,
+            upload_id,
+            part_size: MULTIPART_PART_SIZE.min(self.config.upload.chunk_part_max_size),
+        })
+    }
+
+    #[tracing::instrument(skip(file))]
+    pub async fn chunk(
+        &self,
+        key: String,
+        upload_id: String,
+        part_number: u32,
+        file: NamedTempFile,
+    ) -> ApplicationResult<ChunkResponse> {
+        self.verify_multipart(&key, &upload_id).await?;
+        let size = file.as_file().metadata()?.len();
+        if size > self.config.upload.chunk_part_max_size {
+            return Err(ApplicationError::UploadTooLarge);
+        }
+        // the part is read from its temporary file twice, once to hash and once to
+        // store it, so it never has to be held in memory
+        if part_number == 1 && Self::guess_mime(Some(&key)) == JPEG_MIME {
+            scrub_jpeg_file(file.path().to_path_buf()).await?;
+        }
+        let part_hash = hash_part_file(part_number, file.path().to_path_buf()).await?;
+        let body = ReaderStream::new(tokio::fs::File::open(file.path()).await?);
+        let e_tag = self
+            .object_storage
+            .upload_part(&key, &upload_id, part_number, body, size)
+            .await?;
+        let part_key = Self::part_hash_key(&upload_id, part_number);
+        match part_hash {
+            Some(part_hash) => {
+                self.kvdb
+                    .set_with_ex(&part_key, part_hash, MULTIPART_TTL)
+                    .await?
+            }
+            // a part that doesn't line up sends the finish back to reading the object
+            None => self.kvdb.delete(&part_key).await?,
+        }
+        Ok(ChunkResponse { e_tag })
+    }
+
+    /// Parts already uploaded, so an interrupted upload can resume with the missing ones
+    #[tracing::instrument]
+    pub async fn list_chunks(
+        &self,
+        key: String,
+        upload_id: String,
+    ) -> ApplicationResult<Vec<UploadedPartResponse>> {
+        self.verify_multipart(&key, &upload_id).await?;
+        let parts = self.object_storage.list_parts(&key, &upload_id).await?;
+        Ok(parts
+            .into_iter()
+            .map(|part| UploadedPartResponse {
+                part_number: part.part_number,
+                e_tag: part.e_tag,
+                size: part.size,
+            })
+            .collect())
+    }
+
+    #[tracing::instrument]
+    pub async fn finish_chunk(
+        &self,
+        uploader_id: &UserId,
+        filename: String,
+        key: String,
+        upload_id: String,
+        part_list: Vec<PartItem>,
+    ) -> ApplicationResult<FinishResponse> {
+        self.verify_multipart(&key, &upload_id).await?;
+        let part_numbers = part_list
+            .iter()
+            .map(|part| part.part_number)
+            .collect::<Vec<_>>();
+        let parts = part_list
+            .into_iter()
+            .map(|part| (part.part_number, part.e_tag))
+            .collect();
+        self.object_storage
+            .complete_multipart(&key, &upload_id, parts)
+            .await?;
+        let part_hashes = self.part_hashes(&upload_id, &part_numbers).await;
+        self.kvdb.delete(&Self::multipart_key(&upload_id)).await?;
+        self.kvdb
+            .delete_prefix(&Self::part_hash_prefix(&upload_id))
+            .await?;
+
+        // the file never passes through a node as a whole, it is hashed from the hashes of
+        // its parts, only parts that don't line up have it hashed back from storage
+        let (hash, file_size) = match part_hashes.as_deref().and_then(PartHash::combine) {
+            Some(combined) => combined,
+            None => self.hash_object(&key).await?,
+        };
+        let head = self.read_head(&key, file_size).await?;
+        let mime_type = match self.check_content_rule(
+            &self.config.upload.chunk,
+            file_size,
+            Some(&filename),
+            &head,
+        ) {
+            Ok(mime_type) => mime_type,
+            Err(err) => {
+                self.delete(&key).await?;
+                return Err(err);
+            }
+        };
+        let meta = ContentMeta {
+            mime_type: Some(mime_type),
+            ..Default::default()
+        };
+        self.register_object(uploader_id, &filename, &key, &hash, file_size, meta)
+            .await
+    }
+
+    /// Aborts the chunked uploads started longer than `MULTIPART_TTL` ago, the storage
+    /// keeps their parts until then. Returns how many were aborted
+    pub async fn abort_stale_multiparts(&self) -> ApplicationResult<u64> {
+        let before = self.ct.now_utc() - chrono::Duration::seconds(MULTIPART_TTL.as_secs() as i64);
+        let mut aborted = 0;
+        for upload in self.object_storage.list_multiparts().await? {
+            if upload.initiated_at >= before {
+                continue;
+            }
+            if let Err(err) = self
+                .object_storage
+                .abort_multipart(&upload.path, &upload.upload_id)
+                .await
+            {
+                tracing::warn!(upload_id = %upload.upload_id, %err, "Failed to abort multipart upload");
+                continue;
+            }
+            self.kvdb
+                .delete(&Self::multipart_key(&upload.upload_id))
+                .await?;
+            self.kvdb
+                .delete_prefix(&Self::part_hash_prefix(&upload.upload_id))
+                .await?;
+            aborted += 1;
+        }
+        Ok(aborted)
+    }
+
+    /// Hashes of the parts in order, `None` unless every part was hashed on its way
+    /// through and the parts are numbered from 1 without gaps
+    async fn part_hashes(&self, upload_id: &str, part_numbers: &[u32]) -> Option<Vec<PartHash>> {
+        let mut part_hashes = Vec::with_capacity(part_numbers.len());
+        for (index, part_number) in part_numbers.iter().enumerate() {
+            if *part_number as usize != index + 1 {
+                return None;
+            }
+            let part_hash = self
+                .kvdb
+                .get::<PartHash>(&Self::part_hash_key(upload_id, *part_number))
+                .await?;
+            part_hashes.push(part_hash);
+        }
+        Some(part_hashes)
+    }
+
+    async fn verify_multipart(&self, key: &str, upload_id: &str) -> ApplicationResult<()> {
+        let path: Option<String> = self.kvdb.get(&Self::multipart_key(upload_id)).await;
+        if path.as_deref() != Some(key) {
+            return Err(ApplicationError::UploadNotFound);
+        }
+        Ok(())
+    }
+
+    fn multipart_key(upload_id: &str) -> String {
+        format!("{MULTIPART_KEY_PREFIX}{upload_id}")
+    }
+
+    fn part_hash_prefix(upload_id: &str) -> String {
+        format!("{MULTIPART_KEY_PREFIX}{upload_id}:")
+    }
+
+    fn part_hash_key(upload_id: &str, part_number: u32) -> String {
+        format!("{}{part_number}", Self::part_hash_prefix(upload_id))
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StartChunkResponse {
+    pub key: String,
+    #[serde(rename = "uploadId")]
+    pub upload_id: String,
+    /// Every part but the last one of this size saves reading the file back on finish
+    #[serde(rename = "partSize")]
+    pub part_size: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChunkResponse {
+    #[serde(rename = "eTag")]
+    pub e_tag: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PartItem {
+    #[serde(rename = "partNumber")]
+    pub part_number: u32,
+    #[serde(rename = "eTag")]
+    pub e_tag: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UploadedPartResponse {
+    #[serde(rename = "partNumber")]
+    pub part_number: u32,
+    #[serde(rename = "eTag")]
+    pub e_tag: String,
+    pub size: u64,
+}
+
+/// The blake3 subtree a part of a chunked upload makes up, parts are laid out
+/// `MULTIPART_PART_SIZE` apart
+#[derive(Debug, Serialize, Deserialize)]
+struct PartHash {
+    size: u64,
+    /// Chaining value of the part at its offset, set for every part that can have a
+    /// part after it
+    cv: Option<[u8; 32]>,
+    /// Hash of the first part as the whole file
+    root: Option<String>,
+}
+
+impl PartHash {
+    /// Hashes the part read from `body`, `None` when it can't be a part of a file hashed
+    /// from its parts
+    fn read(part_number: u32, mut body: impl std::io::Read) -> std::io::Result<Option<Self>> {
+        if part_number == 0 {
+            return Ok(None);
+        }
+        let mut hasher = blake3::Hasher::new();
+        hasher.set_input_offset(u64::from(part_number - 1) * MULTIPART_PART_SIZE);
+        let size = std::io::copy(&mut body, &mut hasher)?;
+        if size > MULTIPART_PART_SIZE || (part_number > 1 && size == 0) {
+            return Ok(None);
+        }
+        if part_number == 1 {
+            return Ok(Some(Self {
+                size,
+                cv: (size == MULTIPART_PART_SIZE).then(|| hasher.finalize_non_root()),
+                root: Some(hasher.finalize().to_hex().to_string()),
+            }));
+        }
+        Ok(Some(Self {
+            size,
+            cv: Some(hasher.finalize_non_root()),
+            root: None,
+        }))
+    }
+
+    /// Hash and size of the file the parts make up, `None` when only the last part is
+    /// smaller than `MULTIPART_PART_SIZE`
+    fn combine(parts: &[PartHash]) -> Option<(String, u64)> {
+        let (last, init) = parts.split_last()?;
+        if init.iter().any(|part| part.size != MULTIPART_PART_SIZE) {
+            return None;
+        }
+        let size = init.len() as u64 * MULTIPART_PART_SIZE + last.size;
+        if init.is_empty() {
+            return last.root.clone().map(|root| (root, size));
+        }
+        let cvs = parts
+            .iter()
+            .map(|part| part.cv)
+            .collect::<Option<Vec<_>>>()?;
+        let (left, right) = Self::children(&cvs, size);
+        let hash = merge_subtrees_root(&left, &right, Mode::Hash);
+        Some((hash.to_hex().to_string(), size))
+    }
+
+    /// Chaining values of the left and right subtree of `size` bytes spread over `cvs`
+    fn children(cvs: &[ChainingValue], size: u64) -> (ChainingValue, ChainingValue) {
+        let left_size = left_subtree_len(size);
+        let split = (left_size / MULTIPART_PART_SIZE) as usize;
+        let subtree = |cvs: &[ChainingValue], size| match cvs {
+            [cv] => *cv,
+            cvs => {
+                let (left, right) = Self::children(cvs, size);
+                merge_subtrees_non_root(&left, &right, Mode::Hash)
+            }
+        };
+        (
+            subtree(&cvs[..split], left_size),
+            subtree(&cvs[split..], size - left_size),
+        )
+    }
+}
+
+async fn hash_part_file(part_number: u32, path: PathBuf) -> ApplicationResult<Option<PartHash>> {
+    tokio::task::spawn_blocking(move || {
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        Ok(PartHash::read(part_number, file)?)
+    })
+    .await?
+}
+
+#[cfg(test)]
+mod tests {
+    use infrastructure::shared::{config::ConfigRef, pg_pool::PgPool};
+
+    use super::*;
+    use crate::system::service::upload_service::tests::build_service;
+
+    fn part_file(body: &[u8]) -> NamedTempFile {
+        use std::io::Write as _;
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(body).unwrap();
+        file
+    }
+
+    #[sqlx::test]
+    async fn test_chunk_hash_file_from_parts(pool: PgPool) {
+        let service = build_service(pool.clone()).await;
+        let uploader_id = UserId::generate();
+        let part_size = MULTIPART_PART_SIZE as usize;
+        let content = (0..part_size + 1234)
+            .map(|i| (i % 251) as u8)
+            .collect::<Vec<_>>();
+        let started = service
+            .start_chunk(&uploader_id, "a.bin".to_string(), content.len() as u64)
+            .await
+            .unwrap();
+        assert_eq!(started.part_size, MULTIPART_PART_SIZE);
+
+        let mut part_list = Vec::new();
+        for (body, part_number) in content.chunks(part_size).zip(1..) {
+            let chunk = service
+                .chunk(
+                    started.key.clone(),
+                    started.upload_id.clone(),
+                    part_number,
+                    part_file(body),
+                )
+                .await
+                .unwrap();
+            part_list.push(PartItem {
+                part_number,
+                e_tag: chunk.e_tag,
+            });
+        }
+        let finished = service
+            .finish_chunk(
+                &uploader_id,
+                "a.bin".to_string(),
+                started.key,
+                started.upload_id,
+                part_list,
+            )
+            .await
+            .unwrap();
+
+        let hash = sqlx::query_scalar::<_, String>("SELECT hash FROM _files WHERE path = $1")
+            .bind(&finished.value)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(hash, blake3::hash(&content).to_hex().to_string());
+    }
+
+    #[sqlx::test]
+    async fn test_chunk_return_err_given_part_too_large(pool: PgPool) {
+        let service = build_service(pool).await;
+        let uploader_id = UserId::generate();
+        let started = service
+            .start_chunk(&uploader_id, "a.bin".to_string(), 16 * 1024 * 1024)
+            .await
+            .unwrap();
+        let body = vec![0; ConfigRef::default().upload.chunk_part_max_size as usize + 1];
+
+        let result = service
+            .chunk(started.key, started.upload_id, 1, part_file(&body))
+            .await;
+
+        assert!(matches!(result, Err(ApplicationError::UploadTooLarge)));
+    }
+
+    #[test]
+    fn test_part_hash_combine() {
+        let part_size = MULTIPART_PART_SIZE as usize;
+        let content = (0..part_size * 3 + 1234)
+            .map(|i| (i % 251) as u8)
+            .collect::<Vec<_>>();
+        for len in [10, part_size, part_size + 1, part_size * 2, content.len()] {
+            let parts = content[..len]
+                .chunks(part_size)
+                .zip(1..)
+                .map(|(body, part_number)| PartHash::read(part_number, body).unwrap().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                PartHash::combine(&parts),
+                Some((
+                    blake3::hash(&content[..len]).to_hex().to_string(),
+                    len as u64
+                ))
+            );
+        }
+        let parts = Vec::from([
+            PartHash::read(1, &content[..10]).unwrap().unwrap(),
+            PartHash::read(2, &content[..10]).unwrap().unwrap(),
+        ]);
+        assert_eq!(PartHash::combine(&parts), None);
+        assert!(
+            PartHash::read(1, &content[..part_size + 1])
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,303 @@
+use crate::error::{ApplicationError, ApplicationResult};
+use axum::{body::Bytes, http::Uri};
+use domain::{organization::value_object::user_id::UserId, shared::id_generator::IdGenerator};
+use futures_util::{Stream, StreamExt};
+use kvdb_kit::KvdbTrait as _;
+use object_storage_kit::{
+    ObjectStorageStreamReader as _, ObjectStorageWriter, error::ObjectStorageError,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+use crate::system::service::file_service::ContentMeta;
+
+use super::{
+    FinishResponse, JPEG_HEAD_LEN, JPEG_MIME, UploadService, scrub_jpeg_metadata, sniff_mime,
+};
+
+const PRESIGNED_KEY_PREFIX: &str = "upload:presigned:";
+/// How long a presigned upload can be confirmed
+const PRESIGNED_TTL: Duration = Duration::from_secs(15 * 60);
+/// How long a presigned upload URL can be used, the upload only has to start within it
+const PRESIGNED_URL_TTL: Duration = Duration::from_secs(5 * 60);
+/// The largest object S3 accepts in a single PUT, bigger files go through chunked uploads
+const MAX_PRESIGNED_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+
+impl UploadService {
+    /// Issues a URL the client uploads the file to directly, the upload has to be
+    /// confirmed before it becomes a file
+    #[tracing::instrument]
+    pub async fn presign(
+        &self,
+        uploader_id: &UserId,
+        filename: String,
+        content_type: String,
+        size: u64,
+    ) -> ApplicationResult<PresignResponse> {
+        if size > MAX_PRESIGNED_SIZE {
+            return Err(ApplicationError::UploadTooLarge);
+        }
+        let rule = &self.config.upload.direct;
+        // the declared type is what the storage serves the object with, the extension what it is saved as
+        self.check_rule(rule, size, &content_type)?;
+        self.check_rule(rule, size, &Self::guess_mime(Some(&filename)))?;
+        self.check_quota(uploader_id, size).await?;
+        let extension = Self::extract_extension(Some(filename.clone()));
+        let key = self.build_relative_path(format!(
+            "{}{extension}",
+            IdGenerator::filename().to_lowercase()
+        ));
+        let presigned = self
+            .object_storage
+            .presign_write(&key, &content_type, size, PRESIGNED_URL_TTL)
+            .await?;
+        let url_expires_at =
+            self.ct.now_utc() + chrono::Duration::seconds(PRESIGNED_URL_TTL.as_secs() as i64);
+        self.kvdb
+            .set_with_ex(
+                &Self::presigned_key(&key),
+                PresignedUpload {
+                    filename,
+                    content_type,
+                    size,
+                    url_expires_at: url_expires_at.timestamp(),
+                },
+                PRESIGNED_TTL,
+            )
+            .await?;
+        Ok(PresignResponse {
+            key,
+            method: presigned.method,
+            url: presigned.url,
+            headers: presigned.headers.into_iter().collect(),
+        })
+    }
+
+    /// Registers a presigned upload once the object is in storage with the announced size,
+    /// and with `hash` as its blake3 hash when given
+    #[tracing::instrument]
+    pub async fn confirm(
+        &self,
+        uploader_id: &UserId,
+        key: String,
+        hash: Option<String>,
+    ) -> ApplicationResult<FinishResponse> {
+        let upload: Option<PresignedUpload> = self.kvdb.get(&Self::presigned_key(&key)).await;
+        let Some(upload) = upload else {
+            return Err(ApplicationError::UploadNotFound);
+        };
+        // no write is accepted for the key from here on
+        self.kvdb.delete(&Self::presigned_key(&key)).await?;
+        match self.object_storage.size(&key).await? {
+            Some(size) if size == upload.size => {}
+            Some(_) => {
+                self.delete(&key).await?;
+                return Err(ApplicationError::UploadMismatch);
+            }
+            None => return Err(ApplicationError::UploadNotFound),
+        }
+        let moved = self.build_relative_path(format!(
+            "{}{}",
+            IdGenerator::filename().to_lowercase(),
+            Self::extract_extension(Some(key.clone()))
+        ));
+        // a URL served by the storage itself can't be revoked, while it is still valid the
+        // object is moved out of its reach so what is hashed is what gets registered
+        let (key, sent_hash, actual_hash, file_size) = if upload.content_type == JPEG_MIME
+            || Self::guess_mime(Some(&upload.filename)) == JPEG_MIME
+        {
+            let scrubbed = self.copy_scrubbed(&key, &moved).await;
+            self.delete(&key).await?;
+            let (sent_hash, actual_hash, file_size) = scrubbed?;
+            (moved, sent_hash, actual_hash, file_size)
+        } else {
+            let key = if self.ct.now_utc().timestamp() <= upload.url_expires_at {
+                self.object_storage.rename(&key, &moved).await?;
+                moved
+            } else {
+                key
+            };
+            let (actual_hash, file_size) = self.hash_object(&key).await?;
+            (key, actual_hash.clone(), actual_hash, file_size)
+        };
+        if file_size != upload.size
+            || hash.is_some_and(|hash| !hash.eq_ignore_ascii_case(&sent_hash))
+        {
+            self.delete(&key).await?;
+            return Err(ApplicationError::UploadMismatch);
+        }
+        // the declared type was checked up front, what arrived may still be something else
+        let head = self.read_head(&key, file_size).await?;
+        let rule = &self.config.upload.direct;
+        let mime_type = match sniff_mime(&head) {
+            Some(sniffed) => match self.check_rule(rule, file_size, sniffed) {
+                Ok(()) => sniffed.to_string(),
+                Err(err) => {
+                    self.delete(&key).await?;
+                    return Err(err);
+                }
+            },
+            None => upload.content_type,
+        };
+        let meta = ContentMeta {
+            mime_type: Some(mime_type),
+            ..Default::default()
+        };
+        self.register_object(
+            uploader_id,
+            &upload.filename,
+            &key,
+            &actual_hash,
+            file_size,
+            meta,
+        )
+        .await
+    }
+
+    /// Whether a presigned write arrived at this server is genuine, only backends
+    /// without a public endpoint of their own route uploads here
+    #[tracing::instrument]
+    pub fn verify_write_url(&self, url: Uri, content_type: &str, size: u64) -> bool {
+        self.object_storage
+            .verify_write_url(url, content_type, size)
+    }
+
+    /// Stores the body of a verified presigned write, which must be exactly `size` bytes.
+    /// Only an upload that is still waiting to be confirmed can be written
+    #[tracing::instrument(skip(body))]
+    pub async fn write_presigned<E>(
+        &self,
+        path: &str,
+        size: u64,
+        body: impl Stream<Item = Result<Bytes, E>> + Unpin,
+    ) -> ApplicationResult<()>
+    where
+        E: std::error::Error,
+    {
+        let upload: Option<PresignedUpload> = self.kvdb.get(&Self::presigned_key(path)).await;
+        if upload.is_none_or(|upload| upload.size != size) {
+            return Err(ApplicationError::UploadNotFound);
+        }
+        let written = self.object_storage.write_body(path, body, size).await?;
+        if written != size {
+            self.delete(path).await?;
+            return Err(ApplicationError::UploadMismatch);
+        }
+        Ok(())
+    }
+
+    fn presigned_key(key: &str) -> String {
+        format!("{PRESIGNED_KEY_PREFIX}{key}")
+    }
+
+    /// Copies a JPEG to `to` with its metadata blanked, returns the hash of the content
+    /// as sent, the hash of what is stored and the size, which stays the same
+    async fn copy_scrubbed(
+        &self,
+        from: &str,
+        to: &str,
+    ) -> ApplicationResult<(String, String, u64)> {
+        let mut stream = self.object_storage.read_stream(from).await?;
+        let mut head = Vec::new();
+        while head.len() < JPEG_HEAD_LEN
+            && let Some(chunk) = stream.next().await
+        {
+            head.extend_from_slice(chunk?.as_ref());
+        }
+        let mut sent = blake3::Hasher::new();
+        let mut stored = blake3::Hasher::new();
+        sent.update(&head);
+        scrub_jpeg_metadata(&mut head);
+        stored.update(&head);
+        let rest = stream.map(|chunk| {
+            let chunk = chunk?;
+            sent.update(chunk.as_ref());
+            stored.update(chunk.as_ref());
+            Ok::<_, ObjectStorageError>(Bytes::copy_from_slice(chunk.as_ref()))
+        });
+        let body =
+            futures_util::stream::once(std::future::ready(Ok(Bytes::from(head)))).chain(rest);
+        let size = self.object_storage.write_body(to, body, u64::MAX).await?;
+        Ok((
+            sent.finalize().to_hex().to_string(),
+            stored.finalize().to_hex().to_string(),
+            size,
+        ))
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PresignResponse {
+    pub key: String,
+    pub method: String,
+    pub url: String,
+    /// Headers to send along with the upload, they are part of the signature
+    pub headers: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PresignedUpload {
+    filename: String,
+    content_type: String,
+    size: u64,
+    /// Unix timestamp the upload URL expires at
+    url_expires_at: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use infrastructure::shared::pg_pool::PgPool;
+
+    use super::*;
+    use crate::system::service::upload_service::tests::build_service;
+
+    #[sqlx::test]
+    async fn test_write_presigned_return_err_given_upload_confirmed(pool: PgPool) {
+        let service = build_service(pool).await;
+        let uploader_id = UserId::generate();
+        let presigned = service
+            .presign(
+                &uploader_id,
+                "a.txt".to_string(),
+                "text/plain".to_string(),
+                5,
+            )
+            .await
+            .unwrap();
+        let body =
+            || futures_util::stream::iter([Ok::<_, std::io::Error>(Bytes::from_static(b"hello"))]);
+        assert!(
+            service
+                .write_presigned(&presigned.key, 4, body())
+                .await
+                .is_err()
+        );
+        assert!(
+            service
+                .write_presigned(&presigned.key, 5, body())
+                .await
+                .is_ok()
+        );
+
+        let finished = service
+            .confirm(&uploader_id, presigned.key.clone(), None)
+            .await
+            .unwrap();
+        // moved out of reach of the URL, which was still valid
+        assert_ne!(finished.value, presigned.key);
+        assert!(
+            service
+                .write_presigned(&presigned.key, 5, body())
+                .await
+                .is_err()
+        );
+        assert!(
+            service
+                .confirm(&uploader_id, presigned.key, None)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use crate::error::{ApplicationError, ApplicationResult};
+use domain::system::{
+    error::SystemError,
+    port::file_scanner::{FileScanner as _, ScanVerdict},
+    value_object::{file_id::FileId, scan_status::ScanStatus},
+};
+use object_storage_kit::ObjectStorageWriter;
+
+use crate::system::service::file_service::FileObject;
+
+use super::UploadService;
+
+/// Objects the scanner objected to are moved under this prefix and kept for review
+const QUARANTINE_DIR: &str = "quarantine";
+
+impl UploadService {
+    /// Scans content just written to `path` before it becomes a file, content the scanner
+    /// objects to is moved into quarantine. Returns where the content is stored now
+    pub(super) async fn scan(&self, path: &str) -> ApplicationResult<(String, ScanVerdict)> {
+        let verdict = match self.file_scanner.scan(path).await {
+            Ok(verdict) => verdict,
+            Err(err) => {
+                tracing::error!(%err, path, "Failed to scan upload");
+                self.delete(path).await?;
+                return Err(ApplicationError::FileScanFailed);
+            }
+        };
+        if verdict.status() != ScanStatus::Quarantined {
+            return Ok((path.to_string(), verdict));
+        }
+        let quarantine_path = format!("{QUARANTINE_DIR}/{path}");
+        self.object_storage.rename(path, &quarantine_path).await?;
+        tracing::warn!(
+            path = quarantine_path,
+            result = verdict.result(),
+            "Upload quarantined"
+        );
+        Ok((quarantine_path, verdict))
+    }
+
+    /// Scans a stored file again, e.g. once the scanner's signatures were updated. Content found
+    /// clean leaves quarantine along with every file sharing it, new uploads of it are accepted
+    #[tracing::instrument]
+    pub async fn rescan(&self, id: &FileId) -> ApplicationResult<ScanStatus> {
+        let (hash, object) = self.object_of(id).await?;
+        let verdict = self.file_scanner.scan(&object.path).await.map_err(|err| {
+            tracing::error!(%err, path = object.path, "Failed to rescan file");
+            ApplicationError::FileScanFailed
+        })?;
+        self.apply_verdict(&hash, &object.path, &verdict).await?;
+        Ok(verdict.status())
+    }
+
+    /// Takes a file out of quarantine without a clean scan, for content the scanner
+    /// objects to wrongly. It is recorded as skipped by the scanner from then on
+    #[tracing::instrument]
+    pub async fn release(&self, id: &FileId) -> ApplicationResult<()> {
+        let (hash, object) = self.object_of(id).await?;
+        if object.scan_status != ScanStatus::Quarantined {
+            return Ok(());
+        }
+        self.apply_verdict(&hash, &object.path, &ScanVerdict::Skipped)
+            .await?;
+        tracing::warn!(
+            path = object.path,
+            result = object.scan_result,
+            "Quarantined file released"
+        );
+        Ok(())
+    }
+
+    async fn object_of(&self, id: &FileId) -> ApplicationResult<(String, FileObject)> {
+        self.file_service
+            .object_of(id)
+            .await?
+            .ok_or_else(|| SystemError::FileNotFound.into())
+    }
+
+    /// Moves stored content in or out of quarantine as the verdict demands and records it
+    async fn apply_verdict(
+        &self,
+        hash: &str,
+        path: &str,
+        verdict: &ScanVerdict,
+    ) -> ApplicationResult<()> {
+        let released = path
+            .strip_prefix(QUARANTINE_DIR)
+            .and_then(|path| path.strip_prefix('/'));
+        let target = match (verdict.status(), released) {
+            (ScanStatus::Quarantined, None) => format!("{QUARANTINE_DIR}/{path}"),
+            (ScanStatus::Quarantined, Some(_)) => path.to_string(),
+            (_, released) => released.unwrap_or(path).to_string(),
+        };
+        if target != path {
+            self.object_storage.rename(path, &target).await?;
+        }
+        self.file_service.update_scan(hash, &target, verdict).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::shared::id_generator::IdGenerator;
+    use infrastructure::shared::pg_pool::PgPool;
+    use object_storage_kit::ObjectStorageStreamReader as _;
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::system::service::file_service::{ContentMeta, NewObject, Quota};
+    use crate::system::service::upload_service::tests::build_service;
+
+    #[sqlx::test]
+    async fn test_rescan_return_skipped_given_quarantined_file(pool: PgPool) {
+        let service = build_service(pool.clone()).await;
+        let hash = "q".repeat(64);
+        let path = format!("{QUARANTINE_DIR}/test/{}.txt", IdGenerator::primary_id());
+        service
+            .object_storage
+            .write(&path, Cursor::new(b"hello"))
+            .await
+            .unwrap();
+        let verdict = ScanVerdict::Infected("Eicar-Signature".to_string());
+        let new_object = NewObject::builder()
+            .hash(&hash)
+            .size(5)
+            .path(&path)
+            .verdict(&verdict)
+            .meta(ContentMeta::default())
+            .build();
+        service
+            .file_service
+            .create("a.txt", "uploader", new_object, Quota::default())
+            .await
+            .unwrap();
+        let id = sqlx::query_scalar!("SELECT id FROM _files WHERE hash = $1", hash)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let id = FileId::new_unchecked(id);
+
+        // no scanner is configured, so the content is let through as skipped
+        assert_eq!(service.rescan(&id).await.unwrap(), ScanStatus::Skipped);
+        let (_, object) = service.file_service.object_of(&id).await.unwrap().unwrap();
+        let released = path.strip_prefix("quarantine/").unwrap();
+        assert_eq!(object.path, released);
+        assert_eq!(object.scan_result, None);
+        assert!(service.servable(released).await.unwrap());
+        assert!(
+            service
+                .object_storage
+                .size(released)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert!(service.object_storage.size(&path).await.unwrap().is_none());
+        // releasing a file out of quarantine already changes nothing
+        service.release(&id).await.unwrap();
+        assert_eq!(
+            service
+                .file_service
+                .object_of(&id)
+                .await
+                .unwrap()
+                .unwrap()
+                .1
+                .path,
+            released
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use crate::error::ApplicationResult;
+use bg_worker_kit::EnqueueOptions;
+use futures_util::StreamExt;
+use image::{DynamicImage, ImageFormat, imageops::FilterType};
+use infrastructure::shared::config::{ImageFit, ImageVariant};
+use kvdb_kit::KvdbTrait as _;
+use object_storage_kit::{ObjectStorageStreamReader as _, ObjectStorageWriter};
+use std::io::Cursor;
+use std::time::Duration;
+
+use crate::shared::{
+    bgworker::generate_image_variants::GenerateImageVariants, bgworker_impl::WorkerRegistry,
+};
+
+use super::UploadService;
+
+const VARIANT_KEY_PREFIX: &str = "upload:variant:";
+/// How long the existence of a variant is remembered
+const VARIANT_TTL: Duration = Duration::from_secs(60 * 60);
+/// A missing variant is looked for again after this, its job should have run by then
+const MISSING_VARIANT_TTL: Duration = Duration::from_secs(5 * 60);
+
+impl UploadService {
+    /// Writes the configured variants of a stored image that do not exist yet
+    #[tracing::instrument(skip(self))]
+    pub async fn generate_variants(&self, path: &str) -> ApplicationResult<()> {
+        let mut pending = Vec::new();
+        for variant in &self.config.upload.image_variants {
+            let Some(variant_path) = Self::variant_path(path, &variant.name) else {
+                return Ok(());
+            };
+            if self.object_storage.size(&variant_path).await?.is_none() {
+                pending.push((variant.clone(), variant_path));
+            }
+        }
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let mut data = Vec::new();
+        let mut stream = self.object_storage.read_stream(path).await?;
+        while let Some(chunk) = stream.next().await {
+            data.extend_from_slice(chunk?.as_ref());
+        }
+        let resized = tokio::task::spawn_blocking(move || {
+            let img = image::load_from_memory_with_format(&data, ImageFormat::WebP)?;
+            pending
+                .into_iter()
+                .map(|(variant, variant_path)| {
+                    let mut data = Cursor::new(Vec::new());
+                    resize(&img, &variant).write_to(&mut data, ImageFormat::WebP)?;
+                    Ok((variant_path, data))
+                })
+                .collect::<ApplicationResult<Vec<_>>>()
+        })
+        .await??;
+        for (variant_path, data) in resized {
+            self.object_storage.write(&variant_path, data).await?;
+            self.kvdb
+                .set_with_ex(&Self::variant_key(&variant_path), true, VARIANT_TTL)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Presigns the named variant of an image, the original stands in until the variant is generated
+    #[tracing::instrument(skip(self))]
+    pub async fn presign_variant_url(
+        &self,
+        path: &str,
+        variant: &str,
+    ) -> ApplicationResult<String> {
+        let configured = self
+            .config
+            .upload
+            .image_variants
+            .iter()
+            .any(|v| v.name == variant);
+        if let Some(variant_path) = Self::variant_path(path, variant).filter(|_| configured)
+            && self.variant_exists(path, &variant_path).await?
+        {
+            return self.presign_url(&variant_path).await;
+        }
+        self.presign_url(path).await
+    }
+
+    /// Whether a variant is in storage, remembered so rendering a page of images
+    /// doesn't look up every one of them
+    async fn variant_exists(&self, path: &str, variant_path: &str) -> ApplicationResult<bool> {
+        let cache_key = Self::variant_key(variant_path);
+        if let Some(exists) = self.kvdb.get::<bool>(&cache_key).await {
+            return Ok(exists);
+        }
+        let exists = self.object_storage.size(variant_path).await?.is_some();
+        if !exists {
+            // uploaded before the variant was configured
+            self.enqueue_variants(path).await;
+        }
+        let ttl = if exists {
+            VARIANT_TTL
+        } else {
+            MISSING_VARIANT_TTL
+        };
+        self.kvdb.set_with_ex(&cache_key, exists, ttl).await?;
+        Ok(exists)
+    }
+
+    pub(super) async fn enqueue_variants(&self, path: &str) {
+        if self.config.upload.image_variants.is_empty() {
+            return;
+        }
+        let task = GenerateImageVariants::builder()
+            .path(path.to_string())
+            .build();
+        // a missing variant is requested on every presign until the job runs
+        let options = EnqueueOptions::default().unique(path);
+        if let Err(err) = WorkerRegistry::enqueue_generate_image_variants_with(task, options).await
+        {
+            tracing::error!(error = %err, "Failed to enqueue generate_image_variants");
+        }
+    }
+
+    /// Variants live next to the image as `{name}@{variant}.webp`, only uploaded images have them
+    pub(super) fn variant_path(path: &str, variant: &str) -> Option<String> {
+        path.strip_suffix(".webp")
+            .filter(|stem| !stem.contains('@'))
+            .map(|stem| format!("{stem}@{variant}.webp"))
+    }
+
+    /// The image a variant was generated from, any other path is returned as is
+    pub(super) fn original_path(path: &str) -> String {
+        path.strip_suffix(".webp")
+            .and_then(|stem| stem.rsplit_once('@'))
+            .map(|(stem, _)| format!("{stem}.webp"))
+            .unwrap_or_else(|| path.to_string())
+    }
+
+    pub(super) fn variant_key(variant_path: &str) -> String {
+        format!("{VARIANT_KEY_PREFIX}{variant_path}")
+    }
+}
+
+fn resize(img: &DynamicImage, variant: &ImageVariant) -> DynamicImage {
+    match variant.fit {
+        ImageFit::Cover => img.resize_to_fill(variant.width, variant.height, FilterType::Lanczos3),
+        // never upscale, a small image already fits
+        ImageFit::Contain if img.width() <= variant.width && img.height() <= variant.height => {
+            img.clone()
+        }
+        ImageFit::Contain => img.resize(variant.width, variant.height, FilterType::Lanczos3),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variant_path() {
+        assert_eq!(
+            UploadService::variant_path("2025/01/abc.webp", "thumb"),
+            Some("2025/01/abc@thumb.webp".to_string())
+        );
+        assert_eq!(
+            UploadService::variant_path("2025/01/abc.pdf", "thumb"),
+            None
+        );
+        assert_eq!(
+            UploadService::variant_path("2025/01/abc@thumb.webp", "thumb"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_original_path() {
+        assert_eq!(
+            UploadService::original_path("2025/01/abc@thumb.webp"),
+            "2025/01/abc.webp"
+        );
+        assert_eq!(
+            UploadService::original_path("2025/01/abc.webp"),
+            "2025/01/abc.webp"
+        );
+        assert_eq!(
+            UploadService::original_path("2025/01/a@b.pdf"),
+            "2025/01/a@b.pdf"
+        );
+    }
+
+    #[test]
+    fn test_resize() {
+        let img = DynamicImage::new_rgb8(400, 200);
+        let variant = |fit| ImageVariant {
+            name: "thumb".to_string(),
+            width: 100,
+            height: 100,
+            fit,
+        };
+        let cover = resize(&img, &variant(ImageFit::Cover));
+        assert_eq!((cover.width(), cover.height()), (100, 100));
+        let contain = resize(&img, &variant(ImageFit::Contain));
+        assert_eq!((contain.width(), contain.height()), (100, 50));
+        let small = resize(&DynamicImage::new_rgb8(40, 20), &variant(ImageFit::Contain));
+        assert_eq!((small.width(), small.height()), (40, 20));
+    }
+}
@@ -1,8 +1,25 @@
-use crate::organization::value_object::user_id::UserId;
+use crate::{
+    auth::value_object::client_info::ClientInfo, organization::value_object::user_id::UserId,
+};
 
 #[derive(Debug, Clone)]
 pub enum AuthEvent {
-    UserRefreshTokenSucceeded { id: UserId },
-    UserLoginSucceeded { id: UserId },
-    UserLogoutSucceeded { id: UserId },
+    UserRefreshTokenSucceeded {
+        id: UserId,
+        client: ClientInfo,
+    },
+    UserLoginSucceeded {
+        id: UserId,
+        client: ClientInfo,
+    },
+    UserLoginFailed {
+        account: String,
+        id: Option<UserId>,
+        reason: String,
+        client: ClientInfo,
+    },
+    UserLogoutSucceeded {
+        id: UserId,
+        client: ClientInfo,
+    },
 }
@@ -0,0 +1,7 @@
+use bon::Builder;
+
+#[derive(Debug, Clone, Default, Builder)]
+pub struct ClientInfo {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
@@ -1,3 +1,4 @@
+pub mod client_info;
 pub mod menu;
 pub mod menu_group;
 pub mod permission;
@@ -13,6 +13,12 @@ pub enum OrganizationEvent {
     UsersDeleted {
         items: Vec<User>,
     },
+    UsersRestored {
+        items: Vec<User>,
+    },
+    UsersPurged {
+        items: Vec<User>,
+    },
     RolesCreated {
         items: Vec<Role>,
     },
@@ -22,6 +28,12 @@ pub enum OrganizationEvent {
     RolesDeleted {
         items: Vec<Role>,
     },
+    RolesRestored {
+        items: Vec<Role>,
+    },
+    RolesPurged {
+        items: Vec<Role>,
+    },
     DepartmentsCreated {
         items: Vec<Department>,
     },
@@ -31,4 +43,10 @@ pub enum OrganizationEvent {
     DepartmentsDeleted {
         items: Vec<Department>,
     },
+    DepartmentsRestored {
+        items: Vec<Department>,
+    },
+    DepartmentsPurged {
+        items: Vec<Department>,
+    },
 }
@@ -25,6 +25,17 @@ impl IdGenerator {
         .to_string()
     }
 
+    /// Short enough to paste, random enough not to be guessed
+    pub fn share_code() -> String {
+        tempoid::TempoId::generate_custom(TempoIdOptions {
+            time_length: 4,
+            random_length: 12,
+            alphabet: ALPHABET,
+            ..Default::default()
+        })
+        .to_string()
+    }
+
     pub fn random() -> String {
         tempoid::TempoId::generate_custom(TempoIdOptions {
             time_length: 8,
@@ -52,6 +63,12 @@ mod tests {
         assert_eq!(id.len(), 32);
     }
 
+    #[test]
+    fn test_share_code() {
+        let code = IdGenerator::share_code();
+        assert_eq!(code.len(), 16);
+    }
+
     #[test]
     fn test_random() {
         let id = IdGenerator::random();
@@ -1 +1,2 @@
 pub mod domain_repository;
+pub mod recyclable_repository;
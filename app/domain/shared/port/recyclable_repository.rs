@@ -0,0 +1,23 @@
+use chrono::NaiveDateTime;
+
+use crate::shared::port::domain_repository::DomainRepository;
+
+/// Repository whose `batch_delete` only moves entities into the recycle bin
+pub trait RecyclableRepository: DomainRepository {
+    /// Brings deleted entities back, fails when one of them collides with a live entity
+    fn restore(
+        &self,
+        ids: &[Self::EntityId],
+    ) -> impl Future<Output = Result<Vec<Self::Entity>, Self::Error>>;
+
+    /// Removes deleted entities for good
+    fn purge(
+        &self,
+        ids: &[Self::EntityId],
+    ) -> impl Future<Output = Result<Vec<Self::Entity>, Self::Error>>;
+
+    fn deleted_before(
+        &self,
+        before: NaiveDateTime,
+    ) -> impl Future<Output = Result<Vec<Self::EntityId>, Self::Error>>;
+}
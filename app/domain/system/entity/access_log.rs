@@ -14,6 +14,8 @@ pub struct AccessLog {
     pub status: i16,
     pub elapsed: i64,
     pub occurred_at: chrono::NaiveDateTime,
+    pub request_body: Option<String>,
+    pub response_body: Option<String>,
 }
 
 impl AccessLog {
@@ -42,4 +44,10 @@ impl AccessLog {
     pub fn update_occurred_at(&mut self, occurred_at: chrono::NaiveDateTime) {
         self.occurred_at = occurred_at;
     }
+    pub fn update_request_body(&mut self, request_body: Option<String>) {
+        self.request_body = request_body;
+    }
+    pub fn update_response_body(&mut self, response_body: Option<String>) {
+        self.response_body = response_body;
+    }
 }
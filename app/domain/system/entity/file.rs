@@ -1,6 +1,9 @@
 use bon::Builder;
 
-use crate::system::value_object::file_id::FileId;
+use crate::system::value_object::{file_folder_id::FileFolderId, file_id::FileId};
+
+/// Longest tag kept, longer ones are cut
+const MAX_TAG_LENGTH: usize = 32;
 
 #[derive(Debug, Clone, Builder)]
 #[readonly::make]
@@ -9,7 +12,15 @@ pub struct File {
     pub name: String,
     pub path: String,
     pub size: i64,
-    pub used: bool,
+    pub hash: Option<String>,
+    pub uploader_id: Option<String>,
+    pub folder_id: Option<FileFolderId>,
+    #[builder(default)]
+    pub tags: Vec<String>,
+    pub description: Option<String>,
+    pub mime_type: Option<String>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
 }
 
 impl File {
@@ -22,7 +33,45 @@ impl File {
     pub fn update_size(&mut self, size: i64) {
         self.size = size;
     }
-    pub fn update_used(&mut self, used: bool) {
-        self.used = used;
+    pub fn update_folder_id(&mut self, folder_id: Option<FileFolderId>) {
+        self.folder_id = folder_id;
+    }
+    pub fn update_tags(&mut self, tags: Vec<String>) {
+        self.tags = normalize_tags(tags);
+    }
+    pub fn update_description(&mut self, description: Option<String>) {
+        self.description = description.filter(|description| !description.trim().is_empty());
+    }
+}
+
+/// Trims, shortens and deduplicates tags, keeping them sorted
+pub fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut tags: Vec<String> = tags
+        .into_iter()
+        .map(|tag| tag.trim().chars().take(MAX_TAG_LENGTH).collect::<String>())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_tags() {
+        let tags = normalize_tags(vec![
+            " logo ".to_string(),
+            "banner".to_string(),
+            "logo".to_string(),
+            "  ".to_string(),
+            "x".repeat(40),
+        ]);
+        assert_eq!(
+            tags,
+            vec!["banner".to_string(), "logo".to_string(), "x".repeat(32)]
+        );
     }
 }
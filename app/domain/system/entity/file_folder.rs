@@ -0,0 +1,55 @@
+use bon::Builder;
+
+use crate::system::{error::SystemError, value_object::file_folder_id::FileFolderId};
+
+/// Longest folder name in characters
+const MAX_NAME_LEN: usize = 64;
+
+/// A virtual folder of the file library, objects in storage never move
+#[derive(Debug, Clone, Builder)]
+#[readonly::make]
+pub struct FileFolder {
+    pub id: FileFolderId,
+    pub name: String,
+    pub parent_id: Option<FileFolderId>,
+}
+
+impl FileFolder {
+    /// Trims a folder name as given by a user, it must not end up empty or too long
+    pub fn check_name(name: &str) -> Result<String, SystemError> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+            return Err(SystemError::FileFolderInvalidName);
+        }
+        Ok(name.to_string())
+    }
+
+    pub fn update_name(&mut self, name: String) {
+        self.name = name;
+    }
+    pub fn update_parent_id(&mut self, parent_id: Option<FileFolderId>) {
+        self.parent_id = parent_id;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_name() {
+        assert_eq!(FileFolder::check_name("  docs ").unwrap(), "docs");
+        assert_eq!(
+            FileFolder::check_name(" \t "),
+            Err(SystemError::FileFolderInvalidName)
+        );
+        assert_eq!(
+            FileFolder::check_name(&"文".repeat(64)).unwrap(),
+            "文".repeat(64)
+        );
+        assert_eq!(
+            FileFolder::check_name(&"a".repeat(65)),
+            Err(SystemError::FileFolderInvalidName)
+        );
+    }
+}
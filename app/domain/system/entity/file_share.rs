@@ -0,0 +1,139 @@
+use bon::Builder;
+use chrono::NaiveDateTime;
+
+use crate::{
+    organization::value_object::{hashed_password::HashedPassword, user_id::UserId},
+    system::{
+        error::SystemError,
+        value_object::{file_id::FileId, file_share_id::FileShareId},
+    },
+};
+
+/// A managed link to a file, resolved through its short code
+#[derive(Debug, Clone, Builder)]
+#[readonly::make]
+pub struct FileShare {
+    pub id: FileShareId,
+    pub code: String,
+    pub file_id: FileId,
+    pub password: Option<HashedPassword>,
+    /// Never expires when absent
+    pub expires_at: Option<NaiveDateTime>,
+    /// Unlimited when absent
+    pub max_downloads: Option<i32>,
+    #[builder(default)]
+    pub download_count: i32,
+    #[builder(default)]
+    pub revoked: bool,
+    pub created_by: Option<UserId>,
+}
+
+impl FileShare {
+    pub fn revoke(&mut self) {
+        self.revoked = true;
+    }
+
+    /// Checks whether the link may be downloaded at `now`, the password is only
+    /// verified once the link itself is usable
+    pub fn check_access(
+        &self,
+        now: NaiveDateTime,
+        password: Option<&str>,
+    ) -> Result<(), SystemError> {
+        if self.revoked {
+            return Err(SystemError::FileShareRevoked);
+        }
+        if self.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(SystemError::FileShareExpired);
+        }
+        if self
+            .max_downloads
+            .is_some_and(|max_downloads| self.download_count >= max_downloads)
+        {
+            return Err(SystemError::FileShareExhausted);
+        }
+        if let Some(hashed) = &self.password {
+            let password = password.ok_or(SystemError::FileSharePasswordRequired)?;
+            hashed
+                .verify(password)
+                .map_err(|_| SystemError::FileSharePasswordIncorrect)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, NaiveDate};
+
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 1, 1)
+            .unwrap()
+            .and_hms_opt(8, 0, 0)
+            .unwrap()
+    }
+
+    fn share() -> FileShare {
+        FileShare::builder()
+            .id(FileShareId::generate())
+            .code("abc".to_string())
+            .file_id(FileId::generate())
+            .build()
+    }
+
+    #[test]
+    fn test_check_access_return_ok_given_unrestricted_share() {
+        assert_eq!(share().check_access(now(), None), Ok(()));
+    }
+
+    #[test]
+    fn test_check_access_return_err_given_revoked() {
+        let mut share = share();
+        share.revoke();
+        assert_eq!(
+            share.check_access(now(), None),
+            Err(SystemError::FileShareRevoked)
+        );
+    }
+
+    #[test]
+    fn test_check_access_return_err_given_expired() {
+        let mut share = share();
+        share.expires_at = Some(now() - Duration::minutes(1));
+        assert_eq!(
+            share.check_access(now(), None),
+            Err(SystemError::FileShareExpired)
+        );
+        share.expires_at = Some(now() + Duration::minutes(1));
+        assert_eq!(share.check_access(now(), None), Ok(()));
+    }
+
+    #[test]
+    fn test_check_access_return_err_given_download_limit_reached() {
+        let mut share = share();
+        share.max_downloads = Some(2);
+        share.download_count = 1;
+        assert_eq!(share.check_access(now(), None), Ok(()));
+        share.download_count = 2;
+        assert_eq!(
+            share.check_access(now(), None),
+            Err(SystemError::FileShareExhausted)
+        );
+    }
+
+    #[test]
+    fn test_check_access_verify_password() {
+        let mut share = share();
+        share.password = Some(HashedPassword::try_new("secret1".to_string()).unwrap());
+        assert_eq!(
+            share.check_access(now(), None),
+            Err(SystemError::FileSharePasswordRequired)
+        );
+        assert_eq!(
+            share.check_access(now(), Some("secret2")),
+            Err(SystemError::FileSharePasswordIncorrect)
+        );
+        assert_eq!(share.check_access(now(), Some("secret1")), Ok(()));
+    }
+}
@@ -1,3 +1,7 @@
 pub mod access_log;
 pub mod file;
+pub mod file_folder;
+pub mod file_share;
 pub mod sched;
+pub mod sched_job;
+pub mod security_event;
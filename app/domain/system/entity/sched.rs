@@ -1,6 +1,6 @@
 use bon::Builder;
 
-use crate::system::value_object::sched_id::SchedId;
+use crate::{organization::value_object::user_id::UserId, system::value_object::sched_id::SchedId};
 
 #[derive(Debug, Clone, Builder)]
 #[readonly::make]
@@ -9,8 +9,13 @@ pub struct Sched {
     pub key: String,
     pub name: String,
     pub expr: String,
-    pub succeed: bool,
-    pub result: String,
+    /// `None` while running, like `result` and `duration_ms`
+    pub succeed: Option<bool>,
+    pub result: Option<String>,
     pub run_at: chrono::NaiveDateTime,
-    pub duration_ms: i64,
+    pub duration_ms: Option<i64>,
+    /// Run by hand instead of on schedule
+    #[builder(default)]
+    pub manual: bool,
+    pub triggered_by: Option<UserId>,
 }
@@ -0,0 +1,32 @@
+use bon::Builder;
+
+/// Setting of the scheduled job `key`, overriding what it is compiled with
+#[derive(Debug, Clone, PartialEq, Builder)]
+#[readonly::make]
+pub struct SchedJob {
+    pub key: String,
+    /// `None` keeps the expression of the job
+    pub expr: Option<String>,
+    /// `None` keeps the configured timezone
+    pub timezone: Option<String>,
+    pub enabled: bool,
+    /// JSON handed to the job when it runs
+    pub params: Option<String>,
+}
+
+impl SchedJob {
+    /// The setting of a job nobody has edited
+    pub fn defaults(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            expr: None,
+            timezone: None,
+            enabled: true,
+            params: None,
+        }
+    }
+
+    pub fn update_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+}
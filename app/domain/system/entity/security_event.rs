@@ -0,0 +1,179 @@
+use bon::Builder;
+
+use crate::{
+    organization::value_object::user_id::UserId,
+    system::{
+        port::geo_ip_resolver::GeoLocation,
+        value_object::{
+            security_event_id::SecurityEventId, security_event_kind::SecurityEventKind,
+        },
+    },
+};
+
+/// Speeds above this between two logins are treated as impossible travel
+const MAX_TRAVEL_SPEED_KMH: f64 = 1000.0;
+/// GeoIP is imprecise, shorter distances never count as travel
+const MIN_TRAVEL_DISTANCE_KM: f64 = 500.0;
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+#[derive(Debug, Clone, Builder)]
+#[readonly::make]
+pub struct SecurityEvent {
+    pub id: SecurityEventId,
+    pub user_id: Option<UserId>,
+    pub account: Option<String>,
+    pub kind: SecurityEventKind,
+    pub succeed: bool,
+    pub reason: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub country: Option<String>,
+    pub region: Option<String>,
+    pub city: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    #[builder(default)]
+    pub new_device: bool,
+    #[builder(default)]
+    pub new_country: bool,
+    #[builder(default)]
+    pub impossible_travel: bool,
+    pub occurred_at: chrono::NaiveDateTime,
+}
+
+impl SecurityEvent {
+    pub fn update_location(&mut self, location: GeoLocation) {
+        self.country = location.country;
+        self.region = location.region;
+        self.city = location.city;
+        self.latitude = location.latitude;
+        self.longitude = location.longitude;
+    }
+
+    /// Compares the event with the previous successful logins of the same user,
+    /// nothing is flagged for the very first login
+    pub fn flag_anomalies(&mut self, previous_logins: &[SecurityEvent]) {
+        if previous_logins.is_empty() {
+            return;
+        }
+        if let Some(user_agent) = &self.user_agent {
+            self.new_device = previous_logins
+                .iter()
+                .all(|login| login.user_agent.as_ref() != Some(user_agent));
+        }
+        if let Some(country) = &self.country {
+            let mut known_countries = previous_logins
+                .iter()
+                .filter_map(|login| login.country.as_ref())
+                .peekable();
+            self.new_country =
+                known_countries.peek().is_some() && known_countries.all(|known| known != country);
+        }
+        if let Some(coordinate) = self.coordinate() {
+            let last_login = previous_logins
+                .iter()
+                .filter(|login| login.occurred_at <= self.occurred_at)
+                .filter_map(|login| login.coordinate().map(|c| (login.occurred_at, c)))
+                .max_by_key(|(occurred_at, _)| *occurred_at);
+            if let Some((occurred_at, last_coordinate)) = last_login {
+                let distance = haversine_km(last_coordinate, coordinate);
+                let hours = (self.occurred_at - occurred_at).num_seconds() as f64 / 3600.0;
+                self.impossible_travel = distance >= MIN_TRAVEL_DISTANCE_KM
+                    && (hours <= 0.0 || distance / hours > MAX_TRAVEL_SPEED_KMH);
+            }
+        }
+    }
+
+    fn coordinate(&self) -> Option<(f64, f64)> {
+        self.latitude.zip(self.longitude)
+    }
+}
+
+/// Great-circle distance between two (latitude, longitude) points
+fn haversine_km(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lng1) = (from.0.to_radians(), from.1.to_radians());
+    let (lat2, lng2) = (to.0.to_radians(), to.1.to_radians());
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lng2 - lng1) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+    const BEIJING: (f64, f64) = (39.9042, 116.4074);
+    const TIANJIN: (f64, f64) = (39.3434, 117.3616);
+    const NEW_YORK: (f64, f64) = (40.7128, -74.0060);
+
+    fn base_time() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 1, 1)
+            .unwrap()
+            .and_hms_opt(8, 0, 0)
+            .unwrap()
+    }
+
+    fn login(
+        user_agent: &str,
+        country: &str,
+        coordinate: (f64, f64),
+        occurred_at: NaiveDateTime,
+    ) -> SecurityEvent {
+        SecurityEvent::builder()
+            .id(SecurityEventId::generate())
+            .user_id(UserId::generate())
+            .kind(SecurityEventKind::LoginSucceeded)
+            .succeed(true)
+            .user_agent(user_agent.to_string())
+            .country(country.to_string())
+            .latitude(coordinate.0)
+            .longitude(coordinate.1)
+            .occurred_at(occurred_at)
+            .build()
+    }
+
+    #[test]
+    fn test_haversine_km() {
+        let distance = haversine_km(BEIJING, NEW_YORK);
+        assert!((distance - 11_000.0).abs() < 100.0, "{distance}");
+        assert_eq!(haversine_km(BEIJING, BEIJING), 0.0);
+    }
+
+    #[test]
+    fn test_first_login_is_not_flagged() {
+        let mut event = login("firefox", "US", NEW_YORK, base_time());
+        event.flag_anomalies(&[]);
+        assert!(!event.new_device);
+        assert!(!event.new_country);
+        assert!(!event.impossible_travel);
+    }
+
+    #[test]
+    fn test_flag_new_device_and_country() {
+        let previous = login("chrome", "CN", BEIJING, base_time());
+        let mut event = login("firefox", "US", NEW_YORK, base_time() + Duration::days(2));
+        event.flag_anomalies(&[previous]);
+        assert!(event.new_device);
+        assert!(event.new_country);
+        assert!(!event.impossible_travel);
+    }
+
+    #[test]
+    fn test_flag_impossible_travel() {
+        let previous = login("chrome", "CN", BEIJING, base_time());
+        let mut event = login("chrome", "US", NEW_YORK, base_time() + Duration::hours(1));
+        event.flag_anomalies(&[previous]);
+        assert!(!event.new_device);
+        assert!(event.impossible_travel);
+    }
+
+    #[test]
+    fn test_nearby_login_is_not_travel() {
+        let previous = login("chrome", "CN", BEIJING, base_time());
+        let mut event = login("chrome", "CN", TIANJIN, base_time() + Duration::minutes(5));
+        event.flag_anomalies(&[previous]);
+        assert!(!event.new_country);
+        assert!(!event.impossible_travel);
+    }
+}
@@ -2,10 +2,40 @@
 pub enum SystemError {
     #[error("file_not_found")]
     FileNotFound,
+    #[error("fileFolder_not_found")]
+    FileFolderNotFound,
+    #[error("fileFolder_invalid_name")]
+    FileFolderInvalidName,
+    #[error("fileFolder_name_conflict")]
+    FileFolderNameConflict,
+    #[error("fileFolder_invalid_parent")]
+    FileFolderInvalidParent,
+    #[error("fileShare_not_found")]
+    FileShareNotFound,
+    #[error("fileShare_revoked")]
+    FileShareRevoked,
+    #[error("fileShare_expired")]
+    FileShareExpired,
+    #[error("fileShare_exhausted")]
+    FileShareExhausted,
+    #[error("fileShare_password_required")]
+    FileSharePasswordRequired,
+    #[error("fileShare_password_incorrect")]
+    FileSharePasswordIncorrect,
     #[error("sched_not_found")]
     SchedNotFound,
+    #[error("schedJob_not_found")]
+    SchedJobNotFound,
+    #[error("schedJob_invalid_expr")]
+    SchedJobInvalidExpr,
+    #[error("schedJob_invalid_timezone")]
+    SchedJobInvalidTimezone,
+    #[error("schedJob_invalid_params")]
+    SchedJobInvalidParams,
     #[error("accessLog_not_found")]
     AccessLogNotFound,
+    #[error("securityEvent_not_found")]
+    SecurityEventNotFound,
     #[error("database_error")]
     Sqlx(String),
 }
@@ -1,7 +1,58 @@
+use crate::auth::value_object::client_info::ClientInfo;
+use crate::shared::event_util::UpdatedEvent;
 use crate::system::entity::access_log::AccessLog;
+use crate::system::entity::file::File;
+use crate::system::entity::file_folder::FileFolder;
+use crate::system::entity::file_share::FileShare;
 use crate::system::entity::sched::Sched;
+use crate::system::entity::sched_job::SchedJob;
+use crate::system::entity::security_event::SecurityEvent;
 #[derive(Debug, Clone)]
 pub enum SystemEvent {
-    SchedsDeleted { items: Vec<Sched> },
-    AccessLogsCreated { items: Vec<AccessLog> },
+    SchedsDeleted {
+        items: Vec<Sched>,
+    },
+    /// A scheduled job was rescheduled, enabled, disabled or given other parameters
+    SchedJobsUpdated {
+        items: Vec<UpdatedEvent<SchedJob>>,
+    },
+    AccessLogsCreated {
+        items: Vec<AccessLog>,
+    },
+    SecurityEventsCreated {
+        items: Vec<SecurityEvent>,
+    },
+    FilesUpdated {
+        items: Vec<UpdatedEvent<File>>,
+    },
+    FilesDeleted {
+        items: Vec<File>,
+    },
+    FilesRestored {
+        items: Vec<File>,
+    },
+    FilesPurged {
+        items: Vec<File>,
+    },
+    FileFoldersCreated {
+        items: Vec<FileFolder>,
+    },
+    FileFoldersUpdated {
+        items: Vec<UpdatedEvent<FileFolder>>,
+    },
+    FileFoldersDeleted {
+        items: Vec<FileFolder>,
+    },
+    FileSharesCreated {
+        items: Vec<FileShare>,
+    },
+    FileSharesRevoked {
+        items: Vec<UpdatedEvent<FileShare>>,
+    },
+    /// A share link was opened, `reason` tells why it was refused
+    FileShareAccessed {
+        code: String,
+        client: ClientInfo,
+        reason: Option<String>,
+    },
 }
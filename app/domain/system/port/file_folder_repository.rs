@@ -0,0 +1,12 @@
+use crate::{
+    shared::port::domain_repository::DomainRepository,
+    system::value_object::file_folder_id::FileFolderId,
+};
+
+pub trait FileFolderRepository: DomainRepository {
+    /// The folder itself and every folder below it
+    fn subtree_ids(
+        &self,
+        id: &FileFolderId,
+    ) -> impl Future<Output = Result<Vec<FileFolderId>, Self::Error>>;
+}
@@ -1,3 +1,20 @@
-use crate::shared::port::domain_repository::DomainRepository;
+use crate::{
+    shared::{event_util::UpdatedEvent, port::domain_repository::DomainRepository},
+    system::value_object::{file_folder_id::FileFolderId, file_id::FileId},
+};
 
-pub trait FileRepository: DomainRepository {}
+pub trait FileRepository: DomainRepository {
+    fn move_to_folder(
+        &self,
+        ids: &[FileId],
+        folder_id: Option<&FileFolderId>,
+    ) -> impl Future<Output = Result<Vec<UpdatedEvent<Self::Entity>>, Self::Error>>;
+
+    /// Adds and removes tags on every file, removal wins over addition
+    fn update_tags(
+        &self,
+        ids: &[FileId],
+        add: &[String],
+        remove: &[String],
+    ) -> impl Future<Output = Result<Vec<UpdatedEvent<Self::Entity>>, Self::Error>>;
+}
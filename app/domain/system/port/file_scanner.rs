@@ -0,0 +1,38 @@
+use std::fmt::Display;
+
+use crate::system::value_object::scan_status::ScanStatus;
+
+/// What a scanner concluded about the content of a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanVerdict {
+    /// No scanner is configured
+    Skipped,
+    Clean,
+    /// Carries the name of the matched signature
+    Infected(String),
+}
+
+impl ScanVerdict {
+    pub fn status(&self) -> ScanStatus {
+        match self {
+            Self::Skipped => ScanStatus::Skipped,
+            Self::Clean => ScanStatus::Clean,
+            Self::Infected(_) => ScanStatus::Quarantined,
+        }
+    }
+
+    /// The signature or reason reported along with the verdict
+    pub fn result(&self) -> Option<&str> {
+        match self {
+            Self::Skipped | Self::Clean => None,
+            Self::Infected(result) => Some(result),
+        }
+    }
+}
+
+pub trait FileScanner {
+    type Error: Display;
+    /// Scans the stored object at `path`. Content the scanner could not get through, e.g.
+    /// because it exceeds the scanner's size limit, is an error rather than a verdict
+    fn scan(&self, path: &str) -> impl Future<Output = Result<ScanVerdict, Self::Error>>;
+}
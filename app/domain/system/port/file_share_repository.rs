@@ -0,0 +1,16 @@
+use crate::{
+    shared::{event_util::UpdatedEvent, port::domain_repository::DomainRepository},
+    system::{entity::file_share::FileShare, value_object::file_share_id::FileShareId},
+};
+
+pub trait FileShareRepository: DomainRepository {
+    fn by_code(&self, code: &str) -> impl Future<Output = Result<FileShare, Self::Error>>;
+
+    /// Counts one download, `false` when a concurrent download used up the last one
+    fn count_download(&self, share: &FileShare) -> impl Future<Output = Result<bool, Self::Error>>;
+
+    fn revoke(
+        &self,
+        ids: &[FileShareId],
+    ) -> impl Future<Output = Result<Vec<UpdatedEvent<Self::Entity>>, Self::Error>>;
+}
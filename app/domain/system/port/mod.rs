@@ -1,3 +1,9 @@
 pub mod access_log_repository;
+pub mod file_folder_repository;
 pub mod file_repository;
+pub mod file_scanner;
+pub mod file_share_repository;
+pub mod geo_ip_resolver;
+pub mod sched_job_repository;
 pub mod sched_repository;
+pub mod security_event_repository;
@@ -0,0 +1,12 @@
+use crate::system::entity::sched_job::SchedJob;
+
+pub trait SchedJobRepository {
+    type Error;
+
+    fn all(&self) -> impl Future<Output = Result<Vec<SchedJob>, Self::Error>>;
+
+    /// The stored setting, the defaults when the job was never edited
+    fn by_key(&self, key: &str) -> impl Future<Output = Result<SchedJob, Self::Error>>;
+
+    fn save(&self, entity: SchedJob) -> impl Future<Output = Result<SchedJob, Self::Error>>;
+}
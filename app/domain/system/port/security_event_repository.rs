@@ -0,0 +1,11 @@
+use crate::{
+    organization::value_object::user_id::UserId, shared::port::domain_repository::DomainRepository,
+};
+
+pub trait SecurityEventRepository: DomainRepository {
+    fn recent_succeeded_logins(
+        &self,
+        user_id: &UserId,
+        limit: i64,
+    ) -> impl Future<Output = Result<Vec<Self::Entity>, Self::Error>>;
+}
@@ -1,3 +1,9 @@
 pub mod access_log_id;
+pub mod file_folder_id;
 pub mod file_id;
+pub mod file_share_id;
+pub mod recycle_bin_kind;
+pub mod scan_status;
 pub mod sched_id;
+pub mod security_event_id;
+pub mod security_event_kind;
@@ -0,0 +1,46 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// Kind of entity kept in the recycle bin
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, utoipa::ToSchema, sqlx::Type,
+)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "VARCHAR", rename_all = "snake_case")]
+pub enum RecycleBinKind {
+    User,
+    Role,
+    Department,
+    File,
+}
+
+impl RecycleBinKind {
+    pub const ALL: [Self; 4] = [Self::User, Self::Role, Self::Department, Self::File];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Role => "role",
+            Self::Department => "department",
+            Self::File => "file",
+        }
+    }
+}
+
+impl Display for RecycleBinKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RecycleBinKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| format!("unknown recycle bin kind: {s}"))
+    }
+}
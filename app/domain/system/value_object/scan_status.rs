@@ -0,0 +1,55 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, utoipa::ToSchema, sqlx::Type,
+)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "VARCHAR", rename_all = "snake_case")]
+pub enum ScanStatus {
+    /// Stored without going through a scan
+    Unscanned,
+    /// Accepted while no scanner was configured, or uploaded before scanning existed
+    Skipped,
+    Clean,
+    /// Found infected by the scanner, kept aside and never served
+    Quarantined,
+}
+
+impl ScanStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unscanned => "unscanned",
+            Self::Skipped => "skipped",
+            Self::Clean => "clean",
+            Self::Quarantined => "quarantined",
+        }
+    }
+
+    pub fn is_servable(&self) -> bool {
+        matches!(self, Self::Skipped | Self::Clean)
+    }
+}
+
+impl Display for ScanStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ScanStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            Self::Unscanned,
+            Self::Skipped,
+            Self::Clean,
+            Self::Quarantined,
+        ]
+        .into_iter()
+        .find(|status| status.as_str() == s)
+        .ok_or_else(|| format!("unknown scan status: {s}"))
+    }
+}
@@ -0,0 +1,3 @@
+use crate::id;
+
+id!(SecurityEventId);
@@ -0,0 +1,51 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, utoipa::ToSchema, sqlx::Type,
+)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "VARCHAR", rename_all = "snake_case")]
+pub enum SecurityEventKind {
+    LoginSucceeded,
+    LoginFailed,
+    TokenRefreshed,
+    LogoutSucceeded,
+    FileShareDownloaded,
+}
+
+impl SecurityEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::LoginSucceeded => "login_succeeded",
+            Self::LoginFailed => "login_failed",
+            Self::TokenRefreshed => "token_refreshed",
+            Self::LogoutSucceeded => "logout_succeeded",
+            Self::FileShareDownloaded => "file_share_downloaded",
+        }
+    }
+}
+
+impl Display for SecurityEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SecurityEventKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            Self::LoginSucceeded,
+            Self::LoginFailed,
+            Self::TokenRefreshed,
+            Self::LogoutSucceeded,
+            Self::FileShareDownloaded,
+        ]
+        .into_iter()
+        .find(|kind| kind.as_str() == s)
+        .ok_or_else(|| format!("unknown security event kind: {s}"))
+    }
+}
@@ -31,4 +31,10 @@ pub enum InfrastructureError {
 
     #[error(transparent)]
     Migrate(#[from] migrate_kit::error::MigrateError),
+
+    #[error(transparent)]
+    ObjectStorage(#[from] object_storage_kit::error::ObjectStorageError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
 }
@@ -0,0 +1,11 @@
+CREATE TABLE
+    _access_log_bodies (
+        access_log_id CHAR(24) PRIMARY KEY NOT NULL,
+        request_body TEXT,
+        response_body TEXT,
+        occurred_at TIMESTAMP NOT NULL,
+        created_at TIMESTAMP NOT NULL,
+        updated_at TIMESTAMP NOT NULL
+    );
+
+CREATE INDEX idx_access_log_bodies_occurred_at ON _access_log_bodies (occurred_at);
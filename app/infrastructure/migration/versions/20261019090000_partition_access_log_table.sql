@@ -0,0 +1,61 @@
+ALTER TABLE _access_logs RENAME TO _access_logs_legacy;
+
+DROP INDEX idx_access_logs_user_id;
+
+DROP INDEX idx_access_logs_occurred_at;
+
+DROP INDEX idx_access_logs_status;
+
+DROP INDEX idx_access_logs_elapsed;
+
+CREATE TABLE
+    _access_logs (
+        id CHAR(24) NOT NULL,
+        user_id CHAR(24) NOT NULL,
+        method VARCHAR(8) NOT NULL,
+        uri VARCHAR(256) NOT NULL,
+        user_agent VARCHAR(512),
+        ip VARCHAR(45),
+        status SMALLINT NOT NULL,
+        elapsed BIGINT NOT NULL,
+        occurred_at TIMESTAMP NOT NULL,
+        created_at TIMESTAMP NOT NULL,
+        updated_at TIMESTAMP NOT NULL,
+        PRIMARY KEY (id, occurred_at)
+    )
+PARTITION BY
+    RANGE (occurred_at);
+
+CREATE INDEX idx_access_logs_user_id ON _access_logs (user_id);
+
+CREATE INDEX idx_access_logs_occurred_at ON _access_logs (occurred_at);
+
+CREATE INDEX idx_access_logs_status ON _access_logs (status);
+
+CREATE INDEX idx_access_logs_elapsed ON _access_logs (elapsed);
+
+CREATE TABLE _access_logs_default PARTITION OF _access_logs DEFAULT;
+
+-- one partition per day covering the existing rows, the days are taken from the stored
+-- timestamps which are already in the app timezone, upcoming days are created by the
+-- access log rotation job using the same boundary
+DO $$
+DECLARE
+    day DATE;
+    last_day DATE;
+BEGIN
+    SELECT MIN(occurred_at)::DATE, MAX(occurred_at)::DATE INTO day, last_day FROM _access_logs_legacy;
+    WHILE day <= last_day LOOP
+        EXECUTE format(
+            'CREATE TABLE %I PARTITION OF _access_logs FOR VALUES FROM (%L) TO (%L)',
+            '_access_logs_p' || to_char(day, 'YYYYMMDD'),
+            day::TIMESTAMP,
+            (day + 1)::TIMESTAMP
+        );
+        day := day + 1;
+    END LOOP;
+END $$;
+
+INSERT INTO _access_logs SELECT * FROM _access_logs_legacy;
+
+DROP TABLE _access_logs_legacy;
@@ -0,0 +1,28 @@
+CREATE TABLE
+    _security_events (
+        id CHAR(24) PRIMARY KEY NOT NULL,
+        user_id CHAR(24),
+        account VARCHAR(64),
+        kind VARCHAR(32) NOT NULL,
+        succeed BOOLEAN NOT NULL,
+        reason VARCHAR(64),
+        ip VARCHAR(45),
+        user_agent VARCHAR(512),
+        country VARCHAR(64),
+        region VARCHAR(64),
+        city VARCHAR(64),
+        latitude DOUBLE PRECISION,
+        longitude DOUBLE PRECISION,
+        new_device BOOLEAN NOT NULL DEFAULT FALSE,
+        new_country BOOLEAN NOT NULL DEFAULT FALSE,
+        impossible_travel BOOLEAN NOT NULL DEFAULT FALSE,
+        occurred_at TIMESTAMP NOT NULL,
+        created_at TIMESTAMP NOT NULL,
+        updated_at TIMESTAMP NOT NULL
+    );
+
+CREATE INDEX idx_security_events_user_id_occurred_at ON _security_events (user_id, occurred_at);
+
+CREATE INDEX idx_security_events_occurred_at ON _security_events (occurred_at);
+
+CREATE INDEX idx_security_events_kind ON _security_events (kind);
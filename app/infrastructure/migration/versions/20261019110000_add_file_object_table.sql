@@ -0,0 +1,15 @@
+ALTER TABLE _files ADD COLUMN hash CHAR(64);
+
+CREATE INDEX idx_files_hash ON _files (hash);
+
+CREATE TABLE
+    _file_objects (
+        hash CHAR(64) PRIMARY KEY NOT NULL,
+        path VARCHAR(64) NOT NULL,
+        size BIGINT NOT NULL,
+        ref_count INTEGER NOT NULL,
+        created_at TIMESTAMP NOT NULL,
+        updated_at TIMESTAMP NOT NULL
+    );
+
+CREATE UNIQUE INDEX idx_file_objects_path ON _file_objects (path);
@@ -0,0 +1,54 @@
+CREATE TABLE
+    _file_references (
+        owner_type VARCHAR(32) NOT NULL,
+        owner_id VARCHAR(64) NOT NULL,
+        field VARCHAR(64) NOT NULL,
+        path VARCHAR(128) NOT NULL,
+        created_at TIMESTAMP NOT NULL,
+        PRIMARY KEY (owner_type, owner_id, field, path)
+    );
+
+CREATE INDEX idx_file_references_path ON _file_references (path);
+
+INSERT INTO
+    _file_references (owner_type, owner_id, field, path, created_at)
+SELECT
+    'user',
+    id,
+    'portrait',
+    portrait,
+    NOW()
+FROM
+    _users
+WHERE
+    portrait IS NOT NULL
+    AND portrait <> '';
+
+-- portraits were the only column marking files used, any other file still flagged keeps
+-- a reference of its own so dropping the flag never makes it eligible for cleanup
+INSERT INTO
+    _file_references (owner_type, owner_id, field, path, created_at)
+SELECT
+    'file',
+    f.id,
+    'used',
+    f.path,
+    NOW()
+FROM
+    _files AS f
+WHERE
+    f.used
+    AND NOT EXISTS (
+        SELECT
+            1
+        FROM
+            _file_references AS r
+        WHERE
+            r.path = f.path
+    );
+
+DROP INDEX idx_files_used_created_at;
+
+ALTER TABLE _files DROP COLUMN used;
+
+CREATE INDEX idx_files_created_at ON _files (created_at);
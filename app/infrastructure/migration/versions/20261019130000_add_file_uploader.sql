@@ -0,0 +1,3 @@
+ALTER TABLE _files ADD COLUMN uploader_id CHAR(24);
+
+CREATE INDEX idx_files_uploader_id ON _files (uploader_id);
@@ -0,0 +1,17 @@
+-- existing uploads were never scanned and stay servable
+ALTER TABLE _files ADD COLUMN scan_status VARCHAR(16) NOT NULL DEFAULT 'skipped';
+
+ALTER TABLE _files ALTER COLUMN scan_status SET DEFAULT 'unscanned';
+
+ALTER TABLE _files ADD COLUMN scan_result VARCHAR(255);
+
+ALTER TABLE _file_objects ADD COLUMN scan_status VARCHAR(16) NOT NULL DEFAULT 'skipped';
+
+ALTER TABLE _file_objects ALTER COLUMN scan_status SET DEFAULT 'unscanned';
+
+ALTER TABLE _file_objects ADD COLUMN scan_result VARCHAR(255);
+
+-- quarantined objects move under a prefix of their own
+ALTER TABLE _files ALTER COLUMN path TYPE VARCHAR(128);
+
+ALTER TABLE _file_objects ALTER COLUMN path TYPE VARCHAR(128);
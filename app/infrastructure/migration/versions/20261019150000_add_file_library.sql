@@ -0,0 +1,33 @@
+CREATE TABLE
+    _file_folders (
+        id CHAR(24) PRIMARY KEY NOT NULL,
+        name VARCHAR(64) NOT NULL,
+        parent_id CHAR(24),
+        created_at TIMESTAMP NOT NULL,
+        updated_at TIMESTAMP NOT NULL
+    );
+
+CREATE UNIQUE INDEX idx_file_folders_parent_id_name ON _file_folders (COALESCE(parent_id, ''), name);
+
+ALTER TABLE _files ADD COLUMN folder_id CHAR(24);
+
+ALTER TABLE _files ADD COLUMN tags TEXT[] NOT NULL DEFAULT '{}';
+
+ALTER TABLE _files ADD COLUMN description TEXT;
+
+ALTER TABLE _files ADD COLUMN mime_type VARCHAR(128);
+
+ALTER TABLE _files ADD COLUMN width INTEGER;
+
+ALTER TABLE _files ADD COLUMN height INTEGER;
+
+CREATE INDEX idx_files_folder_id ON _files (folder_id);
+
+CREATE INDEX idx_files_tags ON _files USING GIN (tags);
+
+-- content properties are shared by every file of an object
+ALTER TABLE _file_objects ADD COLUMN mime_type VARCHAR(128);
+
+ALTER TABLE _file_objects ADD COLUMN width INTEGER;
+
+ALTER TABLE _file_objects ADD COLUMN height INTEGER;
@@ -0,0 +1,19 @@
+CREATE TABLE
+    _file_shares (
+        id CHAR(24) PRIMARY KEY NOT NULL,
+        code VARCHAR(16) NOT NULL,
+        file_id CHAR(24) NOT NULL,
+        password VARCHAR(255),
+        expires_at TIMESTAMP,
+        max_downloads INTEGER,
+        download_count INTEGER NOT NULL DEFAULT 0,
+        last_downloaded_at TIMESTAMP,
+        revoked BOOLEAN NOT NULL DEFAULT false,
+        created_by CHAR(24),
+        created_at TIMESTAMP NOT NULL,
+        updated_at TIMESTAMP NOT NULL
+    );
+
+CREATE UNIQUE INDEX idx_file_shares_code ON _file_shares (code);
+
+CREATE INDEX idx_file_shares_file_id ON _file_shares (file_id);
@@ -0,0 +1,24 @@
+ALTER TABLE _users ADD COLUMN deleted_at TIMESTAMP;
+
+ALTER TABLE _roles ADD COLUMN deleted_at TIMESTAMP;
+
+ALTER TABLE _departments ADD COLUMN deleted_at TIMESTAMP;
+
+ALTER TABLE _files ADD COLUMN deleted_at TIMESTAMP;
+
+-- deleted rows keep their values, uniqueness only applies among the live ones
+ALTER TABLE _users DROP CONSTRAINT _users_account_key;
+
+CREATE UNIQUE INDEX _users_account_key ON _users (account) WHERE deleted_at IS NULL;
+
+ALTER TABLE _roles DROP CONSTRAINT _roles_name_key;
+
+CREATE UNIQUE INDEX _roles_name_key ON _roles (name) WHERE deleted_at IS NULL;
+
+CREATE INDEX index_users_deleted_at ON _users (deleted_at) WHERE deleted_at IS NOT NULL;
+
+CREATE INDEX index_roles_deleted_at ON _roles (deleted_at) WHERE deleted_at IS NOT NULL;
+
+CREATE INDEX index_departments_deleted_at ON _departments (deleted_at) WHERE deleted_at IS NOT NULL;
+
+CREATE INDEX index_files_deleted_at ON _files (deleted_at) WHERE deleted_at IS NOT NULL;
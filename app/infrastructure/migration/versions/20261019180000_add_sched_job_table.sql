@@ -0,0 +1,10 @@
+CREATE TABLE
+    _sched_jobs (
+        key VARCHAR(128) PRIMARY KEY NOT NULL,
+        expr VARCHAR(256),
+        timezone VARCHAR(64),
+        enabled BOOLEAN NOT NULL,
+        params JSONB,
+        created_at TIMESTAMP NOT NULL,
+        updated_at TIMESTAMP NOT NULL
+    );
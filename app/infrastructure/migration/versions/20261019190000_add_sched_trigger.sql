@@ -0,0 +1,3 @@
+ALTER TABLE _scheds
+ADD COLUMN manual BOOLEAN NOT NULL DEFAULT FALSE,
+ADD COLUMN triggered_by CHAR(24);
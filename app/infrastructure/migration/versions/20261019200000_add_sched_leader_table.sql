@@ -0,0 +1,9 @@
+CREATE TABLE
+    _sched_leader (
+        name VARCHAR(64) PRIMARY KEY NOT NULL,
+        holder VARCHAR(256) NOT NULL,
+        token BIGINT NOT NULL,
+        acquired_at TIMESTAMPTZ NOT NULL,
+        renewed_at TIMESTAMPTZ NOT NULL,
+        expires_at TIMESTAMPTZ NOT NULL
+    );
@@ -0,0 +1,33 @@
+-- uploads of identical content share a path, so a path no longer tells which file is referenced
+ALTER TABLE _file_references ADD COLUMN file_id CHAR(24);
+
+UPDATE _file_references AS r
+SET
+    file_id = (
+        SELECT
+            f.id
+        FROM
+            _files AS f
+        WHERE
+            f.path = r.path
+        ORDER BY
+            f.deleted_at IS NOT NULL,
+            f.created_at,
+            f.id
+        LIMIT
+            1
+    );
+
+DELETE FROM _file_references WHERE file_id IS NULL;
+
+ALTER TABLE _file_references DROP CONSTRAINT _file_references_pkey;
+
+DROP INDEX idx_file_references_path;
+
+ALTER TABLE _file_references DROP COLUMN path;
+
+ALTER TABLE _file_references ALTER COLUMN file_id SET NOT NULL;
+
+ALTER TABLE _file_references ADD PRIMARY KEY (owner_type, owner_id, field, file_id);
+
+CREATE INDEX idx_file_references_file_id ON _file_references (file_id);
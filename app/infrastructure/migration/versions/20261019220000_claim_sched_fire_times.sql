@@ -0,0 +1,8 @@
+-- a scheduled run is recorded once it claimed its fire time and finished later, so its outcome stays empty meanwhile
+ALTER TABLE _scheds
+ALTER COLUMN succeed DROP NOT NULL,
+ALTER COLUMN result DROP NOT NULL,
+ALTER COLUMN duration_ms DROP NOT NULL,
+ADD COLUMN fire_at TIMESTAMPTZ;
+
+CREATE UNIQUE INDEX idx_scheds_key_fire_at ON _scheds (key, fire_at);
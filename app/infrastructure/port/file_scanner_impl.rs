@@ -0,0 +1,166 @@
+use bon::Builder;
+use domain::system::port::file_scanner::{FileScanner, ScanVerdict};
+use futures_util::{Stream, StreamExt as _};
+use nject::injectable;
+use object_storage_kit::{ObjectStorage, ObjectStorageStreamReader as _};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt as _, AsyncWrite, AsyncWriteExt as _},
+    net::TcpStream,
+};
+
+use crate::{
+    error::{InfrastructureError, InfrastructureResult},
+    shared::config::ConfigRef,
+};
+
+/// clamd rejects chunks larger than its StreamMaxLength, keep them well below the default
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Builder)]
+#[injectable]
+pub struct FileScannerImpl {
+    config: ConfigRef,
+    object_storage: ObjectStorage,
+}
+
+impl FileScanner for FileScannerImpl {
+    type Error = InfrastructureError;
+
+    #[tracing::instrument]
+    async fn scan(&self, path: &str) -> InfrastructureResult<ScanVerdict> {
+        let file_scan = &self.config.file_scan;
+        let Some(address) = file_scan.clamd_address.as_deref() else {
+            return Ok(ScanVerdict::Skipped);
+        };
+        let content = self.object_storage.read_stream(path).await?;
+        let reply = tokio::time::timeout(file_scan.timeout, async {
+            let mut stream = TcpStream::connect(address).await?;
+            instream(&mut stream, content).await
+        })
+        .await
+        .map_err(|_| InfrastructureError::Custom(format!("Scanning {path} timed out")))??;
+        parse_reply(&reply)
+    }
+}
+
+/// Streams the content with clamd's INSTREAM command, returns the raw reply
+async fn instream<S, C, B, E>(stream: &mut S, mut content: C) -> InfrastructureResult<String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    C: Stream<Item = Result<B, E>> + Unpin,
+    B: AsRef<[u8]>,
+    InfrastructureError: From<E>,
+{
+    stream.write_all(b"zINSTREAM\0").await?;
+    while let Some(chunk) = content.next().await {
+        for part in chunk?.as_ref().chunks(CHUNK_SIZE) {
+            stream.write_all(&(part.len() as u32).to_be_bytes()).await?;
+            stream.write_all(part).await?;
+        }
+    }
+    stream.write_all(&0u32.to_be_bytes()).await?;
+    stream.flush().await?;
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply).await?;
+    Ok(String::from_utf8_lossy(&reply).into_owned())
+}
+
+/// Replies look like `stream: OK`, `stream: Eicar-Signature FOUND` or `... ERROR`
+fn parse_reply(reply: &str) -> InfrastructureResult<ScanVerdict> {
+    let reply = reply.trim_end_matches(['\0', '\n']).trim();
+    let reply = reply.strip_prefix("stream:").unwrap_or(reply).trim();
+    if reply == "OK" {
+        return Ok(ScanVerdict::Clean);
+    }
+    if let Some(signature) = reply.strip_suffix(" FOUND") {
+        return Ok(ScanVerdict::Infected(signature.trim().to_string()));
+    }
+    if let Some(reason) = reply.strip_suffix(" ERROR") {
+        return Err(InfrastructureError::Custom(format!(
+            "clamd could not scan the content: {}",
+            reason.trim()
+        )));
+    }
+    Err(InfrastructureError::Custom(format!(
+        "Unexpected clamd reply: {reply}"
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_object_storage;
+    use tokio::net::TcpListener;
+
+    const EICAR: &[u8] = b"X5O!P%@AP[4\\PZX54(P^)7CC)7}$EICAR-STANDARD-ANTIVIRUS-TEST-FILE!$H+H*";
+
+    /// Speaks enough of clamd to answer a single INSTREAM command
+    async fn clamd_stand_in() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut command = [0u8; 10];
+            socket.read_exact(&mut command).await.unwrap();
+            assert_eq!(&command, b"zINSTREAM\0");
+            let mut content = Vec::new();
+            loop {
+                let len = socket.read_u32().await.unwrap() as usize;
+                if len == 0 {
+                    break;
+                }
+                let mut chunk = vec![0u8; len];
+                socket.read_exact(&mut chunk).await.unwrap();
+                content.extend_from_slice(&chunk);
+            }
+            let reply: &[u8] = if content.windows(EICAR.len()).any(|w| w == EICAR) {
+                b"stream: Eicar-Signature FOUND\0"
+            } else {
+                b"stream: OK\0"
+            };
+            socket.write_all(reply).await.unwrap();
+        });
+        address
+    }
+
+    async fn scan_with_stand_in(content: &'static [u8]) -> ScanVerdict {
+        let address = clamd_stand_in().await;
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        let content = futures_util::stream::iter(vec![Ok::<_, std::io::Error>(content)]);
+        let reply = instream(&mut stream, content).await.unwrap();
+        parse_reply(&reply).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_instream_return_clean_given_harmless_content() {
+        assert_eq!(scan_with_stand_in(b"hello world").await, ScanVerdict::Clean);
+    }
+
+    #[tokio::test]
+    async fn test_instream_return_infected_given_eicar() {
+        assert_eq!(
+            scan_with_stand_in(EICAR).await,
+            ScanVerdict::Infected("Eicar-Signature".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_reply() {
+        assert_eq!(
+            parse_reply("stream: Eicar-Signature FOUND\0").unwrap(),
+            ScanVerdict::Infected("Eicar-Signature".to_string())
+        );
+        // content clamd could not get through is neither clean nor infected
+        assert!(parse_reply("stream: INSTREAM size limit exceeded. ERROR\0").is_err());
+        assert!(parse_reply("UNKNOWN COMMAND").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scan_return_skipped_given_no_clamd_address() {
+        let scanner = FileScannerImpl::builder()
+            .config(ConfigRef::default())
+            .object_storage(setup_object_storage().await)
+            .build();
+        assert_eq!(scanner.scan("any.txt").await.unwrap(), ScanVerdict::Skipped);
+    }
+}
@@ -0,0 +1,104 @@
+use std::net::IpAddr;
+
+use bon::Builder;
+use domain::system::port::geo_ip_resolver::{GeoIpResolver, GeoLocation};
+use kvdb_kit::{Kvdb, KvdbTrait as _};
+use nject::injectable;
+
+use crate::shared::config::ConfigRef;
+
+const KEY_PREFIX: &str = "geo_ip:";
+
+#[derive(Debug, Clone, Builder)]
+#[injectable]
+pub struct GeoIpResolverImpl {
+    config: ConfigRef,
+    kvdb: Kvdb,
+}
+
+impl GeoIpResolverImpl {
+    fn full_key(ip: &str) -> String {
+        format!("{KEY_PREFIX}{ip}")
+    }
+
+    async fn lookup(&self, key: &str, ip: &str) -> Option<GeoLocation> {
+        let lookup = tencent_location_kit::ip_location(key, ip);
+        let Ok(response) = tokio::time::timeout(self.config.geo_ip.timeout, lookup).await else {
+            tracing::warn!(ip, "GeoIP lookup timed out");
+            return None;
+        };
+        let response = response?;
+        Some(GeoLocation {
+            country: Self::non_empty(response.ad_info.nation),
+            region: Self::non_empty(response.ad_info.province),
+            city: Self::non_empty(response.ad_info.city),
+            latitude: Some(response.location.lat),
+            longitude: Some(response.location.lng),
+        })
+    }
+
+    fn is_public(ip: IpAddr) -> bool {
+        match ip {
+            IpAddr::V4(ip) => {
+                !(ip.is_private()
+                    || ip.is_loopback()
+                    || ip.is_link_local()
+                    || ip.is_unspecified()
+                    || ip.is_broadcast())
+            }
+            IpAddr::V6(ip) => {
+                let first_segment = ip.segments()[0];
+                let is_unique_local = (first_segment & 0xfe00) == 0xfc00;
+                let is_unicast_link_local = (first_segment & 0xffc0) == 0xfe80;
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || is_unique_local
+                    || is_unicast_link_local)
+            }
+        }
+    }
+
+    fn non_empty(value: String) -> Option<String> {
+        (!value.is_empty()).then_some(value)
+    }
+}
+
+impl GeoIpResolver for GeoIpResolverImpl {
+    #[tracing::instrument]
+    async fn resolve(&self, ip: &str) -> Option<GeoLocation> {
+        let key = self.config.geo_ip.tencent_location_key.as_deref()?;
+        let addr = ip.parse::<IpAddr>().ok()?;
+        if !Self::is_public(addr) {
+            return None;
+        }
+        let full_key = Self::full_key(ip);
+        if let Some(location) = self.kvdb.get(&full_key).await {
+            return Some(location);
+        }
+        let location = self.lookup(key, ip).await?;
+        let _ = self
+            .kvdb
+            .set_with_ex(&full_key, location.clone(), self.config.geo_ip.cache_ttl)
+            .await;
+        Some(location)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_public() {
+        assert!(GeoIpResolverImpl::is_public("8.8.8.8".parse().unwrap()));
+        assert!(GeoIpResolverImpl::is_public(
+            "2001:4860:4860::8888".parse().unwrap()
+        ));
+        assert!(!GeoIpResolverImpl::is_public("127.0.0.1".parse().unwrap()));
+        assert!(!GeoIpResolverImpl::is_public(
+            "192.168.1.1".parse().unwrap()
+        ));
+        assert!(!GeoIpResolverImpl::is_public("::1".parse().unwrap()));
+        assert!(!GeoIpResolverImpl::is_public("fd00::1".parse().unwrap()));
+    }
+}
@@ -73,7 +73,7 @@ impl MenuResolverImpl {
     #[single_flight]
     pub async fn find_from_db(&self, id: UserId) -> Result<MenuGroup, SystemError> {
         let user_record = sqlx::query!(
-            r#"SELECT privileged, role_ids as "role_ids: Vec<RoleId>" from _users WHERE id = $1"#,
+            r#"SELECT privileged, role_ids as "role_ids: Vec<RoleId>" from _users WHERE id = $1 AND deleted_at IS NULL"#,
             &id
         )
         .fetch_one(&self.pool)
@@ -86,7 +86,7 @@ impl MenuResolverImpl {
         let role_records = sqlx::query_as!(
             RoleRecord,
             r#"
-            SELECT privileged, menus as "menus: Vec<Menu>" from _roles WHERE id = ANY($1) AND enabled = true
+            SELECT privileged, menus as "menus: Vec<Menu>" from _roles WHERE id = ANY($1) AND enabled = true AND deleted_at IS NULL
             "#,
             &user_record.role_ids.inner_vec()
         )
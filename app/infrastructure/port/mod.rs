@@ -1,4 +1,6 @@
 pub mod captcha_issuer_impl;
+pub mod file_scanner_impl;
+pub mod geo_ip_resolver_impl;
 pub mod menu_resolver_impl;
 pub mod permission_resolver_impl;
 pub mod sched_receiver_impl;
@@ -73,7 +73,7 @@ impl PermissionResolverImpl {
     #[single_flight]
     pub async fn find_from_db(&self, id: UserId) -> Result<PermissionGroup, SystemError> {
         let user_record = sqlx::query!(
-            r#"SELECT privileged, role_ids as "role_ids: Vec<RoleId>" from _users WHERE id = $1"#,
+            r#"SELECT privileged, role_ids as "role_ids: Vec<RoleId>" from _users WHERE id = $1 AND deleted_at IS NULL"#,
             &id
         )
         .fetch_one(&self.pool)
@@ -86,7 +86,7 @@ impl PermissionResolverImpl {
 
         let mut permissions = HashSet::new();
         let role_records = sqlx::query_as!(RoleRecord,r#"
-            SELECT privileged, permissions as "permissions: Vec<Permission>" from _roles WHERE id = ANY($1) AND enabled = true
+            SELECT privileged, permissions as "permissions: Vec<Permission>" from _roles WHERE id = ANY($1) AND enabled = true AND deleted_at IS NULL
             "#,
             &user_record.role_ids.inner_vec()
         ).fetch_all(&self.pool).await?;
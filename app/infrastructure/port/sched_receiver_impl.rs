@@ -1,19 +1,61 @@
+use std::{collections::HashMap, time::Duration};
+
+use bon::Builder;
 use domain::{
+    organization::value_object::user_id::UserId,
     shared::port::domain_repository::DomainRepository as _,
-    system::{entity::sched::Sched, value_object::sched_id::SchedId},
+    system::{
+        entity::{sched::Sched, sched_job::SchedJob},
+        port::sched_job_repository::SchedJobRepository as _,
+        value_object::sched_id::SchedId,
+    },
 };
 use nject::injectable;
+use sched_kit::{JobSetting, error::SchedError};
+
+use crate::{
+    repository::system::{
+        sched_job_repository::SchedJobRepositoryImpl, sched_repository::SchedRepositoryImpl,
+    },
+    shared::{chrono_tz::ChronoTz, pg_pool::PgPool},
+};
 
-use crate::repository::system::sched_repository::SchedRepositoryImpl;
+/// The one row of `_sched_leader`
+pub const SCHED_LEASE: &str = "sched";
 
-#[derive(Clone)]
+#[derive(Clone, Builder)]
 #[injectable]
 pub struct SchedReceiverImpl {
+    pool: PgPool,
+    ct: ChronoTz,
     repository: SchedRepositoryImpl,
+    sched_job_repository: SchedJobRepositoryImpl,
 }
 
 impl sched_kit::ScheduledJobReceiver for SchedReceiverImpl {
+    /// A scheduled run fills in the record of the fire time it claimed, a run
+    /// made by hand is recorded anew
     async fn receive(&self, params: sched_kit::JobCallbackParams) {
+        if let Some(fire_at) = params.fire_at {
+            let finished = sqlx::query!(
+                r#"
+                UPDATE _scheds SET succeed = $3, result = $4, duration_ms = $5, updated_at = $6
+                WHERE key = $1 AND fire_at = $2
+                "#,
+                params.key,
+                fire_at,
+                params.succeed,
+                params.result,
+                params.duration_ms,
+                self.ct.now()
+            )
+            .execute(&self.pool)
+            .await;
+            if let Err(err) = finished {
+                tracing::error!(error = %err, "Failed to save sched");
+            }
+            return;
+        }
         let sched = Sched::builder()
             .id(SchedId::generate())
             .key(params.key)
@@ -23,9 +65,310 @@ impl sched_kit::ScheduledJobReceiver for SchedReceiverImpl {
             .result(params.result)
             .run_at(params.run_at)
             .duration_ms(params.duration_ms)
+            .manual(params.triggered_by.is_some())
+            .maybe_triggered_by(params.triggered_by.map(UserId::new_unchecked))
             .build();
         if let Err(err) = self.repository.save(sched).await {
             tracing::error!(error = %err, "Failed to save sched");
         }
     }
+
+    async fn settings(&self) -> sched_kit::error::Result<HashMap<String, JobSetting>> {
+        let items = self
+            .sched_job_repository
+            .all()
+            .await
+            .map_err(|e| SchedError::Custom(e.to_string()))?;
+        let settings = items
+            .iter()
+            .map(|item| (item.key.clone(), job_setting(item)))
+            .collect();
+        Ok(settings)
+    }
+
+    /// Expiry is judged by the database clock so instances with drifting
+    /// clocks agree on it, the token goes up every time the holder changes
+    async fn acquire_lease(
+        &self,
+        holder: &str,
+        ttl: Duration,
+    ) -> sched_kit::error::Result<Option<i64>> {
+        let token = sqlx::query_scalar!(
+            r#"
+            INSERT INTO _sched_leader AS l (name, holder, token, acquired_at, renewed_at, expires_at)
+            VALUES ($1, $2, 1, now(), now(), now() + $3::bigint * INTERVAL '1 millisecond')
+            ON CONFLICT (name) DO UPDATE SET
+                holder = EXCLUDED.holder,
+                token = CASE WHEN l.holder = EXCLUDED.holder THEN l.token ELSE l.token + 1 END,
+                acquired_at = CASE WHEN l.holder = EXCLUDED.holder THEN l.acquired_at ELSE EXCLUDED.acquired_at END,
+                renewed_at = EXCLUDED.renewed_at,
+                expires_at = EXCLUDED.expires_at
+            WHERE l.holder = EXCLUDED.holder OR l.expires_at < now()
+            RETURNING token
+            "#,
+            SCHED_LEASE,
+            holder,
+            ttl.as_millis() as i64
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| SchedError::Custom(e.to_string()))?;
+        Ok(token)
+    }
+
+    /// The record of the run is the claim, it is inserted in the statement
+    /// checking the lease and at most once per job and fire time. A holder
+    /// whose lease expired or was taken over claims nothing, neither does
+    /// one that comes second for a fire time
+    async fn claim(
+        &self,
+        claim: &sched_kit::JobClaim,
+        token: i64,
+    ) -> sched_kit::error::Result<bool> {
+        let id = SchedId::generate();
+        let now = self.ct.now();
+        let claimed = sqlx::query!(
+            r#"
+            INSERT INTO _scheds (id, key, name, expr, run_at, fire_at, manual, created_at, updated_at)
+            SELECT $1, $2, $3, $4, $5, $6, FALSE, $7, $7
+            WHERE EXISTS (SELECT 1 FROM _sched_leader WHERE name = $8 AND token = $9 AND expires_at > now())
+            ON CONFLICT (key, fire_at) DO NOTHING
+            "#,
+            &id,
+            claim.key,
+            claim.name,
+            claim.expr,
+            claim.run_at,
+            claim.fire_at,
+            now,
+            SCHED_LEASE,
+            token
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| SchedError::Custom(e.to_string()))?;
+        Ok(claimed.rows_affected() == 1)
+    }
+
+    /// The last scheduled run, or the last edit of the setting when later so a
+    /// job paused for a while has no runs to catch up on once resumed. The edit
+    /// is stored in the timezone of the app and moved to the one of the job
+    async fn last_run_at(
+        &self,
+        key: &str,
+    ) -> sched_kit::error::Result<Option<chrono::NaiveDateTime>> {
+        let last_run_at = sqlx::query_scalar!(
+            r#"
+            SELECT GREATEST(
+                s.run_at,
+                j.updated_at AT TIME ZONE $2 AT TIME ZONE COALESCE(j.timezone, $2)
+            ) AS "last_run_at!"
+            FROM (SELECT MAX(run_at) AS run_at FROM _scheds WHERE key = $1 AND manual = FALSE) AS s
+            LEFT JOIN _sched_jobs AS j ON j.key = $1
+            WHERE s.run_at IS NOT NULL
+            "#,
+            key,
+            self.ct.tz().name()
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| SchedError::Custom(e.to_string()))?;
+        Ok(last_run_at)
+    }
+
+    async fn release_lease(&self, holder: &str) -> sched_kit::error::Result<()> {
+        sqlx::query!(
+            "UPDATE _sched_leader SET expires_at = now() WHERE name = $1 AND holder = $2",
+            SCHED_LEASE,
+            holder
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| SchedError::Custom(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// What the `CronTab` makes of a stored setting, unreadable params count as none
+pub fn job_setting(item: &SchedJob) -> JobSetting {
+    let params = item
+        .params
+        .as_deref()
+        .and_then(|params| serde_json::from_str(params).ok())
+        .unwrap_or_default();
+    JobSetting {
+        expr: item.expr.clone(),
+        timezone: item.timezone.clone(),
+        enabled: item.enabled,
+        params,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sched_kit::ScheduledJobReceiver as _;
+
+    use crate::test_utils::setup_database;
+
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(30);
+
+    async fn build_sched_receiver(pool: PgPool) -> SchedReceiverImpl {
+        setup_database(pool.clone()).await;
+        let ct = ChronoTz::default();
+        SchedReceiverImpl::builder()
+            .pool(pool.clone())
+            .ct(ct.clone())
+            .repository(
+                SchedRepositoryImpl::builder()
+                    .pool(pool.clone())
+                    .ct(ct.clone())
+                    .build(),
+            )
+            .sched_job_repository(SchedJobRepositoryImpl::builder().pool(pool).ct(ct).build())
+            .build()
+    }
+
+    /// Claims the run of `echo` firing `minutes` after the epoch
+    async fn claim(receiver: &SchedReceiverImpl, token: i64, minutes: i64) -> bool {
+        let fire_at = chrono::DateTime::UNIX_EPOCH + chrono::Duration::minutes(minutes);
+        let claim = sched_kit::JobClaim {
+            key: "echo".to_string(),
+            name: "Echo".to_string(),
+            expr: "every minute".to_string(),
+            fire_at,
+            run_at: fire_at.naive_utc(),
+        };
+        receiver.claim(&claim, token).await.unwrap()
+    }
+
+    async fn expire_lease(pool: &PgPool) {
+        sqlx::query!(
+            "UPDATE _sched_leader SET expires_at = now() - INTERVAL '1 second' WHERE name = $1",
+            SCHED_LEASE
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[sqlx::test]
+    async fn test_acquire_lease_renew_given_same_holder(pool: PgPool) {
+        let receiver = build_sched_receiver(pool).await;
+        let token = receiver.acquire_lease("a", TTL).await.unwrap();
+        assert_eq!(token, Some(1));
+        assert_eq!(receiver.acquire_lease("a", TTL).await.unwrap(), token);
+        assert_eq!(receiver.acquire_lease("b", TTL).await.unwrap(), None);
+        assert!(claim(&receiver, 1, 0).await);
+    }
+
+    #[sqlx::test]
+    async fn test_acquire_lease_take_over_given_expired(pool: PgPool) {
+        let receiver = build_sched_receiver(pool.clone()).await;
+        assert_eq!(receiver.acquire_lease("a", TTL).await.unwrap(), Some(1));
+        expire_lease(&pool).await;
+        assert!(!claim(&receiver, 1, 0).await);
+
+        assert_eq!(receiver.acquire_lease("b", TTL).await.unwrap(), Some(2));
+        assert!(!claim(&receiver, 1, 0).await);
+        assert!(claim(&receiver, 2, 0).await);
+        assert_eq!(receiver.acquire_lease("a", TTL).await.unwrap(), None);
+    }
+
+    #[sqlx::test]
+    async fn test_release_lease_bump_token_of_next_holder(pool: PgPool) {
+        let receiver = build_sched_receiver(pool).await;
+        assert_eq!(receiver.acquire_lease("a", TTL).await.unwrap(), Some(1));
+        receiver.release_lease("b").await.unwrap();
+        assert_eq!(receiver.acquire_lease("b", TTL).await.unwrap(), None);
+
+        receiver.release_lease("a").await.unwrap();
+        assert!(!claim(&receiver, 1, 0).await);
+        assert_eq!(receiver.acquire_lease("b", TTL).await.unwrap(), Some(2));
+        receiver.release_lease("b").await.unwrap();
+        assert_eq!(receiver.acquire_lease("a", TTL).await.unwrap(), Some(3));
+    }
+
+    #[sqlx::test]
+    async fn test_claim_once_given_fire_time_claimed(pool: PgPool) {
+        let receiver = build_sched_receiver(pool.clone()).await;
+        assert_eq!(receiver.acquire_lease("a", TTL).await.unwrap(), Some(1));
+        assert!(claim(&receiver, 1, 0).await);
+        assert!(!claim(&receiver, 1, 0).await);
+
+        expire_lease(&pool).await;
+        assert_eq!(receiver.acquire_lease("b", TTL).await.unwrap(), Some(2));
+        assert!(!claim(&receiver, 2, 0).await);
+        assert!(claim(&receiver, 2, 1).await);
+        assert!(!claim(&receiver, 1, 2).await);
+    }
+
+    #[sqlx::test]
+    async fn test_receive_finish_claimed_run(pool: PgPool) {
+        let receiver = build_sched_receiver(pool.clone()).await;
+        assert_eq!(receiver.acquire_lease("a", TTL).await.unwrap(), Some(1));
+        assert!(claim(&receiver, 1, 0).await);
+        let running = sqlx::query!("SELECT succeed, result, duration_ms FROM _scheds")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(running.succeed, None);
+        assert_eq!(running.result, None);
+
+        let fire_at = chrono::DateTime::UNIX_EPOCH;
+        receiver
+            .receive(sched_kit::JobCallbackParams {
+                key: "echo".to_string(),
+                name: "Echo".to_string(),
+                expr: "every minute".to_string(),
+                succeed: true,
+                result: "Ok(())".to_string(),
+                run_at: fire_at.naive_utc(),
+                duration_ms: 5,
+                triggered_by: None,
+                fire_at: Some(fire_at),
+            })
+            .await;
+        let finished = sqlx::query!("SELECT succeed, result, duration_ms, manual FROM _scheds")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].succeed, Some(true));
+        assert_eq!(finished[0].result.as_deref(), Some("Ok(())"));
+        assert_eq!(finished[0].duration_ms, Some(5));
+        assert!(!finished[0].manual);
+    }
+
+    #[sqlx::test]
+    async fn test_last_run_at_given_setting_edited_later(pool: PgPool) {
+        let receiver = build_sched_receiver(pool.clone()).await;
+        assert_eq!(receiver.last_run_at("echo").await.unwrap(), None);
+
+        // runs are kept in the timezone of the job, edits in the one of the app
+        sqlx::query!(
+            r#"
+            INSERT INTO _scheds (id, key, name, expr, succeed, result, run_at, duration_ms, created_at, updated_at)
+            VALUES ('s1', 'echo', 'Echo', 'at 01:01', TRUE, 'Ok(())', '2026-01-01 01:01:00', 1, now(), now())
+            "#
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query!(
+            r#"
+            INSERT INTO _sched_jobs (key, timezone, enabled, created_at, updated_at)
+            VALUES ('echo', 'UTC', TRUE, '2026-01-02 08:30:00', '2026-01-02 08:30:00')
+            "#
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let last_run_at = receiver.last_run_at("echo").await.unwrap();
+        assert_eq!(
+            last_run_at.map(|d| d.to_string()),
+            Some("2026-01-02 00:30:00".to_string())
+        );
+    }
 }
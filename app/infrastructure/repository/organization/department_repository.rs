@@ -1,7 +1,10 @@
+use bon::Builder;
+use chrono::NaiveDateTime;
 use domain::organization::port::department_repository::DepartmentRepository;
 use domain::organization::value_object::department_id::DepartmentId;
 use domain::organization::{entity::department::Department, error::OrganizationError};
 use domain::shared::port::domain_repository::DomainRepository;
+use domain::shared::port::recyclable_repository::RecyclableRepository;
 use domain::shared::to_inner_vec::ToInnerVec;
 use nject::injectable;
 use sqlx::FromRow;
@@ -10,7 +13,7 @@ use std::result::Result;
 use crate::shared::chrono_tz::ChronoTz;
 use crate::shared::pg_pool::PgPool;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Builder)]
 #[injectable]
 pub struct DepartmentRepositoryImpl {
     pool: PgPool,
@@ -29,7 +32,7 @@ impl DomainRepository for DepartmentRepositoryImpl {
         let row_opt = sqlx::query_as!(
             DepartmentDto,
             r#"
-        SELECT id as "id: DepartmentId", name, code, parent_code FROM _departments WHERE id = $1
+        SELECT id as "id: DepartmentId", name, code, parent_code FROM _departments WHERE id = $1 AND deleted_at IS NULL
         "#,
             id
         )
@@ -71,12 +74,15 @@ impl DomainRepository for DepartmentRepositoryImpl {
         if ids.is_empty() {
             return Ok(Vec::with_capacity(0));
         }
+        let now = self.ct.now();
         let items = sqlx::query_as!(
             DepartmentDto,
             r#"
-            DELETE FROM _departments WHERE id = ANY($1) RETURNING id as "id: DepartmentId", name, code, parent_code
+            UPDATE _departments SET deleted_at = $2 WHERE id = ANY($1) AND deleted_at IS NULL
+            RETURNING id as "id: DepartmentId", name, code, parent_code
             "#,
-            &ids.inner_vec()
+            &ids.inner_vec(),
+            now,
         )
         .fetch_all(&self.pool)
         .await?;
@@ -87,6 +93,65 @@ impl DomainRepository for DepartmentRepositoryImpl {
 
 impl DepartmentRepository for DepartmentRepositoryImpl {}
 
+impl RecyclableRepository for DepartmentRepositoryImpl {
+    #[tracing::instrument]
+    async fn restore(&self, ids: &[Self::EntityId]) -> Result<Vec<Self::Entity>, Self::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::with_capacity(0));
+        }
+        let now = self.ct.now();
+        let items = sqlx::query_as!(
+            DepartmentDto,
+            r#"
+            UPDATE _departments SET deleted_at = NULL, updated_at = $2
+            WHERE id = ANY($1) AND deleted_at IS NOT NULL
+            RETURNING id as "id: DepartmentId", name, code, parent_code
+            "#,
+            &ids.inner_vec(),
+            now,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let items = items.into_iter().map(Into::into).collect();
+        Ok(items)
+    }
+
+    #[tracing::instrument]
+    async fn purge(&self, ids: &[Self::EntityId]) -> Result<Vec<Self::Entity>, Self::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::with_capacity(0));
+        }
+        let items = sqlx::query_as!(
+            DepartmentDto,
+            r#"
+            DELETE FROM _departments WHERE id = ANY($1) AND deleted_at IS NOT NULL
+            RETURNING id as "id: DepartmentId", name, code, parent_code
+            "#,
+            &ids.inner_vec()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let items = items.into_iter().map(Into::into).collect();
+        Ok(items)
+    }
+
+    #[tracing::instrument]
+    async fn deleted_before(
+        &self,
+        before: NaiveDateTime,
+    ) -> Result<Vec<Self::EntityId>, Self::Error> {
+        let ids = sqlx::query_scalar!(
+            r#"
+            SELECT id as "id: DepartmentId" FROM _departments WHERE deleted_at < $1
+            "#,
+            before
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(ids)
+    }
+}
+
 #[derive(FromRow)]
 struct DepartmentDto {
     id: DepartmentId,
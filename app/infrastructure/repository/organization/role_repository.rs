@@ -1,4 +1,5 @@
 use bon::Builder;
+use chrono::NaiveDateTime;
 use domain::auth::value_object::menu::Menu;
 use domain::auth::value_object::permission::Permission;
 use domain::organization::port::role_repository::RoleRepository;
@@ -6,6 +7,7 @@ use domain::organization::value_object::role_id::RoleId;
 use domain::organization::{entity::role::Role, error::OrganizationError};
 use domain::shared::event_util::UpdatedEvent;
 use domain::shared::port::domain_repository::DomainRepository;
+use domain::shared::port::recyclable_repository::RecyclableRepository;
 use domain::shared::to_inner_vec::ToInnerVec;
 use nject::injectable;
 use sqlx::prelude::FromRow;
@@ -14,7 +16,7 @@ use crate::shared::chrono_tz::ChronoTz;
 use crate::shared::error_util::is_unique_constraint_error;
 use crate::shared::pg_pool::PgPool;
 
-#[derive(Debug, Builder)]
+#[derive(Debug, Clone, Builder)]
 #[injectable]
 pub struct RoleRepositoryImpl {
     pool: PgPool,
@@ -34,7 +36,7 @@ impl DomainRepository for RoleRepositoryImpl {
             RoleDto,
             r#"
         SELECT id as "id: RoleId", name, privileged, menus as "menus: Vec<Menu>", permissions as "permissions: Vec<Permission>", enabled
-        FROM _roles WHERE id = $1
+        FROM _roles WHERE id = $1 AND deleted_at IS NULL
         "#,
             id
         )
@@ -85,12 +87,16 @@ impl DomainRepository for RoleRepositoryImpl {
         if ids.is_empty() {
             return Ok(Vec::with_capacity(0));
         }
+        let now = self.ct.now();
         let items = sqlx::query_as!(
             RoleDto,
             r#"
-            DELETE FROM _roles WHERE id = ANY($1) AND privileged != true RETURNING id as "id: RoleId", name, privileged, menus as "menus: Vec<Menu>", permissions as "permissions: Vec<Permission>", enabled
+            UPDATE _roles SET deleted_at = $2
+            WHERE id = ANY($1) AND privileged != true AND deleted_at IS NULL
+            RETURNING id as "id: RoleId", name, privileged, menus as "menus: Vec<Menu>", permissions as "permissions: Vec<Permission>", enabled
             "#,
-            &ids.inner_vec()
+            &ids.inner_vec(),
+            now,
         )
         .fetch_all(&self.pool)
         .await?;
@@ -112,11 +118,11 @@ impl RoleRepository for RoleRepositoryImpl {
         let items = sqlx::query!(
             r#"
             WITH before AS (
-                SELECT * FROM _roles WHERE id = ANY($1) AND privileged != true
+                SELECT * FROM _roles WHERE id = ANY($1) AND privileged != true AND deleted_at IS NULL
             ),
             updated AS (
                 UPDATE _roles SET enabled = $2
-                WHERE id = ANY($1) AND privileged != true
+                WHERE id = ANY($1) AND privileged != true AND deleted_at IS NULL
                 RETURNING *
             )
             SELECT
@@ -155,6 +161,71 @@ impl RoleRepository for RoleRepositoryImpl {
     }
 }
 
+impl RecyclableRepository for RoleRepositoryImpl {
+    #[tracing::instrument]
+    async fn restore(&self, ids: &[Self::EntityId]) -> Result<Vec<Self::Entity>, Self::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::with_capacity(0));
+        }
+        let now = self.ct.now();
+        let items = sqlx::query_as!(
+            RoleDto,
+            r#"
+            UPDATE _roles SET deleted_at = NULL, updated_at = $2
+            WHERE id = ANY($1) AND deleted_at IS NOT NULL
+            RETURNING id as "id: RoleId", name, privileged, menus as "menus: Vec<Menu>", permissions as "permissions: Vec<Permission>", enabled
+            "#,
+            &ids.inner_vec(),
+            now,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            if is_unique_constraint_error(&e, "_roles", "name") {
+                return OrganizationError::RoleDuplicated;
+            }
+            OrganizationError::from(e)
+        })?;
+        let items = items.into_iter().map(Into::into).collect();
+        Ok(items)
+    }
+
+    #[tracing::instrument]
+    async fn purge(&self, ids: &[Self::EntityId]) -> Result<Vec<Self::Entity>, Self::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::with_capacity(0));
+        }
+        let items = sqlx::query_as!(
+            RoleDto,
+            r#"
+            DELETE FROM _roles WHERE id = ANY($1) AND deleted_at IS NOT NULL
+            RETURNING id as "id: RoleId", name, privileged, menus as "menus: Vec<Menu>", permissions as "permissions: Vec<Permission>", enabled
+            "#,
+            &ids.inner_vec()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let items = items.into_iter().map(Into::into).collect();
+        Ok(items)
+    }
+
+    #[tracing::instrument]
+    async fn deleted_before(
+        &self,
+        before: NaiveDateTime,
+    ) -> Result<Vec<Self::EntityId>, Self::Error> {
+        let ids = sqlx::query_scalar!(
+            r#"
+            SELECT id as "id: RoleId" FROM _roles WHERE deleted_at < $1
+            "#,
+            before
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(ids)
+    }
+}
+
 #[derive(FromRow)]
 struct RoleDto {
     id: RoleId,
@@ -278,10 +349,11 @@ mod tests {
         assert_eq!(rows.len(), 3); // because the have a privileged role
         let ids = rows.into_iter().map(|row| row.id).collect::<Vec<_>>();
         assert!(role_repository.batch_delete(&ids).await.is_ok());
-        let rows: Vec<RoleRow> = sqlx::query_as(r#"SELECT id from _roles"#)
-            .fetch_all(&pool)
-            .await
-            .unwrap();
+        let rows: Vec<RoleRow> =
+            sqlx::query_as(r#"SELECT id from _roles WHERE deleted_at IS NULL"#)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
         assert_eq!(rows.len(), 1); // because privileged role cannot be deleted
     }
 
@@ -310,4 +382,35 @@ mod tests {
             Some(OrganizationError::RoleDuplicated)
         );
     }
+
+    #[sqlx::test]
+    async fn test_restore_given_name_taken(pool: PgPool) {
+        let role_repository = build_role_repository(pool.clone()).await;
+        let role = |id: RoleId| {
+            Role::builder()
+                .id(id)
+                .name("test".to_string())
+                .privileged(false)
+                .menus(vec![])
+                .permissions(vec![])
+                .enabled(true)
+                .build()
+        };
+        let id = RoleId::generate();
+        assert!(role_repository.save(role(id.clone())).await.is_ok());
+        assert_eq!(
+            role_repository
+                .batch_delete(&[id.clone()])
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(role_repository.save(role(RoleId::generate())).await.is_ok());
+        assert_eq!(
+            role_repository.restore(&[id.clone()]).await.err(),
+            Some(OrganizationError::RoleDuplicated)
+        );
+        assert_eq!(role_repository.purge(&[id]).await.unwrap().len(), 1);
+    }
 }
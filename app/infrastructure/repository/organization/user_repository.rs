@@ -10,7 +10,9 @@ use domain::{
         port::user_repository::UserRepository,
         value_object::{hashed_password::HashedPassword, user_id::UserId},
     },
-    shared::port::domain_repository::DomainRepository,
+    shared::port::{
+        domain_repository::DomainRepository, recyclable_repository::RecyclableRepository,
+    },
 };
 use nject::injectable;
 use sqlx::prelude::FromRow;
@@ -19,7 +21,7 @@ use crate::shared::chrono_tz::ChronoTz;
 use crate::shared::error_util::is_unique_constraint_error;
 use crate::shared::pg_pool::PgPool;
 
-#[derive(Debug, Builder)]
+#[derive(Debug, Clone, Builder)]
 #[injectable]
 pub struct UserRepositoryImpl {
     pool: PgPool,
@@ -39,7 +41,7 @@ impl DomainRepository for UserRepositoryImpl {
             UserDto,
             r#"
         SELECT id as "id: UserId", account, portrait, name, privileged, password as "password: HashedPassword", role_ids as "role_ids: Vec<RoleId>", enabled, refresh_token, refresh_token_expired_at
-        FROM _users WHERE id = $1
+        FROM _users WHERE id = $1 AND deleted_at IS NULL
         "#,
             id
         )
@@ -98,12 +100,16 @@ impl DomainRepository for UserRepositoryImpl {
             return Ok(Vec::with_capacity(0));
         }
 
+        let now = self.ct.now();
         let items = sqlx::query_as!(
             UserDto,
             r#"
-            DELETE FROM _users WHERE id = ANY($1) AND privileged != true RETURNING id as "id: UserId", account, portrait, name, privileged, password as "password: HashedPassword", role_ids as "role_ids: Vec<RoleId>", enabled, refresh_token, refresh_token_expired_at
+            UPDATE _users SET deleted_at = $2
+            WHERE id = ANY($1) AND privileged != true AND deleted_at IS NULL
+            RETURNING id as "id: UserId", account, portrait, name, privileged, password as "password: HashedPassword", role_ids as "role_ids: Vec<RoleId>", enabled, refresh_token, refresh_token_expired_at
             "#,
-            &ids.inner_vec()
+            &ids.inner_vec(),
+            now,
         )
         .fetch_all(&self.pool)
         .await?;
@@ -119,7 +125,7 @@ impl UserRepository for UserRepositoryImpl {
             UserDto,
             r#"
         SELECT id as "id: UserId", account, portrait, name, privileged, password as "password: HashedPassword", role_ids as "role_ids: Vec<RoleId>", enabled, refresh_token, refresh_token_expired_at
-        FROM _users WHERE account = $1
+        FROM _users WHERE account = $1 AND deleted_at IS NULL
         "#,
             account
         )
@@ -136,7 +142,7 @@ impl UserRepository for UserRepositoryImpl {
             UserDto,
             r#"
         SELECT id as "id: UserId", account, portrait, name, privileged, password as "password: HashedPassword", role_ids as "role_ids: Vec<RoleId>", enabled, refresh_token, refresh_token_expired_at
-        FROM _users WHERE refresh_token = $1
+        FROM _users WHERE refresh_token = $1 AND deleted_at IS NULL
         "#,
             refresh_token
         )
@@ -159,11 +165,11 @@ impl UserRepository for UserRepositoryImpl {
         let items = sqlx::query!(
             r#"
             WITH before AS (
-                SELECT * FROM _users WHERE id = ANY($1) AND privileged != true
+                SELECT * FROM _users WHERE id = ANY($1) AND privileged != true AND deleted_at IS NULL
             ),
             updated AS (
                 UPDATE _users SET enabled = $2
-                WHERE id = ANY($1) AND privileged != true
+                WHERE id = ANY($1) AND privileged != true AND deleted_at IS NULL
                 RETURNING *
             )
             SELECT
@@ -210,6 +216,71 @@ impl UserRepository for UserRepositoryImpl {
     }
 }
 
+impl RecyclableRepository for UserRepositoryImpl {
+    #[tracing::instrument]
+    async fn restore(&self, ids: &[Self::EntityId]) -> Result<Vec<Self::Entity>, Self::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::with_capacity(0));
+        }
+        let now = self.ct.now();
+        let items = sqlx::query_as!(
+            UserDto,
+            r#"
+            UPDATE _users SET deleted_at = NULL, updated_at = $2
+            WHERE id = ANY($1) AND deleted_at IS NOT NULL
+            RETURNING id as "id: UserId", account, portrait, name, privileged, password as "password: HashedPassword", role_ids as "role_ids: Vec<RoleId>", enabled, refresh_token, refresh_token_expired_at
+            "#,
+            &ids.inner_vec(),
+            now,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            if is_unique_constraint_error(&e, "_users", "account") {
+                return OrganizationError::UserDuplicated;
+            }
+            OrganizationError::from(e)
+        })?;
+        let items = items.into_iter().map(Into::into).collect();
+        Ok(items)
+    }
+
+    #[tracing::instrument]
+    async fn purge(&self, ids: &[Self::EntityId]) -> Result<Vec<Self::Entity>, Self::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::with_capacity(0));
+        }
+        let items = sqlx::query_as!(
+            UserDto,
+            r#"
+            DELETE FROM _users WHERE id = ANY($1) AND deleted_at IS NOT NULL
+            RETURNING id as "id: UserId", account, portrait, name, privileged, password as "password: HashedPassword", role_ids as "role_ids: Vec<RoleId>", enabled, refresh_token, refresh_token_expired_at
+            "#,
+            &ids.inner_vec()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let items = items.into_iter().map(Into::into).collect();
+        Ok(items)
+    }
+
+    #[tracing::instrument]
+    async fn deleted_before(
+        &self,
+        before: NaiveDateTime,
+    ) -> Result<Vec<Self::EntityId>, Self::Error> {
+        let ids = sqlx::query_scalar!(
+            r#"
+            SELECT id as "id: UserId" FROM _users WHERE deleted_at < $1
+            "#,
+            before
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(ids)
+    }
+}
+
 #[derive(FromRow)]
 struct UserDto {
     id: UserId,
@@ -244,7 +315,7 @@ impl From<UserDto> for User {
 #[cfg(test)]
 mod tests {
 
-    use crate::test_utils::setup_database;
+    use crate::{shared::chrono_tz::Duration, test_utils::setup_database};
 
     use super::*;
 
@@ -371,10 +442,11 @@ mod tests {
         assert_eq!(rows.len(), 3); // because the have a privileged user
         let ids = rows.into_iter().map(|row| row.id).collect::<Vec<_>>();
         assert!(user_repository.batch_delete(&ids).await.is_ok());
-        let rows: Vec<UserRow> = sqlx::query_as(r#"SELECT id from _users"#)
-            .fetch_all(&pool)
-            .await
-            .unwrap();
+        let rows: Vec<UserRow> =
+            sqlx::query_as(r#"SELECT id from _users WHERE deleted_at IS NULL"#)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
         assert_eq!(rows.len(), 1); // because privileged user cannot be deleted
     }
 
@@ -405,4 +477,74 @@ mod tests {
             Some(OrganizationError::UserDuplicated)
         );
     }
+
+    #[sqlx::test]
+    async fn test_restore_and_purge(pool: PgPool) {
+        let user_repository = build_user_repository(pool.clone()).await;
+        let user = |id: UserId| {
+            User::builder()
+                .id(id)
+                .account("test".to_string())
+                .name("test".to_string())
+                .privileged(false)
+                .password(HashedPassword::try_new("123456".to_string()).unwrap())
+                .role_ids(vec![])
+                .enabled(true)
+                .build()
+        };
+        let id = UserId::generate();
+        assert!(user_repository.save(user(id.clone())).await.is_ok());
+        assert_eq!(
+            user_repository
+                .batch_delete(&[id.clone()])
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            user_repository.by_id(&id).await.err(),
+            Some(OrganizationError::UserNotFound)
+        );
+        let deleted = user_repository
+            .deleted_before(ChronoTz::default().now() + Duration::seconds(1))
+            .await
+            .unwrap();
+        assert_eq!(deleted, vec![id.clone()]);
+
+        // the account was taken again while the user sat in the recycle bin
+        let other = UserId::generate();
+        assert!(user_repository.save(user(other.clone())).await.is_ok());
+        assert_eq!(
+            user_repository.restore(&[id.clone()]).await.err(),
+            Some(OrganizationError::UserDuplicated)
+        );
+        assert_eq!(
+            user_repository.batch_delete(&[other]).await.unwrap().len(),
+            1
+        );
+        assert_eq!(
+            user_repository.restore(&[id.clone()]).await.unwrap().len(),
+            1
+        );
+        assert!(user_repository.by_id(&id).await.is_ok());
+
+        // only deleted users can be purged
+        assert!(
+            user_repository
+                .purge(&[id.clone()])
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            user_repository
+                .batch_delete(&[id.clone()])
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(user_repository.purge(&[id]).await.unwrap().len(), 1);
+    }
 }
@@ -29,7 +29,8 @@ impl DomainRepository for AccessLogRepositoryImpl {
         let row_opt = sqlx::query_as!(
             AccessLogDto,
             r#"
-        SELECT id as "id: AccessLogId", user_id, method, uri, user_agent, ip, status, elapsed, occurred_at FROM _access_logs WHERE id = $1
+        SELECT a.id as "id: AccessLogId", a.user_id, a.method, a.uri, a.user_agent, a.ip, a.status, a.elapsed, a.occurred_at, b.request_body as "request_body?", b.response_body as "response_body?"
+        FROM _access_logs a LEFT JOIN _access_log_bodies b ON b.access_log_id = a.id WHERE a.id = $1
         "#,
             id
         )
@@ -44,11 +45,12 @@ impl DomainRepository for AccessLogRepositoryImpl {
     async fn save(&self, entity: Self::Entity) -> Result<Self::Entity, Self::Error> {
         let now = self.ct.now();
 
+        let mut tx = self.pool.begin().await?;
         sqlx::query!(
             r#"
             INSERT INTO _access_logs (id, user_id, method, uri, user_agent, ip, status, elapsed, occurred_at, created_at, updated_at)
             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-            ON CONFLICT (id) DO UPDATE SET
+            ON CONFLICT (id, occurred_at) DO UPDATE SET
                 user_id = EXCLUDED.user_id,
                 method = EXCLUDED.method,
                 uri = EXCLUDED.uri,
@@ -56,7 +58,6 @@ impl DomainRepository for AccessLogRepositoryImpl {
                 ip = EXCLUDED.ip,
                 status = EXCLUDED.status,
                 elapsed = EXCLUDED.elapsed,
-                occurred_at = EXCLUDED.occurred_at,
                 updated_at = EXCLUDED.updated_at
             "#,
             &entity.id,
@@ -71,8 +72,30 @@ impl DomainRepository for AccessLogRepositoryImpl {
             &now,
             &now,
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
+        if entity.request_body.is_some() || entity.response_body.is_some() {
+            sqlx::query!(
+                r#"
+                INSERT INTO _access_log_bodies (access_log_id, request_body, response_body, occurred_at, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (access_log_id) DO UPDATE SET
+                    request_body = EXCLUDED.request_body,
+                    response_body = EXCLUDED.response_body,
+                    occurred_at = EXCLUDED.occurred_at,
+                    updated_at = EXCLUDED.updated_at
+                "#,
+                &entity.id,
+                entity.request_body,
+                entity.response_body,
+                &entity.occurred_at,
+                &now,
+                &now,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
         Ok(entity)
     }
 
@@ -84,7 +107,13 @@ impl DomainRepository for AccessLogRepositoryImpl {
         let items = sqlx::query_as!(
             AccessLogDto,
             r#"
-            DELETE FROM _access_logs WHERE id = ANY($1) RETURNING id as "id: AccessLogId", user_id, method, uri, user_agent, ip, status, elapsed, occurred_at
+            WITH bodies AS (
+                DELETE FROM _access_log_bodies WHERE access_log_id = ANY($1) RETURNING access_log_id, request_body, response_body
+            ), logs AS (
+                DELETE FROM _access_logs WHERE id = ANY($1) RETURNING id, user_id, method, uri, user_agent, ip, status, elapsed, occurred_at
+            )
+            SELECT logs.id as "id!: AccessLogId", logs.user_id as "user_id!", logs.method as "method!", logs.uri as "uri!", logs.user_agent, logs.ip, logs.status as "status!", logs.elapsed as "elapsed!", logs.occurred_at as "occurred_at!", bodies.request_body as "request_body?", bodies.response_body as "response_body?"
+            FROM logs LEFT JOIN bodies ON bodies.access_log_id = logs.id
             "#,
             &ids.inner_vec()
         )
@@ -108,6 +137,8 @@ struct AccessLogDto {
     status: i16,
     elapsed: i64,
     occurred_at: chrono::NaiveDateTime,
+    request_body: Option<String>,
+    response_body: Option<String>,
 }
 
 impl From<AccessLogDto> for AccessLog {
@@ -122,6 +153,8 @@ impl From<AccessLogDto> for AccessLog {
             .status(value.status)
             .elapsed(value.elapsed)
             .occurred_at(value.occurred_at)
+            .maybe_request_body(value.request_body)
+            .maybe_response_body(value.response_body)
             .build()
     }
 }
@@ -0,0 +1,239 @@
+use bon::Builder;
+use domain::shared::port::domain_repository::DomainRepository;
+use domain::shared::to_inner_vec::ToInnerVec;
+use domain::system::port::file_folder_repository::FileFolderRepository;
+use domain::system::value_object::file_folder_id::FileFolderId;
+use domain::system::{entity::file_folder::FileFolder, error::SystemError};
+use nject::injectable;
+use sqlx::FromRow;
+use std::result::Result;
+
+use crate::shared::chrono_tz::ChronoTz;
+use crate::shared::pg_pool::PgPool;
+
+#[derive(Debug, Clone, Builder)]
+#[injectable]
+pub struct FileFolderRepositoryImpl {
+    pool: PgPool,
+    ct: ChronoTz,
+}
+
+impl DomainRepository for FileFolderRepositoryImpl {
+    type Entity = FileFolder;
+
+    type EntityId = FileFolderId;
+
+    type Error = SystemError;
+
+    #[tracing::instrument]
+    async fn by_id(&self, id: &Self::EntityId) -> Result<Self::Entity, Self::Error> {
+        let row_opt = sqlx::query_as!(
+            FileFolderDto,
+            r#"
+        SELECT id as "id: FileFolderId", name, parent_id as "parent_id: FileFolderId" FROM _file_folders WHERE id = $1
+        "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        row_opt
+            .map(Into::into)
+            .ok_or(SystemError::FileFolderNotFound)
+    }
+
+    #[tracing::instrument]
+    async fn save(&self, entity: Self::Entity) -> Result<Self::Entity, Self::Error> {
+        let now = self.ct.now();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO _file_folders (id, name, parent_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (id) DO UPDATE SET
+                name = EXCLUDED.name,
+                parent_id = EXCLUDED.parent_id,
+                updated_at = EXCLUDED.updated_at
+            "#,
+            &entity.id,
+            &entity.name,
+            entity.parent_id.as_deref(),
+            &now,
+            &now,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|err| match err.as_database_error() {
+            Some(db_err) if db_err.is_unique_violation() => SystemError::FileFolderNameConflict,
+            _ => err.into(),
+        })?;
+        Ok(entity)
+    }
+
+    /// Deletes the folders along with their subfolders, the files inside move back to the root
+    #[tracing::instrument]
+    async fn batch_delete(&self, ids: &[Self::EntityId]) -> Result<Vec<Self::Entity>, Self::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::with_capacity(0));
+        }
+        let now = self.ct.now();
+        let mut tx = self.pool.begin().await?;
+        let items = sqlx::query_as!(
+            FileFolderDto,
+            r#"
+            WITH RECURSIVE subtree AS (
+                SELECT id FROM _file_folders WHERE id = ANY($1)
+                UNION
+                SELECT f.id FROM _file_folders AS f JOIN subtree AS s ON f.parent_id = s.id
+            )
+            DELETE FROM _file_folders WHERE id IN (SELECT id FROM subtree)
+            RETURNING id as "id: FileFolderId", name, parent_id as "parent_id: FileFolderId"
+            "#,
+            &ids.inner_vec()
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let deleted_ids: Vec<String> = items.iter().map(|item| item.id.to_string()).collect();
+        sqlx::query!(
+            r#"
+            UPDATE _files SET folder_id = NULL, updated_at = $2 WHERE folder_id = ANY($1)
+            "#,
+            &deleted_ids,
+            now,
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        let items = items.into_iter().map(Into::into).collect();
+        Ok(items)
+    }
+}
+
+impl FileFolderRepository for FileFolderRepositoryImpl {
+    #[tracing::instrument]
+    async fn subtree_ids(&self, id: &FileFolderId) -> Result<Vec<FileFolderId>, Self::Error> {
+        let ids = sqlx::query_scalar!(
+            r#"
+            WITH RECURSIVE subtree AS (
+                SELECT id FROM _file_folders WHERE id = $1
+                UNION
+                SELECT f.id FROM _file_folders AS f JOIN subtree AS s ON f.parent_id = s.id
+            )
+            SELECT id AS "id!: FileFolderId" FROM subtree
+            "#,
+            id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(ids)
+    }
+}
+
+#[derive(FromRow)]
+struct FileFolderDto {
+    id: FileFolderId,
+    name: String,
+    parent_id: Option<FileFolderId>,
+}
+
+impl From<FileFolderDto> for FileFolder {
+    fn from(value: FileFolderDto) -> Self {
+        Self::builder()
+            .id(value.id)
+            .name(value.name)
+            .maybe_parent_id(value.parent_id)
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::setup_database;
+
+    use super::*;
+
+    async fn build_file_folder_repository(pool: PgPool) -> FileFolderRepositoryImpl {
+        setup_database(pool.clone()).await;
+        FileFolderRepositoryImpl::builder()
+            .pool(pool)
+            .ct(ChronoTz::default())
+            .build()
+    }
+
+    fn folder(name: &str, parent_id: Option<&FileFolderId>) -> FileFolder {
+        FileFolder::builder()
+            .id(FileFolderId::generate())
+            .name(name.to_string())
+            .maybe_parent_id(parent_id.cloned())
+            .build()
+    }
+
+    #[sqlx::test]
+    async fn test_save_return_err_given_sibling_with_same_name(pool: PgPool) {
+        let repository = build_file_folder_repository(pool).await;
+        let docs = repository.save(folder("docs", None)).await.unwrap();
+        assert_eq!(
+            repository.save(folder("docs", None)).await.unwrap_err(),
+            SystemError::FileFolderNameConflict
+        );
+        // the same name is fine below another folder
+        let nested = repository
+            .save(folder("docs", Some(&docs.id)))
+            .await
+            .unwrap();
+        let nested = repository.by_id(&nested.id).await.unwrap();
+        assert_eq!(nested.name, "docs");
+        assert_eq!(nested.parent_id, Some(docs.id.clone()));
+        assert_eq!(
+            repository
+                .by_id(&FileFolderId::generate())
+                .await
+                .unwrap_err(),
+            SystemError::FileFolderNotFound
+        );
+    }
+
+    #[sqlx::test]
+    async fn test_batch_delete_remove_subtree_and_move_files_to_root(pool: PgPool) {
+        let repository = build_file_folder_repository(pool.clone()).await;
+        let root = repository.save(folder("root", None)).await.unwrap();
+        let child = repository
+            .save(folder("child", Some(&root.id)))
+            .await
+            .unwrap();
+        let grandchild = repository
+            .save(folder("grandchild", Some(&child.id)))
+            .await
+            .unwrap();
+        let other = repository.save(folder("other", None)).await.unwrap();
+        let mut subtree = repository.subtree_ids(&child.id).await.unwrap();
+        subtree.sort_by_key(|id| id.to_string());
+        let mut expected = vec![child.id.clone(), grandchild.id.clone()];
+        expected.sort_by_key(|id| id.to_string());
+        assert_eq!(subtree, expected);
+
+        let now = ChronoTz::default().now();
+        sqlx::query!(
+            r#"
+            INSERT INTO _files (id, name, size, path, folder_id, created_at, updated_at)
+            VALUES ('f1', 'a.txt', 1, 'test/a.txt', $1, $3, $3),
+                ('f2', 'b.txt', 1, 'test/b.txt', $2, $3, $3)
+            "#,
+            &*grandchild.id,
+            &*other.id,
+            now,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let deleted = repository.batch_delete(&[root.id.clone()]).await.unwrap();
+        assert_eq!(deleted.len(), 3);
+        assert!(repository.by_id(&grandchild.id).await.is_err());
+        assert!(repository.by_id(&other.id).await.is_ok());
+        let folder_ids = sqlx::query_scalar!("SELECT folder_id FROM _files ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(folder_ids, vec![None, Some(other.id.to_string())]);
+    }
+}
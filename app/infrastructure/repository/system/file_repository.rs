@@ -1,16 +1,21 @@
+use bon::Builder;
+use chrono::NaiveDateTime;
+use domain::shared::event_util::UpdatedEvent;
 use domain::shared::port::domain_repository::DomainRepository;
+use domain::shared::port::recyclable_repository::RecyclableRepository;
 use domain::shared::to_inner_vec::ToInnerVec;
 use domain::system::port::file_repository::FileRepository;
-use domain::system::value_object::file_id::FileId;
+use domain::system::value_object::{file_folder_id::FileFolderId, file_id::FileId};
 use domain::system::{entity::file::File, error::SystemError};
 use nject::injectable;
-use sqlx::FromRow;
+use sqlx::{FromRow, Postgres, Transaction};
+use std::collections::HashMap;
 use std::result::Result;
 
 use crate::shared::chrono_tz::ChronoTz;
 use crate::shared::pg_pool::PgPool;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Builder)]
 #[injectable]
 pub struct FileRepositoryImpl {
     pool: PgPool,
@@ -29,7 +34,8 @@ impl DomainRepository for FileRepositoryImpl {
         let row_opt = sqlx::query_as!(
             FileDto,
             r#"
-        SELECT id as "id: FileId", name, path, size, used FROM _files WHERE id = $1
+        SELECT id as "id: FileId", name, path, size, hash, uploader_id, folder_id as "folder_id: FileFolderId", tags, description, mime_type, width, height
+        FROM _files WHERE id = $1 AND deleted_at IS NULL
         "#,
             id
         )
@@ -44,20 +50,35 @@ impl DomainRepository for FileRepositoryImpl {
 
         sqlx::query!(
             r#"
-            INSERT INTO _files (id, name, path, size, used, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            INSERT INTO _files (id, name, path, size, hash, uploader_id, folder_id, tags, description,
+                mime_type, width, height, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
             ON CONFLICT (id) DO UPDATE SET
                 name = EXCLUDED.name,
                 path = EXCLUDED.path,
                 size = EXCLUDED.size,
-                used = EXCLUDED.used,
+                hash = EXCLUDED.hash,
+                uploader_id = EXCLUDED.uploader_id,
+                folder_id = EXCLUDED.folder_id,
+                tags = EXCLUDED.tags,
+                description = EXCLUDED.description,
+                mime_type = EXCLUDED.mime_type,
+                width = EXCLUDED.width,
+                height = EXCLUDED.height,
                 updated_at = EXCLUDED.updated_at
             "#,
             &entity.id,
             &entity.name,
             &entity.path,
             &entity.size,
-            &entity.used,
+            entity.hash.as_deref(),
+            entity.uploader_id.as_deref(),
+            entity.folder_id.as_deref(),
+            &entity.tags,
+            entity.description,
+            entity.mime_type,
+            entity.width,
+            entity.height,
             &now,
             &now,
         )
@@ -68,13 +89,126 @@ impl DomainRepository for FileRepositoryImpl {
 
     #[tracing::instrument]
     async fn batch_delete(&self, ids: &[Self::EntityId]) -> Result<Vec<Self::Entity>, Self::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::with_capacity(0));
+        }
+        let now = self.ct.now();
+        let items = sqlx::query_as!(
+            FileDto,
+            r#"
+            UPDATE _files SET deleted_at = $2 WHERE id = ANY($1) AND deleted_at IS NULL
+            RETURNING id as "id: FileId", name, path, size, hash, uploader_id, folder_id as "folder_id: FileFolderId", tags, description, mime_type, width, height
+            "#,
+            &ids.inner_vec(),
+            now,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let items = items.into_iter().map(Into::into).collect();
+        Ok(items)
+    }
+}
+
+impl FileRepository for FileRepositoryImpl {
+    #[tracing::instrument]
+    async fn move_to_folder(
+        &self,
+        ids: &[FileId],
+        folder_id: Option<&FileFolderId>,
+    ) -> Result<Vec<UpdatedEvent<Self::Entity>>, Self::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::with_capacity(0));
+        }
+        let now = self.ct.now();
+        let mut tx = self.pool.begin().await?;
+        let before = Self::lock(&mut tx, ids).await?;
+        let after = sqlx::query_as!(
+            FileDto,
+            r#"
+            UPDATE _files SET folder_id = $2, updated_at = $3 WHERE id = ANY($1) AND deleted_at IS NULL
+            RETURNING id as "id: FileId", name, path, size, hash, uploader_id, folder_id as "folder_id: FileFolderId", tags, description, mime_type, width, height
+            "#,
+            &ids.inner_vec(),
+            folder_id.map(|id| &**id),
+            now,
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(Self::pair(before, after))
+    }
+
+    #[tracing::instrument]
+    async fn update_tags(
+        &self,
+        ids: &[FileId],
+        add: &[String],
+        remove: &[String],
+    ) -> Result<Vec<UpdatedEvent<Self::Entity>>, Self::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::with_capacity(0));
+        }
+        let now = self.ct.now();
+        let mut tx = self.pool.begin().await?;
+        let before = Self::lock(&mut tx, ids).await?;
+        let after = sqlx::query_as!(
+            FileDto,
+            r#"
+            UPDATE _files SET
+                tags = ARRAY(
+                    SELECT DISTINCT tag FROM UNNEST(ARRAY_CAT(tags, $2::text[])) AS tag
+                    WHERE NOT (tag = ANY($3)) ORDER BY tag
+                ),
+                updated_at = $4
+            WHERE id = ANY($1) AND deleted_at IS NULL
+            RETURNING id as "id: FileId", name, path, size, hash, uploader_id, folder_id as "folder_id: FileFolderId", tags, description, mime_type, width, height
+            "#,
+            &ids.inner_vec(),
+            add,
+            remove,
+            now,
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(Self::pair(before, after))
+    }
+}
+
+impl RecyclableRepository for FileRepositoryImpl {
+    #[tracing::instrument]
+    async fn restore(&self, ids: &[Self::EntityId]) -> Result<Vec<Self::Entity>, Self::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::with_capacity(0));
+        }
+        let now = self.ct.now();
+        let items = sqlx::query_as!(
+            FileDto,
+            r#"
+            UPDATE _files SET deleted_at = NULL, updated_at = $2
+            WHERE id = ANY($1) AND deleted_at IS NOT NULL
+            RETURNING id as "id: FileId", name, path, size, hash, uploader_id, folder_id as "folder_id: FileFolderId", tags, description, mime_type, width, height
+            "#,
+            &ids.inner_vec(),
+            now,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let items = items.into_iter().map(Into::into).collect();
+        Ok(items)
+    }
+
+    /// Only drops the rows, the stored objects are released by the caller
+    #[tracing::instrument]
+    async fn purge(&self, ids: &[Self::EntityId]) -> Result<Vec<Self::Entity>, Self::Error> {
         if ids.is_empty() {
             return Ok(Vec::with_capacity(0));
         }
         let items = sqlx::query_as!(
             FileDto,
             r#"
-            DELETE FROM _files WHERE id = ANY($1) RETURNING id as "id: FileId", name, path, size, used
+            DELETE FROM _files WHERE id = ANY($1) AND deleted_at IS NOT NULL
+            RETURNING id as "id: FileId", name, path, size, hash, uploader_id, folder_id as "folder_id: FileFolderId", tags, description, mime_type, width, height
             "#,
             &ids.inner_vec()
         )
@@ -83,9 +217,59 @@ impl DomainRepository for FileRepositoryImpl {
         let items = items.into_iter().map(Into::into).collect();
         Ok(items)
     }
+
+    #[tracing::instrument]
+    async fn deleted_before(
+        &self,
+        before: NaiveDateTime,
+    ) -> Result<Vec<Self::EntityId>, Self::Error> {
+        let ids = sqlx::query_scalar!(
+            r#"
+            SELECT id as "id: FileId" FROM _files WHERE deleted_at < $1
+            "#,
+            before
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(ids)
+    }
 }
 
-impl FileRepository for FileRepositoryImpl {}
+impl FileRepositoryImpl {
+    async fn lock(
+        tx: &mut Transaction<'_, Postgres>,
+        ids: &[FileId],
+    ) -> Result<Vec<FileDto>, SystemError> {
+        let items = sqlx::query_as!(
+            FileDto,
+            r#"
+            SELECT id as "id: FileId", name, path, size, hash, uploader_id, folder_id as "folder_id: FileFolderId", tags, description, mime_type, width, height
+            FROM _files WHERE id = ANY($1) AND deleted_at IS NULL FOR UPDATE
+            "#,
+            &ids.inner_vec()
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+        Ok(items)
+    }
+
+    fn pair(before: Vec<FileDto>, after: Vec<FileDto>) -> Vec<UpdatedEvent<File>> {
+        let mut before: HashMap<FileId, File> = before
+            .into_iter()
+            .map(|item| (item.id.clone(), item.into()))
+            .collect();
+        after
+            .into_iter()
+            .filter_map(|item| {
+                let before = before.remove(&item.id)?;
+                Some(UpdatedEvent {
+                    before,
+                    after: item.into(),
+                })
+            })
+            .collect()
+    }
+}
 
 #[derive(FromRow)]
 struct FileDto {
@@ -93,7 +277,14 @@ struct FileDto {
     name: String,
     path: String,
     size: i64,
-    used: bool,
+    hash: Option<String>,
+    uploader_id: Option<String>,
+    folder_id: Option<FileFolderId>,
+    tags: Vec<String>,
+    description: Option<String>,
+    mime_type: Option<String>,
+    width: Option<i32>,
+    height: Option<i32>,
 }
 
 impl From<FileDto> for File {
@@ -103,7 +294,14 @@ impl From<FileDto> for File {
             .name(value.name)
             .path(value.path)
             .size(value.size)
-            .used(value.used)
+            .maybe_hash(value.hash)
+            .maybe_uploader_id(value.uploader_id)
+            .maybe_folder_id(value.folder_id)
+            .tags(value.tags)
+            .maybe_description(value.description)
+            .maybe_mime_type(value.mime_type)
+            .maybe_width(value.width)
+            .maybe_height(value.height)
             .build()
     }
 }
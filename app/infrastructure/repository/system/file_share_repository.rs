@@ -0,0 +1,218 @@
+use domain::organization::value_object::{hashed_password::HashedPassword, user_id::UserId};
+use domain::shared::event_util::UpdatedEvent;
+use domain::shared::port::domain_repository::DomainRepository;
+use domain::shared::to_inner_vec::ToInnerVec;
+use domain::system::port::file_share_repository::FileShareRepository;
+use domain::system::value_object::file_id::FileId;
+use domain::system::value_object::file_share_id::FileShareId;
+use domain::system::{entity::file_share::FileShare, error::SystemError};
+use nject::injectable;
+use sqlx::FromRow;
+use std::collections::HashMap;
+use std::result::Result;
+
+use crate::shared::chrono_tz::ChronoTz;
+use crate::shared::pg_pool::PgPool;
+
+#[derive(Debug, Clone)]
+#[injectable]
+pub struct FileShareRepositoryImpl {
+    pool: PgPool,
+    ct: ChronoTz,
+}
+
+impl DomainRepository for FileShareRepositoryImpl {
+    type Entity = FileShare;
+
+    type EntityId = FileShareId;
+
+    type Error = SystemError;
+
+    #[tracing::instrument]
+    async fn by_id(&self, id: &Self::EntityId) -> Result<Self::Entity, Self::Error> {
+        let row_opt = sqlx::query_as!(
+            FileShareDto,
+            r#"
+        SELECT id as "id: FileShareId", code, file_id as "file_id: FileId", password as "password: HashedPassword", expires_at, max_downloads, download_count, revoked, created_by as "created_by: UserId"
+        FROM _file_shares WHERE id = $1
+        "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        row_opt
+            .map(Into::into)
+            .ok_or(SystemError::FileShareNotFound)
+    }
+
+    /// The download counter is left alone, only `count_download` moves it
+    #[tracing::instrument]
+    async fn save(&self, entity: Self::Entity) -> Result<Self::Entity, Self::Error> {
+        let now = self.ct.now();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO _file_shares (id, code, file_id, password, expires_at, max_downloads, download_count, revoked, created_by, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (id) DO UPDATE SET
+                password = EXCLUDED.password,
+                expires_at = EXCLUDED.expires_at,
+                max_downloads = EXCLUDED.max_downloads,
+                revoked = EXCLUDED.revoked,
+                updated_at = EXCLUDED.updated_at
+            "#,
+            &entity.id,
+            &entity.code,
+            &entity.file_id,
+            entity.password.as_deref(),
+            entity.expires_at,
+            entity.max_downloads,
+            entity.download_count,
+            entity.revoked,
+            entity.created_by.as_deref(),
+            &now,
+            &now,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(entity)
+    }
+
+    #[tracing::instrument]
+    async fn batch_delete(&self, ids: &[Self::EntityId]) -> Result<Vec<Self::Entity>, Self::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::with_capacity(0));
+        }
+        let items = sqlx::query_as!(
+            FileShareDto,
+            r#"
+            DELETE FROM _file_shares WHERE id = ANY($1)
+            RETURNING id as "id: FileShareId", code, file_id as "file_id: FileId", password as "password: HashedPassword", expires_at, max_downloads, download_count, revoked, created_by as "created_by: UserId"
+            "#,
+            &ids.inner_vec()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let items = items.into_iter().map(Into::into).collect();
+        Ok(items)
+    }
+}
+
+impl FileShareRepository for FileShareRepositoryImpl {
+    #[tracing::instrument]
+    async fn by_code(&self, code: &str) -> Result<FileShare, Self::Error> {
+        let row_opt = sqlx::query_as!(
+            FileShareDto,
+            r#"
+        SELECT id as "id: FileShareId", code, file_id as "file_id: FileId", password as "password: HashedPassword", expires_at, max_downloads, download_count, revoked, created_by as "created_by: UserId"
+        FROM _file_shares WHERE code = $1
+        "#,
+            code
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        row_opt
+            .map(Into::into)
+            .ok_or(SystemError::FileShareNotFound)
+    }
+
+    /// The limits are checked again in the update, two downloads racing for the
+    /// last slot cannot both pass
+    #[tracing::instrument]
+    async fn count_download(&self, share: &FileShare) -> Result<bool, Self::Error> {
+        let now = self.ct.now();
+        let result = sqlx::query!(
+            r#"
+            UPDATE _file_shares SET download_count = download_count + 1, last_downloaded_at = $2, updated_at = $2
+            WHERE id = $1
+            AND revoked = false
+            AND (expires_at IS NULL OR expires_at > $2)
+            AND (max_downloads IS NULL OR download_count < max_downloads)
+            "#,
+            &share.id,
+            now,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Shares already revoked are skipped
+    #[tracing::instrument]
+    async fn revoke(
+        &self,
+        ids: &[FileShareId],
+    ) -> Result<Vec<UpdatedEvent<Self::Entity>>, Self::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::with_capacity(0));
+        }
+        let now = self.ct.now();
+        let mut tx = self.pool.begin().await?;
+        let before = sqlx::query_as!(
+            FileShareDto,
+            r#"
+            SELECT id as "id: FileShareId", code, file_id as "file_id: FileId", password as "password: HashedPassword", expires_at, max_downloads, download_count, revoked, created_by as "created_by: UserId"
+            FROM _file_shares WHERE id = ANY($1) AND revoked = false FOR UPDATE
+            "#,
+            &ids.inner_vec()
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let after = sqlx::query_as!(
+            FileShareDto,
+            r#"
+            UPDATE _file_shares SET revoked = true, updated_at = $2 WHERE id = ANY($1) AND revoked = false
+            RETURNING id as "id: FileShareId", code, file_id as "file_id: FileId", password as "password: HashedPassword", expires_at, max_downloads, download_count, revoked, created_by as "created_by: UserId"
+            "#,
+            &ids.inner_vec(),
+            now,
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        let mut before: HashMap<FileShareId, FileShare> = before
+            .into_iter()
+            .map(|item| (item.id.clone(), item.into()))
+            .collect();
+        let items = after
+            .into_iter()
+            .filter_map(|item| {
+                let before = before.remove(&item.id)?;
+                Some(UpdatedEvent {
+                    before,
+                    after: item.into(),
+                })
+            })
+            .collect();
+        Ok(items)
+    }
+}
+
+#[derive(FromRow)]
+struct FileShareDto {
+    id: FileShareId,
+    code: String,
+    file_id: FileId,
+    password: Option<HashedPassword>,
+    expires_at: Option<chrono::NaiveDateTime>,
+    max_downloads: Option<i32>,
+    download_count: i32,
+    revoked: bool,
+    created_by: Option<UserId>,
+}
+
+impl From<FileShareDto> for FileShare {
+    fn from(value: FileShareDto) -> Self {
+        Self::builder()
+            .id(value.id)
+            .code(value.code)
+            .file_id(value.file_id)
+            .maybe_password(value.password)
+            .maybe_expires_at(value.expires_at)
+            .maybe_max_downloads(value.max_downloads)
+            .download_count(value.download_count)
+            .revoked(value.revoked)
+            .maybe_created_by(value.created_by)
+            .build()
+    }
+}
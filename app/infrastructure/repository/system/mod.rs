@@ -1,3 +1,7 @@
 pub mod access_log_repository;
+pub mod file_folder_repository;
 pub mod file_repository;
+pub mod file_share_repository;
+pub mod sched_job_repository;
 pub mod sched_repository;
+pub mod security_event_repository;
@@ -0,0 +1,96 @@
+use bon::Builder;
+use domain::system::port::sched_job_repository::SchedJobRepository;
+use domain::system::{entity::sched_job::SchedJob, error::SystemError};
+use nject::injectable;
+use std::result::Result;
+
+use crate::shared::chrono_tz::ChronoTz;
+use crate::shared::pg_pool::PgPool;
+
+#[derive(Debug, Clone, Builder)]
+#[injectable]
+pub struct SchedJobRepositoryImpl {
+    pool: PgPool,
+    ct: ChronoTz,
+}
+
+impl SchedJobRepository for SchedJobRepositoryImpl {
+    type Error = SystemError;
+
+    #[tracing::instrument]
+    async fn all(&self) -> Result<Vec<SchedJob>, Self::Error> {
+        let items = sqlx::query_as!(
+            SchedJobDto,
+            r#"
+            SELECT key, expr, timezone, enabled, params::text AS "params?" FROM _sched_jobs
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(items.into_iter().map(Into::into).collect())
+    }
+
+    #[tracing::instrument]
+    async fn by_key(&self, key: &str) -> Result<SchedJob, Self::Error> {
+        let row_opt = sqlx::query_as!(
+            SchedJobDto,
+            r#"
+            SELECT key, expr, timezone, enabled, params::text AS "params?" FROM _sched_jobs WHERE key = $1
+            "#,
+            key
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row_opt
+            .map(Into::into)
+            .unwrap_or_else(|| SchedJob::defaults(key)))
+    }
+
+    #[tracing::instrument]
+    async fn save(&self, entity: SchedJob) -> Result<SchedJob, Self::Error> {
+        let now = self.ct.now();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO _sched_jobs (key, expr, timezone, enabled, params, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5::text::jsonb, $6, $7)
+            ON CONFLICT (key) DO UPDATE SET
+                expr = EXCLUDED.expr,
+                timezone = EXCLUDED.timezone,
+                enabled = EXCLUDED.enabled,
+                params = EXCLUDED.params,
+                updated_at = EXCLUDED.updated_at
+            "#,
+            entity.key,
+            entity.expr,
+            entity.timezone,
+            entity.enabled,
+            entity.params,
+            now,
+            now
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(entity)
+    }
+}
+
+struct SchedJobDto {
+    key: String,
+    expr: Option<String>,
+    timezone: Option<String>,
+    enabled: bool,
+    params: Option<String>,
+}
+
+impl From<SchedJobDto> for SchedJob {
+    fn from(value: SchedJobDto) -> Self {
+        Self::builder()
+            .key(value.key)
+            .maybe_expr(value.expr)
+            .maybe_timezone(value.timezone)
+            .enabled(value.enabled)
+            .maybe_params(value.params)
+            .build()
+    }
+}
@@ -1,3 +1,5 @@
+use bon::Builder;
+use domain::organization::value_object::user_id::UserId;
 use domain::shared::port::domain_repository::DomainRepository;
 use domain::shared::to_inner_vec::ToInnerVec;
 use domain::system::port::sched_repository::SchedRepository;
@@ -10,7 +12,7 @@ use std::result::Result;
 use crate::shared::chrono_tz::ChronoTz;
 use crate::shared::pg_pool::PgPool;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Builder)]
 #[injectable]
 pub struct SchedRepositoryImpl {
     pool: PgPool,
@@ -29,7 +31,7 @@ impl DomainRepository for SchedRepositoryImpl {
         let row_opt = sqlx::query_as!(
             SchedDto,
             r#"
-        SELECT id as "id: SchedId", key, name, expr, succeed, result, run_at, duration_ms FROM _scheds WHERE id = $1
+        SELECT id as "id: SchedId", key, name, expr, succeed, result, run_at, duration_ms, manual, triggered_by as "triggered_by: UserId" FROM _scheds WHERE id = $1
         "#,
             id
         )
@@ -44,8 +46,8 @@ impl DomainRepository for SchedRepositoryImpl {
 
         sqlx::query!(
             r#"
-            INSERT INTO _scheds (id, key, name, expr, succeed, result, run_at, duration_ms, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            INSERT INTO _scheds (id, key, name, expr, succeed, result, run_at, duration_ms, manual, triggered_by, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
             ON CONFLICT (id) DO UPDATE SET
                 key = EXCLUDED.key,
                 name = EXCLUDED.name,
@@ -54,16 +56,20 @@ impl DomainRepository for SchedRepositoryImpl {
                 result = EXCLUDED.result,
                 run_at = EXCLUDED.run_at,
                 duration_ms = EXCLUDED.duration_ms,
+                manual = EXCLUDED.manual,
+                triggered_by = EXCLUDED.triggered_by,
                 updated_at = EXCLUDED.updated_at
             "#,
             &entity.id,
             &entity.key,
             &entity.name,
             &entity.expr,
-            &entity.succeed,
-            &entity.result,
+            entity.succeed,
+            entity.result.as_deref(),
             &entity.run_at,
-            &entity.duration_ms,
+            entity.duration_ms,
+            &entity.manual,
+            entity.triggered_by.as_deref(),
             &now,
             &now,
         )
@@ -80,7 +86,7 @@ impl DomainRepository for SchedRepositoryImpl {
         let items = sqlx::query_as!(
             SchedDto,
             r#"
-            DELETE FROM _scheds WHERE id = ANY($1) RETURNING id as "id: SchedId", key, name, expr, succeed, result, run_at, duration_ms
+            DELETE FROM _scheds WHERE id = ANY($1) RETURNING id as "id: SchedId", key, name, expr, succeed, result, run_at, duration_ms, manual, triggered_by as "triggered_by: UserId"
             "#,
             &ids.inner_vec()
         )
@@ -99,10 +105,12 @@ struct SchedDto {
     key: String,
     name: String,
     expr: String,
-    succeed: bool,
-    result: String,
+    succeed: Option<bool>,
+    result: Option<String>,
     run_at: chrono::NaiveDateTime,
-    duration_ms: i64,
+    duration_ms: Option<i64>,
+    manual: bool,
+    triggered_by: Option<UserId>,
 }
 
 impl From<SchedDto> for Sched {
@@ -112,10 +120,12 @@ impl From<SchedDto> for Sched {
             .key(value.key)
             .name(value.name)
             .expr(value.expr)
-            .succeed(value.succeed)
-            .result(value.result)
+            .maybe_succeed(value.succeed)
+            .maybe_result(value.result)
             .run_at(value.run_at)
-            .duration_ms(value.duration_ms)
+            .maybe_duration_ms(value.duration_ms)
+            .manual(value.manual)
+            .maybe_triggered_by(value.triggered_by)
             .build()
     }
 }
@@ -0,0 +1,185 @@
+use domain::organization::value_object::user_id::UserId;
+use domain::shared::port::domain_repository::DomainRepository;
+use domain::shared::to_inner_vec::ToInnerVec;
+use domain::system::port::security_event_repository::SecurityEventRepository;
+use domain::system::value_object::security_event_id::SecurityEventId;
+use domain::system::value_object::security_event_kind::SecurityEventKind;
+use domain::system::{entity::security_event::SecurityEvent, error::SystemError};
+use nject::injectable;
+use sqlx::FromRow;
+use std::result::Result;
+
+use crate::shared::chrono_tz::ChronoTz;
+use crate::shared::pg_pool::PgPool;
+
+#[derive(Debug, Clone)]
+#[injectable]
+pub struct SecurityEventRepositoryImpl {
+    pool: PgPool,
+    ct: ChronoTz,
+}
+
+impl DomainRepository for SecurityEventRepositoryImpl {
+    type Entity = SecurityEvent;
+
+    type EntityId = SecurityEventId;
+
+    type Error = SystemError;
+
+    #[tracing::instrument]
+    async fn by_id(&self, id: &Self::EntityId) -> Result<Self::Entity, Self::Error> {
+        let row_opt = sqlx::query_as!(
+            SecurityEventDto,
+            r#"
+        SELECT id as "id: SecurityEventId", user_id as "user_id: UserId", account, kind as "kind: SecurityEventKind", succeed, reason, ip, user_agent, country, region, city, latitude, longitude, new_device, new_country, impossible_travel, occurred_at FROM _security_events WHERE id = $1
+        "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        row_opt
+            .map(Into::into)
+            .ok_or(SystemError::SecurityEventNotFound)
+    }
+
+    #[tracing::instrument]
+    async fn save(&self, entity: Self::Entity) -> Result<Self::Entity, Self::Error> {
+        let now = self.ct.now();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO _security_events (id, user_id, account, kind, succeed, reason, ip, user_agent, country, region, city, latitude, longitude, new_device, new_country, impossible_travel, occurred_at, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)
+            ON CONFLICT (id) DO UPDATE SET
+                user_id = EXCLUDED.user_id,
+                account = EXCLUDED.account,
+                kind = EXCLUDED.kind,
+                succeed = EXCLUDED.succeed,
+                reason = EXCLUDED.reason,
+                ip = EXCLUDED.ip,
+                user_agent = EXCLUDED.user_agent,
+                country = EXCLUDED.country,
+                region = EXCLUDED.region,
+                city = EXCLUDED.city,
+                latitude = EXCLUDED.latitude,
+                longitude = EXCLUDED.longitude,
+                new_device = EXCLUDED.new_device,
+                new_country = EXCLUDED.new_country,
+                impossible_travel = EXCLUDED.impossible_travel,
+                occurred_at = EXCLUDED.occurred_at,
+                updated_at = EXCLUDED.updated_at
+            "#,
+            &entity.id,
+            entity.user_id.as_deref(),
+            entity.account,
+            &entity.kind as &SecurityEventKind,
+            &entity.succeed,
+            entity.reason,
+            entity.ip,
+            entity.user_agent,
+            entity.country,
+            entity.region,
+            entity.city,
+            entity.latitude,
+            entity.longitude,
+            &entity.new_device,
+            &entity.new_country,
+            &entity.impossible_travel,
+            &entity.occurred_at,
+            &now,
+            &now,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(entity)
+    }
+
+    #[tracing::instrument]
+    async fn batch_delete(&self, ids: &[Self::EntityId]) -> Result<Vec<Self::Entity>, Self::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::with_capacity(0));
+        }
+        let items = sqlx::query_as!(
+            SecurityEventDto,
+            r#"
+            DELETE FROM _security_events WHERE id = ANY($1) RETURNING id as "id: SecurityEventId", user_id as "user_id: UserId", account, kind as "kind: SecurityEventKind", succeed, reason, ip, user_agent, country, region, city, latitude, longitude, new_device, new_country, impossible_travel, occurred_at
+            "#,
+            &ids.inner_vec()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let items = items.into_iter().map(Into::into).collect();
+        Ok(items)
+    }
+}
+
+impl SecurityEventRepository for SecurityEventRepositoryImpl {
+    #[tracing::instrument]
+    async fn recent_succeeded_logins(
+        &self,
+        user_id: &UserId,
+        limit: i64,
+    ) -> Result<Vec<Self::Entity>, Self::Error> {
+        let items = sqlx::query_as!(
+            SecurityEventDto,
+            r#"
+            SELECT id as "id: SecurityEventId", user_id as "user_id: UserId", account, kind as "kind: SecurityEventKind", succeed, reason, ip, user_agent, country, region, city, latitude, longitude, new_device, new_country, impossible_travel, occurred_at
+            FROM _security_events
+            WHERE user_id = $1 AND kind = $2 AND succeed = true
+            ORDER BY occurred_at DESC
+            LIMIT $3
+            "#,
+            user_id,
+            SecurityEventKind::LoginSucceeded as SecurityEventKind,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(items.into_iter().map(Into::into).collect())
+    }
+}
+
+#[derive(FromRow)]
+struct SecurityEventDto {
+    id: SecurityEventId,
+    user_id: Option<UserId>,
+    account: Option<String>,
+    kind: SecurityEventKind,
+    succeed: bool,
+    reason: Option<String>,
+    ip: Option<String>,
+    user_agent: Option<String>,
+    country: Option<String>,
+    region: Option<String>,
+    city: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    new_device: bool,
+    new_country: bool,
+    impossible_travel: bool,
+    occurred_at: chrono::NaiveDateTime,
+}
+
+impl From<SecurityEventDto> for SecurityEvent {
+    fn from(value: SecurityEventDto) -> Self {
+        Self::builder()
+            .id(value.id)
+            .maybe_user_id(value.user_id)
+            .maybe_account(value.account)
+            .kind(value.kind)
+            .succeed(value.succeed)
+            .maybe_reason(value.reason)
+            .maybe_ip(value.ip)
+            .maybe_user_agent(value.user_agent)
+            .maybe_country(value.country)
+            .maybe_region(value.region)
+            .maybe_city(value.city)
+            .maybe_latitude(value.latitude)
+            .maybe_longitude(value.longitude)
+            .new_device(value.new_device)
+            .new_country(value.new_country)
+            .impossible_travel(value.impossible_travel)
+            .occurred_at(value.occurred_at)
+            .build()
+    }
+}
@@ -13,6 +13,10 @@ impl ChronoTz {
         self.now_utc().with_timezone(&self.tz).naive_local()
     }
 
+    pub fn tz(&self) -> chrono_tz::Tz {
+        self.tz
+    }
+
     #[tracing::instrument]
     pub fn now_utc(&self) -> DateTime<Utc> {
         Utc::now()
@@ -1,5 +1,6 @@
 use bon::Builder;
 use std::fmt::Debug;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -13,8 +14,15 @@ pub struct Config {
     pub database: Database,
     #[cfg(feature = "kv_redis")]
     pub redis: Redis,
+    #[cfg(feature = "bg_redis")]
+    pub bg_worker: BgWorker,
     pub server: Server,
     pub jwt: Jwt,
+    pub access_log: AccessLog,
+    pub geo_ip: GeoIp,
+    pub upload: Upload,
+    pub file_scan: FileScan,
+    pub recycle_bin: RecycleBin,
     #[cfg(feature = "object_storage_fs")]
     pub fs: StorageFs,
     #[cfg(feature = "object_storage_s3")]
@@ -66,6 +74,14 @@ pub struct Redis {
     pub idle_timeout: Duration,
 }
 
+/// The redis holding the background job queues, kept apart from the kv store
+#[cfg(feature = "bg_redis")]
+#[derive(Clone, Builder)]
+#[readonly::make]
+pub struct BgWorker {
+    pub redis_url: String,
+}
+
 #[derive(Clone, Builder)]
 #[readonly::make]
 pub struct Server {
@@ -81,12 +97,148 @@ pub struct Jwt {
     pub refresh_token_period: Duration,
 }
 
+#[derive(Clone, Builder)]
+#[readonly::make]
+pub struct AccessLog {
+    pub capture_body: bool,
+    pub capture_body_max_size: usize,
+    pub capture_routes: Vec<String>,
+    pub capture_statuses: Vec<String>,
+    pub redact_paths: Vec<String>,
+    pub body_retention: Duration,
+    pub retention: Duration,
+}
+
+#[derive(Clone, Builder)]
+#[readonly::make]
+pub struct RecycleBin {
+    /// How long deleted users, roles, departments and files stay restorable
+    pub retention: Duration,
+}
+
+#[derive(Clone, Builder)]
+#[readonly::make]
+pub struct GeoIp {
+    pub tencent_location_key: Option<String>,
+    /// How long a single lookup may take before the location is left empty
+    pub timeout: Duration,
+    /// How long a resolved location is cached per IP
+    pub cache_ttl: Duration,
+}
+
+#[derive(Clone, Builder)]
+#[readonly::make]
+pub struct FileScan {
+    /// Address of a clamd daemon uploads are streamed to, scanning is skipped when absent
+    pub clamd_address: Option<String>,
+    pub timeout: Duration,
+}
+
+#[derive(Clone, Builder)]
+#[readonly::make]
+pub struct Upload {
+    pub image_variants: Vec<ImageVariant>,
+    /// Bytes a single user may upload, unlimited when absent
+    pub user_quota: Option<u64>,
+    /// Bytes the storage may hold in total, unlimited when absent
+    pub total_quota: Option<u64>,
+    pub single: UploadRule,
+    pub image: UploadRule,
+    pub chunk: UploadRule,
+    /// Largest part in bytes a chunked upload may send at once
+    pub chunk_part_max_size: u64,
+    pub direct: UploadRule,
+}
+
+/// What an upload endpoint accepts
+#[derive(Clone, Builder)]
+#[readonly::make]
+pub struct UploadRule {
+    pub max_size: u64,
+    /// Allowed MIME types, `image/*` matches a whole top-level type, any type is accepted when empty
+    pub mime_types: Vec<String>,
+}
+
+impl UploadRule {
+    pub fn permits_size(&self, size: u64) -> bool {
+        size <= self.max_size
+    }
+
+    pub fn permits_mime(&self, mime: &str) -> bool {
+        let mime = mime.split(';').next().unwrap_or_default().trim();
+        self.mime_types.is_empty()
+            || self
+                .mime_types
+                .iter()
+                .any(|allowed| match allowed.strip_suffix("/*") {
+                    Some(top_level) => mime
+                        .split_once('/')
+                        .is_some_and(|(t, _)| t.eq_ignore_ascii_case(top_level)),
+                    None => allowed.eq_ignore_ascii_case(mime),
+                })
+    }
+}
+
+/// A resized copy generated for every uploaded image, e.g. `thumb:200x200:cover`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageVariant {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub fit: ImageFit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFit {
+    /// Fills the box exactly, cropping what does not fit
+    Cover,
+    /// Fits inside the box, keeping the aspect ratio
+    Contain,
+}
+
+impl FromStr for ImageVariant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            || format!("Invalid image variant `{s}`, expected name:WIDTHxHEIGHT[:cover|contain]");
+        let mut parts = s.trim().split(':');
+        let name = parts
+            .next()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(invalid)?;
+        let (width, height) = parts
+            .next()
+            .and_then(|size| size.split_once('x'))
+            .ok_or_else(invalid)?;
+        let width = width.parse().ok().filter(|w| *w > 0).ok_or_else(invalid)?;
+        let height = height.parse().ok().filter(|h| *h > 0).ok_or_else(invalid)?;
+        let fit = match parts.next() {
+            None | Some("contain") => ImageFit::Contain,
+            Some("cover") => ImageFit::Cover,
+            Some(_) => return Err(invalid()),
+        };
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            name: name.to_string(),
+            width,
+            height,
+            fit,
+        })
+    }
+}
+
 #[cfg(feature = "object_storage_fs")]
 #[derive(Clone, Builder)]
 #[readonly::make]
 pub struct StorageFs {
     pub hmac_secret: &'static [u8],
     pub link_period: Duration,
+    /// Hex encoded master keys, the first one encrypts new objects, plaintext at rest when empty
+    #[builder(default)]
+    pub encryption_keys: Vec<String>,
 }
 
 #[cfg(feature = "object_storage_s3")]
@@ -141,6 +293,59 @@ impl Default for Config {
                     .refresh_token_period(Duration::from_secs(60))
                     .build(),
             )
+            .access_log(
+                AccessLog::builder()
+                    .capture_body(false)
+                    .capture_body_max_size(4096)
+                    .capture_routes(Vec::new())
+                    .capture_statuses(Vec::new())
+                    .redact_paths(Vec::new())
+                    .body_retention(Duration::from_secs(60))
+                    .retention(Duration::from_secs(60))
+                    .build(),
+            )
+            .geo_ip(
+                GeoIp::builder()
+                    .timeout(Duration::from_secs(3))
+                    .cache_ttl(Duration::from_secs(7 * 24 * 60 * 60))
+                    .build(),
+            )
+            .upload(
+                Upload::builder()
+                    .image_variants(vec![ImageVariant::from_str("thumb:200x200:cover").unwrap()])
+                    .single(
+                        UploadRule::builder()
+                            .max_size(2 * 1024 * 1024)
+                            .mime_types(Vec::new())
+                            .build(),
+                    )
+                    .image(
+                        UploadRule::builder()
+                            .max_size(2 * 1024 * 1024)
+                            .mime_types(vec!["image/*".to_string()])
+                            .build(),
+                    )
+                    .chunk(
+                        UploadRule::builder()
+                            .max_size(1024 * 1024 * 1024)
+                            .mime_types(Vec::new())
+                            .build(),
+                    )
+                    .chunk_part_max_size(8 * 1024 * 1024)
+                    .direct(
+                        UploadRule::builder()
+                            .max_size(1024 * 1024 * 1024)
+                            .mime_types(Vec::new())
+                            .build(),
+                    )
+                    .build(),
+            )
+            .file_scan(FileScan::builder().timeout(Duration::from_secs(60)).build())
+            .recycle_bin(
+                RecycleBin::builder()
+                    .retention(Duration::from_secs(60))
+                    .build(),
+            )
             .server(
                 Server::builder()
                     .bind("127.0.0.1".to_string())
@@ -160,6 +365,12 @@ impl Default for Config {
                 .idle_timeout(Duration::from_secs(30))
                 .build(),
         );
+        #[cfg(feature = "bg_redis")]
+        let builder = builder.bg_worker(
+            BgWorker::builder()
+                .redis_url("redis://127.0.0.1:6379/1".to_string())
+                .build(),
+        );
 
         builder.build()
     }
@@ -175,4 +386,46 @@ mod tests {
 
         assert_eq!(format!("{:?}", config), "Config");
     }
+
+    #[test]
+    fn test_image_variant_from_str() {
+        assert_eq!(
+            ImageVariant::from_str("thumb:200x100:cover"),
+            Ok(ImageVariant {
+                name: "thumb".to_string(),
+                width: 200,
+                height: 100,
+                fit: ImageFit::Cover,
+            })
+        );
+        assert_eq!(
+            ImageVariant::from_str("medium:800x800").map(|variant| variant.fit),
+            Ok(ImageFit::Contain)
+        );
+        assert!(ImageVariant::from_str("thumb").is_err());
+        assert!(ImageVariant::from_str("thumb:0x100").is_err());
+        assert!(ImageVariant::from_str("thumb:200x200:stretch").is_err());
+        assert!(ImageVariant::from_str(":200x200").is_err());
+    }
+
+    #[test]
+    fn test_upload_rule_permits_mime() {
+        let rule = UploadRule::builder()
+            .max_size(10)
+            .mime_types(vec!["image/*".to_string(), "application/pdf".to_string()])
+            .build();
+        assert!(rule.permits_mime("image/png"));
+        assert!(rule.permits_mime("Application/PDF"));
+        assert!(rule.permits_mime("application/pdf; charset=binary"));
+        assert!(!rule.permits_mime("application/zip"));
+        assert!(!rule.permits_mime("imagex/png"));
+        assert!(rule.permits_size(10));
+        assert!(!rule.permits_size(11));
+
+        let any = UploadRule::builder()
+            .max_size(10)
+            .mime_types(Vec::new())
+            .build();
+        assert!(any.permits_mime("application/octet-stream"));
+    }
 }
@@ -1,9 +1,12 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use chrono_tz::Tz;
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use humantime::parse_duration;
-use infrastructure::shared::config::{Config, ConfigRef, Database, Jwt, Log, Openapi, Server};
+use infrastructure::shared::config::{
+    AccessLog, Config, ConfigRef, Database, FileScan, GeoIp, ImageVariant, Jwt, Log, Openapi,
+    RecycleBin, Server, Upload, UploadRule,
+};
 
 #[derive(Debug, Parser)]
 #[command(version, about, long_about = None)]
@@ -97,6 +100,11 @@ pub struct Cli {
     #[arg(long, default_value = "5min", env = "REDIS_IDLE_TIMEOUT")]
     pub redis_idle_timeout: String,
 
+    #[cfg(feature = "bg_redis")]
+    /// Redis connection DSN of the background job queues
+    #[arg(long, env = "BG_WORKER_REDIS_URL")]
+    pub bg_worker_redis_url: String,
+
     /// Server bind address
     #[arg(long, default_value = "127.0.0.1", env = "SERVER_BIND")]
     pub server_bind: String,
@@ -117,6 +125,122 @@ pub struct Cli {
     #[arg(long, default_value = "7d", env = "JWT_REFRESH_TOKEN_PERIOD")]
     pub jwt_refresh_token_period: String,
 
+    /// Capture request and response bodies in access logs
+    #[arg(long, action = clap::ArgAction::Set, default_value_t = false, env = "ACCESS_LOG_CAPTURE_BODY")]
+    pub access_log_capture_body: bool,
+
+    /// Maximum stored size of a captured body in bytes, longer bodies are truncated
+    #[arg(long, default_value = "4096", env = "ACCESS_LOG_CAPTURE_BODY_MAX_SIZE")]
+    pub access_log_capture_body_max_size: usize,
+
+    /// Route prefixes whose bodies are captured, e.g. /organization (comma separated, empty for all)
+    #[arg(long, value_delimiter = ',', env = "ACCESS_LOG_CAPTURE_ROUTES")]
+    pub access_log_capture_routes: Vec<String>,
+
+    /// Response statuses whose bodies are captured, e.g. 5xx,401 (comma separated, empty for all)
+    #[arg(long, value_delimiter = ',', env = "ACCESS_LOG_CAPTURE_STATUSES")]
+    pub access_log_capture_statuses: Vec<String>,
+
+    /// Additional JSON paths redacted from captured bodies, e.g. $.items[*].secret (comma separated)
+    #[arg(long, value_delimiter = ',', env = "ACCESS_LOG_REDACT_PATHS")]
+    pub access_log_redact_paths: Vec<String>,
+
+    /// Retention period of captured bodies
+    #[arg(long, default_value = "2d", env = "ACCESS_LOG_BODY_RETENTION")]
+    pub access_log_body_retention: String,
+
+    /// Retention period of access logs, older daily partitions are archived to object storage and dropped
+    #[arg(long, default_value = "7d", env = "ACCESS_LOG_RETENTION")]
+    pub access_log_retention: String,
+
+    /// Tencent location service key used to resolve client IP locations, GeoIP is disabled when absent
+    #[arg(long, env = "GEO_IP_TENCENT_LOCATION_KEY")]
+    pub geo_ip_tencent_location_key: Option<String>,
+
+    /// How long a single GeoIP lookup may take
+    #[arg(long, default_value = "3s", env = "GEO_IP_TIMEOUT")]
+    pub geo_ip_timeout: String,
+
+    /// How long a resolved GeoIP location is cached per IP
+    #[arg(long, default_value = "7d", env = "GEO_IP_CACHE_TTL")]
+    pub geo_ip_cache_ttl: String,
+
+    /// Resized copies generated for uploaded images, as name:WIDTHxHEIGHT[:cover|contain]
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "thumb:200x200:cover,medium:800x800:contain",
+        env = "UPLOAD_IMAGE_VARIANTS"
+    )]
+    pub upload_image_variants: Vec<ImageVariant>,
+
+    /// Bytes a single user may upload, unlimited when absent
+    #[arg(long, env = "UPLOAD_USER_QUOTA")]
+    pub upload_user_quota: Option<u64>,
+
+    /// Bytes the storage may hold in total, unlimited when absent
+    #[arg(long, env = "UPLOAD_TOTAL_QUOTA")]
+    pub upload_total_quota: Option<u64>,
+
+    /// Largest file in bytes accepted by the single file upload
+    #[arg(long, default_value = "2097152", env = "UPLOAD_SINGLE_MAX_SIZE")]
+    pub upload_single_max_size: u64,
+
+    /// MIME types accepted by the single file upload, `type/*` matches a whole type
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "image/*,text/plain,text/csv,application/pdf,application/zip,application/vnd.openxmlformats-officedocument.wordprocessingml.document,application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        env = "UPLOAD_SINGLE_MIME_TYPES"
+    )]
+    pub upload_single_mime_types: Vec<String>,
+
+    /// Largest file in bytes accepted by the image upload
+    #[arg(long, default_value = "2097152", env = "UPLOAD_IMAGE_MAX_SIZE")]
+    pub upload_image_max_size: u64,
+
+    /// MIME types accepted by the image upload
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "image/png,image/jpeg,image/webp",
+        env = "UPLOAD_IMAGE_MIME_TYPES"
+    )]
+    pub upload_image_mime_types: Vec<String>,
+
+    /// Largest file in bytes accepted by the chunked upload
+    #[arg(long, default_value = "1073741824", env = "UPLOAD_CHUNK_MAX_SIZE")]
+    pub upload_chunk_max_size: u64,
+
+    /// MIME types accepted by the chunked upload, any type when empty
+    #[arg(long, value_delimiter = ',', env = "UPLOAD_CHUNK_MIME_TYPES")]
+    pub upload_chunk_mime_types: Vec<String>,
+
+    /// Largest part in bytes a chunked upload may send at once, parts of 8 MiB are
+    /// hashed on their way through and spare reading the file back on finish
+    #[arg(long, default_value = "8388608", env = "UPLOAD_CHUNK_PART_MAX_SIZE")]
+    pub upload_chunk_part_max_size: u64,
+
+    /// Largest file in bytes accepted by the direct upload to storage
+    #[arg(long, default_value = "1073741824", env = "UPLOAD_DIRECT_MAX_SIZE")]
+    pub upload_direct_max_size: u64,
+
+    /// MIME types accepted by the direct upload to storage, any type when empty
+    #[arg(long, value_delimiter = ',', env = "UPLOAD_DIRECT_MIME_TYPES")]
+    pub upload_direct_mime_types: Vec<String>,
+
+    /// Address of a clamd daemon uploads are scanned with, as host:port, scanning is skipped when absent
+    #[arg(long, env = "FILE_SCAN_CLAMD_ADDRESS")]
+    pub file_scan_clamd_address: Option<String>,
+
+    /// How long a single scan may take
+    #[arg(long, default_value = "60s", env = "FILE_SCAN_TIMEOUT")]
+    pub file_scan_timeout: String,
+
+    /// How long deleted users, roles, departments and files stay in the recycle bin before being purged
+    #[arg(long, default_value = "30d", env = "RECYCLE_BIN_RETENTION")]
+    pub recycle_bin_retention: String,
+
     #[cfg(feature = "object_storage_fs")]
     /// File storage link signing secret
     #[arg(long, env = "FS_HMAC_SECRET")]
@@ -127,6 +251,12 @@ pub struct Cli {
     #[arg(long, default_value = "1min", env = "FS_LINK_PERIOD")]
     pub fs_link_period: String,
 
+    #[cfg(feature = "object_storage_fs")]
+    /// Master keys objects are encrypted at rest with, 64 hex characters each (comma separated).
+    /// The first one encrypts, the others only decrypt until `storage rotate-keys` retired them
+    #[arg(long, value_delimiter = ',', env = "FS_ENCRYPTION_KEYS")]
+    pub fs_encryption_keys: Vec<String>,
+
     #[cfg(feature = "object_storage_s3")]
     /// S3 endpoint
     #[arg(long, env = "S3_ENDPOINT")]
@@ -175,6 +305,45 @@ pub enum Commands {
     #[cfg(not(feature = "serve_with_sched"))]
     /// Start scheduled task server
     Sched,
+    /// Object storage maintenance
+    #[command(subcommand)]
+    Storage(StorageCommands),
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum StorageCommands {
+    /// Copy every object from one storage backend to another
+    Migrate(StorageMigrateArgs),
+    #[cfg(feature = "object_storage_fs")]
+    /// Wrap the data key of every encrypted object with the first master key
+    RotateKeys,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct StorageMigrateArgs {
+    /// Source storage as scheme:key=value,..., e.g. fs:root=./data/uploads
+    #[arg(long, env = "STORAGE_MIGRATE_FROM")]
+    pub from: String,
+
+    /// Target storage as scheme:key=value,..., e.g. s3:bucket=files,endpoint=http://localhost:9000,region=auto,access_key_id=..,secret_access_key=..
+    #[arg(long, env = "STORAGE_MIGRATE_TO")]
+    pub to: String,
+
+    /// Prepended to every object key in the target
+    #[arg(long, default_value = "")]
+    pub prefix: String,
+
+    /// Prepend the prefix to the paths stored in the database once every object is copied
+    #[arg(long, default_value_t = false)]
+    pub rewrite_paths: bool,
+
+    /// File recording finished keys so an interrupted run resumes, defaults to the data directory
+    #[arg(long)]
+    pub checkpoint: Option<PathBuf>,
+
+    /// Number of objects copied at the same time
+    #[arg(long, default_value = "4")]
+    pub concurrency: usize,
 }
 
 impl TryFrom<Cli> for ConfigRef {
@@ -208,11 +377,73 @@ impl TryFrom<Cli> for ConfigRef {
                     .access_token_period(parse_duration(&value.jwt_access_token_period)?)
                     .refresh_token_period(parse_duration(&value.jwt_refresh_token_period)?)
                     .build(),
+            )
+            .access_log(
+                AccessLog::builder()
+                    .capture_body(value.access_log_capture_body)
+                    .capture_body_max_size(value.access_log_capture_body_max_size)
+                    .capture_routes(value.access_log_capture_routes)
+                    .capture_statuses(value.access_log_capture_statuses)
+                    .redact_paths(value.access_log_redact_paths)
+                    .body_retention(parse_duration(&value.access_log_body_retention)?)
+                    .retention(parse_duration(&value.access_log_retention)?)
+                    .build(),
+            )
+            .geo_ip(
+                GeoIp::builder()
+                    .maybe_tencent_location_key(value.geo_ip_tencent_location_key)
+                    .timeout(parse_duration(&value.geo_ip_timeout)?)
+                    .cache_ttl(parse_duration(&value.geo_ip_cache_ttl)?)
+                    .build(),
+            )
+            .upload(
+                Upload::builder()
+                    .image_variants(value.upload_image_variants)
+                    .maybe_user_quota(value.upload_user_quota)
+                    .maybe_total_quota(value.upload_total_quota)
+                    .single(
+                        UploadRule::builder()
+                            .max_size(value.upload_single_max_size)
+                            .mime_types(value.upload_single_mime_types)
+                            .build(),
+                    )
+                    .image(
+                        UploadRule::builder()
+                            .max_size(value.upload_image_max_size)
+                            .mime_types(value.upload_image_mime_types)
+                            .build(),
+                    )
+                    .chunk(
+                        UploadRule::builder()
+                            .max_size(value.upload_chunk_max_size)
+                            .mime_types(value.upload_chunk_mime_types)
+                            .build(),
+                    )
+                    .chunk_part_max_size(value.upload_chunk_part_max_size)
+                    .direct(
+                        UploadRule::builder()
+                            .max_size(value.upload_direct_max_size)
+                            .mime_types(value.upload_direct_mime_types)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .file_scan(
+                FileScan::builder()
+                    .maybe_clamd_address(value.file_scan_clamd_address)
+                    .timeout(parse_duration(&value.file_scan_timeout)?)
+                    .build(),
+            )
+            .recycle_bin(
+                RecycleBin::builder()
+                    .retention(parse_duration(&value.recycle_bin_retention)?)
+                    .build(),
             );
         #[cfg(feature = "object_storage_fs")]
         let builder = builder.fs(infrastructure::shared::config::StorageFs::builder()
             .hmac_secret(Box::leak(Box::new(value.fs_hmac_secret)).as_bytes())
             .link_period(parse_duration(&value.fs_link_period)?)
+            .encryption_keys(value.fs_encryption_keys)
             .build());
 
         #[cfg(feature = "object_storage_s3")]
@@ -235,6 +466,12 @@ impl TryFrom<Cli> for ConfigRef {
                 .min_idle(value.redis_min_idle)
                 .build(),
         );
+        #[cfg(feature = "bg_redis")]
+        let builder = builder.bg_worker(
+            infrastructure::shared::config::BgWorker::builder()
+                .redis_url(value.bg_worker_redis_url)
+                .build(),
+        );
         let builder = {
             let log_builder = Log::builder().level(value.log_level);
             #[cfg(feature = "trace_rolling")]
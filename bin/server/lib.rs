@@ -1,11 +1,13 @@
 use adapter::WebState;
-use anyhow::Result;
+use anyhow::{Result, bail};
 use application::{
     re_export::WorkspaceRef,
     shared::{
-        bgworker_impl::register_bgworkers, event_subscriber_impl::register_event_subscribers,
+        bgworker_impl::register_bgworkers, event_subscriber::sched_event_subscriber::SCHED_RELOAD,
+        event_subscriber_impl::register_event_subscribers,
         scheduler_job_impl::register_scheduled_jobs,
     },
+    system::service::file_service::FileService,
 };
 use axum::Router;
 use bg_worker_kit::WorkerManager;
@@ -21,7 +23,10 @@ use infrastructure::{
     },
 };
 use kvdb_kit::{Kvdb, KvdbTrait as _};
-use object_storage_kit::ObjectStorage;
+use object_storage_kit::{
+    ObjectStorage,
+    migrate::{Migrator, operator_from_spec},
+};
 use std::{
     net::{Ipv4Addr, SocketAddr, SocketAddrV4},
     str::FromStr as _,
@@ -33,6 +38,8 @@ use tracing::{info, warn};
 
 pub mod cli;
 
+use cli::StorageMigrateArgs;
+
 pub async fn serve(config: ConfigRef) -> Result<()> {
     let workspace = WorkspaceRef::default();
     let _guard = init_tracing(&config.log, &workspace);
@@ -90,6 +97,70 @@ pub async fn sched(config: ConfigRef) -> Result<()> {
     Ok(())
 }
 
+pub async fn storage_migrate(config: ConfigRef, args: StorageMigrateArgs) -> Result<()> {
+    let workspace = WorkspaceRef::default();
+    let _guard = init_tracing(&config.log, &workspace);
+    let checkpoint = args
+        .checkpoint
+        .unwrap_or_else(|| workspace.data_dir().join("storage_migrate.checkpoint"));
+    let source = operator_from_spec(&args.from)?;
+    let target = operator_from_spec(&args.to)?;
+    // objects on a file system are encrypted at rest with the configured keys
+    #[cfg(feature = "object_storage_fs")]
+    let (source, target) = if config.fs.encryption_keys.is_empty() {
+        (source, target)
+    } else {
+        let master_keys = Arc::new(object_storage_kit::MasterKeys::from_hex(
+            &config.fs.encryption_keys,
+        )?);
+        (
+            object_storage_kit::migrate::encrypt_fs(&args.from, source, &master_keys),
+            object_storage_kit::migrate::encrypt_fs(&args.to, target, &master_keys),
+        )
+    };
+    let migrator = Migrator::builder()
+        .source(source)
+        .target(target)
+        .prefix(args.prefix.clone())
+        .checkpoint(checkpoint)
+        .concurrency(args.concurrency)
+        .build();
+    let report = migrator.run().await?;
+    info!(
+        copied = report.copied.len(),
+        skipped = report.skipped,
+        failed = report.failed.len(),
+        "📦 Storage migration finished"
+    );
+    if !report.failed.is_empty() {
+        bail!(
+            "{} objects failed to migrate, run again to retry them",
+            report.failed.len()
+        );
+    }
+    if args.rewrite_paths && !args.prefix.is_empty() {
+        let pg_pool = build_pg_pool(&config).await?;
+        let file_service = FileService::builder()
+            .ct(ChronoTz::builder().tz(config.timezone).build())
+            .pool(pg_pool.clone())
+            .build();
+        let rewritten = file_service.prefix_paths(&args.prefix).await?;
+        info!(rewritten, "Stored paths rewritten");
+        pg_pool.close().await;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "object_storage_fs")]
+pub async fn storage_rotate_keys(config: ConfigRef) -> Result<()> {
+    let workspace = WorkspaceRef::default();
+    let _guard = init_tracing(&config.log, &workspace);
+    let object_storage = build_object_storage(&config, &workspace).await?;
+    let rotated = object_storage.rotate_encryption_keys().await?;
+    info!(rotated, "🔑 Encryption keys rotated");
+    Ok(())
+}
+
 #[allow(unused)]
 fn init_tracing(config: &Log, workspace: &WorkspaceRef) -> TracingGuard {
     let config_builder = trace_kit::TraceConfig::builder().level(&config.level);
@@ -144,6 +215,7 @@ async fn build_object_storage(
             .basepath(adapter::UPLOAD_PATH.to_string())
             .hmac_secret(config.fs.hmac_secret)
             .link_period(config.fs.link_period)
+            .encryption_keys(config.fs.encryption_keys.clone())
             .build();
         Ok(ObjectStorage::try_new(config)?)
     };
@@ -216,6 +288,13 @@ async fn build_bgworker_manager(provider: &Provider) -> Result<WorkerManager> {
     };
     #[cfg(feature = "bg_postgres")]
     let manager = WorkerManager::try_new(provider.provide::<PgPool>()).await?;
+    #[cfg(feature = "bg_redis")]
+    let manager = {
+        use bg_worker_kit::redis_helper::new_redis_pool;
+        let config = provider.provide::<ConfigRef>();
+        let pool = new_redis_pool(&config.bg_worker.redis_url).await?;
+        WorkerManager::try_new(pool).await?
+    };
     let manager = register_bgworkers(manager, provider.clone());
     Ok(manager)
 }
@@ -228,6 +307,7 @@ async fn build_scheduler_job(
         provider.provide::<ConfigRef>().timezone,
     );
     register_scheduled_jobs(&mut scheduler, provider).await?;
+    scheduler.reload_on(SCHED_RELOAD.clone());
     Ok(scheduler)
 }
 
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use clap::Parser as _;
 use infrastructure::shared::config::ConfigRef;
-use server::cli::{Cli, Commands};
+use server::cli::{Cli, Commands, StorageCommands};
 
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
@@ -26,6 +26,19 @@ async fn main() -> Result<()> {
                 std::process::exit(1);
             }
         }
+        Commands::Storage(StorageCommands::Migrate(args)) => {
+            if let Err(e) = server::storage_migrate(config, args).await {
+                eprintln!("❌ Storage migrate error: {:?}", e);
+                std::process::exit(1);
+            }
+        }
+        #[cfg(feature = "object_storage_fs")]
+        Commands::Storage(StorageCommands::RotateKeys) => {
+            if let Err(e) = server::storage_rotate_keys(config).await {
+                eprintln!("❌ Storage rotate keys error: {:?}", e);
+                std::process::exit(1);
+            }
+        }
     }
     Ok(())
 }
@@ -18,7 +18,10 @@ async fn api_integration_test() {
             run_hurl("system/role", &variables).await;
             run_hurl("system/option", &variables).await;
             run_hurl("system/stat", &variables).await;
+            run_hurl("system/sched", &variables).await;
             run_hurl("upload", &variables).await;
+            run_hurl("system/share", &variables).await;
+            run_hurl("system/recycle_bin", &variables).await;
             run_hurl("last", &variables).await;
             handle.abort();
         })
@@ -14,9 +14,16 @@ pub enum WorkerError {
     #[error(transparent)]
     Apalis(#[from] apalis::prelude::Error),
 
+    #[cfg(feature = "redis")]
+    #[error(transparent)]
+    Redis(#[from] redis::RedisError),
+
     #[error("Timeout")]
     Timeout,
 
+    #[error("Cancelled")]
+    Cancelled,
+
     #[error(transparent)]
     BroadcastSend(#[from] tokio::sync::broadcast::error::SendError<i64>),
 
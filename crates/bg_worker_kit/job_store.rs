@@ -0,0 +1,465 @@
+use std::any::type_name;
+
+use apalis::prelude::State;
+use serde::Serialize;
+use sqlx::FromRow;
+
+#[cfg(not(feature = "redis"))]
+use crate::error::Result;
+use crate::{Pool, WorkerTrait};
+
+/// Timestamps are read as epoch seconds, sqlite stores them that way already
+#[cfg(feature = "sqlite")]
+mod sql {
+    pub const COUNT: &str = "SELECT COUNT(*) FROM Jobs WHERE job_type = $1 AND status = $2";
+    pub const LIST: &str = r#"
+        SELECT id, job_type, status, job AS payload, attempts, max_attempts, last_error,
+            run_at, lock_at, done_at
+        FROM Jobs WHERE job_type = $1 AND status = $2
+        ORDER BY done_at DESC, run_at DESC LIMIT $3 OFFSET $4
+    "#;
+    pub const GET: &str = r#"
+        SELECT id, job_type, status, job AS payload, attempts, max_attempts, last_error,
+            run_at, lock_at, done_at
+        FROM Jobs WHERE job_type = $1 AND id = $2
+    "#;
+    pub const RETRY: &str = r#"
+        UPDATE Jobs SET status = 'Pending', attempts = 0, run_at = strftime('%s', 'now'),
+            lock_at = NULL, lock_by = NULL, done_at = NULL
+        WHERE job_type = $1 AND status IN ('Failed', 'Killed')
+            AND (json_array_length($2) = 0 OR id IN (SELECT value FROM json_each($2)))
+    "#;
+    pub const DELETE: &str = r#"
+        DELETE FROM Jobs WHERE job_type = $1 AND status != 'Running'
+            AND id IN (SELECT value FROM json_each($2))
+    "#;
+    pub const UPDATE_PAYLOAD: &str = r#"
+        UPDATE Jobs SET job = $3 WHERE job_type = $1 AND id = $2 AND status != 'Running'
+    "#;
+    pub const COUNT_TASKS: &str = "SELECT COUNT(*) FROM JobProgress WHERE owner = $1";
+    pub const LIST_TASKS: &str = r#"
+        SELECT p.job_id AS id, p.job_type, p.title, COALESCE(j.status, 'Killed') AS status,
+            p.percent, p.message, p.result, p.cancel_requested, j.last_error,
+            j.run_at, j.done_at, p.created_at, p.updated_at
+        FROM JobProgress p LEFT JOIN Jobs j ON j.id = p.job_id
+        WHERE p.owner = $1
+        ORDER BY p.created_at DESC LIMIT $2 OFFSET $3
+    "#;
+    pub const GET_TASK: &str = r#"
+        SELECT p.job_id AS id, p.job_type, p.title, COALESCE(j.status, 'Killed') AS status,
+            p.percent, p.message, p.result, p.cancel_requested, j.last_error,
+            j.run_at, j.done_at, p.created_at, p.updated_at
+        FROM JobProgress p LEFT JOIN Jobs j ON j.id = p.job_id
+        WHERE p.owner = $1 AND p.job_id = $2
+    "#;
+    pub const REQUEST_CANCEL: &str = r#"
+        UPDATE JobProgress SET cancel_requested = 1, updated_at = strftime('%s', 'now')
+        WHERE owner = $1 AND job_id = $2
+    "#;
+    pub const KILL_PENDING: &str = r#"
+        UPDATE Jobs SET status = 'Killed', last_error = 'Cancelled', done_at = strftime('%s', 'now')
+        WHERE id = $1 AND status = 'Pending'
+    "#;
+}
+
+#[cfg(feature = "postgres")]
+mod sql {
+    pub const COUNT: &str = "SELECT COUNT(*) FROM apalis.jobs WHERE job_type = $1 AND status = $2";
+    pub const LIST: &str = r#"
+        SELECT id, job_type, status, job::TEXT AS payload, attempts, max_attempts, last_error,
+            EXTRACT(EPOCH FROM run_at)::BIGINT AS run_at,
+            EXTRACT(EPOCH FROM lock_at)::BIGINT AS lock_at,
+            EXTRACT(EPOCH FROM done_at)::BIGINT AS done_at
+        FROM apalis.jobs WHERE job_type = $1 AND status = $2
+        ORDER BY done_at DESC NULLS FIRST, run_at DESC LIMIT $3 OFFSET $4
+    "#;
+    pub const GET: &str = r#"
+        SELECT id, job_type, status, job::TEXT AS payload, attempts, max_attempts, last_error,
+            EXTRACT(EPOCH FROM run_at)::BIGINT AS run_at,
+            EXTRACT(EPOCH FROM lock_at)::BIGINT AS lock_at,
+            EXTRACT(EPOCH FROM done_at)::BIGINT AS done_at
+        FROM apalis.jobs WHERE job_type = $1 AND id = $2
+    "#;
+    pub const RETRY: &str = r#"
+        UPDATE apalis.jobs SET status = 'Pending', attempts = 0, run_at = now(),
+            lock_at = NULL, lock_by = NULL, done_at = NULL
+        WHERE job_type = $1 AND status IN ('Failed', 'Killed')
+            AND (jsonb_array_length($2::jsonb) = 0
+                OR id IN (SELECT jsonb_array_elements_text($2::jsonb)))
+    "#;
+    pub const DELETE: &str = r#"
+        DELETE FROM apalis.jobs WHERE job_type = $1 AND status != 'Running'
+            AND id IN (SELECT jsonb_array_elements_text($2::jsonb))
+    "#;
+    pub const UPDATE_PAYLOAD: &str = r#"
+        UPDATE apalis.jobs SET job = $3::jsonb WHERE job_type = $1 AND id = $2 AND status != 'Running'
+    "#;
+    pub const COUNT_TASKS: &str = "SELECT COUNT(*) FROM apalis.job_progress WHERE owner = $1";
+    pub const LIST_TASKS: &str = r#"
+        SELECT p.job_id AS id, p.job_type, p.title, COALESCE(j.status, 'Killed') AS status,
+            p.percent, p.message, p.result, p.cancel_requested, j.last_error,
+            EXTRACT(EPOCH FROM j.run_at)::BIGINT AS run_at,
+            EXTRACT(EPOCH FROM j.done_at)::BIGINT AS done_at,
+            p.created_at, p.updated_at
+        FROM apalis.job_progress p LEFT JOIN apalis.jobs j ON j.id = p.job_id
+        WHERE p.owner = $1
+        ORDER BY p.created_at DESC LIMIT $2 OFFSET $3
+    "#;
+    pub const GET_TASK: &str = r#"
+        SELECT p.job_id AS id, p.job_type, p.title, COALESCE(j.status, 'Killed') AS status,
+            p.percent, p.message, p.result, p.cancel_requested, j.last_error,
+            EXTRACT(EPOCH FROM j.run_at)::BIGINT AS run_at,
+            EXTRACT(EPOCH FROM j.done_at)::BIGINT AS done_at,
+            p.created_at, p.updated_at
+        FROM apalis.job_progress p LEFT JOIN apalis.jobs j ON j.id = p.job_id
+        WHERE p.owner = $1 AND p.job_id = $2
+    "#;
+    pub const REQUEST_CANCEL: &str = r#"
+        UPDATE apalis.job_progress SET cancel_requested = TRUE,
+            updated_at = EXTRACT(EPOCH FROM now())::BIGINT
+        WHERE owner = $1 AND job_id = $2
+    "#;
+    pub const KILL_PENDING: &str = r#"
+        UPDATE apalis.jobs SET status = 'Killed', last_error = 'Cancelled', done_at = now()
+        WHERE id = $1 AND status = 'Pending'
+    "#;
+}
+
+/// A stored job with everything the admin needs to tell why it failed
+#[derive(Debug, Clone, Serialize)]
+pub struct JobRecord {
+    pub id: String,
+    pub job_type: String,
+    pub status: String,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub last_error: Option<String>,
+    /// Epoch seconds, redis only knows it for scheduled jobs
+    pub run_at: Option<i64>,
+    pub lock_at: Option<i64>,
+    pub done_at: Option<i64>,
+}
+
+#[cfg(not(feature = "redis"))]
+#[derive(FromRow)]
+struct JobRow {
+    id: String,
+    job_type: String,
+    status: String,
+    payload: String,
+    attempts: i32,
+    max_attempts: i32,
+    last_error: Option<String>,
+    run_at: i64,
+    lock_at: Option<i64>,
+    done_at: Option<i64>,
+}
+
+/// A job tracked on behalf of a user, see `EnqueueOptions::owner`
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TaskRecord {
+    pub id: String,
+    pub job_type: String,
+    pub title: Option<String>,
+    pub status: String,
+    pub percent: i32,
+    pub message: Option<String>,
+    /// JSON written by `Progress::result`
+    pub result: Option<String>,
+    pub cancel_requested: bool,
+    pub last_error: Option<String>,
+    /// Epoch seconds
+    pub run_at: Option<i64>,
+    pub done_at: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[cfg(not(feature = "redis"))]
+impl From<JobRow> for JobRecord {
+    fn from(row: JobRow) -> Self {
+        let payload =
+            serde_json::from_str(&row.payload).unwrap_or(serde_json::Value::String(row.payload));
+        Self {
+            id: row.id,
+            job_type: row.job_type,
+            status: row.status,
+            payload,
+            attempts: row.attempts,
+            max_attempts: row.max_attempts,
+            last_error: row.last_error,
+            run_at: Some(row.run_at),
+            lock_at: row.lock_at,
+            done_at: row.done_at,
+        }
+    }
+}
+
+/// Job level access to the queues, jobs are addressed by worker type and the
+/// id the backend assigned on push
+#[derive(Clone)]
+pub struct JobStore {
+    pool: Pool,
+}
+
+// the redis connection manager has no `Debug`
+impl std::fmt::Debug for JobStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JobStore").finish_non_exhaustive()
+    }
+}
+
+impl JobStore {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+}
+
+#[cfg(feature = "redis")]
+mod redis;
+
+#[cfg(not(feature = "redis"))]
+impl JobStore {
+    /// Jobs of one status, the most recently finished or scheduled first
+    pub async fn list<T: WorkerTrait>(
+        &self,
+        state: &State,
+        page: i64,
+        page_size: i64,
+    ) -> Result<(i64, Vec<JobRecord>)> {
+        let status = status_of(state);
+        let total: i64 = sqlx::query_scalar(sql::COUNT)
+            .bind(namespace::<T>())
+            .bind(status)
+            .fetch_one(&self.pool)
+            .await?;
+        let rows: Vec<JobRow> = sqlx::query_as(sql::LIST)
+            .bind(namespace::<T>())
+            .bind(status)
+            .bind(page_size)
+            .bind((page.max(1) - 1) * page_size)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok((total, rows.into_iter().map(Into::into).collect()))
+    }
+
+    pub async fn get<T: WorkerTrait>(&self, id: &str) -> Result<Option<JobRecord>> {
+        let row: Option<JobRow> = sqlx::query_as(sql::GET)
+            .bind(namespace::<T>())
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(Into::into))
+    }
+
+    /// Puts failed or killed jobs back in the queue with their attempts reset,
+    /// every failed or killed job of the worker when `ids` is empty
+    pub async fn retry<T: WorkerTrait>(&self, ids: &[String]) -> Result<u64> {
+        let result = sqlx::query(sql::RETRY)
+            .bind(namespace::<T>())
+            .bind(serde_json::to_string(ids)?)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes jobs that are not running at the moment
+    pub async fn delete<T: WorkerTrait>(&self, ids: &[String]) -> Result<u64> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let result = sqlx::query(sql::DELETE)
+            .bind(namespace::<T>())
+            .bind(serde_json::to_string(ids)?)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Replaces the payload of a job that is not running, the payload has to
+    /// deserialize into the worker so the job can still be executed
+    pub async fn update_payload<T: WorkerTrait>(
+        &self,
+        id: &str,
+        payload: serde_json::Value,
+    ) -> Result<bool> {
+        let job: T = serde_json::from_value(payload)?;
+        let result = sqlx::query(sql::UPDATE_PAYLOAD)
+            .bind(namespace::<T>())
+            .bind(id)
+            .bind(serde_json::to_string(&job)?)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(not(feature = "redis"))]
+impl JobStore {
+    /// Tasks of `owner`, the latest first
+    pub async fn list_tasks(
+        &self,
+        owner: &str,
+        page: i64,
+        page_size: i64,
+    ) -> Result<(i64, Vec<TaskRecord>)> {
+        let total: i64 = sqlx::query_scalar(sql::COUNT_TASKS)
+            .bind(owner)
+            .fetch_one(&self.pool)
+            .await?;
+        let items = sqlx::query_as(sql::LIST_TASKS)
+            .bind(owner)
+            .bind(page_size)
+            .bind((page.max(1) - 1) * page_size)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok((total, items))
+    }
+
+    pub async fn get_task(&self, owner: &str, id: &str) -> Result<Option<TaskRecord>> {
+        let task = sqlx::query_as(sql::GET_TASK)
+            .bind(owner)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(task)
+    }
+
+    /// Kills the task right away while it is pending, a running one stops at its
+    /// next `Progress::check_cancelled`
+    pub async fn cancel_task(&self, owner: &str, id: &str) -> Result<bool> {
+        let result = sqlx::query(sql::REQUEST_CANCEL)
+            .bind(owner)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+        sqlx::query(sql::KILL_PENDING)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(true)
+    }
+}
+
+/// The backend files jobs under the type name of the worker
+fn namespace<T: WorkerTrait>() -> &'static str {
+    type_name::<T>()
+}
+
+fn status_of(state: &State) -> &'static str {
+    match state {
+        State::Pending => "Pending",
+        State::Scheduled => "Scheduled",
+        State::Running => "Running",
+        State::Done => "Done",
+        State::Failed => "Failed",
+        State::Killed => "Killed",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_utils::{Echo, db_test, echo_queue, set_status};
+
+    async fn list_and_get(pool: Pool) {
+        let (manager, queue) = echo_queue(pool).await;
+        let store = manager.job_store();
+        let first = queue.push(Echo { n: 1 }).await.unwrap();
+        queue.push(Echo { n: 2 }).await.unwrap();
+
+        let (total, items) = store.list::<Echo>(&State::Pending, 1, 1).await.unwrap();
+        assert_eq!(total, 2);
+        assert_eq!(items.len(), 1);
+        let (total, _) = store.list::<Echo>(&State::Failed, 1, 10).await.unwrap();
+        assert_eq!(total, 0);
+
+        let job = store.get::<Echo>(&first).await.unwrap().unwrap();
+        assert_eq!(job.status, "Pending");
+        assert_eq!(job.payload, json!({"n": 1}));
+        // redis only dates the jobs scheduled for later
+        #[cfg(not(feature = "redis"))]
+        assert!(job.run_at.is_some());
+        assert!(store.get::<Echo>("unknown").await.unwrap().is_none());
+    }
+    db_test!(test_list_and_get, list_and_get);
+
+    async fn retry(pool: Pool) {
+        let (manager, queue) = echo_queue(pool.clone()).await;
+        let store = manager.job_store();
+        let failed = queue.push(Echo { n: 1 }).await.unwrap();
+        let killed = queue.push(Echo { n: 2 }).await.unwrap();
+        let pending = queue.push(Echo { n: 3 }).await.unwrap();
+        set_status(&pool, &failed, "Failed").await;
+        set_status(&pool, &killed, "Killed").await;
+
+        assert_eq!(store.retry::<Echo>(&[pending.clone()]).await.unwrap(), 0);
+        assert_eq!(store.retry::<Echo>(&[failed.clone()]).await.unwrap(), 1);
+        let job = store.get::<Echo>(&failed).await.unwrap().unwrap();
+        assert_eq!(job.status, "Pending");
+        assert_eq!(job.attempts, 0);
+        assert_eq!(job.done_at, None);
+        // every failed or killed job without ids
+        assert_eq!(store.retry::<Echo>(&[]).await.unwrap(), 1);
+        let (total, _) = store.list::<Echo>(&State::Pending, 1, 10).await.unwrap();
+        assert_eq!(total, 3);
+    }
+    db_test!(test_retry, retry);
+
+    async fn delete(pool: Pool) {
+        let (manager, queue) = echo_queue(pool.clone()).await;
+        let store = manager.job_store();
+        let running = queue.push(Echo { n: 1 }).await.unwrap();
+        let pending = queue.push(Echo { n: 2 }).await.unwrap();
+        set_status(&pool, &running, "Running").await;
+
+        assert_eq!(store.delete::<Echo>(&[]).await.unwrap(), 0);
+        let ids = [running.clone(), pending.clone()];
+        assert_eq!(store.delete::<Echo>(&ids).await.unwrap(), 1);
+        assert!(store.get::<Echo>(&pending).await.unwrap().is_none());
+        assert!(store.get::<Echo>(&running).await.unwrap().is_some());
+    }
+    db_test!(test_delete, delete);
+
+    async fn update_payload(pool: Pool) {
+        let (manager, queue) = echo_queue(pool.clone()).await;
+        let store = manager.job_store();
+        let id = queue.push(Echo { n: 1 }).await.unwrap();
+
+        assert!(
+            store
+                .update_payload::<Echo>(&id, json!({"n": 5}))
+                .await
+                .unwrap()
+        );
+        let job = store.get::<Echo>(&id).await.unwrap().unwrap();
+        assert_eq!(job.payload, json!({"n": 5}));
+        // the worker could not run it anymore
+        assert!(
+            store
+                .update_payload::<Echo>(&id, json!({"m": 5}))
+                .await
+                .is_err()
+        );
+        set_status(&pool, &id, "Running").await;
+        assert!(
+            !store
+                .update_payload::<Echo>(&id, json!({"n": 6}))
+                .await
+                .unwrap()
+        );
+        assert!(
+            !store
+                .update_payload::<Echo>("unknown", json!({"n": 6}))
+                .await
+                .unwrap()
+        );
+    }
+    db_test!(test_update_payload, update_payload);
+}
@@ -0,0 +1,304 @@
+use std::{collections::HashMap, str::FromStr as _};
+
+use apalis::prelude::{Attempt, State, Storage as _, TaskId};
+use apalis_redis::Config;
+use redis::AsyncCommands as _;
+
+use super::{JobRecord, JobStore, TaskRecord, namespace, status_of};
+use crate::{
+    StorageBackend, WorkerTrait,
+    error::Result,
+    progress::{
+        epoch,
+        redis::{owner_key, progress_key},
+    },
+    redis_helper::{done_at, job_status, kill_pending, max_attempts},
+};
+
+/// Redis keeps a job's payload in a hash and its id in the list or sorted set of
+/// its state, pages are cut from those. It keeps no error or lock time
+impl JobStore {
+    /// Jobs of one status, the most recently finished first
+    pub async fn list<T: WorkerTrait>(
+        &self,
+        state: &State,
+        page: i64,
+        page_size: i64,
+    ) -> Result<(i64, Vec<JobRecord>)> {
+        let backend = StorageBackend::<T>::new(self.pool.clone());
+        let config = backend.get_config().clone();
+        let mut conn = self.pool.clone();
+        let start = ((page.max(1) - 1) * page_size) as isize;
+        let stop = start + page_size as isize - 1;
+        let (total, ids): (i64, Vec<String>) = match state {
+            State::Pending | State::Scheduled => {
+                let listed: i64 = conn.llen(config.active_jobs_list()).await?;
+                let scheduled: i64 = conn.zcard(config.scheduled_jobs_set()).await?;
+                let mut ids: Vec<String> =
+                    conn.lrange(config.active_jobs_list(), start, stop).await?;
+                let rest = page_size as isize - ids.len() as isize;
+                if rest > 0 {
+                    let offset = (start - listed as isize).max(0);
+                    let more: Vec<String> = conn
+                        .zrange(config.scheduled_jobs_set(), offset, offset + rest - 1)
+                        .await?;
+                    ids.extend(more);
+                }
+                (listed + scheduled, ids)
+            }
+            State::Running => {
+                let consumers: Vec<String> = conn.smembers(config.consumers_set()).await?;
+                let mut ids = Vec::new();
+                for consumer in consumers {
+                    let inflight: Vec<String> = conn.smembers(consumer).await?;
+                    ids.extend(inflight);
+                }
+                let total = ids.len() as i64;
+                let ids = ids
+                    .into_iter()
+                    .skip(start as usize)
+                    .take(page_size as usize)
+                    .collect();
+                (total, ids)
+            }
+            State::Done | State::Failed | State::Killed => {
+                let set = match state {
+                    State::Done => config.done_jobs_set(),
+                    State::Failed => config.failed_jobs_set(),
+                    _ => config.dead_jobs_set(),
+                };
+                let total: i64 = conn.zcard(&set).await?;
+                let ids: Vec<String> = conn.zrevrange(&set, start, stop).await?;
+                (total, ids)
+            }
+        };
+        let mut items = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(record) = self.record::<T>(&id, status_of(state)).await? {
+                items.push(record);
+            }
+        }
+        Ok((total, items))
+    }
+
+    pub async fn get<T: WorkerTrait>(&self, id: &str) -> Result<Option<JobRecord>> {
+        let mut conn = self.pool.clone();
+        let Some(status) = job_status(&mut conn, namespace::<T>(), id).await? else {
+            return Ok(None);
+        };
+        self.record::<T>(id, status).await
+    }
+
+    /// Puts failed or killed jobs back in the queue with their attempts reset,
+    /// every failed or killed job of the worker when `ids` is empty
+    pub async fn retry<T: WorkerTrait>(&self, ids: &[String]) -> Result<u64> {
+        let mut backend = StorageBackend::<T>::new(self.pool.clone());
+        let config = backend.get_config().clone();
+        let mut conn = self.pool.clone();
+        let ids = if ids.is_empty() {
+            let mut ids: Vec<String> = conn.zrange(config.failed_jobs_set(), 0, -1).await?;
+            let dead: Vec<String> = conn.zrange(config.dead_jobs_set(), 0, -1).await?;
+            ids.extend(dead);
+            ids
+        } else {
+            ids.to_vec()
+        };
+        let mut affected = 0;
+        for id in ids {
+            let failed: i64 = conn.zrem(config.failed_jobs_set(), &id).await?;
+            let dead: i64 = conn.zrem(config.dead_jobs_set(), &id).await?;
+            if failed + dead == 0 {
+                continue;
+            }
+            let Ok(task_id) = TaskId::from_str(&id) else {
+                continue;
+            };
+            let Some(mut request) = backend.fetch_by_id(&task_id).await? else {
+                continue;
+            };
+            request.parts.attempt = Attempt::default();
+            backend.push_request(request).await?;
+            affected += 1;
+        }
+        Ok(affected)
+    }
+
+    /// Deletes jobs that are not running at the moment
+    pub async fn delete<T: WorkerTrait>(&self, ids: &[String]) -> Result<u64> {
+        let config = Config::default().set_namespace(namespace::<T>());
+        let mut conn = self.pool.clone();
+        let mut affected = 0;
+        for id in ids {
+            if matches!(
+                job_status(&mut conn, namespace::<T>(), id).await?,
+                None | Some("Running")
+            ) {
+                continue;
+            }
+            let _: () = redis::pipe()
+                .atomic()
+                .hdel(config.job_data_hash(), id)
+                .lrem(config.active_jobs_list(), 0, id)
+                .zrem(config.scheduled_jobs_set(), id)
+                .zrem(config.done_jobs_set(), id)
+                .zrem(config.failed_jobs_set(), id)
+                .zrem(config.dead_jobs_set(), id)
+                .query_async(&mut conn)
+                .await?;
+            affected += 1;
+        }
+        Ok(affected)
+    }
+
+    /// Replaces the payload of a job that is not running, the payload has to
+    /// deserialize into the worker so the job can still be executed
+    pub async fn update_payload<T: WorkerTrait>(
+        &self,
+        id: &str,
+        payload: serde_json::Value,
+    ) -> Result<bool> {
+        let job: T = serde_json::from_value(payload)?;
+        let mut conn = self.pool.clone();
+        if matches!(
+            job_status(&mut conn, namespace::<T>(), id).await?,
+            None | Some("Running")
+        ) {
+            return Ok(false);
+        }
+        let Ok(task_id) = TaskId::from_str(id) else {
+            return Ok(false);
+        };
+        let mut backend = StorageBackend::<T>::new(self.pool.clone());
+        let Some(mut request) = backend.fetch_by_id(&task_id).await? else {
+            return Ok(false);
+        };
+        request.args = job;
+        backend.update(request).await?;
+        Ok(true)
+    }
+
+    async fn record<T: WorkerTrait>(&self, id: &str, status: &str) -> Result<Option<JobRecord>> {
+        let Ok(task_id) = TaskId::from_str(id) else {
+            return Ok(None);
+        };
+        let mut backend = StorageBackend::<T>::new(self.pool.clone());
+        let Some(request) = backend.fetch_by_id(&task_id).await? else {
+            return Ok(None);
+        };
+        let mut conn = self.pool.clone();
+        let scheduled: Option<f64> = conn
+            .zscore(backend.get_config().scheduled_jobs_set(), id)
+            .await?;
+        Ok(Some(JobRecord {
+            id: id.to_string(),
+            job_type: namespace::<T>().to_string(),
+            status: status.to_string(),
+            payload: serde_json::to_value(&request.args)?,
+            attempts: request.parts.attempt.current() as i32,
+            max_attempts: max_attempts(&request.parts.context)? as i32,
+            last_error: None,
+            run_at: scheduled.map(|run_at| run_at as i64),
+            lock_at: None,
+            done_at: done_at(&mut conn, namespace::<T>(), id).await?,
+        }))
+    }
+}
+
+impl JobStore {
+    /// Tasks of `owner`, the latest first
+    pub async fn list_tasks(
+        &self,
+        owner: &str,
+        page: i64,
+        page_size: i64,
+    ) -> Result<(i64, Vec<TaskRecord>)> {
+        let mut conn = self.pool.clone();
+        let start = ((page.max(1) - 1) * page_size) as isize;
+        let total: i64 = conn.zcard(owner_key(owner)).await?;
+        let ids: Vec<String> = conn
+            .zrevrange(owner_key(owner), start, start + page_size as isize - 1)
+            .await?;
+        let mut items = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(task) = self.task(&id).await? {
+                items.push(task);
+            }
+        }
+        Ok((total, items))
+    }
+
+    pub async fn get_task(&self, owner: &str, id: &str) -> Result<Option<TaskRecord>> {
+        if !self.owns(owner, id).await? {
+            return Ok(None);
+        }
+        self.task(id).await
+    }
+
+    /// Kills the task right away while it is pending, a running one stops at its
+    /// next `Progress::check_cancelled`
+    pub async fn cancel_task(&self, owner: &str, id: &str) -> Result<bool> {
+        if !self.owns(owner, id).await? {
+            return Ok(false);
+        }
+        let mut conn = self.pool.clone();
+        let key = progress_key(id);
+        let _: () = conn
+            .hset_multiple(
+                &key,
+                &[
+                    ("cancel_requested", "1".to_string()),
+                    ("updated_at", epoch().to_string()),
+                ],
+            )
+            .await?;
+        let namespace: Option<String> = conn.hget(&key, "namespace").await?;
+        if let Some(namespace) = namespace {
+            kill_pending(&mut conn, &namespace, id).await?;
+        }
+        Ok(true)
+    }
+
+    async fn owns(&self, owner: &str, id: &str) -> Result<bool> {
+        let holder: Option<String> = self.pool.clone().hget(progress_key(id), "owner").await?;
+        Ok(holder.as_deref() == Some(owner))
+    }
+
+    async fn task(&self, id: &str) -> Result<Option<TaskRecord>> {
+        let mut conn = self.pool.clone();
+        let mut fields: HashMap<String, String> = conn.hgetall(progress_key(id)).await?;
+        if fields.is_empty() {
+            return Ok(None);
+        }
+        let namespace = fields.remove("namespace").unwrap_or_default();
+        let status = job_status(&mut conn, &namespace, id).await?;
+        let scheduled: Option<f64> = conn
+            .zscore(
+                Config::default()
+                    .set_namespace(&namespace)
+                    .scheduled_jobs_set(),
+                id,
+            )
+            .await?;
+        let number = |fields: &HashMap<String, String>, name: &str| {
+            fields
+                .get(name)
+                .and_then(|value| value.parse::<i64>().ok())
+                .unwrap_or_default()
+        };
+        Ok(Some(TaskRecord {
+            id: id.to_string(),
+            job_type: fields.remove("job_type").unwrap_or_default(),
+            title: fields.remove("title"),
+            status: status.unwrap_or("Killed").to_string(),
+            percent: number(&fields, "percent") as i32,
+            message: fields.remove("message"),
+            result: fields.remove("result"),
+            cancel_requested: number(&fields, "cancel_requested") != 0,
+            last_error: None,
+            run_at: scheduled.map(|run_at| run_at as i64),
+            done_at: done_at(&mut conn, &namespace, id).await?,
+            created_at: number(&fields, "created_at"),
+            updated_at: number(&fields, "updated_at"),
+        }))
+    }
+}
@@ -1,13 +1,27 @@
 pub mod error;
-use std::time::Duration;
+pub mod job_store;
+pub mod policy;
+pub mod progress;
+pub mod queue;
+pub mod workflow;
+use std::{sync::Arc, time::Duration};
 
 use crate::error::{Result, WorkerError};
 use apalis::layers::WorkerBuilderExt as _;
-use apalis::layers::retry::RetryPolicy;
+use apalis::prelude::{
+    Attempt, Data, Error as ApalisError, Monitor, TaskId, WorkerBuilder, WorkerFactoryFn as _,
+};
 pub use apalis::prelude::{BackendExpose, Stat, State, Storage};
-use apalis::prelude::{Data, Monitor, WorkerBuilder, WorkerFactoryFn as _};
 use apalis_core::codec::json::JsonCodec;
+pub use job_store::{JobRecord, JobStore, TaskRecord};
+use policy::Limiter;
+pub use policy::{Backoff, RateLimit};
+pub use progress::Progress;
+use progress::epoch;
+pub use queue::{EnqueueOptions, Queue};
 use serde::{Serialize, de::DeserializeOwned};
+use workflow::Dispatcher;
+pub use workflow::{FailurePolicy, Workflow, WorkflowJobRecord, WorkflowRecord, Workflows};
 #[cfg(feature = "sqlite")]
 pub type Pool = apalis_sql::sqlite::SqlitePool;
 #[cfg(feature = "postgres")]
@@ -17,13 +31,30 @@ pub type StorageBackend<T, C = JsonCodec<String>> = apalis_sql::sqlite::SqliteSt
 #[cfg(feature = "postgres")]
 pub type StorageBackend<T, C = JsonCodec<serde_json::Value>> =
     apalis_sql::postgres::PostgresStorage<T, C>;
+#[cfg(feature = "redis")]
+pub type Pool = redis::aio::ConnectionManager;
+#[cfg(feature = "redis")]
+pub type StorageBackend<T, C = JsonCodec<Vec<u8>>> = apalis_redis::RedisStorage<T, Pool, C>;
 
 #[cfg(feature = "sqlite")]
 pub mod sqlite_helper;
 
+#[cfg(feature = "redis")]
+pub mod redis_helper;
+
+#[cfg(test)]
+mod test_utils;
+
+/// How often workflows are checked for having stalled
+const RECOVER_INTERVAL: Duration = Duration::from_secs(60);
+/// How long a workflow stands still before it counts as stalled, longer than it takes
+/// to push the jobs of a stage
+const RECOVER_AFTER: Duration = Duration::from_secs(5 * 60);
+
 pub struct WorkerManager {
     pool: Pool,
     monitor: Monitor,
+    dispatcher: Dispatcher,
 }
 
 impl WorkerManager {
@@ -31,6 +62,7 @@ impl WorkerManager {
         let instance = Self {
             pool: pool.clone(),
             monitor: Monitor::new(),
+            dispatcher: Dispatcher::default(),
         };
         instance.setup().await?;
         Ok(instance)
@@ -43,36 +75,101 @@ impl WorkerManager {
         tracing::info!("Choosing database backend: SQLite");
         #[cfg(feature = "postgres")]
         tracing::info!("Choosing database backend: PosgreSQL");
-        StorageBackend::setup(&self.pool).await?;
+        #[cfg(feature = "redis")]
+        tracing::info!("Choosing database backend: Redis");
+        // Redis needs no schema, the keys are created as jobs come in
+        #[cfg(not(feature = "redis"))]
+        {
+            StorageBackend::setup(&self.pool).await?;
+            queue::setup(&self.pool).await?;
+            progress::setup(&self.pool).await?;
+            workflow::setup(&self.pool).await?;
+        }
         Ok(())
     }
 
-    pub fn register<T>(mut self, data: T::State) -> (Self, StorageBackend<T>)
+    pub fn register<T>(mut self, data: T::State) -> (Self, Queue<T>)
     where
         T: WorkerTrait,
     {
         let backend = StorageBackend::new(self.pool.clone());
+        let limiter = T::RATE_LIMIT.map(|limit| Arc::new(Limiter::new(limit)));
+        let pool = self.pool.clone();
+        let workflows = self.workflows();
         let worker = WorkerBuilder::new(T::NAME)
             .enable_tracing()
             .concurrency(T::CONCURRENCY)
-            .retry(RetryPolicy::retries(T::RETRIES))
             .data(data)
             .backend(backend.clone())
-            .build_fn(|job: T, data: Data<T::State>| async move {
-                match tokio::time::timeout(T::TIMEOUT, T::execute(job, &data)).await {
-                    Ok(res) => res,
-                    Err(_) => Err(WorkerError::Timeout),
-                }
-            });
+            .build_fn(
+                move |job: T, data: Data<T::State>, task_id: TaskId, attempt: Attempt| {
+                    let limiter = limiter.clone();
+                    let workflows = workflows.clone();
+                    let pool = pool.clone();
+                    let progress = Progress::new(pool.clone(), task_id.to_string(), T::NAME);
+                    async move {
+                        let result = execute(job, &data, &progress, limiter.as_deref()).await;
+                        let retry = attempt.current().saturating_sub(1);
+                        if let Err(err) = &result
+                            && !matches!(err, WorkerError::Cancelled)
+                            && retry < T::RETRIES
+                        {
+                            let delay = T::BACKOFF.delay(retry as u32);
+                            tracing::warn!(worker = T::NAME, %err, retry, ?delay, "Job failed, retrying");
+                            let run_at = epoch() + delay.as_millis().div_ceil(1000) as i64;
+                            if let Err(err) =
+                                queue::delay_retry(&pool, progress.job_id(), run_at).await
+                            {
+                                tracing::error!(worker = T::NAME, %err, "Failed to delay the retry");
+                            }
+                            // the workflow moves on once the last attempt is over
+                            return result.map_err(|err| ApalisError::Failed(Arc::new(err.into())));
+                        }
+                        if let Err(err) = workflows.finished(progress.job_id(), &result).await {
+                            tracing::error!(worker = T::NAME, %err, "Failed to move the workflow on");
+                        }
+                        result.map_err(|err| match err {
+                            // aborted jobs are never picked up again
+                            WorkerError::Cancelled => ApalisError::Abort(Arc::new(err.into())),
+                            err => ApalisError::Failed(Arc::new(err.into())),
+                        })
+                    }
+                },
+            );
         self.monitor = self.monitor.register(worker);
-        (self, backend)
+        let queue = Queue::new(backend, self.pool.clone());
+        self.dispatcher.insert(queue.clone());
+        (self, queue)
+    }
+
+    pub fn job_store(&self) -> JobStore {
+        JobStore::new(self.pool.clone())
+    }
+
+    pub fn workflows(&self) -> Workflows {
+        Workflows::new(self.pool.clone(), self.dispatcher.clone())
     }
 
+    /// Runs the workers until `signal`, stalled workflows are recovered on the side
     pub async fn run_with_signal<S>(self, signal: S) -> Result<()>
     where
         S: Send + Future<Output = std::io::Result<()>>,
     {
-        self.monitor.run_with_signal(signal).await?;
+        let workflows = self.workflows();
+        let recovery = async move {
+            let mut interval = tokio::time::interval(RECOVER_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(err) = workflows.recover(RECOVER_AFTER).await {
+                    tracing::error!(%err, "Failed to recover stalled workflows");
+                }
+            }
+        };
+        tokio::select! {
+            result = self.monitor.run_with_signal(signal) => result?,
+            _ = recovery => {}
+        }
+        #[cfg(not(feature = "redis"))]
         self.pool.close().await;
         Ok(())
     }
@@ -84,8 +181,37 @@ pub trait WorkerTrait:
     type State: Clone + Send + Sync + Unpin + 'static;
     const NAME: &'static str;
     const CONCURRENCY: usize;
+    /// Attempts after the first one, each goes through the backend again
     const RETRIES: usize;
     const TIMEOUT: Duration;
+    /// Default priority of pushed jobs, higher ones are fetched first. The redis backend
+    /// refuses jobs with a priority
+    const PRIORITY: i32 = 0;
+    /// Wait before a failed job is fetched again, redis requeues it right away
+    const BACKOFF: Backoff = Backoff::NONE;
+    /// Throttles job starts, for workers calling rate limited external APIs
+    const RATE_LIMIT: Option<RateLimit> = None;
 
-    fn execute(job: Self, state: &Self::State) -> impl Future<Output = Result<()>> + Send;
+    fn execute(
+        job: Self,
+        state: &Self::State,
+        progress: &Progress,
+    ) -> impl Future<Output = Result<()>> + Send;
+}
+
+/// Runs a single attempt, failed attempts are retried through the backend which
+/// picks the job up again once `T::BACKOFF` has passed
+async fn execute<T: WorkerTrait>(
+    job: T,
+    state: &T::State,
+    progress: &Progress,
+    limiter: Option<&Limiter>,
+) -> Result<()> {
+    if let Some(limiter) = limiter {
+        limiter.acquire().await;
+    }
+    match tokio::time::timeout(T::TIMEOUT, T::execute(job, state, progress)).await {
+        Ok(result) => result,
+        Err(_) => Err(WorkerError::Timeout),
+    }
 }
@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use rand::Rng as _;
+
+use tokio::{sync::Mutex, time::Instant};
+
+/// How long a worker waits before retrying a failed job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backoff {
+    Fixed(Duration),
+    /// `base * 2^retry` capped at `max`, with jitter a random delay up to that
+    Exponential {
+        base: Duration,
+        max: Duration,
+        jitter: bool,
+    },
+}
+
+impl Backoff {
+    /// Retries right away, what workers did before backoff existed
+    pub const NONE: Self = Self::Fixed(Duration::ZERO);
+
+    /// Delay before retry number `retry`, counting from zero
+    pub fn delay(&self, retry: u32) -> Duration {
+        match *self {
+            Self::Fixed(delay) => delay,
+            Self::Exponential { base, max, jitter } => {
+                let delay = base
+                    .checked_mul(2u32.saturating_pow(retry))
+                    .map_or(max, |delay| delay.min(max));
+                if jitter { jittered(delay) } else { delay }
+            }
+        }
+    }
+}
+
+/// At most `num` jobs started per `per`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub num: u32,
+    pub per: Duration,
+}
+
+impl RateLimit {
+    pub const fn new(num: u32, per: Duration) -> Self {
+        Self { num, per }
+    }
+}
+
+/// Fixed window limiter shared by the concurrent executions of one worker,
+/// the limit holds per process
+#[derive(Debug)]
+pub(crate) struct Limiter {
+    limit: RateLimit,
+    window: Mutex<(Instant, u32)>,
+}
+
+impl Limiter {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            window: Mutex::new((Instant::now(), 0)),
+        }
+    }
+
+    pub(crate) async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut window = self.window.lock().await;
+                let now = Instant::now();
+                if now.duration_since(window.0) >= self.limit.per {
+                    *window = (now, 0);
+                }
+                if window.1 < self.limit.num {
+                    window.1 += 1;
+                    return;
+                }
+                self.limit.per - now.duration_since(window.0)
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Full jitter, a random delay between zero and `delay`
+fn jittered(delay: Duration) -> Duration {
+    let millis = delay.as_millis() as u64;
+    if millis == 0 {
+        return delay;
+    }
+    Duration::from_millis(rand::rng().random_range(0..=millis))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exponential_backoff_is_capped() {
+        let backoff = Backoff::Exponential {
+            base: Duration::from_secs(1),
+            max: Duration::from_secs(10),
+            jitter: false,
+        };
+        assert_eq!(backoff.delay(0), Duration::from_secs(1));
+        assert_eq!(backoff.delay(3), Duration::from_secs(8));
+        assert_eq!(backoff.delay(4), Duration::from_secs(10));
+        assert_eq!(backoff.delay(64), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_jitter_stays_below_delay() {
+        let backoff = Backoff::Exponential {
+            base: Duration::from_millis(100),
+            max: Duration::from_secs(1),
+            jitter: true,
+        };
+        assert!(backoff.delay(2) <= Duration::from_millis(400));
+    }
+
+    #[tokio::test]
+    async fn test_limiter_waits_for_next_window() {
+        let limiter = Limiter::new(RateLimit::new(2, Duration::from_millis(200)));
+        let started = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+        assert!(started.elapsed() >= Duration::from_millis(200));
+    }
+}
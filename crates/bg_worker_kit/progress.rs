@@ -0,0 +1,227 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(not(feature = "redis"))]
+use serde::Serialize;
+
+#[cfg(not(feature = "redis"))]
+use crate::WorkerTrait;
+use crate::{
+    Pool,
+    error::{Result, WorkerError},
+};
+
+#[cfg(feature = "sqlite")]
+mod sql {
+    pub const SETUP: &str = r#"
+        CREATE TABLE IF NOT EXISTS JobProgress (
+            job_id TEXT PRIMARY KEY NOT NULL,
+            job_type TEXT NOT NULL,
+            owner TEXT,
+            title TEXT,
+            percent INTEGER NOT NULL DEFAULT 0,
+            message TEXT,
+            result TEXT,
+            cancel_requested INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS JobProgress_owner ON JobProgress (owner, created_at);
+    "#;
+    pub const TRACK: &str = r#"
+        INSERT INTO JobProgress (job_id, job_type, owner, title, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $5)
+        ON CONFLICT (job_id) DO UPDATE SET owner = excluded.owner, title = excluded.title
+    "#;
+    pub const REPORT: &str = r#"
+        INSERT INTO JobProgress (job_id, job_type, percent, message, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $5)
+        ON CONFLICT (job_id) DO UPDATE SET percent = excluded.percent,
+            message = excluded.message, updated_at = excluded.updated_at
+    "#;
+    pub const RESULT: &str = r#"
+        INSERT INTO JobProgress (job_id, job_type, result, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $4)
+        ON CONFLICT (job_id) DO UPDATE SET result = excluded.result,
+            updated_at = excluded.updated_at
+    "#;
+    pub const CANCELLED: &str = "SELECT cancel_requested FROM JobProgress WHERE job_id = $1";
+}
+
+#[cfg(feature = "postgres")]
+mod sql {
+    pub const SETUP: &str = r#"
+        CREATE TABLE IF NOT EXISTS apalis.job_progress (
+            job_id TEXT PRIMARY KEY NOT NULL,
+            job_type TEXT NOT NULL,
+            owner TEXT,
+            title TEXT,
+            percent INTEGER NOT NULL DEFAULT 0,
+            message TEXT,
+            result TEXT,
+            cancel_requested BOOLEAN NOT NULL DEFAULT FALSE,
+            created_at BIGINT NOT NULL,
+            updated_at BIGINT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS job_progress_owner ON apalis.job_progress (owner, created_at);
+    "#;
+    pub const TRACK: &str = r#"
+        INSERT INTO apalis.job_progress (job_id, job_type, owner, title, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $5)
+        ON CONFLICT (job_id) DO UPDATE SET owner = excluded.owner, title = excluded.title
+    "#;
+    pub const REPORT: &str = r#"
+        INSERT INTO apalis.job_progress (job_id, job_type, percent, message, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $5)
+        ON CONFLICT (job_id) DO UPDATE SET percent = excluded.percent,
+            message = excluded.message, updated_at = excluded.updated_at
+    "#;
+    pub const RESULT: &str = r#"
+        INSERT INTO apalis.job_progress (job_id, job_type, result, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $4)
+        ON CONFLICT (job_id) DO UPDATE SET result = excluded.result,
+            updated_at = excluded.updated_at
+    "#;
+    pub const CANCELLED: &str =
+        "SELECT cancel_requested FROM apalis.job_progress WHERE job_id = $1";
+}
+
+#[cfg(feature = "redis")]
+pub(crate) mod redis;
+#[cfg(feature = "redis")]
+pub(crate) use self::redis::track;
+
+#[cfg(not(feature = "redis"))]
+pub(crate) async fn setup(pool: &Pool) -> Result<()> {
+    sqlx::raw_sql(sql::SETUP).execute(pool).await?;
+    Ok(())
+}
+
+/// Starts tracking a job on behalf of `owner` so it shows up in their task list.
+/// The worker may already have reported on it, that progress is kept
+#[cfg(not(feature = "redis"))]
+pub(crate) async fn track<T: WorkerTrait>(
+    pool: &Pool,
+    job_id: &str,
+    owner: &str,
+    title: Option<&str>,
+) -> Result<()> {
+    sqlx::query(sql::TRACK)
+        .bind(job_id)
+        .bind(T::NAME)
+        .bind(owner)
+        .bind(title)
+        .bind(epoch())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Handed to `WorkerTrait::execute` so a long running job can tell how far it got
+/// and notice that its owner cancelled it
+#[derive(Clone)]
+pub struct Progress {
+    pool: Pool,
+    job_id: String,
+    job_type: &'static str,
+}
+
+impl std::fmt::Debug for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Progress")
+            .field("job_id", &self.job_id)
+            .field("job_type", &self.job_type)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Progress {
+    pub(crate) fn new(pool: Pool, job_id: String, job_type: &'static str) -> Self {
+        Self {
+            pool,
+            job_id,
+            job_type,
+        }
+    }
+
+    pub fn job_id(&self) -> &str {
+        &self.job_id
+    }
+
+    /// Fails with `WorkerError::Cancelled` once cancelling was requested, call it between batches
+    pub async fn check_cancelled(&self) -> Result<()> {
+        if self.is_cancelled().await? {
+            return Err(WorkerError::Cancelled);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "redis"))]
+impl Progress {
+    /// `percent` is clamped to 100
+    pub async fn report(&self, percent: u8, message: impl AsRef<str>) -> Result<()> {
+        sqlx::query(sql::REPORT)
+            .bind(&self.job_id)
+            .bind(self.job_type)
+            .bind(percent.min(100) as i32)
+            .bind(message.as_ref())
+            .bind(epoch())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Stores a partial or final result, a later call replaces it
+    pub async fn result<R: Serialize>(&self, result: &R) -> Result<()> {
+        sqlx::query(sql::RESULT)
+            .bind(&self.job_id)
+            .bind(self.job_type)
+            .bind(serde_json::to_string(result)?)
+            .bind(epoch())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn is_cancelled(&self) -> Result<bool> {
+        let cancelled: Option<bool> = sqlx::query_scalar(sql::CANCELLED)
+            .bind(&self.job_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(cancelled.unwrap_or_default())
+    }
+}
+
+pub(crate) fn epoch() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{Echo, db_test, echo_queue};
+
+    async fn track_after_report(pool: Pool) {
+        let (manager, queue) = echo_queue(pool.clone()).await;
+        let id = queue.push(Echo { n: 1 }).await.unwrap();
+        // the worker got to the job before its owner was recorded
+        let progress = Progress::new(pool.clone(), id.clone(), <Echo as crate::WorkerTrait>::NAME);
+        progress.report(40, "halfway").await.unwrap();
+        track::<Echo>(&pool, &id, "alice", Some("echo 1"))
+            .await
+            .unwrap();
+
+        let task = manager.job_store().get_task("alice", &id).await.unwrap();
+        let task = task.unwrap();
+        assert_eq!(task.title.as_deref(), Some("echo 1"));
+        assert_eq!(task.percent, 40);
+        assert_eq!(task.message.as_deref(), Some("halfway"));
+    }
+    db_test!(
+        test_track_keep_progress_given_reported_first,
+        track_after_report
+    );
+}
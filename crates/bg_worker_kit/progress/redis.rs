@@ -0,0 +1,92 @@
+use std::any::type_name;
+
+use redis::AsyncCommands as _;
+use serde::Serialize;
+
+use super::{Progress, epoch};
+use crate::{Pool, WorkerTrait, error::Result, redis_helper::PREFIX};
+
+/// A hash per job with the fields of the sql `job_progress` table, plus the
+/// `namespace` of its queue
+pub(crate) fn progress_key(job_id: &str) -> String {
+    format!("{PREFIX}:job_progress:{job_id}")
+}
+
+/// Job ids of `owner` scored by when they were pushed
+pub(crate) fn owner_key(owner: &str) -> String {
+    format!("{PREFIX}:job_progress:owner:{owner}")
+}
+
+/// Starts tracking a job on behalf of `owner` so it shows up in their task list.
+/// The worker may already have reported on it, that progress is kept
+pub(crate) async fn track<T: WorkerTrait>(
+    pool: &Pool,
+    job_id: &str,
+    owner: &str,
+    title: Option<&str>,
+) -> Result<()> {
+    let now = epoch();
+    let key = progress_key(job_id);
+    let mut fields = vec![
+        ("job_type", T::NAME.to_string()),
+        ("namespace", type_name::<T>().to_string()),
+        ("owner", owner.to_string()),
+    ];
+    if let Some(title) = title {
+        fields.push(("title", title.to_string()));
+    }
+    let mut conn = pool.clone();
+    let _: () = redis::pipe()
+        .atomic()
+        .hset_multiple(&key, &fields)
+        .hset_nx(&key, "percent", 0)
+        .hset_nx(&key, "cancel_requested", 0)
+        .hset_nx(&key, "created_at", now)
+        .hset_nx(&key, "updated_at", now)
+        .zadd(owner_key(owner), job_id, now)
+        .query_async(&mut conn)
+        .await?;
+    Ok(())
+}
+
+impl Progress {
+    /// `percent` is clamped to 100
+    pub async fn report(&self, percent: u8, message: impl AsRef<str>) -> Result<()> {
+        let fields = [
+            ("percent", percent.min(100).to_string()),
+            ("message", message.as_ref().to_string()),
+        ];
+        self.write(&fields).await
+    }
+
+    /// Stores a partial or final result, a later call replaces it
+    pub async fn result<R: Serialize>(&self, result: &R) -> Result<()> {
+        self.write(&[("result", serde_json::to_string(result)?)])
+            .await
+    }
+
+    pub async fn is_cancelled(&self) -> Result<bool> {
+        let cancelled: Option<i64> = self
+            .pool
+            .clone()
+            .hget(progress_key(&self.job_id), "cancel_requested")
+            .await?;
+        Ok(cancelled.unwrap_or_default() != 0)
+    }
+
+    /// Jobs reporting without being tracked get their hash created on the way
+    async fn write(&self, fields: &[(&str, String)]) -> Result<()> {
+        let key = progress_key(&self.job_id);
+        let now = epoch();
+        let mut conn = self.pool.clone();
+        let _: () = redis::pipe()
+            .atomic()
+            .hset_nx(&key, "job_type", self.job_type)
+            .hset_nx(&key, "created_at", now)
+            .hset_multiple(&key, fields)
+            .hset(&key, "updated_at", now)
+            .query_async(&mut conn)
+            .await?;
+        Ok(())
+    }
+}
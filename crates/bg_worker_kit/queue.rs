@@ -0,0 +1,392 @@
+#[cfg(not(feature = "redis"))]
+use std::any::type_name;
+use std::time::Duration;
+
+use apalis::prelude::{Request, Storage, TaskId};
+use chrono::{DateTime, Utc};
+
+#[cfg(feature = "redis")]
+use crate::error::WorkerError;
+use crate::{
+    Pool, StorageBackend, WorkerTrait,
+    error::Result,
+    progress::{self, epoch},
+};
+
+/// A claim whose job never made it into the queue is given up after this long
+const CLAIM_TIMEOUT: i64 = 60;
+
+#[cfg(feature = "sqlite")]
+mod sql {
+    pub const SETUP: &str = r#"
+        CREATE TABLE IF NOT EXISTS JobKeys (
+            job_type TEXT NOT NULL,
+            unique_key TEXT NOT NULL,
+            job_id TEXT,
+            claimed_at INTEGER NOT NULL,
+            PRIMARY KEY (job_type, unique_key)
+        )
+    "#;
+    pub const RELEASE_STALE: &str = r#"
+        DELETE FROM JobKeys WHERE job_type = $1 AND unique_key = $2 AND (
+            (job_id IS NULL AND claimed_at < $3)
+            OR (job_id IS NOT NULL AND NOT EXISTS (
+                SELECT 1 FROM Jobs WHERE Jobs.id = JobKeys.job_id AND Jobs.status = 'Pending'
+            ))
+        )
+    "#;
+    pub const CLAIM: &str = r#"
+        INSERT INTO JobKeys (job_type, unique_key, claimed_at) VALUES ($1, $2, $3)
+        ON CONFLICT DO NOTHING
+    "#;
+    pub const BIND: &str = "UPDATE JobKeys SET job_id = $3 WHERE job_type = $1 AND unique_key = $2";
+    pub const RELEASE: &str = "DELETE FROM JobKeys WHERE job_type = $1 AND unique_key = $2";
+    pub const DELAY_RETRY: &str = "UPDATE Jobs SET run_at = $2 WHERE id = $1";
+}
+
+#[cfg(feature = "postgres")]
+mod sql {
+    pub const SETUP: &str = r#"
+        CREATE TABLE IF NOT EXISTS apalis.job_keys (
+            job_type TEXT NOT NULL,
+            unique_key TEXT NOT NULL,
+            job_id TEXT,
+            claimed_at BIGINT NOT NULL,
+            PRIMARY KEY (job_type, unique_key)
+        )
+    "#;
+    pub const RELEASE_STALE: &str = r#"
+        DELETE FROM apalis.job_keys k WHERE k.job_type = $1 AND k.unique_key = $2 AND (
+            (k.job_id IS NULL AND k.claimed_at < $3)
+            OR (k.job_id IS NOT NULL AND NOT EXISTS (
+                SELECT 1 FROM apalis.jobs j WHERE j.id = k.job_id AND j.status = 'Pending'
+            ))
+        )
+    "#;
+    pub const CLAIM: &str = r#"
+        INSERT INTO apalis.job_keys (job_type, unique_key, claimed_at) VALUES ($1, $2, $3)
+        ON CONFLICT DO NOTHING
+    "#;
+    pub const BIND: &str =
+        "UPDATE apalis.job_keys SET job_id = $3 WHERE job_type = $1 AND unique_key = $2";
+    pub const RELEASE: &str = "DELETE FROM apalis.job_keys WHERE job_type = $1 AND unique_key = $2";
+    pub const DELAY_RETRY: &str = "UPDATE apalis.jobs SET run_at = to_timestamp($2) WHERE id = $1";
+}
+
+#[cfg(feature = "redis")]
+mod redis;
+
+#[cfg(not(feature = "redis"))]
+pub(crate) async fn setup(pool: &Pool) -> Result<()> {
+    sqlx::query(sql::SETUP).execute(pool).await?;
+    Ok(())
+}
+
+/// Holds a failed job back until `run_at`, the backend marks it failed once the attempt
+/// returns and fetches it again from then on while it has attempts left
+#[cfg(not(feature = "redis"))]
+pub(crate) async fn delay_retry(pool: &Pool, id: &str, run_at: i64) -> Result<()> {
+    sqlx::query(sql::DELAY_RETRY)
+        .bind(id)
+        .bind(run_at)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+#[cfg(feature = "redis")]
+pub(crate) use redis::delay_retry;
+
+/// When and how a job is pushed, the default runs it right away
+#[derive(Debug, Clone, Default)]
+pub struct EnqueueOptions {
+    run_at: Option<i64>,
+    priority: Option<i32>,
+    unique_key: Option<String>,
+    owner: Option<String>,
+    title: Option<String>,
+}
+
+impl EnqueueOptions {
+    pub fn at(at: DateTime<Utc>) -> Self {
+        Self {
+            run_at: Some(at.timestamp()),
+            ..Default::default()
+        }
+    }
+
+    pub fn delay(delay: Duration) -> Self {
+        Self {
+            run_at: Some(epoch() + delay.as_secs() as i64),
+            ..Default::default()
+        }
+    }
+
+    /// Overrides the worker's `PRIORITY`, urgent jobs jump the queue.
+    /// The redis backend keeps plain FIFO order and refuses the push
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Tracks the job as a task of `owner`, listed with its progress by
+    /// `JobStore::list_tasks` and cancellable by them
+    pub fn owner(mut self, owner: impl Into<String>, title: impl Into<String>) -> Self {
+        self.owner = Some(owner.into());
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Skips the push while a pending job of the same worker holds `key`
+    pub fn unique(mut self, key: impl Into<String>) -> Self {
+        self.unique_key = Some(key.into());
+        self
+    }
+}
+
+/// The queue of one worker, returned by `WorkerManager::register`
+#[derive(Clone)]
+pub struct Queue<T> {
+    backend: StorageBackend<T>,
+    pool: Pool,
+}
+
+impl<T> std::fmt::Debug for Queue<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Queue")
+            .field("job_type", &std::any::type_name::<T>())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: WorkerTrait> Queue<T> {
+    pub(crate) fn new(backend: StorageBackend<T>, pool: Pool) -> Self {
+        Self { backend, pool }
+    }
+
+    pub fn backend(&self) -> &StorageBackend<T> {
+        &self.backend
+    }
+
+    /// Returns the id of the pushed job
+    pub async fn push(&self, job: T) -> Result<String> {
+        self.push_with(job, None, T::PRIORITY).await
+    }
+
+    pub async fn push_at(&self, job: T, at: DateTime<Utc>) -> Result<String> {
+        self.push_with(job, Some(at.timestamp()), T::PRIORITY).await
+    }
+
+    pub async fn push_in(&self, job: T, delay: Duration) -> Result<String> {
+        let run_at = epoch() + delay.as_secs() as i64;
+        self.push_with(job, Some(run_at), T::PRIORITY).await
+    }
+
+    /// Returns `None` when the job was deduplicated against a pending one
+    pub async fn enqueue(&self, job: T, options: EnqueueOptions) -> Result<Option<String>> {
+        let priority = options.priority.unwrap_or(T::PRIORITY);
+        let Some(key) = options.unique_key.clone() else {
+            let id = self.push_with(job, options.run_at, priority).await?;
+            self.track(&id, &options).await?;
+            return Ok(Some(id));
+        };
+        if !self.claim(&key).await? {
+            tracing::debug!(key, "Skipping job, a pending one holds the key");
+            return Ok(None);
+        }
+        match self.push_with(job, options.run_at, priority).await {
+            Ok(id) => {
+                self.bind(&key, &id).await?;
+                self.track(&id, &options).await?;
+                Ok(Some(id))
+            }
+            Err(err) => {
+                self.release(&key).await?;
+                Err(err)
+            }
+        }
+    }
+
+    async fn push_with(&self, job: T, run_at: Option<i64>, priority: i32) -> Result<String> {
+        self.push_as(job, TaskId::new(), run_at, priority).await
+    }
+
+    /// Pushes under an id chosen by the caller, who can record it beforehand
+    pub(crate) async fn push_as(
+        &self,
+        job: T,
+        task_id: TaskId,
+        run_at: Option<i64>,
+        priority: i32,
+    ) -> Result<String> {
+        // rather than losing the order the caller asked for
+        #[cfg(feature = "redis")]
+        if priority != 0 {
+            return Err(WorkerError::Custom(format!(
+                "the redis backend can't prioritize jobs, {} asked for priority {priority}",
+                T::NAME
+            )));
+        }
+        let mut request: Request<T, <StorageBackend<T> as Storage>::Context> = Request::new(job);
+        request.parts.task_id = task_id;
+        #[cfg(not(feature = "redis"))]
+        request.parts.context.set_priority(priority);
+        // the backend requeues a failed job until its attempts run out
+        #[cfg(not(feature = "redis"))]
+        request
+            .parts
+            .context
+            .set_max_attempts((T::RETRIES + 1) as _);
+        #[cfg(feature = "redis")]
+        {
+            request.parts.context = crate::redis_helper::context_with_max_attempts(T::RETRIES + 1)?;
+        }
+        let mut backend = self.backend.clone();
+        let parts = match run_at {
+            Some(run_at) => backend.schedule_request(request, run_at).await?,
+            None => backend.push_request(request).await?,
+        };
+        Ok(parts.task_id.to_string())
+    }
+
+    async fn track(&self, id: &str, options: &EnqueueOptions) -> Result<()> {
+        if let Some(owner) = &options.owner {
+            progress::track::<T>(&self.pool, id, owner, options.title.as_deref()).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "redis"))]
+impl<T: WorkerTrait> Queue<T> {
+    /// Frees the key if its job already left the pending state, then tries to take it
+    async fn claim(&self, key: &str) -> Result<bool> {
+        let now = epoch();
+        sqlx::query(sql::RELEASE_STALE)
+            .bind(type_name::<T>())
+            .bind(key)
+            .bind(now - CLAIM_TIMEOUT)
+            .execute(&self.pool)
+            .await?;
+        let result = sqlx::query(sql::CLAIM)
+            .bind(type_name::<T>())
+            .bind(key)
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn bind(&self, key: &str, id: &str) -> Result<()> {
+        sqlx::query(sql::BIND)
+            .bind(type_name::<T>())
+            .bind(key)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn release(&self, key: &str) -> Result<()> {
+        sqlx::query(sql::RELEASE)
+            .bind(type_name::<T>())
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{Echo, db_test, echo_queue, set_status};
+
+    async fn enqueue_unique(pool: Pool) {
+        let (manager, queue) = echo_queue(pool.clone()).await;
+        let store = manager.job_store();
+        let options = || EnqueueOptions::default().unique("report");
+        let first = queue.enqueue(Echo { n: 1 }, options()).await.unwrap();
+        let first = first.unwrap();
+        assert_eq!(queue.enqueue(Echo { n: 2 }, options()).await.unwrap(), None);
+        // keys are per worker and per key
+        let other = EnqueueOptions::default().unique("other");
+        assert!(queue.enqueue(Echo { n: 3 }, other).await.unwrap().is_some());
+
+        // the key is free again once its job left the pending state
+        set_status(&pool, &first, "Running").await;
+        let second = queue.enqueue(Echo { n: 4 }, options()).await.unwrap();
+        let second = second.unwrap();
+        assert_ne!(second, first);
+        assert_eq!(queue.enqueue(Echo { n: 5 }, options()).await.unwrap(), None);
+        let job = store.get::<Echo>(&second).await.unwrap().unwrap();
+        assert_eq!(job.payload, serde_json::json!({"n": 4}));
+    }
+    db_test!(
+        test_enqueue_skip_given_pending_job_holds_key,
+        enqueue_unique
+    );
+
+    async fn enqueue_at(pool: Pool) {
+        let (manager, queue) = echo_queue(pool).await;
+        let store = manager.job_store();
+        let at = Utc::now() + chrono::Duration::hours(1);
+        let id = queue
+            .enqueue(Echo { n: 1 }, EnqueueOptions::at(at))
+            .await
+            .unwrap()
+            .unwrap();
+        let job = store.get::<Echo>(&id).await.unwrap().unwrap();
+        assert_eq!(job.run_at, Some(at.timestamp()));
+
+        let before = epoch();
+        let options = EnqueueOptions::delay(Duration::from_secs(600));
+        let id = queue
+            .enqueue(Echo { n: 2 }, options)
+            .await
+            .unwrap()
+            .unwrap();
+        let run_at = store
+            .get::<Echo>(&id)
+            .await
+            .unwrap()
+            .unwrap()
+            .run_at
+            .unwrap();
+        assert!((before + 600..=epoch() + 600).contains(&run_at));
+
+        let id = queue
+            .push_in(Echo { n: 3 }, Duration::from_secs(60))
+            .await
+            .unwrap();
+        let run_at = store
+            .get::<Echo>(&id)
+            .await
+            .unwrap()
+            .unwrap()
+            .run_at
+            .unwrap();
+        assert!(run_at >= before + 60);
+    }
+    db_test!(test_enqueue_schedule_given_run_at, enqueue_at);
+
+    #[cfg(feature = "redis")]
+    async fn enqueue_priority(pool: Pool) {
+        let (manager, queue) = echo_queue(pool).await;
+        let options = EnqueueOptions::default().priority(5);
+        assert!(queue.enqueue(Echo { n: 1 }, options).await.is_err());
+        let (total, _) = manager
+            .job_store()
+            .list::<Echo>(&apalis::prelude::State::Pending, 1, 10)
+            .await
+            .unwrap();
+        assert_eq!(total, 0);
+        let options = EnqueueOptions::default().priority(0);
+        assert!(queue.enqueue(Echo { n: 2 }, options).await.is_ok());
+    }
+    #[cfg(feature = "redis")]
+    db_test!(
+        test_enqueue_return_err_given_priority_on_redis,
+        enqueue_priority
+    );
+}
@@ -0,0 +1,58 @@
+use std::any::type_name;
+
+use redis::{AsyncCommands as _, SetExpiry, SetOptions};
+
+use super::{CLAIM_TIMEOUT, Queue};
+use crate::{
+    WorkerTrait,
+    error::Result,
+    redis_helper::{PREFIX, job_status},
+};
+
+/// A claim holds no job id until the push went through and expires on its own
+/// should the push never happen
+impl<T: WorkerTrait> Queue<T> {
+    pub(super) async fn claim(&self, key: &str) -> Result<bool> {
+        let key = unique_key::<T>(key);
+        let mut conn = self.pool.clone();
+        if try_claim(&mut conn, &key).await? {
+            return Ok(true);
+        }
+        let holder: Option<String> = conn.get(&key).await?;
+        let Some(holder) = holder.filter(|holder| !holder.is_empty()) else {
+            return Ok(false);
+        };
+        if job_status(&mut conn, type_name::<T>(), &holder).await? == Some("Pending") {
+            return Ok(false);
+        }
+        let _: () = conn.del(&key).await?;
+        try_claim(&mut conn, &key).await
+    }
+
+    pub(super) async fn bind(&self, key: &str, id: &str) -> Result<()> {
+        let _: () = self.pool.clone().set(unique_key::<T>(key), id).await?;
+        Ok(())
+    }
+
+    pub(super) async fn release(&self, key: &str) -> Result<()> {
+        let _: () = self.pool.clone().del(unique_key::<T>(key)).await?;
+        Ok(())
+    }
+}
+
+async fn try_claim(conn: &mut crate::Pool, key: &str) -> Result<bool> {
+    let options = SetOptions::default()
+        .conditional_set(redis::ExistenceCheck::NX)
+        .with_expiration(SetExpiry::EX(CLAIM_TIMEOUT as u64));
+    let claimed: Option<String> = conn.set_options(key, "", options).await?;
+    Ok(claimed.is_some())
+}
+
+fn unique_key<T: WorkerTrait>(key: &str) -> String {
+    format!("{PREFIX}:job_keys:{}:{key}", type_name::<T>())
+}
+
+/// Redis requeues a failed job right away and keeps no run time for it to delay
+pub(crate) async fn delay_retry(_pool: &crate::Pool, _id: &str, _run_at: i64) -> Result<()> {
+    Ok(())
+}
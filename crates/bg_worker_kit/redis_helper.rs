@@ -0,0 +1,141 @@
+use apalis_redis::{Config, RedisContext};
+use redis::{AsyncCommands as _, Client, cmd};
+
+use crate::{Pool, error::Result};
+
+/// Prefix of the keys the kit keeps next to the apalis queues
+pub(crate) const PREFIX: &str = "apalis";
+
+pub async fn new_redis_pool(url: impl AsRef<str>) -> Result<Pool> {
+    let client = Client::open(url.as_ref())?;
+    let pool = client.get_connection_manager().await?;
+    Ok(pool)
+}
+
+/// `RedisContext` keeps its attempts private, they are only reachable through the
+/// form it is stored in
+pub(crate) fn context_with_max_attempts(max_attempts: usize) -> Result<RedisContext> {
+    let context =
+        serde_json::json!({ "max_attempts": max_attempts, "lock_by": null, "run_at": null });
+    Ok(serde_json::from_value(context)?)
+}
+
+pub(crate) fn max_attempts(context: &RedisContext) -> Result<usize> {
+    let context = serde_json::to_value(context)?;
+    Ok(context["max_attempts"].as_u64().unwrap_or_default() as usize)
+}
+
+/// Where a job sits in the queues of `namespace`, `None` once it is gone.
+/// Redis keeps no status per job, so it is read from the set holding it
+pub(crate) async fn job_status(
+    conn: &mut Pool,
+    namespace: &str,
+    id: &str,
+) -> Result<Option<&'static str>> {
+    let config = Config::default().set_namespace(namespace);
+    let exists: bool = conn.hexists(config.job_data_hash(), id).await?;
+    if !exists {
+        return Ok(None);
+    }
+    let sets = [
+        (config.done_jobs_set(), "Done"),
+        (config.failed_jobs_set(), "Failed"),
+        (config.dead_jobs_set(), "Killed"),
+        (config.scheduled_jobs_set(), "Pending"),
+    ];
+    for (set, status) in sets {
+        let score: Option<f64> = conn.zscore(set, id).await?;
+        if score.is_some() {
+            return Ok(Some(status));
+        }
+    }
+    let position: Option<i64> = cmd("LPOS")
+        .arg(config.active_jobs_list())
+        .arg(id)
+        .query_async(conn)
+        .await?;
+    Ok(Some(if position.is_some() {
+        "Pending"
+    } else {
+        "Running"
+    }))
+}
+
+/// Epoch seconds a job entered its final set
+pub(crate) async fn done_at(conn: &mut Pool, namespace: &str, id: &str) -> Result<Option<i64>> {
+    let config = Config::default().set_namespace(namespace);
+    for set in [
+        config.done_jobs_set(),
+        config.failed_jobs_set(),
+        config.dead_jobs_set(),
+    ] {
+        let score: Option<f64> = conn.zscore(set, id).await?;
+        if let Some(score) = score {
+            return Ok(Some(score as i64));
+        }
+    }
+    Ok(None)
+}
+
+/// Takes a pending job out of the queue and files it as killed, false when it
+/// was no longer pending
+pub(crate) async fn kill_pending(conn: &mut Pool, namespace: &str, id: &str) -> Result<bool> {
+    let config = Config::default().set_namespace(namespace);
+    let listed: i64 = conn.lrem(config.active_jobs_list(), 0, id).await?;
+    let scheduled: i64 = conn.zrem(config.scheduled_jobs_set(), id).await?;
+    if listed + scheduled == 0 {
+        return Ok(false);
+    }
+    let _: () = conn
+        .zadd(config.dead_jobs_set(), id, crate::progress::epoch())
+        .await?;
+    Ok(true)
+}
+
+/// Runs against the redis at `TEST_REDIS_URL`, see `test_utils::redis_pool`
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use apalis::prelude::State;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::{JobStore, Progress, WorkerManager, WorkerTrait};
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Ping {
+        n: u32,
+    }
+
+    impl WorkerTrait for Ping {
+        type State = ();
+        const NAME: &'static str = "redis_helper_ping";
+        const CONCURRENCY: usize = 1;
+        const RETRIES: usize = 0;
+        const TIMEOUT: Duration = Duration::from_secs(5);
+
+        async fn execute(_job: Self, _state: &(), _progress: &Progress) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pending_job_round_trip() {
+        let _guard = crate::test_utils::REDIS_LOCK.lock().await;
+        let pool = crate::test_utils::redis_pool().await;
+        let (manager, queue) = WorkerManager::try_new(pool.clone())
+            .await
+            .unwrap()
+            .register::<Ping>(());
+        let id = queue.push(Ping { n: 1 }).await.unwrap();
+        let store: JobStore = manager.job_store();
+        let job = store.get::<Ping>(&id).await.unwrap().unwrap();
+        assert_eq!(job.status, "Pending");
+        assert_eq!(job.payload["n"], 1);
+        let (total, _) = store.list::<Ping>(&State::Pending, 1, 10).await.unwrap();
+        assert!(total >= 1);
+        assert_eq!(store.delete::<Ping>(&[id.clone()]).await.unwrap(), 1);
+        assert!(store.get::<Ping>(&id).await.unwrap().is_none());
+    }
+}
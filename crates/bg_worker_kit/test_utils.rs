@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Pool, Progress, Queue, WorkerManager, WorkerTrait, error::Result};
+
+#[cfg(feature = "sqlite")]
+const SET_STATUS: &str = "UPDATE Jobs SET status = $2 WHERE id = $1";
+#[cfg(feature = "postgres")]
+const SET_STATUS: &str = "UPDATE apalis.jobs SET status = $2 WHERE id = $1";
+
+/// Redis tests share one database, they take turns and start from an empty one
+#[cfg(feature = "redis")]
+pub(crate) static REDIS_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// The redis at `TEST_REDIS_URL`, flushed, database 15 of a local one by default
+#[cfg(feature = "redis")]
+pub(crate) async fn redis_pool() -> Pool {
+    let url = std::env::var("TEST_REDIS_URL").unwrap_or("redis://127.0.0.1:6379/15".to_string());
+    let mut pool = crate::redis_helper::new_redis_pool(url).await.unwrap();
+    let _: () = redis::cmd("FLUSHDB").query_async(&mut pool).await.unwrap();
+    pool
+}
+
+/// Declares a test running `$body` against a fresh database of the backend the crate
+/// is built for
+macro_rules! db_test {
+    ($name:ident, $body:path) => {
+        #[cfg(feature = "sqlite")]
+        #[tokio::test]
+        async fn $name() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = format!("sqlite://{}", dir.path().join("jobs.db").display());
+            let pool = $crate::sqlite_helper::new_sqlite_pool(path).await.unwrap();
+            $body(pool).await;
+        }
+
+        #[cfg(feature = "postgres")]
+        #[sqlx::test(migrations = false)]
+        async fn $name(pool: $crate::Pool) {
+            $body(pool).await;
+        }
+
+        #[cfg(feature = "redis")]
+        #[tokio::test]
+        async fn $name() {
+            let _guard = $crate::test_utils::REDIS_LOCK.lock().await;
+            $body($crate::test_utils::redis_pool().await).await;
+        }
+    };
+}
+pub(crate) use db_test;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Echo {
+    pub n: i32,
+}
+
+impl WorkerTrait for Echo {
+    type State = ();
+    const NAME: &'static str = "echo";
+    const CONCURRENCY: usize = 1;
+    const RETRIES: usize = 0;
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    async fn execute(_job: Self, _state: &Self::State, _progress: &Progress) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Sets up the schema and registers `Echo`, its jobs are never picked up
+pub(crate) async fn echo_queue(pool: Pool) -> (WorkerManager, Queue<Echo>) {
+    let manager = WorkerManager::try_new(pool).await.unwrap();
+    manager.register::<Echo>(())
+}
+
+/// Moves a job along as a worker would
+#[cfg(not(feature = "redis"))]
+pub(crate) async fn set_status(pool: &Pool, id: &str, status: &str) {
+    sqlx::query(SET_STATUS)
+        .bind(id)
+        .bind(status)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+/// Moves a job of `Echo` along as a worker would, out of the queue and into the set
+/// of its final state, a running job sits in neither
+#[cfg(feature = "redis")]
+pub(crate) async fn set_status(pool: &Pool, id: &str, status: &str) {
+    use redis::AsyncCommands as _;
+
+    let config = apalis_redis::Config::default().set_namespace(std::any::type_name::<Echo>());
+    let mut conn = pool.clone();
+    let _: () = redis::pipe()
+        .lrem(config.active_jobs_list(), 0, id)
+        .zrem(config.scheduled_jobs_set(), id)
+        .query_async(&mut conn)
+        .await
+        .unwrap();
+    let set = match status {
+        "Done" => config.done_jobs_set(),
+        "Failed" => config.failed_jobs_set(),
+        "Killed" => config.dead_jobs_set(),
+        _ => return,
+    };
+    let _: () = conn.zadd(set, id, crate::progress::epoch()).await.unwrap();
+}
@@ -0,0 +1,931 @@
+use std::{
+    any::type_name,
+    collections::HashMap,
+    pin::Pin,
+    sync::{Arc, PoisonError, RwLock},
+    time::Duration,
+};
+
+use apalis::prelude::TaskId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+
+use crate::{
+    Pool, Queue, WorkerTrait,
+    error::{Result, WorkerError},
+    progress::epoch,
+};
+
+#[cfg(feature = "sqlite")]
+mod sql {
+    pub const SETUP: &str = r#"
+        CREATE TABLE IF NOT EXISTS Workflows (
+            id TEXT PRIMARY KEY NOT NULL,
+            name TEXT NOT NULL,
+            status TEXT NOT NULL,
+            on_failure TEXT NOT NULL,
+            stage INTEGER NOT NULL DEFAULT 0,
+            stage_count INTEGER NOT NULL,
+            stages TEXT NOT NULL,
+            error TEXT,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS WorkflowJobs (
+            workflow_id TEXT NOT NULL,
+            stage INTEGER NOT NULL,
+            idx INTEGER NOT NULL,
+            job_id TEXT NOT NULL UNIQUE,
+            job_type TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'Pending',
+            PRIMARY KEY (workflow_id, stage, idx)
+        );
+    "#;
+    pub const INSERT: &str = r#"
+        INSERT INTO Workflows (id, name, status, on_failure, stage_count, stages, created_at, updated_at)
+        VALUES ($1, $2, 'Running', $3, $4, $5, $6, $6)
+    "#;
+    pub const INSERT_JOB: &str = r#"
+        INSERT INTO WorkflowJobs (workflow_id, stage, idx, job_id, job_type) VALUES ($1, $2, $3, $4, $5)
+    "#;
+    pub const FIND_JOB: &str = r#"
+        SELECT w.id, w.on_failure, w.stages, j.stage
+        FROM WorkflowJobs j JOIN Workflows w ON w.id = j.workflow_id
+        WHERE j.job_id = $1
+    "#;
+    pub const FINISH_JOB: &str = "UPDATE WorkflowJobs SET status = $2 WHERE job_id = $1";
+    pub const FAIL: &str = r#"
+        UPDATE Workflows SET status = 'Failed', error = $2, updated_at = $3
+        WHERE id = $1 AND status = 'Running'
+    "#;
+    pub const ADVANCE: &str = r#"
+        UPDATE Workflows SET stage = stage + 1, error = NULL, updated_at = $3,
+            status = CASE WHEN stage + 1 >= stage_count THEN 'Done' ELSE 'Running' END
+        WHERE id = $1 AND stage = $2 AND status != 'Done'
+            AND NOT EXISTS (
+                SELECT 1 FROM WorkflowJobs j
+                WHERE j.workflow_id = Workflows.id AND j.stage = Workflows.stage
+                    AND (j.status = 'Pending' OR (j.status = 'Failed' AND Workflows.on_failure = 'Abort'))
+            )
+    "#;
+    pub const OUTPUTS: &str = r#"
+        SELECT CASE WHEN j.status = 'Done' THEN p.result END AS output
+        FROM WorkflowJobs j LEFT JOIN JobProgress p ON p.job_id = j.job_id
+        WHERE j.workflow_id = $1 AND j.stage = $2
+        ORDER BY j.idx
+    "#;
+    pub const STALLED: &str = r#"
+        SELECT id, stage, stages, updated_at FROM Workflows
+        WHERE status = 'Running' AND updated_at <= $1
+    "#;
+    pub const TOUCH: &str = r#"
+        UPDATE Workflows SET updated_at = $4
+        WHERE id = $1 AND stage = $2 AND updated_at = $3 AND status = 'Running'
+    "#;
+    pub const COUNT: &str = "SELECT COUNT(*) FROM Workflows";
+    pub const LIST: &str = r#"
+        SELECT id, name, status, on_failure, stage, stage_count, error, created_at, updated_at
+        FROM Workflows ORDER BY created_at DESC LIMIT $1 OFFSET $2
+    "#;
+    pub const GET: &str = r#"
+        SELECT id, name, status, on_failure, stage, stage_count, error, created_at, updated_at
+        FROM Workflows WHERE id = $1
+    "#;
+    pub const JOBS: &str = r#"
+        SELECT w.stage, w.idx, w.job_id, w.job_type, w.status,
+            COALESCE(j.status, 'Deleted') AS job_status, j.last_error, p.result AS output
+        FROM WorkflowJobs w
+            LEFT JOIN Jobs j ON j.id = w.job_id
+            LEFT JOIN JobProgress p ON p.job_id = w.job_id
+        WHERE w.workflow_id = $1
+        ORDER BY w.stage, w.idx
+    "#;
+}
+
+#[cfg(feature = "postgres")]
+mod sql {
+    pub const SETUP: &str = r#"
+        CREATE TABLE IF NOT EXISTS apalis.workflows (
+            id TEXT PRIMARY KEY NOT NULL,
+            name TEXT NOT NULL,
+            status TEXT NOT NULL,
+            on_failure TEXT NOT NULL,
+            stage INTEGER NOT NULL DEFAULT 0,
+            stage_count INTEGER NOT NULL,
+            stages TEXT NOT NULL,
+            error TEXT,
+            created_at BIGINT NOT NULL,
+            updated_at BIGINT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS apalis.workflow_jobs (
+            workflow_id TEXT NOT NULL,
+            stage INTEGER NOT NULL,
+            idx INTEGER NOT NULL,
+            job_id TEXT NOT NULL UNIQUE,
+            job_type TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'Pending',
+            PRIMARY KEY (workflow_id, stage, idx)
+        );
+    "#;
+    pub const INSERT: &str = r#"
+        INSERT INTO apalis.workflows (id, name, status, on_failure, stage_count, stages, created_at, updated_at)
+        VALUES ($1, $2, 'Running', $3, $4, $5, $6, $6)
+    "#;
+    pub const INSERT_JOB: &str = r#"
+        INSERT INTO apalis.workflow_jobs (workflow_id, stage, idx, job_id, job_type)
+        VALUES ($1, $2, $3, $4, $5)
+    "#;
+    pub const FIND_JOB: &str = r#"
+        SELECT w.id, w.on_failure, w.stages, j.stage
+        FROM apalis.workflow_jobs j JOIN apalis.workflows w ON w.id = j.workflow_id
+        WHERE j.job_id = $1
+    "#;
+    pub const FINISH_JOB: &str = "UPDATE apalis.workflow_jobs SET status = $2 WHERE job_id = $1";
+    pub const FAIL: &str = r#"
+        UPDATE apalis.workflows SET status = 'Failed', error = $2, updated_at = $3
+        WHERE id = $1 AND status = 'Running'
+    "#;
+    pub const ADVANCE: &str = r#"
+        UPDATE apalis.workflows w SET stage = w.stage + 1, error = NULL, updated_at = $3,
+            status = CASE WHEN w.stage + 1 >= w.stage_count THEN 'Done' ELSE 'Running' END
+        WHERE w.id = $1 AND w.stage = $2 AND w.status != 'Done'
+            AND NOT EXISTS (
+                SELECT 1 FROM apalis.workflow_jobs j
+                WHERE j.workflow_id = w.id AND j.stage = w.stage
+                    AND (j.status = 'Pending' OR (j.status = 'Failed' AND w.on_failure = 'Abort'))
+            )
+    "#;
+    pub const OUTPUTS: &str = r#"
+        SELECT CASE WHEN j.status = 'Done' THEN p.result END AS output
+        FROM apalis.workflow_jobs j LEFT JOIN apalis.job_progress p ON p.job_id = j.job_id
+        WHERE j.workflow_id = $1 AND j.stage = $2
+        ORDER BY j.idx
+    "#;
+    pub const STALLED: &str = r#"
+        SELECT id, stage, stages, updated_at FROM apalis.workflows
+        WHERE status = 'Running' AND updated_at <= $1
+    "#;
+    pub const TOUCH: &str = r#"
+        UPDATE apalis.workflows SET updated_at = $4
+        WHERE id = $1 AND stage = $2 AND updated_at = $3 AND status = 'Running'
+    "#;
+    pub const COUNT: &str = "SELECT COUNT(*) FROM apalis.workflows";
+    pub const LIST: &str = r#"
+        SELECT id, name, status, on_failure, stage, stage_count, error, created_at, updated_at
+        FROM apalis.workflows ORDER BY created_at DESC LIMIT $1 OFFSET $2
+    "#;
+    pub const GET: &str = r#"
+        SELECT id, name, status, on_failure, stage, stage_count, error, created_at, updated_at
+        FROM apalis.workflows WHERE id = $1
+    "#;
+    pub const JOBS: &str = r#"
+        SELECT w.stage, w.idx, w.job_id, w.job_type, w.status,
+            COALESCE(j.status, 'Deleted') AS job_status, j.last_error, p.result AS output
+        FROM apalis.workflow_jobs w
+            LEFT JOIN apalis.jobs j ON j.id = w.job_id
+            LEFT JOIN apalis.job_progress p ON p.job_id = w.job_id
+        WHERE w.workflow_id = $1
+        ORDER BY w.stage, w.idx
+    "#;
+}
+
+#[cfg(not(feature = "redis"))]
+pub(crate) async fn setup(pool: &Pool) -> Result<()> {
+    sqlx::raw_sql(sql::SETUP).execute(pool).await?;
+    Ok(())
+}
+
+/// What happens to a workflow once one of its jobs failed for good
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FailurePolicy {
+    /// Stops before the next stage, the jobs already running still finish.
+    /// Retrying the failed jobs resumes the workflow
+    #[default]
+    Abort,
+    /// Treats the failed job as finished without output
+    Continue,
+}
+
+impl FailurePolicy {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Abort => "Abort",
+            Self::Continue => "Continue",
+        }
+    }
+}
+
+/// Where the jobs of a stage get their payloads from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum Stage {
+    /// One job, `None` runs it with the output of the previous stage
+    Job {
+        job_type: String,
+        payload: Option<Value>,
+    },
+    /// Parallel jobs, `None` runs one per element of the previous output
+    FanOut {
+        job_type: String,
+        payloads: Option<Vec<Value>>,
+    },
+}
+
+impl Stage {
+    fn job_type(&self) -> &str {
+        match self {
+            Self::Job { job_type, .. } | Self::FanOut { job_type, .. } => job_type,
+        }
+    }
+
+    fn payloads(&self, input: Value) -> Result<Vec<Value>> {
+        match self {
+            Self::Job {
+                payload: Some(payload),
+                ..
+            } => Ok(vec![payload.clone()]),
+            Self::Job { payload: None, .. } => Ok(vec![input]),
+            Self::FanOut {
+                payloads: Some(payloads),
+                ..
+            } => Ok(payloads.clone()),
+            Self::FanOut { payloads: None, .. } => match input {
+                Value::Array(items) => Ok(items),
+                _ => Err(WorkerError::Custom(
+                    "fan out expects the previous output to be an array".to_string(),
+                )),
+            },
+        }
+    }
+
+    /// A single job hands on its output, a fan out the outputs of all its jobs in order
+    fn output(&self, mut outputs: Vec<Value>) -> Value {
+        match self {
+            Self::Job { .. } => outputs.pop().unwrap_or_default(),
+            Self::FanOut { .. } => Value::Array(outputs),
+        }
+    }
+}
+
+/// Stages of jobs run one after another, a job's output is what it stored with
+/// `Progress::result`
+///
+/// ```ignore
+/// let workflow = Workflow::new("nightly_report")
+///     .job(CollectSections { day })
+///     .fan_out_each::<RenderSection>()
+///     .pipe::<PublishReport>()
+///     .on_failure(FailurePolicy::Abort);
+/// ```
+#[derive(Debug)]
+pub struct Workflow {
+    name: String,
+    on_failure: FailurePolicy,
+    stages: Vec<Stage>,
+    error: Option<serde_json::Error>,
+}
+
+impl Workflow {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            on_failure: FailurePolicy::default(),
+            stages: Vec::new(),
+            error: None,
+        }
+    }
+
+    pub fn on_failure(mut self, policy: FailurePolicy) -> Self {
+        self.on_failure = policy;
+        self
+    }
+
+    /// Runs `job` as it is once the previous stage finished
+    pub fn job<T: WorkerTrait>(mut self, job: T) -> Self {
+        let payload = self.payload(job);
+        self.stages.push(Stage::Job {
+            job_type: T::NAME.to_string(),
+            payload: Some(payload),
+        });
+        self
+    }
+
+    /// Runs a `T` deserialized from the previous output, after a fan out this is
+    /// the fan in callback receiving the array of outputs
+    pub fn pipe<T: WorkerTrait>(mut self) -> Self {
+        self.stages.push(Stage::Job {
+            job_type: T::NAME.to_string(),
+            payload: None,
+        });
+        self
+    }
+
+    /// Runs `jobs` in parallel
+    pub fn fan_out<T: WorkerTrait>(mut self, jobs: impl IntoIterator<Item = T>) -> Self {
+        let payloads = jobs.into_iter().map(|job| self.payload(job)).collect();
+        self.stages.push(Stage::FanOut {
+            job_type: T::NAME.to_string(),
+            payloads: Some(payloads),
+        });
+        self
+    }
+
+    /// Runs a `T` in parallel for every element of the previous output,
+    /// which has to be an array
+    pub fn fan_out_each<T: WorkerTrait>(mut self) -> Self {
+        self.stages.push(Stage::FanOut {
+            job_type: T::NAME.to_string(),
+            payloads: None,
+        });
+        self
+    }
+
+    fn payload<T: WorkerTrait>(&mut self, job: T) -> Value {
+        serde_json::to_value(job).unwrap_or_else(|err| {
+            self.error.get_or_insert(err);
+            Value::Null
+        })
+    }
+}
+
+type PushFn =
+    Arc<dyn Fn(Value, TaskId) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> + Send + Sync>;
+
+/// Pushes jobs by worker name, the workflow only knows its jobs as JSON.
+/// Next to the push lives the namespace the queue files the jobs under
+#[derive(Clone, Default)]
+pub(crate) struct Dispatcher(Arc<RwLock<HashMap<&'static str, (&'static str, PushFn)>>>);
+
+impl Dispatcher {
+    pub(crate) fn insert<T: WorkerTrait>(&self, queue: Queue<T>) {
+        let push: PushFn = Arc::new(move |payload, task_id| {
+            let queue = queue.clone();
+            Box::pin(async move {
+                let job: T = serde_json::from_value(payload)?;
+                queue.push_as(job, task_id, None, T::PRIORITY).await?;
+                Ok(())
+            })
+        });
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(T::NAME, (type_name::<T>(), push));
+    }
+
+    fn get(&self, job_type: &str) -> Option<PushFn> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(job_type)
+            .map(|(_, push)| push.clone())
+    }
+
+    #[cfg(feature = "redis")]
+    fn namespace(&self, job_type: &str) -> Option<&'static str> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(job_type)
+            .map(|(namespace, _)| *namespace)
+    }
+}
+
+/// A workflow with how far it got
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct WorkflowRecord {
+    pub id: String,
+    pub name: String,
+    /// `Running`, `Done` or `Failed`
+    pub status: String,
+    pub on_failure: String,
+    /// Index of the stage that is running, `stage_count` once done
+    pub stage: i32,
+    pub stage_count: i32,
+    pub error: Option<String>,
+    /// Epoch seconds
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// A job of a workflow, `status` is what the workflow made of it and
+/// `job_status` the state of the job in its queue
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct WorkflowJobRecord {
+    pub stage: i32,
+    pub idx: i32,
+    pub job_id: String,
+    pub job_type: String,
+    pub status: String,
+    pub job_status: String,
+    pub last_error: Option<String>,
+    /// JSON written by `Progress::result`
+    pub output: Option<String>,
+}
+
+#[derive(FromRow)]
+struct JobLink {
+    id: String,
+    on_failure: String,
+    stages: String,
+    stage: i32,
+}
+
+/// A running workflow nothing happened to for a while
+#[derive(FromRow)]
+struct Stalled {
+    id: String,
+    stage: i32,
+    stages: String,
+    updated_at: i64,
+}
+
+/// Starts workflows and moves them on as their jobs finish, returned by `WorkerManager::workflows`
+#[derive(Clone)]
+pub struct Workflows {
+    pool: Pool,
+    dispatcher: Dispatcher,
+}
+
+impl std::fmt::Debug for Workflows {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Workflows").finish_non_exhaustive()
+    }
+}
+
+impl Workflows {
+    pub(crate) fn new(pool: Pool, dispatcher: Dispatcher) -> Self {
+        Self { pool, dispatcher }
+    }
+
+    /// Persists the workflow and pushes the jobs of its first stage, returns its id
+    pub async fn start(&self, workflow: Workflow) -> Result<String> {
+        if let Some(err) = workflow.error {
+            return Err(err.into());
+        }
+        let Some(first) = workflow.stages.first() else {
+            return Err(WorkerError::Custom("workflow has no stages".to_string()));
+        };
+        if matches!(
+            first,
+            Stage::Job { payload: None, .. } | Stage::FanOut { payloads: None, .. }
+        ) {
+            return Err(WorkerError::Custom(
+                "the first stage of a workflow has no output to run with".to_string(),
+            ));
+        }
+        if let Some(stage) = workflow
+            .stages
+            .iter()
+            .find(|stage| self.dispatcher.get(stage.job_type()).is_none())
+        {
+            return Err(WorkerError::Custom(format!(
+                "worker {} is not registered",
+                stage.job_type()
+            )));
+        }
+        let id = TaskId::new().to_string();
+        self.insert(&id, &workflow).await?;
+        tracing::info!(id, name = workflow.name, "Workflow started");
+        self.enter(&id, &workflow.stages, 0, Value::Null).await?;
+        Ok(id)
+    }
+
+    /// Called after every job, moves the workflow of the job on once its stage is complete
+    pub(crate) async fn finished(&self, job_id: &str, result: &Result<()>) -> Result<()> {
+        let Some(link) = self.find_job(job_id).await? else {
+            return Ok(());
+        };
+        let status = if result.is_ok() { "Done" } else { "Failed" };
+        self.finish_job(job_id, status).await?;
+        if let Err(err) = result
+            && link.on_failure == FailurePolicy::Abort.as_str()
+        {
+            return self
+                .fail(&link.id, &format!("job {job_id} failed: {err}"))
+                .await;
+        }
+        let stages: Vec<Stage> = serde_json::from_str(&link.stages)?;
+        self.proceed(&link.id, &stages, link.stage as usize).await
+    }
+
+    /// Moves on the workflows that stood still for `stale_after`, as a crash between
+    /// finishing a job and pushing the next stage leaves them. Their jobs that were
+    /// deleted or killed in the queue count as failed. Returns how many were looked at
+    pub async fn recover(&self, stale_after: Duration) -> Result<u64> {
+        let before = epoch() - stale_after.as_secs() as i64;
+        let mut recovered = 0;
+        for stalled in self.stalled(before).await? {
+            // another node got to it first
+            if !self.touch(&stalled).await? {
+                continue;
+            }
+            recovered += 1;
+            let stages: Vec<Stage> = serde_json::from_str(&stalled.stages)?;
+            let index = stalled.stage as usize;
+            let jobs: Vec<WorkflowJobRecord> = self
+                .jobs(&stalled.id)
+                .await?
+                .into_iter()
+                .filter(|job| job.stage == stalled.stage)
+                .collect();
+            if jobs.is_empty() {
+                let input = match index.checked_sub(1) {
+                    Some(previous) => self.stage_output(&stalled.id, &stages, previous).await?,
+                    None => Value::Null,
+                };
+                tracing::warn!(
+                    id = stalled.id,
+                    index,
+                    "Entering the stage of a stalled workflow"
+                );
+                self.enter(&stalled.id, &stages, index, input).await?;
+                continue;
+            }
+            for job in jobs.iter().filter(|job| job.status == "Pending") {
+                let result = match job.job_status.as_str() {
+                    "Done" => Ok(()),
+                    "Deleted" | "Killed" => Err(WorkerError::Custom(format!(
+                        "job is {}",
+                        job.job_status.to_lowercase()
+                    ))),
+                    _ => continue,
+                };
+                tracing::warn!(
+                    id = stalled.id,
+                    job_id = job.job_id,
+                    "Finishing the job of a stalled workflow"
+                );
+                self.finished(&job.job_id, &result).await?;
+            }
+            // the last job may have finished without moving the workflow on
+            self.proceed(&stalled.id, &stages, index).await?;
+        }
+        Ok(recovered)
+    }
+
+    /// Enters the next stage once stage `index` is complete
+    async fn proceed(&self, id: &str, stages: &[Stage], index: usize) -> Result<()> {
+        if !self.advance(id, index).await? {
+            return Ok(());
+        }
+        let input = self.stage_output(id, stages, index).await?;
+        self.enter(id, stages, index + 1, input).await
+    }
+
+    /// What stage `index` hands on to the next one
+    async fn stage_output(&self, id: &str, stages: &[Stage], index: usize) -> Result<Value> {
+        let outputs = self
+            .outputs(id, index)
+            .await?
+            .into_iter()
+            .map(|output| {
+                output
+                    .and_then(|output| serde_json::from_str(&output).ok())
+                    .unwrap_or_default()
+            })
+            .collect();
+        Ok(stages[index].output(outputs))
+    }
+
+    /// Pushes the jobs of stage `index`, stages without jobs are passed right away
+    async fn enter(
+        &self,
+        id: &str,
+        stages: &[Stage],
+        mut index: usize,
+        mut input: Value,
+    ) -> Result<()> {
+        while let Some(stage) = stages.get(index) {
+            let payloads = match stage.payloads(input) {
+                Ok(payloads) => payloads,
+                Err(err) => return self.fail(id, &err.to_string()).await,
+            };
+            if !payloads.is_empty() {
+                return self.push(id, stage, index, payloads).await;
+            }
+            if !self.advance(id, index).await? {
+                return Ok(());
+            }
+            input = stage.output(Vec::new());
+            index += 1;
+        }
+        Ok(())
+    }
+
+    /// Links the jobs to the workflow before pushing them so a quick job can't finish unseen
+    async fn push(
+        &self,
+        id: &str,
+        stage: &Stage,
+        index: usize,
+        payloads: Vec<Value>,
+    ) -> Result<()> {
+        let Some(push) = self.dispatcher.get(stage.job_type()) else {
+            let error = format!("worker {} is not registered", stage.job_type());
+            return self.fail(id, &error).await;
+        };
+        let mut jobs = Vec::with_capacity(payloads.len());
+        for (idx, payload) in payloads.into_iter().enumerate() {
+            let task_id = TaskId::new();
+            self.insert_job(id, index, idx, &task_id.to_string(), stage.job_type())
+                .await?;
+            jobs.push((task_id, payload));
+        }
+        for (task_id, payload) in jobs {
+            let job_id = task_id.to_string();
+            if let Err(err) = push(payload, task_id).await {
+                self.finish_job(&job_id, "Failed").await?;
+                return self
+                    .fail(id, &format!("failed to push job {job_id}: {err}"))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn fail(&self, id: &str, error: &str) -> Result<()> {
+        tracing::warn!(id, error, "Workflow failed");
+        self.mark_failed(id, error).await
+    }
+}
+
+#[cfg(feature = "redis")]
+mod redis;
+
+#[cfg(not(feature = "redis"))]
+impl Workflows {
+    /// Workflows, the latest first
+    pub async fn list(&self, page: i64, page_size: i64) -> Result<(i64, Vec<WorkflowRecord>)> {
+        let total: i64 = sqlx::query_scalar(sql::COUNT).fetch_one(&self.pool).await?;
+        let items = sqlx::query_as(sql::LIST)
+            .bind(page_size)
+            .bind((page.max(1) - 1) * page_size)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok((total, items))
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<WorkflowRecord>> {
+        let workflow = sqlx::query_as(sql::GET)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(workflow)
+    }
+
+    /// Jobs pushed so far, by stage
+    pub async fn jobs(&self, id: &str) -> Result<Vec<WorkflowJobRecord>> {
+        let jobs = sqlx::query_as(sql::JOBS)
+            .bind(id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(jobs)
+    }
+
+    async fn insert(&self, id: &str, workflow: &Workflow) -> Result<()> {
+        sqlx::query(sql::INSERT)
+            .bind(id)
+            .bind(&workflow.name)
+            .bind(workflow.on_failure.as_str())
+            .bind(workflow.stages.len() as i32)
+            .bind(serde_json::to_string(&workflow.stages)?)
+            .bind(epoch())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn insert_job(
+        &self,
+        id: &str,
+        index: usize,
+        idx: usize,
+        job_id: &str,
+        job_type: &str,
+    ) -> Result<()> {
+        sqlx::query(sql::INSERT_JOB)
+            .bind(id)
+            .bind(index as i32)
+            .bind(idx as i32)
+            .bind(job_id)
+            .bind(job_type)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn find_job(&self, job_id: &str) -> Result<Option<JobLink>> {
+        let link = sqlx::query_as(sql::FIND_JOB)
+            .bind(job_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(link)
+    }
+
+    async fn finish_job(&self, job_id: &str, status: &str) -> Result<()> {
+        sqlx::query(sql::FINISH_JOB)
+            .bind(job_id)
+            .bind(status)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Only the last job of a stage gets to advance it
+    async fn advance(&self, id: &str, index: usize) -> Result<bool> {
+        let result = sqlx::query(sql::ADVANCE)
+            .bind(id)
+            .bind(index as i32)
+            .bind(epoch())
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Outputs of the jobs of stage `index` in order, `None` for failed ones
+    async fn outputs(&self, id: &str, index: usize) -> Result<Vec<Option<String>>> {
+        let outputs = sqlx::query_scalar(sql::OUTPUTS)
+            .bind(id)
+            .bind(index as i32)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(outputs)
+    }
+
+    async fn stalled(&self, before: i64) -> Result<Vec<Stalled>> {
+        let stalled = sqlx::query_as(sql::STALLED)
+            .bind(before)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(stalled)
+    }
+
+    /// Claims a stalled workflow, false when it moved in the meantime
+    async fn touch(&self, stalled: &Stalled) -> Result<bool> {
+        let result = sqlx::query(sql::TOUCH)
+            .bind(&stalled.id)
+            .bind(stalled.stage)
+            .bind(stalled.updated_at)
+            .bind(epoch())
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn mark_failed(&self, id: &str, error: &str) -> Result<()> {
+        sqlx::query(sql::FAIL)
+            .bind(id)
+            .bind(error)
+            .bind(epoch())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::test_utils::{Echo, db_test, echo_queue};
+
+    #[test]
+    fn test_stage_payloads() {
+        let pipe = Stage::Job {
+            job_type: "a".to_string(),
+            payload: None,
+        };
+        assert_eq!(
+            pipe.payloads(json!({"n": 1})).unwrap(),
+            vec![json!({"n": 1})]
+        );
+        let each = Stage::FanOut {
+            job_type: "b".to_string(),
+            payloads: None,
+        };
+        assert_eq!(
+            each.payloads(json!([1, 2])).unwrap(),
+            vec![json!(1), json!(2)]
+        );
+        assert!(each.payloads(json!({"n": 1})).is_err());
+    }
+
+    #[test]
+    fn test_stage_output() {
+        let job = Stage::Job {
+            job_type: "a".to_string(),
+            payload: None,
+        };
+        assert_eq!(job.output(vec![json!(1)]), json!(1));
+        let fan_out = Stage::FanOut {
+            job_type: "b".to_string(),
+            payloads: None,
+        };
+        assert_eq!(
+            fan_out.output(vec![json!(1), Value::Null]),
+            json!([1, null])
+        );
+    }
+
+    async fn stage_jobs(workflows: &Workflows, id: &str, stage: i32) -> Vec<WorkflowJobRecord> {
+        let jobs = workflows.jobs(id).await.unwrap();
+        jobs.into_iter().filter(|job| job.stage == stage).collect()
+    }
+
+    async fn run_through(pool: Pool) {
+        let (manager, _queue) = echo_queue(pool).await;
+        let workflows = manager.workflows();
+        let workflow = Workflow::new("echo")
+            .fan_out([Echo { n: 1 }, Echo { n: 2 }])
+            .job(Echo { n: 3 });
+        let id = workflows.start(workflow).await.unwrap();
+        let first = stage_jobs(&workflows, &id, 0).await;
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].job_status, "Pending");
+
+        workflows.finished(&first[0].job_id, &Ok(())).await.unwrap();
+        assert_eq!(workflows.get(&id).await.unwrap().unwrap().stage, 0);
+        workflows.finished(&first[1].job_id, &Ok(())).await.unwrap();
+        assert_eq!(workflows.get(&id).await.unwrap().unwrap().stage, 1);
+        let second = stage_jobs(&workflows, &id, 1).await;
+        let job = manager.job_store().get::<Echo>(&second[0].job_id).await;
+        assert_eq!(job.unwrap().unwrap().payload, json!({"n": 3}));
+
+        workflows
+            .finished(&second[0].job_id, &Ok(()))
+            .await
+            .unwrap();
+        let workflow = workflows.get(&id).await.unwrap().unwrap();
+        assert_eq!(workflow.status, "Done");
+        assert_eq!(workflow.stage, 2);
+    }
+    db_test!(test_finished_run_workflow_through, run_through);
+
+    async fn recover_deleted_job(pool: Pool) {
+        let (manager, _queue) = echo_queue(pool).await;
+        let workflows = manager.workflows();
+        let store = manager.job_store();
+        let workflow = Workflow::new("lenient")
+            .fan_out([Echo { n: 1 }, Echo { n: 2 }])
+            .job(Echo { n: 3 })
+            .on_failure(FailurePolicy::Continue);
+        let lenient = workflows.start(workflow).await.unwrap();
+        let jobs = stage_jobs(&workflows, &lenient, 0).await;
+        store
+            .delete::<Echo>(&[jobs[0].job_id.clone()])
+            .await
+            .unwrap();
+        workflows.finished(&jobs[1].job_id, &Ok(())).await.unwrap();
+        let strict = workflows
+            .start(Workflow::new("strict").job(Echo { n: 4 }))
+            .await
+            .unwrap();
+        let jobs = stage_jobs(&workflows, &strict, 0).await;
+        store
+            .delete::<Echo>(&[jobs[0].job_id.clone()])
+            .await
+            .unwrap();
+        // nothing reports the deleted jobs, the workflows are stuck
+        assert_eq!(workflows.get(&lenient).await.unwrap().unwrap().stage, 0);
+
+        assert!(workflows.recover(Duration::ZERO).await.unwrap() >= 2);
+        let workflow = workflows.get(&lenient).await.unwrap().unwrap();
+        assert_eq!((workflow.status.as_str(), workflow.stage), ("Running", 1));
+        assert_eq!(
+            stage_jobs(&workflows, &lenient, 0).await[0].status,
+            "Failed"
+        );
+        assert_eq!(stage_jobs(&workflows, &lenient, 1).await.len(), 1);
+        let workflow = workflows.get(&strict).await.unwrap().unwrap();
+        assert_eq!(workflow.status, "Failed");
+    }
+    db_test!(test_recover_fail_given_job_deleted, recover_deleted_job);
+
+    async fn recover_unentered_stage(pool: Pool) {
+        let (manager, _queue) = echo_queue(pool).await;
+        let workflows = manager.workflows();
+        let workflow = Workflow::new("echo").job(Echo { n: 1 }).job(Echo { n: 2 });
+        let id = workflows.start(workflow).await.unwrap();
+        // the process died after advancing, before pushing the next stage
+        let first = stage_jobs(&workflows, &id, 0).await;
+        workflows
+            .finish_job(&first[0].job_id, "Done")
+            .await
+            .unwrap();
+        assert!(workflows.advance(&id, 0).await.unwrap());
+        assert!(stage_jobs(&workflows, &id, 1).await.is_empty());
+
+        assert_eq!(workflows.recover(Duration::ZERO).await.unwrap(), 1);
+        let second = stage_jobs(&workflows, &id, 1).await;
+        assert_eq!(second.len(), 1);
+        let job = manager.job_store().get::<Echo>(&second[0].job_id).await;
+        assert_eq!(job.unwrap().unwrap().payload, json!({"n": 2}));
+        // a workflow that is fine is left alone
+        assert_eq!(workflows.recover(Duration::from_secs(60)).await.unwrap(), 0);
+    }
+    db_test!(
+        test_recover_enter_given_stage_not_entered,
+        recover_unentered_stage
+    );
+}
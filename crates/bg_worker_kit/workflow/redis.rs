@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+
+use redis::{AsyncCommands as _, Script};
+
+use super::{JobLink, Stalled, Workflow, WorkflowJobRecord, WorkflowRecord, Workflows, epoch};
+use crate::{
+    error::Result,
+    progress::redis::progress_key,
+    redis_helper::{PREFIX, job_status},
+};
+
+/// Same checks as the sql `ADVANCE`, run as one script so only the last job of
+/// a stage gets to advance it
+const ADVANCE: &str = r#"
+    local workflow = KEYS[1]
+    if redis.call('HGET', workflow, 'stage') ~= ARGV[1] then return 0 end
+    if redis.call('HGET', workflow, 'status') == 'Done' then return 0 end
+    local abort = redis.call('HGET', workflow, 'on_failure') == 'Abort'
+    for _, status in ipairs(redis.call('HVALS', KEYS[2])) do
+        if status == 'Pending' or (status == 'Failed' and abort) then return 0 end
+    end
+    local stage = tonumber(ARGV[1]) + 1
+    local status = 'Running'
+    if stage >= tonumber(redis.call('HGET', workflow, 'stage_count')) then status = 'Done' end
+    redis.call('HSET', workflow, 'stage', stage, 'status', status, 'updated_at', ARGV[2])
+    redis.call('HDEL', workflow, 'error')
+    return 1
+"#;
+
+const FAIL: &str = r#"
+    if redis.call('HGET', KEYS[1], 'status') ~= 'Running' then return 0 end
+    redis.call('HSET', KEYS[1], 'status', 'Failed', 'error', ARGV[1], 'updated_at', ARGV[2])
+    return 1
+"#;
+
+/// Same as the sql `TOUCH`
+const TOUCH: &str = r#"
+    local fields = redis.call('HMGET', KEYS[1], 'status', 'stage', 'updated_at')
+    if fields[1] ~= 'Running' or fields[2] ~= ARGV[1] or fields[3] ~= ARGV[2] then return 0 end
+    redis.call('HSET', KEYS[1], 'updated_at', ARGV[3])
+    return 1
+"#;
+
+/// Workflow ids scored by when they started
+fn index_key() -> String {
+    format!("{PREFIX}:workflows")
+}
+
+/// A hash per workflow with the fields of the sql `workflows` table. The keys of a
+/// workflow share the `{id}` hash tag, so its scripts stay on one node of a cluster
+fn workflow_key(id: &str) -> String {
+    format!("{PREFIX}:workflows:{{{id}}}")
+}
+
+/// Job ids of one stage in order
+fn stage_key(id: &str, index: usize) -> String {
+    format!("{}:{index}", workflow_key(id))
+}
+
+/// What the workflow made of the jobs of one stage, by job id
+fn stage_status_key(id: &str, index: usize) -> String {
+    format!("{}:{index}:status", workflow_key(id))
+}
+
+/// A hash per job with the fields of the sql `workflow_jobs` table but the
+/// status, plus the `namespace` of its queue
+fn job_key(job_id: &str) -> String {
+    format!("{PREFIX}:workflow_jobs:{job_id}")
+}
+
+impl Workflows {
+    /// Workflows, the latest first
+    pub async fn list(&self, page: i64, page_size: i64) -> Result<(i64, Vec<WorkflowRecord>)> {
+        let mut conn = self.pool.clone();
+        let start = ((page.max(1) - 1) * page_size) as isize;
+        let total: i64 = conn.zcard(index_key()).await?;
+        let ids: Vec<String> = conn
+            .zrevrange(index_key(), start, start + page_size as isize - 1)
+            .await?;
+        let mut items = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(workflow) = self.get(&id).await? {
+                items.push(workflow);
+            }
+        }
+        Ok((total, items))
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<WorkflowRecord>> {
+        let mut fields: HashMap<String, String> =
+            self.pool.clone().hgetall(workflow_key(id)).await?;
+        if fields.is_empty() {
+            return Ok(None);
+        }
+        let number = |fields: &HashMap<String, String>, name: &str| {
+            fields
+                .get(name)
+                .and_then(|value| value.parse::<i64>().ok())
+                .unwrap_or_default()
+        };
+        Ok(Some(WorkflowRecord {
+            id: id.to_string(),
+            name: fields.remove("name").unwrap_or_default(),
+            status: fields.remove("status").unwrap_or_default(),
+            on_failure: fields.remove("on_failure").unwrap_or_default(),
+            stage: number(&fields, "stage") as i32,
+            stage_count: number(&fields, "stage_count") as i32,
+            error: fields.remove("error"),
+            created_at: number(&fields, "created_at"),
+            updated_at: number(&fields, "updated_at"),
+        }))
+    }
+
+    /// Jobs pushed so far, by stage
+    pub async fn jobs(&self, id: &str) -> Result<Vec<WorkflowJobRecord>> {
+        let Some(workflow) = self.get(id).await? else {
+            return Ok(Vec::new());
+        };
+        let mut conn = self.pool.clone();
+        let mut jobs = Vec::new();
+        for index in 0..=workflow.stage.min(workflow.stage_count - 1).max(0) as usize {
+            let ids: Vec<String> = conn.lrange(stage_key(id, index), 0, -1).await?;
+            let mut statuses: HashMap<String, String> =
+                conn.hgetall(stage_status_key(id, index)).await?;
+            for (idx, job_id) in ids.into_iter().enumerate() {
+                let mut fields: HashMap<String, String> = conn.hgetall(job_key(&job_id)).await?;
+                let namespace = fields.remove("namespace").unwrap_or_default();
+                let job_status = job_status(&mut conn, &namespace, &job_id).await?;
+                let output: Option<String> = conn.hget(progress_key(&job_id), "result").await?;
+                jobs.push(WorkflowJobRecord {
+                    stage: index as i32,
+                    idx: idx as i32,
+                    job_type: fields.remove("job_type").unwrap_or_default(),
+                    status: statuses.remove(&job_id).unwrap_or_default(),
+                    job_status: job_status.unwrap_or("Deleted").to_string(),
+                    last_error: None,
+                    output,
+                    job_id,
+                });
+            }
+        }
+        Ok(jobs)
+    }
+
+    pub(super) async fn insert(&self, id: &str, workflow: &Workflow) -> Result<()> {
+        let now = epoch();
+        let fields = [
+            ("name", workflow.name.clone()),
+            ("status", "Running".to_string()),
+            ("on_failure", workflow.on_failure.as_str().to_string()),
+            ("stage", "0".to_string()),
+            ("stage_count", workflow.stages.len().to_string()),
+            ("stages", serde_json::to_string(&workflow.stages)?),
+            ("created_at", now.to_string()),
+            ("updated_at", now.to_string()),
+        ];
+        let mut conn = self.pool.clone();
+        // listed once complete, the two keys may live on different nodes
+        let _: () = conn.hset_multiple(workflow_key(id), &fields).await?;
+        let _: () = conn.zadd(index_key(), id, now).await?;
+        Ok(())
+    }
+
+    pub(super) async fn insert_job(
+        &self,
+        id: &str,
+        index: usize,
+        idx: usize,
+        job_id: &str,
+        job_type: &str,
+    ) -> Result<()> {
+        let fields = [
+            ("workflow_id", id.to_string()),
+            ("stage", index.to_string()),
+            ("idx", idx.to_string()),
+            ("job_type", job_type.to_string()),
+            (
+                "namespace",
+                self.dispatcher
+                    .namespace(job_type)
+                    .unwrap_or_default()
+                    .to_string(),
+            ),
+        ];
+        let mut conn = self.pool.clone();
+        let _: () = conn.hset_multiple(job_key(job_id), &fields).await?;
+        let _: () = redis::pipe()
+            .atomic()
+            .rpush(stage_key(id, index), job_id)
+            .hset(stage_status_key(id, index), job_id, "Pending")
+            .query_async(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    pub(super) async fn find_job(&self, job_id: &str) -> Result<Option<JobLink>> {
+        let mut conn = self.pool.clone();
+        let (id, stage): (Option<String>, Option<i32>) = conn
+            .hget(job_key(job_id), &["workflow_id", "stage"])
+            .await?;
+        let (Some(id), Some(stage)) = (id, stage) else {
+            return Ok(None);
+        };
+        let (on_failure, stages): (Option<String>, Option<String>) = conn
+            .hget(workflow_key(&id), &["on_failure", "stages"])
+            .await?;
+        let (Some(on_failure), Some(stages)) = (on_failure, stages) else {
+            return Ok(None);
+        };
+        Ok(Some(JobLink {
+            id,
+            on_failure,
+            stages,
+            stage,
+        }))
+    }
+
+    pub(super) async fn finish_job(&self, job_id: &str, status: &str) -> Result<()> {
+        let mut conn = self.pool.clone();
+        let (id, stage): (Option<String>, Option<usize>) = conn
+            .hget(job_key(job_id), &["workflow_id", "stage"])
+            .await?;
+        if let (Some(id), Some(stage)) = (id, stage) {
+            let _: () = conn
+                .hset(stage_status_key(&id, stage), job_id, status)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Only the last job of a stage gets to advance it
+    pub(super) async fn advance(&self, id: &str, index: usize) -> Result<bool> {
+        let advanced: i64 = Script::new(ADVANCE)
+            .key(workflow_key(id))
+            .key(stage_status_key(id, index))
+            .arg(index)
+            .arg(epoch())
+            .invoke_async(&mut self.pool.clone())
+            .await?;
+        Ok(advanced > 0)
+    }
+
+    /// Outputs of the jobs of stage `index` in order, `None` for failed ones
+    pub(super) async fn outputs(&self, id: &str, index: usize) -> Result<Vec<Option<String>>> {
+        let mut conn = self.pool.clone();
+        let ids: Vec<String> = conn.lrange(stage_key(id, index), 0, -1).await?;
+        let mut outputs = Vec::with_capacity(ids.len());
+        for job_id in ids {
+            let status: Option<String> = conn.hget(stage_status_key(id, index), &job_id).await?;
+            let output = if status.as_deref() == Some("Done") {
+                conn.hget(progress_key(&job_id), "result").await?
+            } else {
+                None
+            };
+            outputs.push(output);
+        }
+        Ok(outputs)
+    }
+
+    pub(super) async fn stalled(&self, before: i64) -> Result<Vec<Stalled>> {
+        let mut conn = self.pool.clone();
+        let ids: Vec<String> = conn.zrange(index_key(), 0, -1).await?;
+        let mut stalled = Vec::new();
+        for id in ids {
+            let (status, stage, stages, updated_at): (
+                Option<String>,
+                Option<i32>,
+                Option<String>,
+                Option<i64>,
+            ) = conn
+                .hget(
+                    workflow_key(&id),
+                    &["status", "stage", "stages", "updated_at"],
+                )
+                .await?;
+            if let (Some(stage), Some(stages), Some(updated_at)) = (stage, stages, updated_at)
+                && status.as_deref() == Some("Running")
+                && updated_at <= before
+            {
+                stalled.push(Stalled {
+                    id,
+                    stage,
+                    stages,
+                    updated_at,
+                });
+            }
+        }
+        Ok(stalled)
+    }
+
+    /// Claims a stalled workflow, false when it moved in the meantime
+    pub(super) async fn touch(&self, stalled: &Stalled) -> Result<bool> {
+        let touched: i64 = Script::new(TOUCH)
+            .key(workflow_key(&stalled.id))
+            .arg(stalled.stage)
+            .arg(stalled.updated_at)
+            .arg(epoch())
+            .invoke_async(&mut self.pool.clone())
+            .await?;
+        Ok(touched > 0)
+    }
+
+    pub(super) async fn mark_failed(&self, id: &str, error: &str) -> Result<()> {
+        let _: i64 = Script::new(FAIL)
+            .key(workflow_key(id))
+            .arg(error)
+            .arg(epoch())
+            .invoke_async(&mut self.pool.clone())
+            .await?;
+        Ok(())
+    }
+}
@@ -1,8 +1,10 @@
-pub use reqwest::header;
+pub use reqwest::Body;
 use reqwest::Client;
 pub use reqwest::Error as ReqwestError;
+pub use reqwest::Response;
+pub use reqwest::header;
 pub use reqwest_middleware::Error as ReqwestMiddlewareError;
-use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
+use reqwest_retry::{RetryTransientMiddleware, policies::ExponentialBackoff};
 use std::sync::LazyLock;
 
 pub struct HttpClient;
@@ -14,6 +16,10 @@ pub static HTTP_CLIENT: LazyLock<reqwest_middleware::ClientWithMiddleware> = Laz
         .build()
 });
 
+/// A streamed body can't be replayed, requests sending one go through this client
+/// without the retries of `HTTP_CLIENT`
+pub static STREAMING_HTTP_CLIENT: LazyLock<Client> = LazyLock::new(Client::new);
+
 #[cfg(test)]
 mod tests {
     use super::*;
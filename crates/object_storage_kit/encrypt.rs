@@ -0,0 +1,638 @@
+//! Envelope encryption of objects at rest. Every object gets its own data key, wrapped by
+//! the active master key and kept in a fixed size header in front of the content:
+//!
+//! `magic (4) | master key fingerprint (8) | wrap nonce (24) | wrapped data key (48) | chunk nonce prefix (16)`
+//!
+//! The content follows in XChaCha20-Poly1305 sealed chunks of `CHUNK_SIZE` bytes, so any
+//! byte range can be decrypted without reading what comes before it. Objects without the
+//! header are passed through untouched, which keeps storages written before encryption
+//! was switched on readable.
+
+use std::{fmt::Debug, sync::Arc};
+
+use chacha20poly1305::{
+    Key, KeyInit as _, XChaCha20Poly1305, XNonce,
+    aead::{Aead as _, AeadCore as _, OsRng, Payload, rand_core::RngCore as _},
+};
+use opendal::{
+    Buffer, Error, ErrorKind, Metadata,
+    raw::{
+        Access, BytesRange, Layer, LayeredAccess, OpList, OpRead, OpStat, OpWrite, RpDelete,
+        RpList, RpRead, RpStat, RpWrite, oio,
+    },
+};
+
+use crate::error::{ObjectStorageError, Result};
+
+const MAGIC: &[u8; 4] = b"OXE1";
+const FINGERPRINT_LEN: usize = 8;
+const WRAP_NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+const WRAPPED_KEY_LEN: usize = KEY_LEN + TAG_LEN;
+const CHUNK_NONCE_LEN: usize = 16;
+pub(crate) const HEADER_LEN: usize =
+    MAGIC.len() + FINGERPRINT_LEN + WRAP_NONCE_LEN + WRAPPED_KEY_LEN + CHUNK_NONCE_LEN;
+const TAG_LEN: usize = 16;
+const CHUNK_SIZE: usize = 64 * 1024;
+const SEALED_CHUNK_SIZE: usize = CHUNK_SIZE + TAG_LEN;
+
+/// Master keys as 64 hex characters each, the first one wraps new data keys and every
+/// one of them unwraps. Rotating means putting a new key in front and rewrapping
+#[derive(Clone)]
+pub struct MasterKeys {
+    keys: Vec<([u8; FINGERPRINT_LEN], XChaCha20Poly1305)>,
+}
+
+impl Debug for MasterKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MasterKeys").finish()
+    }
+}
+
+impl MasterKeys {
+    pub fn from_hex(keys: &[String]) -> Result<Self> {
+        let keys = keys
+            .iter()
+            .map(|key| {
+                let key: [u8; KEY_LEN] = hex::decode(key.trim())
+                    .ok()
+                    .and_then(|key| key.try_into().ok())
+                    .ok_or_else(|| {
+                        ObjectStorageError::Custom(
+                            "Master key must be 64 hex characters".to_string(),
+                        )
+                    })?;
+                let hash = hmac_sha256::Hash::hash(&key);
+                let mut fingerprint = [0u8; FINGERPRINT_LEN];
+                fingerprint.copy_from_slice(&hash[..FINGERPRINT_LEN]);
+                Ok((fingerprint, XChaCha20Poly1305::new(&Key::from(key))))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if keys.is_empty() {
+            return Err(ObjectStorageError::Custom(
+                "At least one master key is required".to_string(),
+            ));
+        }
+        Ok(Self { keys })
+    }
+
+    /// Generates a data key and returns the header carrying it along with the cipher
+    fn seal_header(&self) -> std::result::Result<(Vec<u8>, Chunks), Error> {
+        let data_key = XChaCha20Poly1305::generate_key(&mut OsRng);
+        let mut nonce_prefix = [0u8; CHUNK_NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce_prefix);
+        let header = self.wrap(&data_key, &nonce_prefix)?;
+        let chunks = Chunks {
+            cipher: XChaCha20Poly1305::new(&data_key),
+            nonce_prefix,
+        };
+        Ok((header, chunks))
+    }
+
+    fn wrap(
+        &self,
+        data_key: &Key,
+        nonce_prefix: &[u8; CHUNK_NONCE_LEN],
+    ) -> std::result::Result<Vec<u8>, Error> {
+        let (fingerprint, master) = &self.keys[0];
+        let wrap_nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let wrapped = master
+            .encrypt(
+                &wrap_nonce,
+                Payload {
+                    msg: &data_key[..],
+                    aad: &Self::aad(fingerprint, nonce_prefix),
+                },
+            )
+            .map_err(|_| crypto_error("Failed to wrap data key"))?;
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(fingerprint);
+        header.extend_from_slice(&wrap_nonce);
+        header.extend_from_slice(&wrapped);
+        header.extend_from_slice(nonce_prefix);
+        Ok(header)
+    }
+
+    /// Unwraps the data key of a header, `None` when the object is not encrypted
+    fn open_header(&self, header: &[u8]) -> std::result::Result<Option<Chunks>, Error> {
+        let Some(opened) = self.unwrap(header)? else {
+            return Ok(None);
+        };
+        let (data_key, nonce_prefix, _) = opened;
+        Ok(Some(Chunks {
+            cipher: XChaCha20Poly1305::new(&data_key),
+            nonce_prefix,
+        }))
+    }
+
+    /// Wraps the data key of a header with the active master key again, `None` when the
+    /// object is not encrypted or already uses the active key
+    pub(crate) fn rewrap(&self, header: &[u8]) -> Result<Option<Vec<u8>>> {
+        let Some((data_key, nonce_prefix, fingerprint)) = self.unwrap(header)? else {
+            return Ok(None);
+        };
+        if fingerprint == self.keys[0].0 {
+            return Ok(None);
+        }
+        Ok(Some(self.wrap(&data_key, &nonce_prefix)?))
+    }
+
+    fn unwrap(&self, header: &[u8]) -> std::result::Result<Option<Unwrapped>, Error> {
+        if header.len() < HEADER_LEN || &header[..MAGIC.len()] != MAGIC {
+            return Ok(None);
+        }
+        let (fingerprint, rest) = header[MAGIC.len()..].split_at(FINGERPRINT_LEN);
+        let (wrap_nonce, rest) = rest.split_at(WRAP_NONCE_LEN);
+        let (wrapped, rest) = rest.split_at(WRAPPED_KEY_LEN);
+        let mut nonce_prefix = [0u8; CHUNK_NONCE_LEN];
+        nonce_prefix.copy_from_slice(&rest[..CHUNK_NONCE_LEN]);
+        let mut wrap_nonce_bytes = [0u8; WRAP_NONCE_LEN];
+        wrap_nonce_bytes.copy_from_slice(wrap_nonce);
+        let (fingerprint, master) = self
+            .keys
+            .iter()
+            .find(|(candidate, _)| candidate == fingerprint)
+            .ok_or_else(|| crypto_error("Object is encrypted with an unknown master key"))?;
+        let data_key = master
+            .decrypt(
+                &XNonce::from(wrap_nonce_bytes),
+                Payload {
+                    msg: wrapped,
+                    aad: &Self::aad(fingerprint, &nonce_prefix),
+                },
+            )
+            .map_err(|_| crypto_error("Failed to unwrap data key"))?;
+        let data_key: [u8; KEY_LEN] = data_key
+            .try_into()
+            .map_err(|_| crypto_error("Failed to unwrap data key"))?;
+        Ok(Some((Key::from(data_key), nonce_prefix, *fingerprint)))
+    }
+
+    fn aad(fingerprint: &[u8; FINGERPRINT_LEN], nonce_prefix: &[u8; CHUNK_NONCE_LEN]) -> Vec<u8> {
+        [MAGIC.as_slice(), fingerprint, nonce_prefix].concat()
+    }
+}
+
+/// Data key, chunk nonce prefix and fingerprint of the master key found in a header
+type Unwrapped = (Key, [u8; CHUNK_NONCE_LEN], [u8; FINGERPRINT_LEN]);
+
+/// Seals and opens the chunks of one object. The chunk index is part of the nonce and the
+/// last chunk is marked in the associated data, so chunks can be neither reordered nor cut off
+struct Chunks {
+    cipher: XChaCha20Poly1305,
+    nonce_prefix: [u8; CHUNK_NONCE_LEN],
+}
+
+impl Chunks {
+    fn nonce(&self, index: u64) -> XNonce {
+        let mut nonce = [0u8; WRAP_NONCE_LEN];
+        nonce[..CHUNK_NONCE_LEN].copy_from_slice(&self.nonce_prefix);
+        nonce[CHUNK_NONCE_LEN..].copy_from_slice(&index.to_be_bytes());
+        XNonce::from(nonce)
+    }
+
+    fn seal(&self, index: u64, chunk: &[u8], last: bool) -> std::result::Result<Vec<u8>, Error> {
+        self.cipher
+            .encrypt(
+                &self.nonce(index),
+                Payload {
+                    msg: chunk,
+                    aad: &[last as u8],
+                },
+            )
+            .map_err(|_| crypto_error("Failed to encrypt chunk"))
+    }
+
+    fn open(&self, index: u64, sealed: &[u8], last: bool) -> std::result::Result<Vec<u8>, Error> {
+        self.cipher
+            .decrypt(
+                &self.nonce(index),
+                Payload {
+                    msg: sealed,
+                    aad: &[last as u8],
+                },
+            )
+            .map_err(|_| crypto_error("Encrypted chunk is corrupted"))
+    }
+}
+
+/// Size of the content an encrypted object of `sealed_len` bytes holds
+fn plain_len(sealed_len: u64) -> u64 {
+    let body = sealed_len.saturating_sub(HEADER_LEN as u64);
+    let full = body / SEALED_CHUNK_SIZE as u64;
+    let rest = body % SEALED_CHUNK_SIZE as u64;
+    full * CHUNK_SIZE as u64 + rest.saturating_sub(TAG_LEN as u64)
+}
+
+fn crypto_error(message: &'static str) -> Error {
+    Error::new(ErrorKind::Unexpected, message)
+}
+
+/// Encrypts whatever is written through the operator and decrypts whatever is read
+#[derive(Debug, Clone)]
+pub struct EncryptionLayer {
+    keys: Arc<MasterKeys>,
+}
+
+impl EncryptionLayer {
+    pub fn new(keys: Arc<MasterKeys>) -> Self {
+        Self { keys }
+    }
+}
+
+impl<A: Access> Layer<A> for EncryptionLayer {
+    type LayeredAccess = EncryptionAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccess {
+        EncryptionAccessor {
+            inner,
+            keys: self.keys.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EncryptionAccessor<A: Access> {
+    inner: A,
+    keys: Arc<MasterKeys>,
+}
+
+impl<A: Access> EncryptionAccessor<A> {
+    /// Length of the stored object and its chunk cipher, `None` when it is not encrypted
+    async fn open(&self, path: &str) -> std::result::Result<(u64, Option<Chunks>), Error> {
+        let sealed_len = self
+            .inner
+            .stat(path, OpStat::new())
+            .await?
+            .into_metadata()
+            .content_length();
+        if sealed_len < (HEADER_LEN + TAG_LEN) as u64 {
+            return Ok((sealed_len, None));
+        }
+        let (_, mut reader) = self
+            .inner
+            .read(
+                path,
+                OpRead::new().with_range(BytesRange::new(0, Some(HEADER_LEN as u64))),
+            )
+            .await?;
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        loop {
+            let buffer = oio::Read::read(&mut reader).await?;
+            if buffer.is_empty() {
+                break;
+            }
+            header.extend_from_slice(&buffer.to_bytes());
+        }
+        Ok((sealed_len, self.keys.open_header(&header)?))
+    }
+}
+
+impl<A: Access> LayeredAccess for EncryptionAccessor<A> {
+    type Inner = A;
+    type Reader = DecryptReader<A::Reader>;
+    type Writer = EncryptWriter<A::Writer>;
+    type Lister = A::Lister;
+    type Deleter = A::Deleter;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> std::result::Result<RpStat, Error> {
+        let mut metadata = self.inner.stat(path, args).await?.into_metadata();
+        if metadata.is_file() {
+            let (sealed_len, chunks) = self.open(path).await?;
+            if chunks.is_some() {
+                metadata.set_content_length(plain_len(sealed_len));
+            }
+        }
+        Ok(RpStat::new(metadata))
+    }
+
+    async fn read(
+        &self,
+        path: &str,
+        args: OpRead,
+    ) -> std::result::Result<(RpRead, Self::Reader), Error> {
+        let (sealed_len, chunks) = self.open(path).await?;
+        let Some(chunks) = chunks else {
+            let (rp, reader) = self.inner.read(path, args).await?;
+            return Ok((rp, DecryptReader::Plain(reader)));
+        };
+        let range = args.range();
+        let chunk_size = CHUNK_SIZE as u64;
+        let sealed_chunk_size = SEALED_CHUNK_SIZE as u64;
+        let last_index = (sealed_len - HEADER_LEN as u64)
+            .div_ceil(sealed_chunk_size)
+            .saturating_sub(1);
+        let first_index = range.offset() / chunk_size;
+        let sealed_offset = HEADER_LEN as u64 + first_index * sealed_chunk_size;
+        // the inner reader fails when asked for more than the object holds
+        let sealed_range = BytesRange::new(
+            sealed_offset,
+            range.size().map(|size| {
+                let chunks = (range.offset() + size)
+                    .div_ceil(chunk_size)
+                    .saturating_sub(first_index)
+                    .max(1);
+                (chunks * sealed_chunk_size).min(sealed_len.saturating_sub(sealed_offset))
+            }),
+        );
+        let (_, reader) = self
+            .inner
+            .read(path, OpRead::new().with_range(sealed_range))
+            .await?;
+        Ok((
+            RpRead::new(),
+            DecryptReader::Sealed(SealedReader {
+                inner: reader,
+                chunks,
+                index: first_index,
+                last_index,
+                skip: (range.offset() % chunk_size) as usize,
+                remaining: range.size(),
+                buf: Vec::with_capacity(SEALED_CHUNK_SIZE),
+                eof: false,
+            }),
+        ))
+    }
+
+    async fn write(
+        &self,
+        path: &str,
+        args: OpWrite,
+    ) -> std::result::Result<(RpWrite, Self::Writer), Error> {
+        let (header, chunks) = self.keys.seal_header()?;
+        let (rp, writer) = self.inner.write(path, args).await?;
+        Ok((
+            rp,
+            EncryptWriter {
+                inner: writer,
+                chunks,
+                header: Some(header),
+                index: 0,
+                buf: Vec::with_capacity(SEALED_CHUNK_SIZE),
+            },
+        ))
+    }
+
+    async fn list(
+        &self,
+        path: &str,
+        args: OpList,
+    ) -> std::result::Result<(RpList, Self::Lister), Error> {
+        self.inner.list(path, args).await
+    }
+
+    async fn delete(&self) -> std::result::Result<(RpDelete, Self::Deleter), Error> {
+        self.inner.delete().await
+    }
+}
+
+pub enum DecryptReader<R> {
+    Plain(R),
+    Sealed(SealedReader<R>),
+}
+
+pub struct SealedReader<R> {
+    inner: R,
+    chunks: Chunks,
+    index: u64,
+    last_index: u64,
+    /// Bytes of the first chunk in front of the requested range
+    skip: usize,
+    remaining: Option<u64>,
+    buf: Vec<u8>,
+    eof: bool,
+}
+
+impl<R: oio::Read> oio::Read for DecryptReader<R> {
+    async fn read(&mut self) -> std::result::Result<Buffer, Error> {
+        let reader = match self {
+            DecryptReader::Plain(reader) => return reader.read().await,
+            DecryptReader::Sealed(reader) => reader,
+        };
+        loop {
+            if reader.remaining == Some(0) || reader.index > reader.last_index {
+                return Ok(Buffer::new());
+            }
+            while reader.buf.len() < SEALED_CHUNK_SIZE && !reader.eof {
+                let buffer = reader.inner.read().await?;
+                if buffer.is_empty() {
+                    reader.eof = true;
+                } else {
+                    reader.buf.extend_from_slice(&buffer.to_bytes());
+                }
+            }
+            if reader.buf.is_empty() {
+                return Ok(Buffer::new());
+            }
+            let sealed: Vec<u8> = reader
+                .buf
+                .drain(..reader.buf.len().min(SEALED_CHUNK_SIZE))
+                .collect();
+            let last = reader.index == reader.last_index;
+            let mut plain = reader.chunks.open(reader.index, &sealed, last)?;
+            reader.index += 1;
+            if reader.skip > 0 {
+                plain.drain(..reader.skip.min(plain.len()));
+                reader.skip = 0;
+            }
+            if let Some(remaining) = reader.remaining.as_mut() {
+                plain.truncate((*remaining).min(plain.len() as u64) as usize);
+                *remaining -= plain.len() as u64;
+            }
+            if !plain.is_empty() {
+                return Ok(Buffer::from(plain));
+            }
+        }
+    }
+}
+
+pub struct EncryptWriter<W> {
+    inner: W,
+    chunks: Chunks,
+    /// Goes out in front of the first chunk
+    header: Option<Vec<u8>>,
+    index: u64,
+    buf: Vec<u8>,
+}
+
+impl<W: oio::Write> oio::Write for EncryptWriter<W> {
+    async fn write(&mut self, bs: Buffer) -> std::result::Result<(), Error> {
+        self.buf.extend_from_slice(&bs.to_bytes());
+        let mut sealed = Vec::new();
+        // a full chunk is held back until more arrives, only close knows which one is last
+        while self.buf.len() > CHUNK_SIZE {
+            if let Some(header) = self.header.take() {
+                sealed.extend(header);
+            }
+            let chunk: Vec<u8> = self.buf.drain(..CHUNK_SIZE).collect();
+            sealed.extend(self.chunks.seal(self.index, &chunk, false)?);
+            self.index += 1;
+        }
+        if !sealed.is_empty() {
+            self.inner.write(Buffer::from(sealed)).await?;
+        }
+        Ok(())
+    }
+
+    async fn close(&mut self) -> std::result::Result<Metadata, Error> {
+        let mut sealed = self.header.take().unwrap_or_default();
+        let chunk = std::mem::take(&mut self.buf);
+        sealed.extend(self.chunks.seal(self.index, &chunk, true)?);
+        self.index += 1;
+        self.inner.write(Buffer::from(sealed)).await?;
+        self.inner.close().await
+    }
+
+    async fn abort(&mut self) -> std::result::Result<(), Error> {
+        self.inner.abort().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::{Operator, services};
+
+    use super::*;
+
+    const KEY_A: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+    const KEY_B: &str = "1f1e1d1c1b1a191817161514131211100f0e0d0c0b0a09080706050403020100";
+
+    fn keys(keys: &[&str]) -> Arc<MasterKeys> {
+        let keys: Vec<String> = keys.iter().map(|key| key.to_string()).collect();
+        Arc::new(MasterKeys::from_hex(&keys).unwrap())
+    }
+
+    fn operators(dir: &tempfile::TempDir, master_keys: Arc<MasterKeys>) -> (Operator, Operator) {
+        let raw = Operator::new(services::Fs::default().root(&dir.path().to_string_lossy()))
+            .unwrap()
+            .finish();
+        let encrypted = raw.clone().layer(EncryptionLayer::new(master_keys));
+        (raw, encrypted)
+    }
+
+    fn content(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_from_hex_return_err_given_invalid_key() {
+        assert!(MasterKeys::from_hex(&[]).is_err());
+        assert!(MasterKeys::from_hex(&["abcd".to_string()]).is_err());
+        assert!(MasterKeys::from_hex(&["zz".repeat(32)]).is_err());
+    }
+
+    #[test]
+    fn test_plain_len() {
+        let header = HEADER_LEN as u64;
+        let tag = TAG_LEN as u64;
+        let sealed_chunk = SEALED_CHUNK_SIZE as u64;
+        assert_eq!(plain_len(header + tag), 0);
+        assert_eq!(plain_len(header + 5 + tag), 5);
+        assert_eq!(plain_len(header + sealed_chunk), CHUNK_SIZE as u64);
+        assert_eq!(
+            plain_len(header + sealed_chunk + 3 + tag),
+            CHUNK_SIZE as u64 + 3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_then_read_given_sizes_around_chunk_boundaries() {
+        let dir = tempfile::tempdir().unwrap();
+        let (raw, encrypted) = operators(&dir, keys(&[KEY_A]));
+        for len in [
+            0,
+            1,
+            CHUNK_SIZE - 1,
+            CHUNK_SIZE,
+            CHUNK_SIZE + 1,
+            3 * CHUNK_SIZE,
+        ] {
+            let path = format!("{len}.bin");
+            let content = content(len);
+            encrypted.write(&path, content.clone()).await.unwrap();
+            let stored = raw.read(&path).await.unwrap().to_vec();
+            assert_ne!(stored, content);
+            assert_eq!(&stored[..MAGIC.len()], MAGIC);
+            assert_eq!(
+                encrypted.stat(&path).await.unwrap().content_length(),
+                len as u64
+            );
+            assert_eq!(encrypted.read(&path).await.unwrap().to_vec(), content);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_with_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_, encrypted) = operators(&dir, keys(&[KEY_A]));
+        let content = content(3 * CHUNK_SIZE + 100);
+        encrypted.write("a.bin", content.clone()).await.unwrap();
+        for (start, end) in [
+            (0, 10),
+            (CHUNK_SIZE - 5, CHUNK_SIZE + 5),
+            (CHUNK_SIZE, 2 * CHUNK_SIZE),
+            (2 * CHUNK_SIZE + 1, content.len()),
+        ] {
+            let read = encrypted
+                .read_with("a.bin")
+                .range(start as u64..end as u64)
+                .await
+                .unwrap()
+                .to_vec();
+            assert_eq!(read, content[start..end]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_given_plain_object() {
+        let dir = tempfile::tempdir().unwrap();
+        let (raw, encrypted) = operators(&dir, keys(&[KEY_A]));
+        raw.write("plain.txt", "written before encryption")
+            .await
+            .unwrap();
+        assert_eq!(
+            encrypted.read("plain.txt").await.unwrap().to_vec(),
+            b"written before encryption"
+        );
+        assert_eq!(
+            encrypted.stat("plain.txt").await.unwrap().content_length(),
+            25
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_return_err_given_tampered_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let (raw, encrypted) = operators(&dir, keys(&[KEY_A]));
+        encrypted.write("a.txt", "secret").await.unwrap();
+        let mut stored = raw.read("a.txt").await.unwrap().to_vec();
+        stored[HEADER_LEN] ^= 1;
+        raw.write("a.txt", stored).await.unwrap();
+        assert!(encrypted.read("a.txt").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rewrap_given_rotated_master_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let (raw, encrypted) = operators(&dir, keys(&[KEY_A]));
+        encrypted.write("a.txt", "secret").await.unwrap();
+
+        let rotated = keys(&[KEY_B, KEY_A]);
+        let mut stored = raw.read("a.txt").await.unwrap().to_vec();
+        let header = rotated.rewrap(&stored[..HEADER_LEN]).unwrap().unwrap();
+        assert!(rotated.rewrap(&header).unwrap().is_none());
+        stored[..HEADER_LEN].copy_from_slice(&header);
+        raw.write("a.txt", stored).await.unwrap();
+
+        // the retired key is no longer needed
+        let (_, encrypted) = operators(&dir, keys(&[KEY_B]));
+        assert_eq!(encrypted.read("a.txt").await.unwrap().to_vec(), b"secret");
+        let (_, encrypted) = operators(&dir, keys(&[KEY_A]));
+        assert!(encrypted.read("a.txt").await.is_err());
+    }
+}
@@ -8,6 +8,12 @@ pub enum ObjectStorageError {
     Opendal(#[from] opendal::Error),
     #[error("{0}")]
     Io(#[from] std::io::Error),
+    #[cfg(feature = "s3")]
+    #[error("{0}")]
+    Http(#[from] http_client_kit::ReqwestMiddlewareError),
+    #[cfg(feature = "s3")]
+    #[error("{0}")]
+    Reqwest(#[from] http_client_kit::ReqwestError),
     #[error("{0}")]
     Custom(String),
 }
@@ -1,13 +1,30 @@
-use std::{fmt::Debug, time::Duration};
+use std::{fmt::Debug, path::PathBuf, pin, sync::Arc, time::Duration};
 
 use crate::error::Result;
-use axum::http::Uri;
+use axum::{body::Bytes, http::Uri};
 use bon::Builder;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use futures_util::{Stream, StreamExt as _, TryStreamExt as _};
 use opendal::{Operator, layers::LoggingLayer, services};
 use serde::Deserialize;
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
 
-use crate::{ObjectStorageReader, ObjectStorageTrait};
+use crate::{
+    ObjectStorageMultipart, ObjectStorageReader, ObjectStorageTrait, ObjectStorageWriter,
+    PendingMultipart, PresignedWrite, UploadedPart,
+    encrypt::{EncryptionLayer, HEADER_LEN, MasterKeys},
+    error::ObjectStorageError,
+};
+
+/// Parts of unfinished multipart uploads are staged here, next to the objects
+const MULTIPART_DIR: &str = ".multipart";
+/// Holds the path of the object a staged upload is for
+const MULTIPART_MARKER: &str = "upload";
+/// Appended to an object while it is rewritten with a rotated key
+const ROTATE_SUFFIX: &str = ".rotating";
+/// Objects are written here and moved in place once closed, so an aborted or
+/// unfinished write never shows up as an object
+const WRITING_DIR: &str = ".writing";
 
 #[derive(Builder)]
 pub struct FsConfig {
@@ -15,11 +32,16 @@ pub struct FsConfig {
     basepath: String,
     hmac_secret: &'static [u8],
     link_period: Duration,
+    /// Hex encoded master keys, objects are encrypted at rest with the first one when present
+    #[builder(default)]
+    encryption_keys: Vec<String>,
 }
 
 #[derive(Clone)]
 pub struct Fs {
     operator: Operator,
+    root: PathBuf,
+    master_keys: Option<Arc<MasterKeys>>,
     basepath: String,
     hmac_secret: &'static [u8],
     link_period: Duration,
@@ -32,10 +54,22 @@ impl Debug for Fs {
 
 impl Fs {
     pub fn try_new(config: FsConfig) -> Result<Self> {
-        let builder = services::Fs::default().root(&config.root);
+        let writing_dir = PathBuf::from(&config.root).join(WRITING_DIR);
+        let builder = services::Fs::default()
+            .root(&config.root)
+            .atomic_write_dir(&writing_dir.to_string_lossy());
         let operator = Operator::new(builder)?
             .layer(LoggingLayer::default())
             .finish();
+        let master_keys = if config.encryption_keys.is_empty() {
+            None
+        } else {
+            Some(Arc::new(MasterKeys::from_hex(&config.encryption_keys)?))
+        };
+        let operator = match &master_keys {
+            Some(master_keys) => operator.layer(EncryptionLayer::new(master_keys.clone())),
+            None => operator,
+        };
         let basepath = if config.basepath.ends_with("/") {
             config.basepath.to_string()
         } else {
@@ -43,6 +77,8 @@ impl Fs {
         };
         Ok(Self {
             operator,
+            root: PathBuf::from(config.root),
+            master_keys,
             basepath,
             hmac_secret: config.hmac_secret,
             link_period: config.link_period,
@@ -58,23 +94,13 @@ impl ObjectStorageTrait for Fs {
 
 impl ObjectStorageReader for Fs {
     async fn presign_url(&self, path: impl AsRef<str>) -> Result<String> {
-        Ok(self.sign_path(path.as_ref()))
+        Ok(self.sign_path(path.as_ref(), self.link_period))
+    }
+    async fn presign_url_with(&self, path: impl AsRef<str>, expires: Duration) -> Result<String> {
+        Ok(self.sign_path(path.as_ref(), expires))
     }
     fn verify_url(&self, url: Uri) -> bool {
-        let path = url.path();
-        let path = path.strip_prefix(&self.basepath).unwrap_or(path);
-        let Some(query) = url.query() else {
-            return false;
-        };
-        let Ok(params) = serde_urlencoded::from_str::<FileParams>(query) else {
-            return false;
-        };
-        if Utc::now() > params.exp {
-            return false;
-        }
-        let expected = self.encode_hmac(path, params.exp.timestamp() as u64);
-
-        expected == params.sign
+        self.verify_signed(&url, |path, expired_at| format!("{}:{}", path, expired_at))
     }
 
     fn purify_url(&self, signed: String) -> String {
@@ -92,14 +118,253 @@ impl ObjectStorageReader for Fs {
     }
 }
 
+/// Uploads go to a PUT endpoint of this server, signed like the GET links
+/// with the content type and size bound into the signature
+impl ObjectStorageWriter for Fs {
+    async fn presign_write(
+        &self,
+        path: impl AsRef<str>,
+        content_type: &str,
+        size: u64,
+        expires: Duration,
+    ) -> Result<PresignedWrite> {
+        let path = path.as_ref();
+        let expired_at = (Utc::now() + expires).timestamp() as u64;
+        let sign = self.hmac(&Self::write_data(path, content_type, size, expired_at));
+        Ok(PresignedWrite {
+            method: "PUT".to_string(),
+            url: format!("{}{}?sign={}&exp={}", self.basepath, path, sign, expired_at),
+            headers: vec![("Content-Type".to_string(), content_type.to_string())],
+        })
+    }
+
+    fn verify_write_url(&self, url: Uri, content_type: &str, size: u64) -> bool {
+        self.verify_signed(&url, |path, expired_at| {
+            Self::write_data(path, content_type, size, expired_at)
+        })
+    }
+}
+
+impl ObjectStorageMultipart for Fs {
+    async fn create_multipart(&self, path: impl AsRef<str>) -> Result<String> {
+        let nanos = Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let seed = format!("{}:{}", path.as_ref(), nanos);
+        let upload_id = hex::encode(hmac_sha256::HMAC::mac(seed.as_bytes(), self.hmac_secret));
+        let upload_id = upload_id[..32].to_string();
+        let marker = format!("{}{MULTIPART_MARKER}", Self::multipart_dir(&upload_id)?);
+        self.operator
+            .write(&marker, path.as_ref().as_bytes().to_vec())
+            .await?;
+        Ok(upload_id)
+    }
+
+    async fn upload_part(
+        &self,
+        _path: impl AsRef<str>,
+        upload_id: &str,
+        part_number: u32,
+        body: impl Stream<Item = std::io::Result<Bytes>> + Send + 'static,
+        size: u64,
+    ) -> Result<String> {
+        let part_path = format!("{}{part_number}", Self::multipart_dir(upload_id)?);
+        let mut writer = self.operator.writer(&part_path).await?;
+        let mut body = pin::pin!(body);
+        let mut written: u64 = 0;
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk?;
+            written += chunk.len() as u64;
+            writer.write(chunk).await?;
+        }
+        if written != size {
+            let _ = writer.abort().await;
+            self.operator.delete(&part_path).await?;
+            return Err(ObjectStorageError::Custom(format!(
+                "Part {part_number} has {written} bytes, expected {size}"
+            )));
+        }
+        writer.close().await?;
+        Ok(part_number.to_string())
+    }
+
+    async fn list_parts(
+        &self,
+        _path: impl AsRef<str>,
+        upload_id: &str,
+    ) -> Result<Vec<UploadedPart>> {
+        let dir = Self::multipart_dir(upload_id)?;
+        let mut parts = Vec::new();
+        for entry in self.operator.list(&dir).await? {
+            let Ok(part_number) = entry.name().parse::<u32>() else {
+                continue;
+            };
+            let metadata = self.operator.stat(entry.path()).await?;
+            parts.push(UploadedPart {
+                part_number,
+                e_tag: part_number.to_string(),
+                size: metadata.content_length(),
+            });
+        }
+        parts.sort_by_key(|part| part.part_number);
+        Ok(parts)
+    }
+
+    async fn complete_multipart(
+        &self,
+        path: impl AsRef<str>,
+        upload_id: &str,
+        parts: Vec<(u32, String)>,
+    ) -> Result<()> {
+        let dir = Self::multipart_dir(upload_id)?;
+        let mut writer = self.operator.writer(path.as_ref()).await?;
+        for (part_number, _) in parts {
+            let buffer = self.operator.read(&format!("{dir}{part_number}")).await?;
+            writer.write(buffer).await?;
+        }
+        writer.close().await?;
+        self.operator.remove_all(&dir).await?;
+        Ok(())
+    }
+
+    async fn abort_multipart(&self, _path: impl AsRef<str>, upload_id: &str) -> Result<()> {
+        self.operator
+            .remove_all(&Self::multipart_dir(upload_id)?)
+            .await?;
+        Ok(())
+    }
+
+    async fn list_multiparts(&self) -> Result<Vec<PendingMultipart>> {
+        let entries = match self.operator.list(&format!("{MULTIPART_DIR}/")).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == opendal::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut uploads = Vec::new();
+        for entry in entries {
+            let upload_id = entry.name().trim_end_matches('/');
+            let Ok(dir) = Self::multipart_dir(upload_id) else {
+                continue;
+            };
+            // the marker is written when the upload starts, a staging dir without
+            // one is dated by the dir itself
+            let marker = format!("{dir}{MULTIPART_MARKER}");
+            let (path, staged) = match self.operator.read(&marker).await {
+                Ok(buffer) => (
+                    String::from_utf8_lossy(&buffer.to_vec()).to_string(),
+                    self.root.join(&marker),
+                ),
+                Err(_) => (String::new(), self.root.join(&dir)),
+            };
+            let modified = tokio::fs::metadata(staged).await?.modified()?;
+            uploads.push(PendingMultipart {
+                path,
+                upload_id: upload_id.to_string(),
+                initiated_at: DateTime::<Utc>::from(modified),
+            });
+        }
+        Ok(uploads)
+    }
+}
+
 impl Fs {
+    /// Wraps the data key of every encrypted object with the active master key, returns
+    /// how many objects were rewrapped. Each object is rewritten to a temporary file that
+    /// replaces it once synced, so a crash leaves either the old or the new header behind.
+    /// After this the retired master keys can be dropped from the configuration
+    pub async fn rotate_encryption_keys(&self) -> Result<u64> {
+        let Some(master_keys) = &self.master_keys else {
+            return Err(ObjectStorageError::Custom(
+                "Encryption is not enabled".to_string(),
+            ));
+        };
+        // listed up front, the temporary files must not show up in the listing
+        let paths: Vec<String> = self
+            .operator
+            .lister_with("")
+            .recursive(true)
+            .await?
+            .try_filter_map(|entry| async move {
+                let path = entry.path();
+                let writing = path.starts_with(&format!("{WRITING_DIR}/"));
+                Ok((entry.metadata().is_file() && !writing).then(|| path.to_string()))
+            })
+            .try_collect()
+            .await?;
+        let mut rotated = 0;
+        for path in paths {
+            let path = self.root.join(path);
+            let mut file = tokio::fs::File::open(&path).await?;
+            let mut header = vec![0u8; HEADER_LEN];
+            if file.read_exact(&mut header).await.is_err() {
+                continue;
+            }
+            let Some(header) = master_keys.rewrap(&header)? else {
+                continue;
+            };
+            let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+            temp_name.push(ROTATE_SUFFIX);
+            let temp_path = path.with_file_name(temp_name);
+            let rewritten = async {
+                let mut temp = tokio::fs::File::create(&temp_path).await?;
+                temp.write_all(&header).await?;
+                tokio::io::copy(&mut file, &mut temp).await?;
+                temp.sync_all().await?;
+                tokio::fs::rename(&temp_path, &path).await?;
+                if let Some(dir) = path.parent() {
+                    tokio::fs::File::open(dir).await?.sync_all().await?;
+                }
+                Ok::<_, std::io::Error>(())
+            }
+            .await;
+            if let Err(e) = rewritten {
+                let _ = tokio::fs::remove_file(&temp_path).await;
+                return Err(e.into());
+            }
+            rotated += 1;
+        }
+        Ok(rotated)
+    }
+
+    fn multipart_dir(upload_id: &str) -> Result<String> {
+        // the id ends up in a path, never let it escape the staging directory
+        if upload_id.is_empty() || !upload_id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ObjectStorageError::Custom(format!(
+                "Invalid upload id: {upload_id}"
+            )));
+        }
+        Ok(format!("{MULTIPART_DIR}/{upload_id}/"))
+    }
+
     fn encode_hmac(&self, path: &str, expired_at: u64) -> String {
-        let data = format!("{}:{}", path, expired_at);
+        self.hmac(&format!("{}:{}", path, expired_at))
+    }
+
+    fn hmac(&self, data: &str) -> String {
         hex::encode(hmac_sha256::HMAC::mac(data.as_bytes(), self.hmac_secret))
     }
 
-    fn sign_path(&self, path: &str) -> String {
-        let expired_at = (Utc::now() + self.link_period).timestamp() as u64;
+    fn write_data(path: &str, content_type: &str, size: u64, expired_at: u64) -> String {
+        format!("PUT:{}:{}:{}:{}", path, content_type, size, expired_at)
+    }
+
+    fn verify_signed(&self, url: &Uri, data: impl FnOnce(&str, u64) -> String) -> bool {
+        let path = url.path();
+        let path = path.strip_prefix(&self.basepath).unwrap_or(path);
+        let Some(query) = url.query() else {
+            return false;
+        };
+        let Ok(params) = serde_urlencoded::from_str::<FileParams>(query) else {
+            return false;
+        };
+        if Utc::now() > params.exp {
+            return false;
+        }
+        let expected = self.hmac(&data(path, params.exp.timestamp() as u64));
+
+        expected == params.sign
+    }
+
+    fn sign_path(&self, path: &str, expires: Duration) -> String {
+        let expired_at = (Utc::now() + expires).timestamp() as u64;
         let sign = self.encode_hmac(path, expired_at);
         let url = format!("{}{}?sign={}&exp={}", self.basepath, path, sign, expired_at);
         url
@@ -118,7 +383,16 @@ pub struct FileParams {
 mod tests {
     use std::str::FromStr;
 
+    use axum::body::Bytes;
+
+    use futures_util::TryStreamExt as _;
+
     use super::*;
+    use crate::ObjectStorageStreamReader as _;
+
+    fn part(data: &'static [u8]) -> impl Stream<Item = std::io::Result<Bytes>> + Send + 'static {
+        futures_util::stream::once(async move { Ok(Bytes::from_static(data)) })
+    }
 
     fn build_fs() -> Fs {
         let dir = tempfile::tempdir().unwrap();
@@ -183,6 +457,126 @@ mod tests {
         assert!(!fs.verify_url(uri));
     }
 
+    #[tokio::test]
+    async fn test_presign_url_with() {
+        let fs = build_fs();
+        let signed = fs
+            .presign_url_with("test.txt", Duration::from_secs(3600))
+            .await
+            .unwrap();
+        let uri = Uri::from_str(&signed).unwrap();
+        assert!(fs.verify_url(uri.clone()));
+        let exp: i64 = uri
+            .query()
+            .and_then(|query| query.split('&').find_map(|kv| kv.strip_prefix("exp=")))
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(exp > (Utc::now() + Duration::from_secs(3000)).timestamp());
+    }
+
+    #[tokio::test]
+    async fn test_verify_write_url() {
+        let fs = build_fs();
+        let presigned = fs
+            .presign_write(
+                "2025/01/test.txt",
+                "text/plain",
+                11,
+                Duration::from_secs(60),
+            )
+            .await
+            .unwrap();
+        assert_eq!(presigned.method, "PUT");
+        let uri = Uri::from_str(&presigned.url).unwrap();
+        assert!(fs.verify_write_url(uri.clone(), "text/plain", 11));
+        assert!(!fs.verify_write_url(uri.clone(), "text/html", 11));
+        assert!(!fs.verify_write_url(uri.clone(), "text/plain", 12));
+        // a write link does not grant reads and vice versa
+        assert!(!fs.verify_url(uri));
+        let read = Uri::from_str(&fs.presign_url("2025/01/test.txt").await.unwrap()).unwrap();
+        assert!(!fs.verify_write_url(read, "text/plain", 11));
+    }
+
+    #[tokio::test]
+    async fn test_write_body_return_err_given_body_exceeds_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let fs = Fs::try_new(
+            FsConfig::builder()
+                .root(dir.path().to_string_lossy().to_string())
+                .basepath("/uploads".to_string())
+                .hmac_secret(b"secret")
+                .link_period(Duration::from_secs(60))
+                .build(),
+        )
+        .unwrap();
+        let body = || {
+            futures_util::stream::iter(vec![
+                Ok::<_, std::io::Error>(Bytes::from_static(b"hello ")),
+                Ok(Bytes::from_static(b"world")),
+            ])
+        };
+        assert_eq!(fs.write_body("test.txt", body(), 11).await.unwrap(), 11);
+        assert!(fs.write_body("large.txt", body(), 10).await.is_err());
+        assert_eq!(fs.size("test.txt").await.unwrap(), Some(11));
+        assert_eq!(fs.size("large.txt").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_rename() {
+        let dir = tempfile::tempdir().unwrap();
+        let fs = Fs::try_new(
+            FsConfig::builder()
+                .root(dir.path().to_string_lossy().to_string())
+                .basepath("/uploads".to_string())
+                .hmac_secret(b"secret")
+                .link_period(Duration::from_secs(60))
+                .build(),
+        )
+        .unwrap();
+        fs.write("a/test.txt", std::io::Cursor::new(b"hello".to_vec()))
+            .await
+            .unwrap();
+        fs.rename("a/test.txt", "b/test.txt").await.unwrap();
+        assert_eq!(fs.size("a/test.txt").await.unwrap(), None);
+        assert_eq!(fs.size("b/test.txt").await.unwrap(), Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_writer_list_dir_and_delete_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let fs = Fs::try_new(
+            FsConfig::builder()
+                .root(dir.path().to_string_lossy().to_string())
+                .basepath("/uploads".to_string())
+                .hmac_secret(b"secret")
+                .link_period(Duration::from_secs(60))
+                .build(),
+        )
+        .unwrap();
+        assert!(fs.list_dir("exports").await.unwrap().is_empty());
+
+        let mut writer = fs.writer("exports/20260101/a.csv").await.unwrap();
+        writer.write(b"id\n".to_vec()).await.unwrap();
+        writer.write(b"1\n".to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+        let writer = fs.writer("exports/20260102/b.csv").await.unwrap();
+        writer.abort().await.unwrap();
+        assert_eq!(fs.size("exports/20260101/a.csv").await.unwrap(), Some(5));
+        assert_eq!(fs.size("exports/20260102/b.csv").await.unwrap(), None);
+
+        let dirs = fs.list_dir("exports").await.unwrap();
+        assert!(dirs.contains(&"20260101".to_string()));
+        fs.delete_dir("exports/20260101/").await.unwrap();
+        assert_eq!(fs.size("exports/20260101/a.csv").await.unwrap(), None);
+        assert!(
+            !fs.list_dir("exports")
+                .await
+                .unwrap()
+                .contains(&"20260101".to_string())
+        );
+    }
+
     #[test]
     fn test_purify_url_opt() {
         let fs = build_fs();
@@ -197,4 +591,156 @@ mod tests {
                 .is_some()
         );
     }
+
+    #[tokio::test]
+    async fn test_multipart_upload() {
+        let dir = tempfile::tempdir().unwrap();
+        let fs = Fs::try_new(
+            FsConfig::builder()
+                .root(dir.path().to_string_lossy().to_string())
+                .basepath("/uploads".to_string())
+                .hmac_secret(b"secret")
+                .link_period(Duration::from_secs(60))
+                .build(),
+        )
+        .unwrap();
+        let path = "2025/01/test.txt";
+        let upload_id = fs.create_multipart(path).await.unwrap();
+        let e_tag2 = fs
+            .upload_part(path, &upload_id, 2, part(b"world"), 5)
+            .await
+            .unwrap();
+        let e_tag1 = fs
+            .upload_part(path, &upload_id, 1, part(b"hello "), 6)
+            .await
+            .unwrap();
+        let parts = fs.list_parts(path, &upload_id).await.unwrap();
+        assert_eq!(
+            parts
+                .iter()
+                .map(|part| (part.part_number, part.size))
+                .collect::<Vec<_>>(),
+            vec![(1, 6), (2, 5)]
+        );
+
+        fs.complete_multipart(path, &upload_id, vec![(1, e_tag1), (2, e_tag2)])
+            .await
+            .unwrap();
+        let content = fs.operator().read(path).await.unwrap().to_vec();
+        assert_eq!(content, b"hello world");
+        assert!(fs.list_parts(path, &upload_id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_multiparts() {
+        let dir = tempfile::tempdir().unwrap();
+        let fs = build_encrypted_fs(&dir, &[]);
+        assert!(fs.list_multiparts().await.unwrap().is_empty());
+
+        let upload_id = fs.create_multipart("2025/01/a.txt").await.unwrap();
+        fs.upload_part("2025/01/a.txt", &upload_id, 1, part(b"hello"), 5)
+            .await
+            .unwrap();
+        let uploads = fs.list_multiparts().await.unwrap();
+        assert_eq!(uploads.len(), 1);
+        assert_eq!(uploads[0].path, "2025/01/a.txt");
+        assert_eq!(uploads[0].upload_id, upload_id);
+        assert!(uploads[0].initiated_at <= Utc::now());
+        assert_eq!(
+            fs.list_parts("2025/01/a.txt", &upload_id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        fs.abort_multipart("2025/01/a.txt", &upload_id)
+            .await
+            .unwrap();
+        assert!(fs.list_multiparts().await.unwrap().is_empty());
+    }
+
+    fn build_encrypted_fs(dir: &tempfile::TempDir, keys: &[&str]) -> Fs {
+        Fs::try_new(
+            FsConfig::builder()
+                .root(dir.path().to_string_lossy().to_string())
+                .basepath("/uploads".to_string())
+                .hmac_secret(b"secret")
+                .link_period(Duration::from_secs(60))
+                .encryption_keys(keys.iter().map(|key| key.to_string()).collect())
+                .build(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_write_stream_given_encryption_enabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let fs = build_encrypted_fs(&dir, &[&"a".repeat(64)]);
+        fs.write("a.txt", std::io::Cursor::new(b"identity document".to_vec()))
+            .await
+            .unwrap();
+        let stored = tokio::fs::read(dir.path().join("a.txt")).await.unwrap();
+        assert!(!stored.windows(8).any(|w| w == b"identity"));
+        assert_eq!(fs.size("a.txt").await.unwrap(), Some(17));
+        let read: Vec<u8> = fs
+            .read_range_stream("a.txt", 9..17)
+            .await
+            .unwrap()
+            .map_ok(|chunk| chunk.to_vec())
+            .try_concat()
+            .await
+            .unwrap();
+        assert_eq!(read, b"document");
+    }
+
+    #[tokio::test]
+    async fn test_rotate_encryption_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let (old, new) = ("a".repeat(64), "b".repeat(64));
+        let fs = build_encrypted_fs(&dir, &[&old]);
+        fs.write("2025/01/a.txt", std::io::Cursor::new(b"hello".to_vec()))
+            .await
+            .unwrap();
+        assert!(build_fs().rotate_encryption_keys().await.is_err());
+
+        let fs = build_encrypted_fs(&dir, &[&new, &old]);
+        assert_eq!(fs.rotate_encryption_keys().await.unwrap(), 1);
+        assert_eq!(fs.rotate_encryption_keys().await.unwrap(), 0);
+        let mut names = tokio::fs::read_dir(dir.path().join("2025/01"))
+            .await
+            .unwrap();
+        let name = names.next_entry().await.unwrap().unwrap().file_name();
+        assert_eq!(name, "a.txt");
+        assert!(names.next_entry().await.unwrap().is_none());
+
+        let fs = build_encrypted_fs(&dir, &[&new]);
+        let content = fs.operator().read("2025/01/a.txt").await.unwrap().to_vec();
+        assert_eq!(content, b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_upload_part_return_err_given_upload_id_invalid() {
+        let fs = build_fs();
+        let result = fs
+            .upload_part("test.txt", "../../etc", 1, part(b""), 0)
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_upload_part_return_err_given_size_mismatch() {
+        let fs = build_fs();
+        let upload_id = fs.create_multipart("test.txt").await.unwrap();
+        let result = fs
+            .upload_part("test.txt", &upload_id, 1, part(b"hello"), 4)
+            .await;
+        assert!(result.is_err());
+        assert!(
+            fs.list_parts("test.txt", &upload_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
 }
@@ -1,19 +1,33 @@
-use futures_util::{SinkExt as _, Stream, StreamExt as _};
-use std::io::{Read, Seek};
+use futures_util::{SinkExt as _, Stream, StreamExt as _, TryStreamExt as _};
+use std::{
+    io::{Read, Seek},
+    ops::Range,
+    time::Duration,
+};
 
-use crate::error::Result;
-use axum::http::Uri;
+use crate::error::{ObjectStorageError, Result};
+use axum::{body::Bytes, http::Uri};
+use chrono::{DateTime, Utc};
+pub use opendal::FuturesBytesStream;
 use opendal::{DeleteInput, IntoDeleteInput, Operator};
 use tokio::fs::File;
 use tokio_util::io::ReaderStream;
+#[cfg(feature = "fs")]
+mod encrypt;
 pub mod error;
 #[cfg(feature = "fs")]
 mod fs;
+#[cfg(feature = "migrate")]
+pub mod migrate;
 #[cfg(feature = "s3")]
 mod s3;
+#[cfg(feature = "s3")]
+mod sigv4;
 #[cfg(feature = "fs")]
 pub type ObjectStorage = fs::Fs;
 
+#[cfg(feature = "fs")]
+pub use encrypt::MasterKeys;
 #[cfg(feature = "fs")]
 pub use fs::FsConfig;
 
@@ -71,6 +85,55 @@ pub trait ObjectStorageWriter: ObjectStorageTrait {
         }
     }
 
+    /// Opens `path` for content produced piece by piece, the object is complete once
+    /// the writer is closed
+    fn writer(&self, path: impl AsRef<str>) -> impl Future<Output = Result<ObjectWriter>> {
+        async move { Ok(ObjectWriter(self.operator().writer(path.as_ref()).await?)) }
+    }
+
+    /// Writes a request body to `path`, fails once it grows past `limit` bytes
+    fn write_body<E>(
+        &self,
+        path: impl AsRef<str>,
+        mut body: impl Stream<Item = std::result::Result<Bytes, E>> + Unpin,
+        limit: u64,
+    ) -> impl Future<Output = Result<u64>>
+    where
+        E: std::error::Error,
+    {
+        async move {
+            let mut writer = self.operator().writer(path.as_ref()).await?;
+            let mut total_size: u64 = 0;
+            while let Some(chunk) = body.next().await {
+                let chunk = chunk.map_err(|e| ObjectStorageError::Custom(e.to_string()))?;
+                total_size += chunk.len() as u64;
+                if total_size > limit {
+                    // not every backend can abort, make sure no partial object is left behind
+                    let _ = writer.abort().await;
+                    self.operator().delete(path.as_ref()).await?;
+                    return Err(ObjectStorageError::Custom(format!(
+                        "Body exceeds {limit} bytes"
+                    )));
+                }
+                writer.write(chunk).await?;
+            }
+            writer.close().await?;
+            Ok(total_size)
+        }
+    }
+
+    /// Issues a URL the client can upload `size` bytes of `content_type` to within `expires`
+    fn presign_write(
+        &self,
+        path: impl AsRef<str>,
+        content_type: &str,
+        size: u64,
+        expires: Duration,
+    ) -> impl Future<Output = Result<PresignedWrite>>;
+
+    /// Checks a presigned write that arrived at this server
+    fn verify_write_url(&self, url: Uri, content_type: &str, size: u64) -> bool;
+
     fn delete_many(&self, paths: Vec<String>) -> impl Future<Output = Result<()>> {
         async move {
             let items: Vec<DeleteInput> = paths
@@ -81,13 +144,184 @@ pub trait ObjectStorageWriter: ObjectStorageTrait {
             Ok(())
         }
     }
+
+    /// Removes `dir` and every object under it
+    fn delete_dir(&self, dir: impl AsRef<str>) -> impl Future<Output = Result<()>> {
+        async move {
+            self.operator().remove_all(dir.as_ref()).await?;
+            Ok(())
+        }
+    }
+
+    /// Moves an object, copied and deleted since not every backend can rename
+    fn rename(
+        &self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+    ) -> impl Future<Output = Result<()>> {
+        async move {
+            let operator = self.operator();
+            operator.copy(from.as_ref(), to.as_ref()).await?;
+            operator.delete(from.as_ref()).await?;
+            Ok(())
+        }
+    }
 }
 
-impl<T> ObjectStorageWriter for T where T: ObjectStorageTrait {}
+/// Fills an object piece by piece, see `ObjectStorageWriter::writer`
+pub struct ObjectWriter(opendal::Writer);
+
+impl ObjectWriter {
+    pub async fn write(&mut self, bytes: impl Into<Bytes>) -> Result<()> {
+        self.0.write(bytes.into()).await?;
+        Ok(())
+    }
+
+    pub async fn close(mut self) -> Result<()> {
+        self.0.close().await?;
+        Ok(())
+    }
+
+    /// Drops what was written so far, no partial object is left behind
+    pub async fn abort(mut self) -> Result<()> {
+        self.0.abort().await?;
+        Ok(())
+    }
+}
+
+/// A request the client sends itself to put an object into storage
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresignedWrite {
+    pub method: String,
+    pub url: String,
+    /// Headers the client must send as they are, they are part of the signature
+    pub headers: Vec<(String, String)>,
+}
 
 pub trait ObjectStorageReader {
     fn presign_url(&self, path: impl AsRef<str>) -> impl Future<Output = Result<String>>;
+    /// Like `presign_url` but valid for `expires` instead of the storage default
+    fn presign_url_with(
+        &self,
+        path: impl AsRef<str>,
+        expires: Duration,
+    ) -> impl Future<Output = Result<String>>;
     fn verify_url(&self, url: Uri) -> bool;
     fn purify_url(&self, signed: String) -> String;
     fn purify_url_opt(&self, signed: Option<String>) -> Option<String>;
 }
+
+pub trait ObjectStorageStreamReader: ObjectStorageTrait {
+    fn read_stream(
+        &self,
+        path: impl AsRef<str>,
+    ) -> impl Future<Output = Result<impl Stream<Item = Result<impl AsRef<[u8]>>> + Unpin>> {
+        async move {
+            let stream = self
+                .operator()
+                .reader(path.as_ref())
+                .await?
+                .into_bytes_stream(..)
+                .await?;
+            Ok(stream.map_err(ObjectStorageError::from))
+        }
+    }
+
+    /// Like `read_stream` but only the bytes within `range`, the stream outlives the storage
+    fn read_range_stream(
+        &self,
+        path: impl AsRef<str>,
+        range: Range<u64>,
+    ) -> impl Future<Output = Result<FuturesBytesStream>> {
+        async move {
+            let stream = self
+                .operator()
+                .reader(path.as_ref())
+                .await?
+                .into_bytes_stream(range)
+                .await?;
+            Ok(stream)
+        }
+    }
+
+    /// Names of the objects and dirs right under `dir`, none when it does not exist
+    fn list_dir(&self, dir: impl AsRef<str>) -> impl Future<Output = Result<Vec<String>>> {
+        async move {
+            let dir = format!("{}/", dir.as_ref().trim_end_matches('/'));
+            let entries = match self.operator().list(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == opendal::ErrorKind::NotFound => return Ok(Vec::new()),
+                Err(e) => return Err(e.into()),
+            };
+            Ok(entries
+                .into_iter()
+                .filter(|entry| entry.path() != dir)
+                .map(|entry| entry.name().trim_end_matches('/').to_string())
+                .collect())
+        }
+    }
+
+    /// Returns the size of the object, `None` when it does not exist
+    fn size(&self, path: impl AsRef<str>) -> impl Future<Output = Result<Option<u64>>> {
+        async move {
+            match self.operator().stat(path.as_ref()).await {
+                Ok(metadata) => Ok(Some(metadata.content_length())),
+                Err(e) if e.kind() == opendal::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        }
+    }
+}
+
+impl<T> ObjectStorageStreamReader for T where T: ObjectStorageTrait {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadedPart {
+    pub part_number: u32,
+    pub e_tag: String,
+    pub size: u64,
+}
+
+/// A multipart upload that was started and neither completed nor aborted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingMultipart {
+    pub path: String,
+    pub upload_id: String,
+    pub initiated_at: DateTime<Utc>,
+}
+
+/// Uploads an object in parts that can arrive from different requests and nodes
+pub trait ObjectStorageMultipart {
+    /// Starts a multipart upload to `path`, returns the upload id
+    fn create_multipart(&self, path: impl AsRef<str>) -> impl Future<Output = Result<String>>;
+    /// Uploads one part of `size` bytes as it is read from `body`, returns its ETag
+    fn upload_part(
+        &self,
+        path: impl AsRef<str>,
+        upload_id: &str,
+        part_number: u32,
+        body: impl Stream<Item = std::io::Result<Bytes>> + Send + 'static,
+        size: u64,
+    ) -> impl Future<Output = Result<String>>;
+    /// Lists the parts already uploaded, ordered by part number
+    fn list_parts(
+        &self,
+        path: impl AsRef<str>,
+        upload_id: &str,
+    ) -> impl Future<Output = Result<Vec<UploadedPart>>>;
+    /// Assembles the `(part_number, e_tag)` parts in order into the final object
+    fn complete_multipart(
+        &self,
+        path: impl AsRef<str>,
+        upload_id: &str,
+        parts: Vec<(u32, String)>,
+    ) -> impl Future<Output = Result<()>>;
+    /// Drops the upload and every part uploaded so far
+    fn abort_multipart(
+        &self,
+        path: impl AsRef<str>,
+        upload_id: &str,
+    ) -> impl Future<Output = Result<()>>;
+    /// Lists the uploads still pending, so abandoned ones can be aborted
+    fn list_multiparts(&self) -> impl Future<Output = Result<Vec<PendingMultipart>>>;
+}
@@ -0,0 +1,325 @@
+#[cfg(feature = "fs")]
+use std::sync::Arc;
+use std::{collections::HashSet, path::PathBuf, str::FromStr as _};
+
+use bon::Builder;
+use futures_util::{StreamExt as _, TryStreamExt as _};
+use opendal::{Operator, Scheme, layers::LoggingLayer};
+use tokio::{
+    fs::OpenOptions,
+    io::{AsyncBufReadExt as _, AsyncWriteExt as _, BufReader},
+};
+
+#[cfg(feature = "fs")]
+use crate::encrypt::{EncryptionLayer, MasterKeys};
+use crate::error::{ObjectStorageError, Result};
+
+/// Staging areas of unfinished multipart uploads and writes, never worth migrating
+const SKIPPED_PREFIXES: &[&str] = &[".multipart/", ".writing/"];
+
+/// Builds an operator from `scheme:key=value,...`, the keys are the opendal options
+/// of the service, e.g. `fs:root=./data/uploads` or
+/// `s3:bucket=files,endpoint=http://localhost:9000,region=auto,access_key_id=..,secret_access_key=..`
+pub fn operator_from_spec(spec: &str) -> Result<Operator> {
+    let invalid = || ObjectStorageError::Custom(format!("Invalid storage `{spec}`"));
+    let (scheme, options) = spec.split_once(':').ok_or_else(invalid)?;
+    let scheme = Scheme::from_str(scheme.trim())?;
+    let options = options
+        .split(',')
+        .filter(|option| !option.trim().is_empty())
+        .map(|option| {
+            option
+                .split_once('=')
+                .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                .ok_or_else(invalid)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let operator = Operator::via_iter(scheme, options)?.layer(LoggingLayer::default());
+    Ok(operator)
+}
+
+/// Encrypts at rest what goes through an `fs` storage the way the application does, so
+/// objects are read in plain text and written encrypted with the active key. Other
+/// storages are never encrypted and are returned as they are
+#[cfg(feature = "fs")]
+pub fn encrypt_fs(spec: &str, operator: Operator, master_keys: &Arc<MasterKeys>) -> Operator {
+    if spec.trim_start().starts_with("fs:") {
+        operator.layer(EncryptionLayer::new(master_keys.clone()))
+    } else {
+        operator
+    }
+}
+
+/// Copies every object from one storage to another. Each copy is verified by size and
+/// blake3 hash, finished keys go to the checkpoint so an interrupted run resumes there
+#[derive(Builder)]
+pub struct Migrator {
+    source: Operator,
+    target: Operator,
+    /// Prepended to every key in the target
+    #[builder(default)]
+    prefix: String,
+    checkpoint: PathBuf,
+    #[builder(default = 4)]
+    concurrency: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct MigrateReport {
+    /// Keys written to the target in this run
+    pub copied: Vec<String>,
+    /// Keys finished by an earlier run or already identical in the target
+    pub skipped: usize,
+    /// Keys that could not be copied, with the reason
+    pub failed: Vec<(String, String)>,
+}
+
+enum Outcome {
+    Copied,
+    Identical,
+}
+
+impl Migrator {
+    pub async fn run(&self) -> Result<MigrateReport> {
+        let finished = self.finished_keys().await?;
+        let mut checkpoint = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.checkpoint)
+            .await?;
+        let keys: Vec<String> = self
+            .source
+            .lister_with("")
+            .recursive(true)
+            .await?
+            .try_filter_map(|entry| async move {
+                let key = entry.path();
+                let skipped = !entry.metadata().is_file()
+                    || SKIPPED_PREFIXES
+                        .iter()
+                        .any(|prefix| key.starts_with(prefix));
+                Ok((!skipped).then(|| key.to_string()))
+            })
+            .try_collect()
+            .await?;
+
+        let mut report = MigrateReport::default();
+        let pending: Vec<String> = keys
+            .into_iter()
+            .filter(|key| !finished.contains(key))
+            .collect();
+        report.skipped = finished.len();
+        let mut results = futures_util::stream::iter(pending)
+            .map(|key| async move {
+                let outcome = self.migrate(&key).await;
+                (key, outcome)
+            })
+            .buffer_unordered(self.concurrency.max(1));
+        while let Some((key, outcome)) = results.next().await {
+            match outcome {
+                Ok(outcome) => {
+                    checkpoint.write_all(format!("{key}\n").as_bytes()).await?;
+                    checkpoint.flush().await?;
+                    match outcome {
+                        Outcome::Copied => report.copied.push(key),
+                        Outcome::Identical => report.skipped += 1,
+                    }
+                }
+                Err(e) => {
+                    tracing::error!(key, error = %e, "Failed to migrate object");
+                    report.failed.push((key, e.to_string()));
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    async fn finished_keys(&self) -> Result<HashSet<String>> {
+        let file = match tokio::fs::File::open(&self.checkpoint).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashSet::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut lines = BufReader::new(file).lines();
+        let mut keys = HashSet::new();
+        while let Some(line) = lines.next_line().await? {
+            if !line.is_empty() {
+                keys.insert(line);
+            }
+        }
+        Ok(keys)
+    }
+
+    async fn migrate(&self, key: &str) -> Result<Outcome> {
+        let target_key = format!("{}{}", self.prefix, key);
+        let size = self.source.stat(key).await?.content_length();
+        if stat_size(&self.target, &target_key).await? == Some(size)
+            && hash(&self.source, key).await? == hash(&self.target, &target_key).await?
+        {
+            return Ok(Outcome::Identical);
+        }
+
+        let mut stream = self.source.reader(key).await?.into_bytes_stream(..).await?;
+        let mut writer = self.target.writer(&target_key).await?;
+        let mut hasher = blake3::Hasher::new();
+        let mut sent = 0;
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            sent += chunk.len() as u64;
+            hasher.update(&chunk);
+            writer.write(chunk).await?;
+        }
+        writer.close().await?;
+
+        let sent = (hasher.finalize().to_hex().to_string(), sent);
+        if hash(&self.target, &target_key).await? != sent {
+            self.target.delete(&target_key).await?;
+            return Err(ObjectStorageError::Custom(format!(
+                "Verification of {target_key} failed"
+            )));
+        }
+        Ok(Outcome::Copied)
+    }
+}
+
+async fn stat_size(operator: &Operator, key: &str) -> Result<Option<u64>> {
+    match operator.stat(key).await {
+        Ok(metadata) => Ok(Some(metadata.content_length())),
+        Err(e) if e.kind() == opendal::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn hash(operator: &Operator, key: &str) -> Result<(String, u64)> {
+    let mut stream = operator.reader(key).await?.into_bytes_stream(..).await?;
+    let mut hasher = blake3::Hasher::new();
+    let mut size = 0;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        size += chunk.len() as u64;
+        hasher.update(&chunk);
+    }
+    Ok((hasher.finalize().to_hex().to_string(), size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fs_operator(dir: &tempfile::TempDir) -> Operator {
+        operator_from_spec(&format!("fs:root={}", dir.path().to_string_lossy())).unwrap()
+    }
+
+    async fn setup() -> (tempfile::TempDir, tempfile::TempDir, Operator, Operator) {
+        let source_dir = tempfile::tempdir().unwrap();
+        let target_dir = tempfile::tempdir().unwrap();
+        let source = fs_operator(&source_dir);
+        let target = fs_operator(&target_dir);
+        source.write("2025/01/a.txt", "hello").await.unwrap();
+        source.write("2025/02/b.txt", "world!").await.unwrap();
+        source.write(".multipart/x/1", "part").await.unwrap();
+        (source_dir, target_dir, source, target)
+    }
+
+    #[test]
+    fn test_operator_from_spec_return_err_given_invalid_spec() {
+        assert!(operator_from_spec("fs").is_err());
+        assert!(operator_from_spec("fs:root").is_err());
+        assert!(operator_from_spec("unknown:root=/tmp").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_copy_every_object_with_prefix() {
+        let (_source_dir, _target_dir, source, target) = setup().await;
+        let checkpoint = tempfile::tempdir().unwrap();
+        let migrator = Migrator::builder()
+            .source(source)
+            .target(target.clone())
+            .prefix("uploads/".to_string())
+            .checkpoint(checkpoint.path().join("checkpoint"))
+            .build();
+        let mut report = migrator.run().await.unwrap();
+        report.copied.sort();
+        assert_eq!(report.copied, vec!["2025/01/a.txt", "2025/02/b.txt"]);
+        assert!(report.failed.is_empty());
+        let content = target.read("uploads/2025/02/b.txt").await.unwrap();
+        assert_eq!(content.to_vec(), b"world!");
+        assert!(!target.exists("uploads/.multipart/x/1").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_run_resume_given_checkpoint() {
+        let (_source_dir, _target_dir, source, target) = setup().await;
+        let checkpoint = tempfile::tempdir().unwrap();
+        let checkpoint = checkpoint.path().join("checkpoint");
+        tokio::fs::write(&checkpoint, "2025/01/a.txt\n")
+            .await
+            .unwrap();
+        let migrator = Migrator::builder()
+            .source(source.clone())
+            .target(target.clone())
+            .checkpoint(checkpoint.clone())
+            .build();
+        let report = migrator.run().await.unwrap();
+        assert_eq!(report.copied, vec!["2025/02/b.txt"]);
+        assert_eq!(report.skipped, 1);
+        assert!(!target.exists("2025/01/a.txt").await.unwrap());
+
+        // a second run finds everything finished
+        let report = migrator.run().await.unwrap();
+        assert!(report.copied.is_empty());
+        assert_eq!(report.skipped, 2);
+    }
+
+    #[cfg(feature = "fs")]
+    #[tokio::test]
+    async fn test_run_encrypt_given_fs_target() {
+        let (_source_dir, target_dir, source, raw_target) = setup().await;
+        let spec = format!("fs:root={}", target_dir.path().to_string_lossy());
+        let master_keys = Arc::new(MasterKeys::from_hex(&["a".repeat(64)]).unwrap());
+        let target = encrypt_fs(&spec, raw_target.clone(), &master_keys);
+        let checkpoint = tempfile::tempdir().unwrap();
+        let migrator = Migrator::builder()
+            .source(source)
+            .target(target.clone())
+            .checkpoint(checkpoint.path().join("checkpoint"))
+            .build();
+        let report = migrator.run().await.unwrap();
+        assert_eq!(report.copied.len(), 2);
+        assert!(report.failed.is_empty());
+        let stored = raw_target.read("2025/01/a.txt").await.unwrap().to_vec();
+        assert!(!stored.windows(5).any(|w| w == b"hello"));
+        let content = target.read("2025/01/a.txt").await.unwrap();
+        assert_eq!(content.to_vec(), b"hello");
+
+        // copied back in plain text, verified against the decrypted content
+        let plain_dir = tempfile::tempdir().unwrap();
+        let plain = fs_operator(&plain_dir);
+        let migrator = Migrator::builder()
+            .source(target)
+            .target(plain.clone())
+            .checkpoint(checkpoint.path().join("back"))
+            .build();
+        assert_eq!(migrator.run().await.unwrap().copied.len(), 2);
+        let content = plain.read("2025/01/a.txt").await.unwrap();
+        assert_eq!(content.to_vec(), b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_run_overwrite_given_target_differs() {
+        let (_source_dir, _target_dir, source, target) = setup().await;
+        target.write("2025/01/a.txt", "hellO").await.unwrap();
+        target.write("2025/02/b.txt", "world!").await.unwrap();
+        let checkpoint = tempfile::tempdir().unwrap();
+        let migrator = Migrator::builder()
+            .source(source)
+            .target(target.clone())
+            .checkpoint(checkpoint.path().join("checkpoint"))
+            .build();
+        let report = migrator.run().await.unwrap();
+        assert_eq!(report.copied, vec!["2025/01/a.txt"]);
+        assert_eq!(report.skipped, 1);
+        let content = target.read("2025/01/a.txt").await.unwrap();
+        assert_eq!(content.to_vec(), b"hello");
+    }
+}
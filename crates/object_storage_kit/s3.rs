@@ -1,11 +1,27 @@
 use std::{fmt::Debug, time::Duration};
 
-use crate::error::Result;
-use axum::http::Uri;
+use crate::error::{ObjectStorageError, Result};
+use axum::{
+    body::Bytes,
+    http::{Method, Uri},
+};
 use bon::Builder;
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
+use http_client_kit::{
+    Body, HTTP_CLIENT, STREAMING_HTTP_CLIENT,
+    header::{HeaderMap, HeaderValue},
+};
 use opendal::{Operator, layers::LoggingLayer, services};
 
-use crate::{ObjectStorageReader, ObjectStorageTrait};
+use crate::{
+    ObjectStorageMultipart, ObjectStorageReader, ObjectStorageTrait, ObjectStorageWriter,
+    PendingMultipart, PresignedWrite, UploadedPart,
+    sigv4::{self, SigV4, UNSIGNED_PAYLOAD},
+};
+
+/// How long the links of `presign_url` stay valid
+const LINK_PERIOD: Duration = Duration::from_secs(60);
 
 #[derive(Builder)]
 pub struct S3Config {
@@ -20,6 +36,8 @@ pub struct S3Config {
 pub struct S3 {
     operator: Operator,
     bucket: String,
+    endpoint: String,
+    signer: SigV4,
 }
 
 impl Debug for S3 {
@@ -42,7 +60,17 @@ impl S3 {
             .finish();
         operator.check().await?;
         tracing::info!("S3 bucket: {} connected", bucket);
-        Ok(Self { operator, bucket })
+        let signer = SigV4::new(
+            config.access_key_id,
+            config.secret_access_key,
+            config.region,
+        );
+        Ok(Self {
+            operator,
+            bucket,
+            endpoint: config.endpoint.trim_end_matches('/').to_string(),
+            signer,
+        })
     }
 }
 
@@ -54,10 +82,10 @@ impl ObjectStorageTrait for S3 {
 
 impl ObjectStorageReader for S3 {
     async fn presign_url(&self, path: impl AsRef<str>) -> Result<String> {
-        let req = self
-            .operator()
-            .presign_read(path.as_ref(), Duration::from_secs(60))
-            .await?;
+        self.presign_url_with(path, LINK_PERIOD).await
+    }
+    async fn presign_url_with(&self, path: impl AsRef<str>, expires: Duration) -> Result<String> {
+        let req = self.operator().presign_read(path.as_ref(), expires).await?;
 
         Ok(req.uri().to_string())
     }
@@ -81,3 +109,392 @@ impl ObjectStorageReader for S3 {
         Some(self.purify_url(signed).to_string())
     }
 }
+
+/// Clients upload straight to the bucket, S3 rejects a body whose
+/// type or length differs from the signed headers
+impl ObjectStorageWriter for S3 {
+    async fn presign_write(
+        &self,
+        path: impl AsRef<str>,
+        content_type: &str,
+        size: u64,
+        expires: Duration,
+    ) -> Result<PresignedWrite> {
+        let canonical_uri = self.canonical_uri(path.as_ref());
+        let query = self.signer.presign(
+            Method::PUT.as_str(),
+            &canonical_uri,
+            &[
+                ("content-length", size.to_string()),
+                ("content-type", content_type.to_string()),
+                ("host", self.host()?),
+            ],
+            expires,
+            Utc::now(),
+        );
+        Ok(PresignedWrite {
+            method: Method::PUT.to_string(),
+            url: format!(
+                "{}{canonical_uri}?{}",
+                self.endpoint,
+                sigv4::canonical_query(&query)
+            ),
+            headers: vec![("Content-Type".to_string(), content_type.to_string())],
+        })
+    }
+
+    fn verify_write_url(&self, _url: Uri, _content_type: &str, _size: u64) -> bool {
+        // presigned writes are served by S3 itself
+        false
+    }
+}
+
+/// Multipart calls go straight to the S3 API with path-style addressing,
+/// opendal only uses multipart internally within a single writer
+impl ObjectStorageMultipart for S3 {
+    async fn create_multipart(&self, path: impl AsRef<str>) -> Result<String> {
+        let body = self
+            .send(
+                Method::POST,
+                path.as_ref(),
+                &[("uploads", String::new())],
+                Vec::new(),
+            )
+            .await?;
+        xml_value(&body, "UploadId")
+            .map(xml_unescape)
+            .ok_or_else(|| ObjectStorageError::Custom(format!("Missing UploadId: {body}")))
+    }
+
+    async fn upload_part(
+        &self,
+        path: impl AsRef<str>,
+        upload_id: &str,
+        part_number: u32,
+        body: impl Stream<Item = std::io::Result<Bytes>> + Send + 'static,
+        size: u64,
+    ) -> Result<String> {
+        let query = [
+            ("partNumber", part_number.to_string()),
+            ("uploadId", upload_id.to_string()),
+        ];
+        let (url, headers) = self.signed(&Method::PUT, path.as_ref(), &query)?;
+        let response = STREAMING_HTTP_CLIENT
+            .put(url)
+            .headers(headers)
+            .header(http_client_kit::header::CONTENT_LENGTH, size)
+            .body(Body::wrap_stream(body))
+            .send()
+            .await?;
+        let response = Self::check(&Method::PUT, path.as_ref(), response).await?;
+        response
+            .headers()
+            .get(http_client_kit::header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| ObjectStorageError::Custom("Missing ETag".to_string()))
+    }
+
+    async fn list_parts(
+        &self,
+        path: impl AsRef<str>,
+        upload_id: &str,
+    ) -> Result<Vec<UploadedPart>> {
+        let mut parts = Vec::new();
+        let mut marker: Option<String> = None;
+        loop {
+            let mut query = vec![("uploadId", upload_id.to_string())];
+            if let Some(marker) = marker.take() {
+                query.push(("part-number-marker", marker));
+            }
+            let body = self
+                .send(Method::GET, path.as_ref(), &query, Vec::new())
+                .await?;
+            for part in xml_values(&body, "Part") {
+                let part_number = xml_value(part, "PartNumber").and_then(|v| v.parse().ok());
+                let e_tag = xml_value(part, "ETag").map(xml_unescape);
+                let size = xml_value(part, "Size").and_then(|v| v.parse().ok());
+                if let (Some(part_number), Some(e_tag), Some(size)) = (part_number, e_tag, size) {
+                    parts.push(UploadedPart {
+                        part_number,
+                        e_tag,
+                        size,
+                    });
+                }
+            }
+            if xml_value(&body, "IsTruncated") != Some("true") {
+                break;
+            }
+            marker = xml_value(&body, "NextPartNumberMarker").map(str::to_string);
+            if marker.is_none() {
+                break;
+            }
+        }
+        parts.sort_by_key(|part| part.part_number);
+        Ok(parts)
+    }
+
+    async fn complete_multipart(
+        &self,
+        path: impl AsRef<str>,
+        upload_id: &str,
+        parts: Vec<(u32, String)>,
+    ) -> Result<()> {
+        let parts_xml: String = parts
+            .iter()
+            .map(|(part_number, e_tag)| {
+                format!(
+                    "<Part><PartNumber>{part_number}</PartNumber><ETag>{}</ETag></Part>",
+                    xml_escape(e_tag)
+                )
+            })
+            .collect();
+        let body = format!("<CompleteMultipartUpload>{parts_xml}</CompleteMultipartUpload>");
+        let response = self
+            .send(
+                Method::POST,
+                path.as_ref(),
+                &[("uploadId", upload_id.to_string())],
+                body.into_bytes(),
+            )
+            .await?;
+        // S3 may answer 200 and still report the failure in the body
+        if response.contains("<Error>") {
+            return Err(ObjectStorageError::Custom(response));
+        }
+        Ok(())
+    }
+
+    async fn abort_multipart(&self, path: impl AsRef<str>, upload_id: &str) -> Result<()> {
+        self.send(
+            Method::DELETE,
+            path.as_ref(),
+            &[("uploadId", upload_id.to_string())],
+            Vec::new(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn list_multiparts(&self) -> Result<Vec<PendingMultipart>> {
+        let mut uploads = Vec::new();
+        let mut markers: Option<(String, String)> = None;
+        loop {
+            let mut query = vec![("uploads", String::new())];
+            if let Some((key_marker, upload_id_marker)) = markers.take() {
+                query.push(("key-marker", key_marker));
+                query.push(("upload-id-marker", upload_id_marker));
+            }
+            let body = self.send(Method::GET, "", &query, Vec::new()).await?;
+            for upload in xml_values(&body, "Upload") {
+                let path = xml_value(upload, "Key").map(xml_unescape);
+                let upload_id = xml_value(upload, "UploadId").map(xml_unescape);
+                let initiated_at = xml_value(upload, "Initiated")
+                    .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+                    .map(|v| v.with_timezone(&Utc));
+                if let (Some(path), Some(upload_id), Some(initiated_at)) =
+                    (path, upload_id, initiated_at)
+                {
+                    uploads.push(PendingMultipart {
+                        path,
+                        upload_id,
+                        initiated_at,
+                    });
+                }
+            }
+            if xml_value(&body, "IsTruncated") != Some("true") {
+                break;
+            }
+            let key_marker = xml_value(&body, "NextKeyMarker").map(xml_unescape);
+            let upload_id_marker = xml_value(&body, "NextUploadIdMarker").map(xml_unescape);
+            let (Some(key_marker), Some(upload_id_marker)) = (key_marker, upload_id_marker) else {
+                break;
+            };
+            markers = Some((key_marker, upload_id_marker));
+        }
+        Ok(uploads)
+    }
+}
+
+impl S3 {
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Vec<u8>,
+    ) -> Result<String> {
+        let response = self.request(method, path, query, body).await?;
+        Ok(response.text().await?)
+    }
+
+    async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Vec<u8>,
+    ) -> Result<http_client_kit::Response> {
+        let (url, headers) = self.signed(&method, path, query)?;
+        let response = HTTP_CLIENT
+            .request(method.clone(), url)
+            .headers(headers)
+            .body(body)
+            .send()
+            .await?;
+        Self::check(&method, path, response).await
+    }
+
+    /// URL and headers of a request signed without a payload hash
+    fn signed(
+        &self,
+        method: &Method,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<(String, HeaderMap)> {
+        let host = self.host()?;
+        let canonical_uri = self.canonical_uri(path);
+        let canonical_query = sigv4::canonical_query(query);
+        let now = Utc::now();
+        let amz_date = sigv4::amz_date(now);
+        let authorization = self.signer.authorization(
+            method.as_str(),
+            &canonical_uri,
+            query,
+            &[
+                ("host", host),
+                ("x-amz-content-sha256", UNSIGNED_PAYLOAD.to_string()),
+                ("x-amz-date", amz_date.clone()),
+            ],
+            UNSIGNED_PAYLOAD,
+            now,
+        );
+        let url = format!("{}{canonical_uri}?{canonical_query}", self.endpoint);
+        let mut headers = HeaderMap::new();
+        let header_value = |value: &str| {
+            HeaderValue::from_str(value).map_err(|e| ObjectStorageError::Custom(e.to_string()))
+        };
+        headers.insert("x-amz-content-sha256", header_value(UNSIGNED_PAYLOAD)?);
+        headers.insert("x-amz-date", header_value(&amz_date)?);
+        headers.insert(
+            http_client_kit::header::AUTHORIZATION,
+            header_value(&authorization)?,
+        );
+        Ok((url, headers))
+    }
+
+    async fn check(
+        method: &Method,
+        path: &str,
+        response: http_client_kit::Response,
+    ) -> Result<http_client_kit::Response> {
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(ObjectStorageError::Custom(format!(
+                "S3 {method} {path} failed with {status}: {body}"
+            )));
+        }
+        Ok(response)
+    }
+
+    fn host(&self) -> Result<String> {
+        let endpoint = self
+            .endpoint
+            .parse::<Uri>()
+            .map_err(|e| ObjectStorageError::Custom(e.to_string()))?;
+        endpoint
+            .authority()
+            .map(|authority| authority.to_string())
+            .ok_or_else(|| {
+                ObjectStorageError::Custom(format!("Invalid endpoint: {}", self.endpoint))
+            })
+    }
+
+    fn canonical_uri(&self, path: &str) -> String {
+        sigv4::uri_encode(
+            &format!("/{}/{}", self.bucket, path.trim_start_matches('/')),
+            true,
+        )
+    }
+}
+
+/// Text of every `<tag>` element, enough for the flat responses of the multipart API
+fn xml_values<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let (open, close) = (format!("<{tag}>"), format!("</{tag}>"));
+    let mut values = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after_open = &rest[start + open.len()..];
+        let Some(end) = after_open.find(&close) else {
+            break;
+        };
+        values.push(&after_open[..end]);
+        rest = &after_open[end + close.len()..];
+    }
+    values
+}
+
+fn xml_value<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    xml_values(xml, tag).into_iter().next()
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn xml_unescape(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&#34;", "\"")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xml_values() {
+        let xml = r#"<ListPartsResult><IsTruncated>false</IsTruncated><Part><PartNumber>1</PartNumber><ETag>&quot;a1&quot;</ETag><Size>5242880</Size></Part><Part><PartNumber>2</PartNumber><ETag>"b2"</ETag><Size>10</Size></Part></ListPartsResult>"#;
+        let parts = xml_values(xml, "Part");
+        assert_eq!(parts.len(), 2);
+        assert_eq!(xml_value(parts[0], "PartNumber"), Some("1"));
+        assert_eq!(
+            xml_value(parts[0], "ETag").map(xml_unescape),
+            Some("\"a1\"".to_string())
+        );
+        assert_eq!(
+            xml_value(parts[1], "ETag").map(xml_unescape),
+            Some("\"b2\"".to_string())
+        );
+        assert_eq!(xml_value(xml, "IsTruncated"), Some("false"));
+        assert_eq!(xml_value(xml, "UploadId"), None);
+    }
+
+    #[test]
+    fn test_xml_values_given_list_multipart_uploads() {
+        let xml = r#"<ListMultipartUploadsResult><Bucket>b</Bucket><KeyMarker></KeyMarker><IsTruncated>false</IsTruncated><Upload><Key>2025/01/a.txt</Key><UploadId>VXBsb2FkIElE</UploadId><Initiator><ID>x</ID></Initiator><Initiated>2025-01-02T03:04:05.000Z</Initiated></Upload></ListMultipartUploadsResult>"#;
+        let uploads = xml_values(xml, "Upload");
+        assert_eq!(uploads.len(), 1);
+        assert_eq!(xml_value(uploads[0], "Key"), Some("2025/01/a.txt"));
+        assert_eq!(xml_value(uploads[0], "UploadId"), Some("VXBsb2FkIElE"));
+        assert!(
+            xml_value(uploads[0], "Initiated")
+                .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_xml_escape() {
+        assert_eq!(xml_escape("\"a&b\""), "&quot;a&amp;b&quot;");
+        assert_eq!(xml_unescape(&xml_escape("\"a&b\"")), "\"a&b\"");
+    }
+}
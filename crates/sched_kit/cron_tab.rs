@@ -1,60 +1,252 @@
-use std::str::FromStr as _;
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    pin::Pin,
+    str::FromStr as _,
+    sync::{
+        Arc, LazyLock, PoisonError,
+        atomic::{AtomicI64, Ordering},
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use crate::error::{Result, SchedError};
-use crate::{JobCallbackParams, ScheduledJob, ScheduledJobReceiver};
-use chrono::{DateTime, Utc};
+use crate::{
+    JobCallbackParams, JobClaim, JobSetting, Misfire, Overlap, ScheduledJob, ScheduledJobReceiver,
+};
+use chrono::{DateTime, NaiveDateTime, TimeZone as _, Timelike as _, Utc};
+use chrono_tz::Tz;
 use cron_tab::AsyncCron;
-use tokio::time::Instant;
-use tracing::info;
+use tokio::{
+    sync::{Mutex, Notify, OwnedMutexGuard},
+    time::Instant,
+};
+use tracing::{error, info, warn};
 
+/// How often the settings of the receiver are read again
+const RELOAD_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How long the lease lasts without being renewed, also how long it takes
+/// another instance to take over from a leader that died
+const LEASE_TTL: Duration = Duration::from_secs(30);
+
+/// Token the jobs hold for no lease
+const NO_LEASE: i64 = -1;
+
+/// Most missed runs made up for by `Misfire::RunAll`, a frequent job down for
+/// days would otherwise keep running for hours
+const MAX_MISSED: usize = 100;
+
+/// Held by the running run of every job key, shared by the schedule and the runs
+/// made by hand in this process. It lives in memory only, so the overlap policy
+/// keeps apart the runs of one process and runs on other instances are not seen
+static RUNNING: LazyLock<std::sync::Mutex<HashMap<String, Arc<Mutex<()>>>>> =
+    LazyLock::new(Default::default);
+
+type RunFn = Arc<
+    dyn Fn(Arc<serde_json::Value>) -> Pin<Box<dyn Future<Output = Result<()>> + Send>>
+        + Send
+        + Sync,
+>;
+
+struct Job<R> {
+    name: &'static str,
+    expr: &'static str,
+    misfire: Misfire,
+    overlap: Overlap,
+    run: RunFn,
+    /// Held by the running run, kept across settings so they can't overlap either
+    running: Arc<Mutex<()>>,
+    /// Setting the job is scheduled with
+    applied: Option<JobSetting>,
+    /// Timezone of the cron holding the job and its id there, `None` while disabled
+    handle: Option<(Tz, usize)>,
+    /// What the cron runs on every fire time, `None` while disabled
+    tick: Option<Tick<R>>,
+}
+
+/// One fire time of a job as scheduled by its setting
+#[derive(Clone)]
+struct Tick<R> {
+    receiver: R,
+    token: Arc<AtomicI64>,
+    key: String,
+    name: &'static str,
+    expr: String,
+    timezone: Tz,
+    misfire: Misfire,
+    overlap: Overlap,
+    running: Arc<Mutex<()>>,
+    run: RunFn,
+    params: Arc<serde_json::Value>,
+}
+
+impl<R: ScheduledJobReceiver> Tick<R> {
+    /// Waits for its turn as the overlap policy says, then runs the job for
+    /// `fire_at` once the fire time is claimed
+    async fn fire(self, fire_at: DateTime<Utc>) {
+        let Some(_turn) = take_turn(&self.running, self.overlap, self.name).await else {
+            return;
+        };
+        let run_at = Utc::now().with_timezone(&self.timezone).naive_local();
+        if !self.claim(fire_at, run_at).await {
+            return;
+        }
+        let output = (self.run)(self.params.clone());
+        let run = Run {
+            key: self.key,
+            name: self.name,
+            expr: self.expr,
+            run_at,
+            fire_at: Some(fire_at),
+            triggered_by: None,
+        };
+        execute(&self.receiver, run, output).await;
+    }
+
+    /// Whether this instance leads and got the fire time before anyone else
+    async fn claim(&self, fire_at: DateTime<Utc>, run_at: NaiveDateTime) -> bool {
+        let token = self.token.load(Ordering::Acquire);
+        if token == NO_LEASE {
+            return false;
+        }
+        let claim = JobClaim {
+            key: self.key.clone(),
+            name: self.name.to_string(),
+            expr: self.expr.clone(),
+            fire_at,
+            run_at,
+        };
+        match self.receiver.claim(&claim, token).await {
+            Ok(true) => true,
+            Ok(false) => {
+                info!("[{}] run for {fire_at} not claimed, skipped", self.name);
+                false
+            }
+            Err(err) => {
+                warn!(%err, "[{}] failed to claim the run, run skipped", self.name);
+                false
+            }
+        }
+    }
+
+    /// Makes up for the fire times missed since the last run as the misfire
+    /// policy says
+    async fn catch_up(self) {
+        if self.misfire == Misfire::Skip {
+            return;
+        }
+        let last_run_at = match self.receiver.last_run_at(&self.key).await {
+            Ok(last_run_at) => last_run_at,
+            Err(err) => {
+                warn!(%err, "[{}] failed to read the last run, missed runs skipped", self.name);
+                return;
+            }
+        };
+        let Some(since) =
+            last_run_at.and_then(|d| self.timezone.from_local_datetime(&d).earliest())
+        else {
+            return;
+        };
+        let until = Utc::now().with_timezone(&self.timezone);
+        let mut missed = missed_ticks(&self.expr, since, until);
+        let count = missed.len();
+        if self.misfire == Misfire::RunOnce {
+            missed.drain(..count.saturating_sub(1));
+        }
+        if missed.is_empty() {
+            return;
+        }
+        info!(
+            "[{}] missed {count} runs since {since}, catching up with {}",
+            self.name,
+            missed.len()
+        );
+        for fire_at in missed {
+            self.clone().fire(fire_at.with_timezone(&Utc)).await;
+        }
+    }
+}
+
+/// Jobs are grouped in one cron per timezone so a setting can move a job to
+/// another timezone.
+///
+/// Every instance keeps its crons ticking, only the one holding the lease of the
+/// receiver runs the jobs. Every run claims its fire time from the receiver
+/// with the token of the lease right before it starts, so a leader that lost
+/// the lease stays idle and a fire time runs once whichever instance gets it.
+/// Taking the lease catches up on the runs missed while nobody held it
 pub struct CronTab<R: ScheduledJobReceiver> {
-    cron: AsyncCron<chrono_tz::Tz>,
-    timezone: chrono_tz::Tz,
+    crons: HashMap<Tz, AsyncCron<Tz>>,
+    timezone: Tz,
     receiver: R,
+    jobs: HashMap<String, Job<R>>,
+    running: bool,
+    holder: String,
+    token: Arc<AtomicI64>,
+    reload_signal: Arc<Notify>,
 }
 
 impl<R: ScheduledJobReceiver> CronTab<R> {
-    pub fn new(receiver: R, timezone: chrono_tz::Tz) -> Self {
-        let cron = AsyncCron::new(timezone);
+    pub fn new(receiver: R, timezone: Tz) -> Self {
         Self {
-            cron,
+            crons: HashMap::new(),
             timezone,
             receiver,
+            jobs: HashMap::new(),
+            running: false,
+            holder: holder(),
+            token: Arc::new(AtomicI64::new(NO_LEASE)),
+            reload_signal: Arc::new(Notify::new()),
         }
     }
 
+    /// Reloads the settings as soon as `signal` is notified instead of waiting
+    /// for the next periodic reload
+    pub fn reload_on(&mut self, signal: Arc<Notify>) {
+        self.reload_signal = signal;
+    }
+
+    /// Identifies this instance as a lease holder
+    pub fn holder(&self) -> &str {
+        &self.holder
+    }
+
+    /// Schedules the job with its defaults, settings are applied once running.
+    /// Every run is given up after `ScheduledJob::TIMEOUT`
     pub async fn add<T: ScheduledJob>(&mut self, key: &str, job: T) -> Result<()> {
-        let cron_expr =
-            english_to_cron::str_cron_syntax(T::EXPR).map_err(|_| SchedError::EnglishToCron)?;
-        let key = key.to_string();
-        let timezone = self.timezone;
-        let receiver = self.receiver.clone();
-        self.cron
-            .add_fn(&cron_expr, move || {
-                let key = key.clone();
-                let job = job.clone();
-                let receiver = receiver.clone();
-                async move {
-                    let now = Utc::now().with_timezone(&timezone).naive_local();
-                    let instant = Instant::now();
-                    let output = job.run().await;
-
-                    let params = JobCallbackParams {
-                        key,
-                        name: T::NAME.to_string(),
-                        expr: T::EXPR.to_string(),
-                        succeed: output.is_ok(),
-                        result: format!("{:?}", output),
-                        run_at: now,
-                        duration_ms: instant.elapsed().as_millis() as i64,
-                    };
-                    receiver.receive(params).await;
-                }
-            })
-            .await?;
-        let next_tick = next_tick_expr(&cron_expr, timezone);
-        info!("[{}] registerd. next tick: {:?}", T::NAME, next_tick);
+        to_cron(T::EXPR)?;
+        let run: RunFn = Arc::new(move |params| {
+            let job = job.clone();
+            Box::pin(async move { run_within(&job, &params).await })
+        });
+        self.jobs.insert(
+            key.to_string(),
+            Job {
+                name: T::NAME,
+                expr: T::EXPR,
+                misfire: T::MISFIRE,
+                overlap: T::OVERLAP,
+                run,
+                running: running(key),
+                applied: None,
+                handle: None,
+                tick: None,
+            },
+        );
+        self.apply(key, JobSetting::default()).await
+    }
 
+    /// Reschedules the jobs whose setting changed, jobs without a setting go
+    /// back to their defaults. A rejected setting leaves the job as it was
+    pub async fn reload(&mut self) -> Result<()> {
+        let mut settings = self.receiver.settings().await?;
+        let keys = self.jobs.keys().cloned().collect::<Vec<_>>();
+        for key in keys {
+            let setting = settings.remove(&key).unwrap_or_default();
+            if let Err(err) = self.apply(&key, setting).await {
+                error!(%err, "[{key}] setting rejected, schedule kept");
+            }
+        }
         Ok(())
     }
 
@@ -62,14 +254,290 @@ impl<R: ScheduledJobReceiver> CronTab<R> {
     where
         S: Future<Output = ()> + 'static + Send,
     {
-        self.cron.start().await;
-        signal.await;
-        self.cron.stop().await;
+        if let Err(err) = self.reload().await {
+            warn!(%err, "Failed to load scheduled job settings, running with defaults");
+        }
+        self.renew_lease().await;
+        for cron in self.crons.values_mut() {
+            cron.start().await;
+        }
+        self.running = true;
+        tokio::pin!(signal);
+        let mut reload = tokio::time::interval(RELOAD_INTERVAL);
+        reload.tick().await;
+        let mut lease = tokio::time::interval(LEASE_TTL / 3);
+        lease.tick().await;
+        let reload_signal = self.reload_signal.clone();
+        loop {
+            tokio::select! {
+                _ = &mut signal => break,
+                _ = reload_signal.notified() => {
+                    if let Err(err) = self.reload().await {
+                        warn!(%err, "Failed to reload scheduled job settings");
+                    }
+                }
+                _ = reload.tick() => {
+                    if let Err(err) = self.reload().await {
+                        warn!(%err, "Failed to reload scheduled job settings");
+                    }
+                }
+                _ = lease.tick() => self.renew_lease().await,
+            }
+        }
+        for cron in self.crons.values_mut() {
+            cron.stop().await;
+        }
+        self.running = false;
+        if self.token.swap(NO_LEASE, Ordering::AcqRel) != NO_LEASE
+            && let Err(err) = self.receiver.release_lease(&self.holder).await
+        {
+            warn!(%err, "Failed to release the sched lease");
+        }
+        Ok(())
+    }
+
+    /// Renews the lease or tries to take it, an instance that can't tell
+    /// whether it still holds it steps down
+    async fn renew_lease(&self) {
+        let token = match self.receiver.acquire_lease(&self.holder, LEASE_TTL).await {
+            Ok(token) => token.unwrap_or(NO_LEASE),
+            Err(err) => {
+                warn!(%err, "Failed to renew the sched lease");
+                NO_LEASE
+            }
+        };
+        let previous = self.token.swap(token, Ordering::AcqRel);
+        if previous == NO_LEASE && token != NO_LEASE {
+            info!(holder = %self.holder, token, "Leading the scheduled jobs");
+            for tick in self.jobs.values().filter_map(|job| job.tick.clone()) {
+                tokio::spawn(tick.catch_up());
+            }
+        } else if previous != NO_LEASE && token == NO_LEASE {
+            warn!(holder = %self.holder, "Stopped leading the scheduled jobs");
+        }
+    }
+
+    async fn apply(&mut self, key: &str, setting: JobSetting) -> Result<()> {
+        let Some(job) = self.jobs.get_mut(key) else {
+            return Ok(());
+        };
+        if job.applied.as_ref() == Some(&setting) {
+            return Ok(());
+        }
+        let expr = setting.expr.clone().unwrap_or(job.expr.to_string());
+        let cron_expr = to_cron(&expr)?;
+        let timezone = match &setting.timezone {
+            Some(timezone) => Tz::from_str(timezone)
+                .map_err(|_| SchedError::Custom(format!("unknown timezone: {timezone}")))?,
+            None => self.timezone,
+        };
+
+        if let Some((timezone, id)) = job.handle.take()
+            && let Some(cron) = self.crons.get(&timezone)
+        {
+            cron.remove(id).await;
+        }
+        job.tick = None;
+        if !setting.enabled {
+            info!("[{}] disabled", job.name);
+            job.applied = Some(setting);
+            return Ok(());
+        }
+
+        let cron = match self.crons.entry(timezone) {
+            Entry::Occupied(occupied) => occupied.into_mut(),
+            Entry::Vacant(vacant) => {
+                let mut cron = AsyncCron::new(timezone);
+                if self.running {
+                    cron.start().await;
+                }
+                vacant.insert(cron)
+            }
+        };
+        let tick = Tick {
+            receiver: self.receiver.clone(),
+            token: self.token.clone(),
+            key: key.to_string(),
+            name: job.name,
+            expr: expr.clone(),
+            timezone,
+            misfire: job.misfire,
+            overlap: job.overlap,
+            running: job.running.clone(),
+            run: job.run.clone(),
+            params: Arc::new(setting.params.clone()),
+        };
+        let id = {
+            let tick = tick.clone();
+            cron.add_fn(&cron_expr, move || {
+                let now = Utc::now().with_timezone(&tick.timezone);
+                let fire_at = last_tick(&tick.expr, now).unwrap_or(now.to_utc());
+                tick.clone().fire(fire_at)
+            })
+            .await?
+        };
+        job.handle = Some((timezone, id));
+        job.tick = Some(tick);
+        job.applied = Some(setting);
+        let next_tick = next_tick_expr(&cron_expr, timezone);
+        info!(
+            "[{}] registerd with {expr}. next tick: {:?}",
+            job.name, next_tick
+        );
+
         Ok(())
     }
 }
 
-fn next_tick_expr(expr: &str, timezone: chrono_tz::Tz) -> Option<DateTime<chrono_tz::Tz>> {
+/// Runs a job once outside its schedule on behalf of `triggered_by`, it is
+/// reported to the receiver like a scheduled run. It waits for or skips the
+/// scheduled run of this process as `ScheduledJob::OVERLAP` says
+pub async fn run_now<T: ScheduledJob, R: ScheduledJobReceiver>(
+    receiver: &R,
+    key: &str,
+    job: &T,
+    setting: &JobSetting,
+    timezone: Tz,
+    triggered_by: &str,
+) {
+    let expr = setting.expr.clone().unwrap_or(T::EXPR.to_string());
+    let timezone = setting
+        .timezone
+        .as_deref()
+        .and_then(|timezone| Tz::from_str(timezone).ok())
+        .unwrap_or(timezone);
+    let Some(_turn) = take_turn(&running(key), T::OVERLAP, T::NAME).await else {
+        return;
+    };
+    let run = Run {
+        key: key.to_string(),
+        name: T::NAME,
+        expr,
+        run_at: Utc::now().with_timezone(&timezone).naive_local(),
+        fire_at: None,
+        triggered_by: Some(triggered_by.to_string()),
+    };
+    let output = run_within(job, &setting.params);
+    execute(receiver, run, output).await;
+}
+
+fn running(key: &str) -> Arc<Mutex<()>> {
+    RUNNING
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(key.to_string())
+        .or_default()
+        .clone()
+}
+
+/// Waits for the turn of a run as the overlap policy says, `None` when the run
+/// is skipped
+async fn take_turn(
+    running: &Arc<Mutex<()>>,
+    overlap: Overlap,
+    name: &str,
+) -> Option<Option<OwnedMutexGuard<()>>> {
+    match overlap {
+        Overlap::Allow => Some(None),
+        Overlap::Skip => match running.clone().try_lock_owned() {
+            Ok(turn) => Some(Some(turn)),
+            Err(_) => {
+                warn!("[{name}] still running, run skipped");
+                None
+            }
+        },
+        Overlap::Queue => Some(Some(running.clone().lock_owned().await)),
+    }
+}
+
+/// Drops the run once `ScheduledJob::TIMEOUT` passed, which stops it at its
+/// next await. Tasks the job spawned are not cancelled with it
+async fn run_within<T: ScheduledJob>(job: &T, params: &serde_json::Value) -> Result<()> {
+    match T::TIMEOUT {
+        Some(timeout) => tokio::time::timeout(timeout, job.run_with(params))
+            .await
+            .map_err(|_| SchedError::Timeout(timeout))?,
+        None => job.run_with(params).await,
+    }
+}
+
+/// Host, process and start time, the pid alone repeats across containers
+fn holder() -> String {
+    let host = std::env::var("HOSTNAME").unwrap_or("localhost".to_string());
+    let started = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    format!("{host}:{}:{started}", std::process::id())
+}
+
+/// A run about to start, reported to the receiver once it finished
+struct Run {
+    key: String,
+    name: &'static str,
+    expr: String,
+    /// Local time of the timezone the job runs on
+    run_at: NaiveDateTime,
+    fire_at: Option<DateTime<Utc>>,
+    triggered_by: Option<String>,
+}
+
+async fn execute<R: ScheduledJobReceiver>(
+    receiver: &R,
+    run: Run,
+    output: impl Future<Output = Result<()>>,
+) {
+    let instant = Instant::now();
+    let output = output.await;
+
+    let callback = JobCallbackParams {
+        key: run.key,
+        name: run.name.to_string(),
+        expr: run.expr,
+        succeed: output.is_ok(),
+        result: format!("{:?}", output),
+        run_at: run.run_at,
+        duration_ms: instant.elapsed().as_millis() as i64,
+        triggered_by: run.triggered_by,
+        fire_at: run.fire_at,
+    };
+    receiver.receive(callback).await;
+}
+
+/// Turns an english or cron expression into cron syntax
+pub fn to_cron(expr: &str) -> Result<String> {
+    english_to_cron::str_cron_syntax(expr).map_err(|_| SchedError::EnglishToCron)
+}
+
+/// Fire times after `since` up to `until`, the first `MAX_MISSED` of them
+fn missed_ticks(expr: &str, since: DateTime<Tz>, until: DateTime<Tz>) -> Vec<DateTime<Tz>> {
+    let Ok(cron_expr) = to_cron(expr) else {
+        return Vec::new();
+    };
+    let Ok(schedule) = cron::Schedule::from_str(&cron_expr) else {
+        return Vec::new();
+    };
+    schedule
+        .after(&since)
+        .take_while(|tick| *tick <= until)
+        .take(MAX_MISSED)
+        .collect()
+}
+
+/// The latest fire time up to `now`. The cron fires a little late at times,
+/// the fire time it fired for has to be the same on every instance
+fn last_tick(expr: &str, now: DateTime<Tz>) -> Option<DateTime<Utc>> {
+    let cron_expr = to_cron(expr).ok()?;
+    let schedule = cron::Schedule::from_str(&cron_expr).ok()?;
+    // ticks fall on whole seconds and the ones looked back on are strictly earlier
+    let next_second = now.with_nanosecond(0)? + chrono::Duration::seconds(1);
+    schedule
+        .after(&next_second)
+        .next_back()
+        .map(|tick| tick.with_timezone(&Utc))
+}
+
+fn next_tick_expr(expr: &str, timezone: Tz) -> Option<DateTime<Tz>> {
     if let Ok(schedule) = cron::Schedule::from_str(expr) {
         return schedule.upcoming(timezone).next();
     }
@@ -77,10 +545,382 @@ fn next_tick_expr(expr: &str, timezone: chrono_tz::Tz) -> Option<DateTime<chrono
     None
 }
 
-pub fn next_tick(expr: &str, timezone: chrono_tz::Tz) -> Option<DateTime<chrono_tz::Tz>> {
-    if let Ok(cron_expr) = english_to_cron::str_cron_syntax(expr) {
-        return next_tick_expr(&cron_expr, timezone);
+pub fn next_tick(expr: &str, timezone: Tz) -> Option<DateTime<Tz>> {
+    next_ticks(expr, timezone, 1).into_iter().next()
+}
+
+/// The next `count` times the expression fires, empty when it is invalid
+pub fn next_ticks(expr: &str, timezone: Tz, count: usize) -> Vec<DateTime<Tz>> {
+    if let Ok(cron_expr) = english_to_cron::str_cron_syntax(expr)
+        && let Ok(schedule) = cron::Schedule::from_str(&cron_expr)
+    {
+        return schedule.upcoming(timezone).take(count).collect();
     }
 
-    None
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use chrono::TimeZone as _;
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct Echo;
+
+    impl ScheduledJob for Echo {
+        const EXPR: &'static str = "at 01:01";
+        const NAME: &'static str = "Echo";
+
+        async fn run(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn run_with(&self, params: &serde_json::Value) -> Result<()> {
+            if params["fail"].as_bool().unwrap_or_default() {
+                return Err(SchedError::Custom("failed".to_string()));
+            }
+            self.run().await
+        }
+    }
+
+    #[derive(Clone)]
+    struct Sleepy;
+
+    impl ScheduledJob for Sleepy {
+        const EXPR: &'static str = "every 5 minutes";
+        const NAME: &'static str = "Sleepy";
+        const TIMEOUT: Option<Duration> = Some(Duration::from_millis(10));
+
+        async fn run(&self) -> Result<()> {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        }
+    }
+
+    #[derive(Clone)]
+    struct Exclusive;
+
+    impl ScheduledJob for Exclusive {
+        const EXPR: &'static str = "every 5 minutes";
+        const NAME: &'static str = "Exclusive";
+        const OVERLAP: Overlap = Overlap::Skip;
+
+        async fn run(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<JobCallbackParams>>>);
+
+    impl ScheduledJobReceiver for Recorder {
+        async fn receive(&self, params: JobCallbackParams) {
+            self.0.lock().unwrap().push(params);
+        }
+    }
+
+    /// Keeps the fire times claimed like the receiver of the app does, for one job
+    #[derive(Clone, Default)]
+    struct Claims {
+        claimed: Arc<Mutex<std::collections::HashSet<DateTime<Utc>>>>,
+        runs: Arc<Mutex<Vec<JobCallbackParams>>>,
+    }
+
+    impl ScheduledJobReceiver for Claims {
+        async fn receive(&self, params: JobCallbackParams) {
+            self.runs.lock().unwrap().push(params);
+        }
+
+        async fn claim(&self, claim: &JobClaim, _token: i64) -> Result<bool> {
+            Ok(self.claimed.lock().unwrap().insert(claim.fire_at))
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct Settings(Arc<Mutex<HashMap<String, JobSetting>>>);
+
+    impl Settings {
+        fn set(&self, key: &str, setting: JobSetting) {
+            self.0.lock().unwrap().insert(key.to_string(), setting);
+        }
+    }
+
+    impl ScheduledJobReceiver for Settings {
+        async fn receive(&self, _params: JobCallbackParams) {}
+
+        async fn settings(&self) -> Result<HashMap<String, JobSetting>> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+    }
+
+    fn tick<R: ScheduledJobReceiver>(receiver: R, token: i64) -> Tick<R> {
+        let run: RunFn = Arc::new(|_| Box::pin(async { Ok(()) }));
+        Tick {
+            receiver,
+            token: Arc::new(AtomicI64::new(token)),
+            key: "echo".to_string(),
+            name: Echo::NAME,
+            expr: Echo::EXPR.to_string(),
+            timezone: chrono_tz::UTC,
+            misfire: Misfire::Skip,
+            overlap: Overlap::Skip,
+            running: Arc::new(tokio::sync::Mutex::new(())),
+            run,
+            params: Arc::new(serde_json::Value::Null),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fire_given_no_lease() {
+        let receiver = Recorder::default();
+        tick(receiver.clone(), NO_LEASE).fire(Utc::now()).await;
+        assert!(receiver.0.lock().unwrap().is_empty());
+
+        let fire_at = Utc::now();
+        tick(receiver.clone(), 0).fire(fire_at).await;
+        let runs = receiver.0.lock().unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].fire_at, Some(fire_at));
+        assert_eq!(runs[0].triggered_by, None);
+    }
+
+    #[tokio::test]
+    async fn test_fire_with_overlap() {
+        let receiver = Recorder::default();
+        let tick = tick(receiver.clone(), 0);
+        let turn = tick.running.clone().lock_owned().await;
+        tick.clone().fire(Utc::now()).await;
+        assert!(receiver.0.lock().unwrap().is_empty());
+        drop(turn);
+        tick.clone().fire(Utc::now()).await;
+        assert_eq!(receiver.0.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fire_given_fire_time_claimed() {
+        let receiver = Claims::default();
+        let fire_at = Utc::now();
+        tick(receiver.clone(), 1).fire(fire_at).await;
+        tick(receiver.clone(), 2).fire(fire_at).await;
+        tick(receiver.clone(), 2)
+            .fire(fire_at + chrono::Duration::days(1))
+            .await;
+        let runs = receiver.runs.lock().unwrap();
+        assert_eq!(runs.len(), 2);
+        assert!(runs.iter().all(|run| run.succeed));
+    }
+
+    #[test]
+    fn test_missed_ticks() {
+        let since = chrono_tz::UTC
+            .with_ymd_and_hms(2026, 10, 10, 1, 1, 30)
+            .unwrap();
+        let until = since + chrono::Duration::days(3);
+        let missed = missed_ticks("at 01:01", since, until);
+        assert_eq!(missed.len(), 3);
+        assert_eq!(
+            missed[0],
+            chrono_tz::UTC
+                .with_ymd_and_hms(2026, 10, 11, 1, 1, 0)
+                .unwrap()
+        );
+        assert!(missed_ticks("at 01:01", since, since + chrono::Duration::hours(1)).is_empty());
+        assert_eq!(
+            missed_ticks("every 5 minutes", since, until).len(),
+            MAX_MISSED
+        );
+    }
+
+    #[test]
+    fn test_last_tick() {
+        let fired = chrono_tz::UTC
+            .with_ymd_and_hms(2026, 10, 10, 1, 5, 0)
+            .unwrap();
+        let late = fired + chrono::Duration::milliseconds(300);
+        assert_eq!(
+            last_tick("every 5 minutes", late),
+            Some(fired.with_timezone(&Utc))
+        );
+        assert_eq!(
+            last_tick("every 5 minutes", fired),
+            Some(fired.with_timezone(&Utc))
+        );
+        assert_eq!(
+            last_tick("every 5 minutes", fired + chrono::Duration::minutes(2)),
+            Some(fired.with_timezone(&Utc))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_now_timeout() {
+        let receiver = Recorder::default();
+        let setting = JobSetting::default();
+        run_now(
+            &receiver,
+            "sleepy",
+            &Sleepy,
+            &setting,
+            chrono_tz::UTC,
+            "user",
+        )
+        .await;
+
+        let runs = receiver.0.lock().unwrap();
+        assert!(!runs[0].succeed);
+        assert!(runs[0].result.contains("Timeout"));
+    }
+
+    #[test]
+    fn test_next_ticks() {
+        let ticks = next_ticks("at 01:01", chrono_tz::UTC, 3);
+        assert_eq!(ticks.len(), 3);
+        assert!(
+            ticks
+                .windows(2)
+                .all(|w| w[1] - w[0] == chrono::Duration::days(1))
+        );
+        assert_eq!(
+            next_tick("at 01:01", chrono_tz::UTC),
+            ticks.first().cloned()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_now() {
+        let receiver = Recorder::default();
+        let setting = JobSetting {
+            expr: Some("every 5 minutes".to_string()),
+            ..Default::default()
+        };
+        run_now(&receiver, "echo", &Echo, &setting, chrono_tz::UTC, "user").await;
+        let setting = JobSetting {
+            params: serde_json::json!({ "fail": true }),
+            ..Default::default()
+        };
+        run_now(&receiver, "echo", &Echo, &setting, chrono_tz::UTC, "user").await;
+
+        let runs = receiver.0.lock().unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].key, "echo");
+        assert_eq!(runs[0].expr, "every 5 minutes");
+        assert!(runs[0].succeed);
+        assert_eq!(runs[0].triggered_by.as_deref(), Some("user"));
+        assert_eq!(runs[1].expr, Echo::EXPR);
+        assert!(!runs[1].succeed);
+    }
+
+    #[tokio::test]
+    async fn test_reload_disable() {
+        let receiver = Settings::default();
+        let mut cron_tab = CronTab::new(receiver.clone(), chrono_tz::UTC);
+        cron_tab.add("echo", Echo).await.unwrap();
+        assert!(cron_tab.jobs["echo"].handle.is_some());
+
+        let setting = JobSetting {
+            enabled: false,
+            ..Default::default()
+        };
+        receiver.set("echo", setting.clone());
+        cron_tab.reload().await.unwrap();
+        let job = &cron_tab.jobs["echo"];
+        assert_eq!(job.applied, Some(setting));
+        assert!(job.handle.is_none());
+        assert!(job.tick.is_none());
+
+        receiver.0.lock().unwrap().clear();
+        cron_tab.reload().await.unwrap();
+        let job = &cron_tab.jobs["echo"];
+        assert_eq!(job.applied, Some(JobSetting::default()));
+        assert!(job.handle.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_reload_move_timezone() {
+        let receiver = Settings::default();
+        let mut cron_tab = CronTab::new(receiver.clone(), chrono_tz::UTC);
+        cron_tab.add("echo", Echo).await.unwrap();
+        assert_eq!(cron_tab.jobs["echo"].handle.unwrap().0, chrono_tz::UTC);
+
+        receiver.set(
+            "echo",
+            JobSetting {
+                timezone: Some("Asia/Tokyo".to_string()),
+                ..Default::default()
+            },
+        );
+        cron_tab.reload().await.unwrap();
+        let job = &cron_tab.jobs["echo"];
+        assert_eq!(job.handle.unwrap().0, chrono_tz::Asia::Tokyo);
+        assert_eq!(
+            job.tick.as_ref().map(|tick| tick.timezone),
+            Some(chrono_tz::Asia::Tokyo)
+        );
+        assert!(cron_tab.crons.contains_key(&chrono_tz::Asia::Tokyo));
+    }
+
+    #[tokio::test]
+    async fn test_reload_keep_schedule_given_bad_setting() {
+        let receiver = Settings::default();
+        let mut cron_tab = CronTab::new(receiver.clone(), chrono_tz::UTC);
+        cron_tab.add("echo", Echo).await.unwrap();
+        let setting = JobSetting {
+            expr: Some("every 5 minutes".to_string()),
+            ..Default::default()
+        };
+        receiver.set("echo", setting.clone());
+        cron_tab.reload().await.unwrap();
+        let handle = cron_tab.jobs["echo"].handle;
+
+        receiver.set(
+            "echo",
+            JobSetting {
+                timezone: Some("Mars/Olympus".to_string()),
+                ..Default::default()
+            },
+        );
+        cron_tab.reload().await.unwrap();
+        let job = &cron_tab.jobs["echo"];
+        assert_eq!(job.applied, Some(setting));
+        assert_eq!(job.handle, handle);
+        assert_eq!(
+            job.tick.as_ref().map(|tick| tick.expr.as_str()),
+            Some("every 5 minutes")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_now_with_overlap() {
+        let receiver = Recorder::default();
+        let mut cron_tab = CronTab::new(receiver.clone(), chrono_tz::UTC);
+        cron_tab.add("exclusive", Exclusive).await.unwrap();
+        let tick = cron_tab.jobs["exclusive"].tick.clone().unwrap();
+        let setting = JobSetting::default();
+
+        let turn = tick.running.clone().lock_owned().await;
+        run_now(
+            &receiver,
+            "exclusive",
+            &Exclusive,
+            &setting,
+            chrono_tz::UTC,
+            "user",
+        )
+        .await;
+        assert!(receiver.0.lock().unwrap().is_empty());
+        drop(turn);
+        run_now(
+            &receiver,
+            "exclusive",
+            &Exclusive,
+            &setting,
+            chrono_tz::UTC,
+            "user",
+        )
+        .await;
+        assert_eq!(receiver.0.lock().unwrap().len(), 1);
+    }
 }
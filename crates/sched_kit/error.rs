@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use cron_tab::CronError;
 use thiserror::Error;
 
@@ -9,6 +11,8 @@ pub enum SchedError {
     EnglishToCron,
     #[error("{0}")]
     Cron(#[from] CronError),
+    #[error("Timed out after {0:?}")]
+    Timeout(Duration),
     #[error("{0}")]
     Custom(String),
 }
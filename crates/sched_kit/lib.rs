@@ -1,4 +1,6 @@
-use chrono::NaiveDateTime;
+use std::{collections::HashMap, time::Duration};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
 
 use crate::error::Result;
 
@@ -8,11 +10,106 @@ pub mod error;
 pub trait ScheduledJob: Clone + Send + Sync + 'static {
     const EXPR: &'static str;
     const NAME: &'static str;
+    /// What to do about the fire times missed while no instance was leading
+    const MISFIRE: Misfire = Misfire::Skip;
+    /// What to do when the job fires while its last run in this process hasn't
+    /// finished. Runs on other instances aren't seen, one made by hand there or
+    /// still going on a former leader can overlap
+    const OVERLAP: Overlap = Overlap::Allow;
+    /// Runs taking longer are reported as failed. Only the future of the run is
+    /// dropped, tasks it spawned keep going
+    const TIMEOUT: Option<Duration> = None;
+
     fn run(&self) -> impl Future<Output = Result<()>> + Send;
+
+    /// Runs with the parameters set for the job, `Null` when there are none.
+    /// Jobs taking no parameters keep the default
+    fn run_with(&self, params: &serde_json::Value) -> impl Future<Output = Result<()>> + Send {
+        let _ = params;
+        self.run()
+    }
 }
 
 pub trait ScheduledJobReceiver: Clone + Send + Sync + 'static {
     fn receive(&self, params: JobCallbackParams) -> impl Future<Output = ()> + Send;
+
+    /// Settings overriding the compiled defaults by job key, read when the
+    /// `CronTab` starts and again on every reload
+    fn settings(&self) -> impl Future<Output = Result<HashMap<String, JobSetting>>> + Send {
+        async { Ok(HashMap::new()) }
+    }
+
+    /// Takes or renews the lease that lets `holder` run jobs for `ttl`. Returns
+    /// the token of the lease while `holder` has it, `None` while another
+    /// instance does. The token goes up whenever the lease changes hands. A
+    /// single instance always holds it
+    fn acquire_lease(
+        &self,
+        holder: &str,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<Option<i64>>> + Send {
+        let _ = (holder, ttl);
+        async { Ok(Some(0)) }
+    }
+
+    /// Claims the fire time of a scheduled run right before it starts, only
+    /// while the lease taken with `token` is held and only once per job and
+    /// fire time. The run is skipped when this returns `false`, so a leader
+    /// that lost the lease or a fire time run already by another instance
+    /// starts nothing
+    fn claim(&self, claim: &JobClaim, token: i64) -> impl Future<Output = Result<bool>> + Send {
+        let _ = (claim, token);
+        async { Ok(true) }
+    }
+
+    /// Local time of the last scheduled run of the job, in the timezone it ran
+    /// on. Missed fire times are counted from there
+    fn last_run_at(&self, key: &str) -> impl Future<Output = Result<Option<NaiveDateTime>>> + Send {
+        let _ = key;
+        async { Ok(None) }
+    }
+
+    /// Gives the lease up so another instance takes over without waiting for
+    /// it to expire
+    fn release_lease(&self, holder: &str) -> impl Future<Output = Result<()>> + Send {
+        let _ = holder;
+        async { Ok(()) }
+    }
+}
+
+/// Missed fire times are found once an instance starts leading, a job that
+/// never ran has none
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Misfire {
+    /// Waits for the next fire time
+    #[default]
+    Skip,
+    /// Runs once for all the fire times missed
+    RunOnce,
+    /// Runs once per fire time missed, one after another
+    RunAll,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Overlap {
+    /// Starts another run next to the running one
+    #[default]
+    Allow,
+    /// Drops the fire time
+    Skip,
+    /// Runs once the running one finishes
+    Queue,
+}
+
+/// A scheduled run about to start
+#[derive(Clone, Debug)]
+pub struct JobClaim {
+    pub key: String,
+    pub name: String,
+    pub expr: String,
+    /// Fire time the run is for, a missed one when catching up
+    pub fire_at: DateTime<Utc>,
+    pub run_at: NaiveDateTime,
 }
 
 #[derive(Clone, Debug)]
@@ -24,4 +121,30 @@ pub struct JobCallbackParams {
     pub result: String,
     pub run_at: NaiveDateTime,
     pub duration_ms: i64,
+    /// Who ran the job by hand, `None` when it ran on schedule
+    pub triggered_by: Option<String>,
+    /// Fire time claimed by the run, `None` when it ran by hand
+    pub fire_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct JobSetting {
+    /// Replaces `ScheduledJob::EXPR`
+    pub expr: Option<String>,
+    /// Replaces the timezone of the `CronTab`
+    pub timezone: Option<String>,
+    pub enabled: bool,
+    /// Handed to `ScheduledJob::run_with`
+    pub params: serde_json::Value,
+}
+
+impl Default for JobSetting {
+    fn default() -> Self {
+        Self {
+            expr: None,
+            timezone: None,
+            enabled: true,
+            params: serde_json::Value::Null,
+        }
+    }
 }
@@ -0,0 +1,46 @@
+use http_client_kit::HTTP_CLIENT;
+use serde::Deserialize;
+
+use crate::TencentLocationResponse;
+
+#[derive(Debug, Deserialize)]
+pub struct IpLocationResponse {
+    pub location: IpLocationResponseLocation,
+    pub ad_info: IpLocationResponseAdInfo,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IpLocationResponseLocation {
+    pub lat: f64,
+    pub lng: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IpLocationResponseAdInfo {
+    #[serde(default)]
+    pub nation: String,
+    #[serde(default)]
+    pub province: String,
+    #[serde(default)]
+    pub city: String,
+}
+
+#[tracing::instrument]
+pub async fn ip_location(key: &str, ip: &str) -> Option<IpLocationResponse> {
+    let builder = HTTP_CLIENT
+        .get("https://apis.map.qq.com/ws/location/v1/ip")
+        .query(&[("key", key), ("ip", ip)]);
+    let Ok(response) = builder.send().await else {
+        return None;
+    };
+    let Ok(ip_location_response) = response
+        .json::<TencentLocationResponse<IpLocationResponse>>()
+        .await
+    else {
+        return None;
+    };
+    if ip_location_response.status != 0 {
+        return None;
+    }
+    Some(ip_location_response.result)
+}
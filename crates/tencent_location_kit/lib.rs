@@ -1,6 +1,8 @@
 mod geocoder;
+mod ip;
 
 pub use geocoder::*;
+pub use ip::*;
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
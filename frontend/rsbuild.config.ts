@@ -54,6 +54,7 @@ export default defineConfig(async () => {
       proxy: {
         "/api": "http://localhost:8080",
         "/uploads": "http://localhost:8080",
+        "/s": "http://localhost:8080",
       },
     },
     html: {
@@ -22,7 +22,7 @@ const buildDropdown = () => {
   return {
     type: "dropdown-button",
     style: {
-      "--portrait-url": `url('\${user.portrait}'), url('${logoUrl}')`,
+      "--portrait-url": `url('\${user.portrait_thumb}'), url('${logoUrl}')`,
     },
     className: "header-dropdown",
     label: "${user.name}(${user.account})",
@@ -71,6 +71,54 @@ const buildDropdown = () => {
         actionType: "link",
         link: "/profile/update_password",
       },
+      {
+        type: "button",
+        label: " " + _t('storage_usage'),
+        icon: "fas fa-hard-drive",
+        actionType: "dialog",
+        dialog: {
+          title: _t('storage_usage'),
+          actions: [],
+          body: {
+            type: "form",
+            static: true,
+            wrapWithPanel: false,
+            body: [
+              {
+                type: "pretty-bytes",
+                name: "storage_usage.used",
+                label: _t('storage_used'),
+              },
+              {
+                type: "pretty-bytes",
+                name: "storage_usage.quota",
+                label: _t('storage_quota'),
+                visibleOn: "storage_usage.quota",
+              },
+              {
+                type: "static",
+                label: _t('storage_quota'),
+                value: _t('storage_unlimited'),
+                visibleOn: "!storage_usage.quota",
+              },
+            ],
+          },
+        },
+      },
+      {
+        type: "button",
+        label: " " + _t('account_activity'),
+        icon: "fas fa-clock-rotate-left",
+        actionType: "link",
+        link: "/profile/activity",
+      },
+      {
+        type: "button",
+        label: " " + _t('my_tasks'),
+        icon: "fas fa-list-check",
+        actionType: "link",
+        link: "/profile/tasks",
+      },
       {
         type: "button",
         label: " " + _t('sign_out'),
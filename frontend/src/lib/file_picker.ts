@@ -0,0 +1,86 @@
+const endpoint = "/system/files";
+
+type buildFilePickerProps = {
+  name: string;
+  label: string;
+  /** MIME prefix the library is narrowed to, e.g. `image/` */
+  mimeType?: string;
+  multiple?: boolean;
+  required?: boolean;
+};
+
+/** Picks already uploaded files from the file library, the value is the file path */
+export const buildFilePicker = (props: buildFilePickerProps) => {
+  return {
+    type: "picker",
+    name: props.name,
+    label: props.label,
+    required: props.required,
+    multiple: props.multiple ?? false,
+    valueField: "path",
+    labelField: "name",
+    modalMode: "dialog",
+    size: "lg",
+    source: {
+      method: "get",
+      url: endpoint,
+      data: {
+        "&": "$$",
+        mime_type: props.mimeType,
+      },
+    },
+    pickerSchema: {
+      mode: "table",
+      pageField: "page",
+      perPageField: "page_size",
+      perPage: 20,
+      filter: {
+        title: "",
+        body: [
+          {
+            type: "tree-select",
+            name: "folder_id",
+            label: "目录",
+            source: `${endpoint}/folders`,
+            placeholder: "全部",
+            clearable: true,
+            clearValueOnEmpty: true,
+          },
+          {
+            type: "input-text",
+            name: "name",
+            label: "文件名称",
+            clearable: true,
+          },
+          {
+            type: "input-text",
+            name: "tag",
+            label: "标签",
+            clearable: true,
+          },
+        ],
+      },
+      columns: [
+        {
+          name: "name",
+          label: "文件名称",
+        },
+        {
+          name: "folder_name",
+          label: "目录",
+          placeholder: "-",
+        },
+        {
+          name: "tags",
+          label: "标签",
+          placeholder: "-",
+        },
+        {
+          type: "pretty-bytes",
+          name: "size",
+          label: "文件大小",
+        },
+      ],
+    },
+  };
+};
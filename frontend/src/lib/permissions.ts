@@ -29,13 +29,21 @@ export const PERMISSIONS = {
       READ: 300,
       UPLOAD: 301,
       DOWNLOAD: 302,
+      UPDATE: 303,
+      SHARE: 304,
+      DELETE: 305,
     },
     SCHED: {
       READ: 400,
       DELETE: 401,
+      UPDATE: 402,
+      RUN: 403,
     },
     BGWORKER: {
       READ: 500,
+      RETRY: 501,
+      UPDATE: 502,
+      DELETE: 503,
     },
     CACHE: {
       READ: 700,
@@ -44,5 +52,13 @@ export const PERMISSIONS = {
     ACCESS_LOG: {
       READ: 800,
     },
+    SECURITY_EVENT: {
+      READ: 900,
+    },
+    RECYCLE_BIN: {
+      READ: 1000,
+      RESTORE: 1001,
+      PURGE: 1002,
+    },
   },
 };
@@ -12,6 +12,11 @@ delete_selected_items = Delete Selected items
 are_you_sure_to_delete = Are you sure to delete?
 are_you_sure_to_batch_delete = Are you sure to batch delete?
 change_password = Change Password
+account_activity = Account Activity
+storage_usage = Storage Usage
+storage_used = Used
+storage_quota = Quota
+storage_unlimited = Unlimited
 created_at = Created At
 updated_at = Updated At
 query = Query
@@ -48,4 +53,32 @@ sign_in_captcha = Captcha
 sign_in_please_enter_captcha = Please enter captcha
 sign_in_now = Sign In
 sign_in_submitting = Signing in...
-sign_in_success_after_redirect = Sign in success, redirecting...
\ No newline at end of file
+sign_in_success_after_redirect = Sign in success, redirecting...
+activity_kind = Activity
+activity_login_succeeded = Signed in
+activity_login_failed = Sign in failed
+activity_token_refreshed = Session refreshed
+activity_logout_succeeded = Signed out
+activity_ip = IP Address
+activity_location = Location
+activity_user_agent = Device
+activity_anomalies = Anomalies
+activity_new_device = New device
+activity_new_country = New country
+activity_impossible_travel = Impossible travel
+activity_occurred_at = Occurred At
+my_tasks = My Tasks
+task_title = Task
+task_status = Status
+task_progress = Progress
+task_message = Message
+task_done_at = Finished At
+task_pending = Pending
+task_running = Running
+task_done = Done
+task_failed = Failed
+task_killed = Killed
+task_cancel = Cancel
+task_cancelled = Cancelled
+task_download = Download
+are_you_sure_to_cancel_task = Are you sure to cancel this task?
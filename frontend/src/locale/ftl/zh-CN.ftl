@@ -12,6 +12,11 @@ delete_selected_items = 删除选中项
 are_you_sure_to_delete = 确定要删除吗？
 are_you_sure_to_batch_delete = 确定要批量删除吗？
 change_password = 修改密码
+account_activity = 账号动态
+storage_usage = 存储用量
+storage_used = 已用空间
+storage_quota = 空间配额
+storage_unlimited = 不限
 created_at = 创建时间
 updated_at = 更新时间
 query = 查询
@@ -48,4 +53,32 @@ sign_in_captcha = 验证码
 sign_in_please_enter_captcha = 请输入验证码
 sign_in_now = 登录
 sign_in_submitting = 登录中...
-sign_in_success_after_redirect = 登录成功，正在重定向...
\ No newline at end of file
+sign_in_success_after_redirect = 登录成功，正在重定向...
+activity_kind = 动态
+activity_login_succeeded = 登录成功
+activity_login_failed = 登录失败
+activity_token_refreshed = 刷新会话
+activity_logout_succeeded = 退出登录
+activity_ip = IP地址
+activity_location = 位置
+activity_user_agent = 设备
+activity_anomalies = 异常
+activity_new_device = 新设备
+activity_new_country = 新国家/地区
+activity_impossible_travel = 异地登录
+activity_occurred_at = 发生时间
+my_tasks = 我的任务
+task_title = 任务
+task_status = 状态
+task_progress = 进度
+task_message = 信息
+task_done_at = 完成时间
+task_pending = 排队中
+task_running = 执行中
+task_done = 已完成
+task_failed = 已失败
+task_killed = 已终止
+task_cancel = 取消
+task_cancelled = 已取消
+task_download = 下载
+are_you_sure_to_cancel_task = 确定取消该任务吗？
@@ -243,7 +243,7 @@ const schema = {
         defaultAvatar: logoUrl,
         shape: "rounded",
         onError: "return true;",
-        src: "${portrait}",
+        src: "${portrait_thumb}",
       },
       {
         name: "account",
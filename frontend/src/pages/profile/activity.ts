@@ -0,0 +1,64 @@
+import { buildCrudTable } from "../../lib/table";
+
+export { };
+
+const kindOptions = [
+  { label: _t('activity_login_succeeded'), value: "login_succeeded" },
+  { label: _t('activity_login_failed'), value: "login_failed" },
+  { label: _t('activity_token_refreshed'), value: "token_refreshed" },
+  { label: _t('activity_logout_succeeded'), value: "logout_succeeded" },
+];
+
+const schema = {
+  type: "page",
+  body: buildCrudTable({
+    endpoint: "/profile/activities",
+    filters: [
+      {
+        type: "select",
+        name: "kind",
+        label: _t('activity_kind'),
+        clearable: true,
+        options: kindOptions,
+      },
+    ],
+    headerToolbar: [],
+    bulkActions: [],
+    operations: [],
+    deletable: false,
+    showCreatedAt: false,
+    showUpdatedAt: false,
+    columns: [
+      {
+        name: "kind",
+        label: _t('activity_kind'),
+        type: "mapping",
+        map: Object.fromEntries(kindOptions.map(({ label, value }) => [value, label])),
+      },
+      {
+        name: "ip",
+        label: _t('activity_ip'),
+      },
+      {
+        label: _t('activity_location'),
+        value: "${[country, region, city] | filter | join: ' '}",
+      },
+      {
+        name: "user_agent",
+        label: _t('activity_user_agent'),
+        type: "tpl",
+        tpl: "${user_agent | truncate: 40}",
+      },
+      {
+        label: _t('activity_anomalies'),
+        value: `\${[new_device ? '${_t('activity_new_device')}' : '', new_country ? '${_t('activity_new_country')}' : '', impossible_travel ? '${_t('activity_impossible_travel')}' : ''] | filter | join: ', '}`,
+      },
+      {
+        name: "occurred_at",
+        label: _t('activity_occurred_at'),
+        type: "datetime",
+      },
+    ],
+  }),
+};
+window._j && window._j(schema);
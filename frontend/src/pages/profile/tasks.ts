@@ -0,0 +1,92 @@
+import { buildCrudTable } from "../../lib/table";
+
+export { };
+
+const endpoint = "/tasks";
+
+const statusOptions = [
+  { label: _t('task_pending'), value: "Pending" },
+  { label: _t('task_running'), value: "Running" },
+  { label: _t('task_done'), value: "Done" },
+  { label: _t('task_failed'), value: "Failed" },
+  { label: _t('task_killed'), value: "Killed" },
+];
+
+const schema = {
+  type: "page",
+  body: {
+    ...buildCrudTable({
+      endpoint,
+      filters: [],
+      headerToolbar: [],
+      bulkActions: [],
+      operations: [
+        {
+          type: "button",
+          icon: "fas fa-ban",
+          tooltip: _t('task_cancel'),
+          level: "link",
+          actionType: "ajax",
+          api: {
+            method: "post",
+            url: `${endpoint}/$id/cancel`,
+          },
+          confirmText: _t('are_you_sure_to_cancel_task'),
+          visibleOn: "(status === 'Pending' || status === 'Running') && !cancel_requested",
+        },
+        {
+          type: "button",
+          icon: "fas fa-download",
+          tooltip: _t('task_download'),
+          level: "link",
+          actionType: "ajax",
+          api: `${endpoint}/$id/download`,
+          redirect: "${url}",
+          visibleOn: "status === 'Done' && result && result.path",
+        },
+      ],
+      deletable: false,
+      showCreatedAt: false,
+      showUpdatedAt: false,
+      columns: [
+        {
+          name: "title",
+          label: _t('task_title'),
+          placeholder: "${job_type}",
+        },
+        {
+          name: "status",
+          label: _t('task_status'),
+          type: "mapping",
+          map: Object.fromEntries(statusOptions.map(({ label, value }) => [value, label])),
+        },
+        {
+          name: "percent",
+          label: _t('task_progress'),
+          type: "progress",
+          value: "${status === 'Done' ? 100 : percent}",
+        },
+        {
+          name: "message",
+          label: _t('task_message'),
+          type: "tpl",
+          tpl: "${cancel_requested && status !== 'Done' ? '" + _t('task_cancelled') + "' : (last_error || message || '-')}",
+        },
+        {
+          name: "created_at",
+          label: _t('created_at'),
+          type: "datetime",
+        },
+        {
+          name: "done_at",
+          label: _t('task_done_at'),
+          type: "datetime",
+          placeholder: "-",
+        },
+      ],
+    }),
+    interval: 3000,
+    silentPolling: true,
+  },
+};
+window._j && window._j(schema);
@@ -4,6 +4,49 @@ export { };
 
 const endpoint = "/system/access_logs";
 
+const buildDetailDrawer = () => {
+  return {
+    icon: "fas fa-file-lines",
+    align: "right",
+    tooltip: "请求详情",
+    level: "link",
+    actionType: "drawer",
+    drawer: {
+      title: "${method} ${uri | raw}",
+      size: "lg",
+      body: {
+        type: "service",
+        api: `${endpoint}/$id`,
+        body: [
+          {
+            type: "static",
+            name: "request_body",
+            label: "请求内容",
+            tpl: "${request_body ? '' : '-'}",
+          },
+          {
+            type: "pre-code",
+            name: "request_body",
+            visibleOn: "this.request_body",
+          },
+          {
+            type: "static",
+            name: "response_body",
+            label: "响应内容",
+            tpl: "${response_body ? '' : '-'}",
+          },
+          {
+            type: "pre-code",
+            name: "response_body",
+            visibleOn: "this.response_body",
+          },
+        ],
+      },
+      actions: [],
+    },
+  };
+};
+
 const schema = {
   type: "page",
   body: buildCrudTable({
@@ -11,11 +54,47 @@ const schema = {
     filters: [
 
     ],
-    headerToolbar: [],
+    headerToolbar: [
+      {
+        type: "button",
+        label: "导出",
+        icon: "fas fa-file-export",
+        actionType: "dialog",
+        dialog: {
+          title: "导出访问日志",
+          body: {
+            type: "form",
+            api: {
+              method: "post",
+              url: `${endpoint}/export`,
+            },
+            redirect: "/profile/tasks",
+            body: [
+              {
+                type: "input-datetime",
+                name: "from",
+                label: "开始时间",
+                format: "YYYY-MM-DDTHH:mm:ss",
+                clearable: true,
+              },
+              {
+                type: "input-datetime",
+                name: "to",
+                label: "结束时间",
+                format: "YYYY-MM-DDTHH:mm:ss",
+                clearable: true,
+              },
+            ],
+          },
+        },
+      },
+    ],
     bulkActions: [
 
     ],
-    operations: [],
+    operations: [
+      buildDetailDrawer(),
+    ],
     deletable: false,
     showCreatedAt: false,
     showUpdatedAt: false,
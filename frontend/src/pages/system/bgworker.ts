@@ -1,35 +1,135 @@
+import { ifElementAuthorized } from "../../lib/auth";
+import { PERMISSIONS } from "../../lib/permissions";
+
 export { };
 
 const endpoint = "/system/bgworkers";
 
+const retryable = (status: string) => status === "Failed" || status === "Killed";
+
 const buildJobList = (status: string) => {
+  const target = "tabs,pending,running,done,failed,killed";
   return {
     type: "crud",
     id: status.toLowerCase(),
     api: `${endpoint}/$name/jobs/${status}/$page`,
+    primaryField: "id",
+    bulkActions: [
+      ...(retryable(status) ? ifElementAuthorized(PERMISSIONS.SYSTEM.BGWORKER.RETRY, {
+        label: "重试",
+        icon: "fas fa-rotate-right",
+        level: "primary",
+        actionType: "ajax",
+        api: {
+          method: "post",
+          url: `${endpoint}/$name/jobs/batch/retry`,
+          data: {
+            ids: "${ids | split}",
+          },
+        },
+        reload: target,
+      }) : []),
+      ...(status !== "Running" ? ifElementAuthorized(PERMISSIONS.SYSTEM.BGWORKER.DELETE, {
+        label: "删除",
+        icon: "fas fa-trash",
+        level: "danger",
+        actionType: "ajax",
+        api: {
+          method: "post",
+          url: `${endpoint}/$name/jobs/batch/delete`,
+          data: {
+            ids: "${ids | split}",
+          },
+        },
+        confirmText: "确定删除选中的任务吗？",
+        reload: target,
+      }) : []),
+    ],
+    headerToolbar: [
+      "bulkActions",
+      ...(retryable(status) ? ifElementAuthorized(PERMISSIONS.SYSTEM.BGWORKER.RETRY, {
+        type: "button",
+        label: "全部重试",
+        icon: "fas fa-rotate",
+        actionType: "ajax",
+        api: {
+          method: "post",
+          url: `${endpoint}/$name/retry`,
+        },
+        confirmText: "确定重试该队列中所有失败和已放弃的任务吗？",
+        reload: target,
+      }) : []),
+    ],
     columns: [
       {
         label: "参数",
-        value: "${args | json}"
+        value: "${payload | json}"
       },
       {
-        label: "状态",
-        name: "parts.context.status"
+        label: "尝试次数",
+        value: "${attempts} / ${max_attempts}"
       },
       {
-        label: "输出",
-        name: "parts.context.last_error"
+        label: "错误",
+        name: "last_error",
+        placeholder: "-"
       },
       {
         label: "开始时间",
         type: "datetime",
-        name: "parts.context.run_at"
+        name: "run_at",
+        placeholder: "-"
+      },
+      {
+        label: "锁定时间",
+        type: "datetime",
+        name: "lock_at",
+        placeholder: "-"
       },
       {
         label: "结束时间",
         type: "datetime",
-        name: "parts.context.done_at"
+        name: "done_at",
+        placeholder: "-"
       },
+      ...(status !== "Running" ? ifElementAuthorized(PERMISSIONS.SYSTEM.BGWORKER.UPDATE, {
+        type: "operation",
+        label: "操作",
+        buttons: [
+          {
+            type: "button",
+            label: "编辑参数",
+            level: "link",
+            actionType: "dialog",
+            dialog: {
+              title: "编辑参数",
+              size: "lg",
+              body: {
+                type: "form",
+                api: {
+                  method: "put",
+                  url: `${endpoint}/$name/job/$id/payload`,
+                  data: {
+                    payload: "${payload | toJson}",
+                  },
+                },
+                data: {
+                  payload: "${payload | json}",
+                },
+                body: [
+                  {
+                    type: "editor",
+                    language: "json",
+                    name: "payload",
+                    required: true,
+                  },
+                ],
+              },
+            },
+            reload: target,
+          },
+        ],
+      }) : []),
     ]
   };
 };
@@ -86,8 +186,116 @@ const builderWorkerDrawer = () => {
   }
 };
 
+const buildWorkflowDrawer = () => {
+  return {
+    title: "工作流",
+    size: "xl",
+    body: {
+      type: "crud",
+      api: `${endpoint}/workflows/$page`,
+      primaryField: "id",
+      headerToolbar: ["reload"],
+      columns: [
+        {
+          label: "名称",
+          name: "name"
+        },
+        {
+          label: "状态",
+          name: "status"
+        },
+        {
+          label: "进度",
+          value: "${stage} / ${stage_count}"
+        },
+        {
+          label: "失败策略",
+          name: "on_failure"
+        },
+        {
+          label: "错误",
+          name: "error",
+          placeholder: "-"
+        },
+        {
+          label: "创建时间",
+          type: "datetime",
+          name: "created_at"
+        },
+        {
+          label: "更新时间",
+          type: "datetime",
+          name: "updated_at"
+        },
+        {
+          type: "operation",
+          label: "操作",
+          buttons: [
+            {
+              type: "button",
+              label: "查看任务",
+              level: "link",
+              actionType: "dialog",
+              dialog: {
+                title: "${name}",
+                size: "xl",
+                actions: [],
+                body: {
+                  type: "service",
+                  api: `${endpoint}/workflow/$id`,
+                  body: {
+                    type: "table",
+                    source: "$jobs",
+                    columns: [
+                      {
+                        label: "阶段",
+                        value: "${stage + 1}.${idx + 1}"
+                      },
+                      {
+                        label: "队列",
+                        name: "job_type"
+                      },
+                      {
+                        label: "任务",
+                        name: "job_id"
+                      },
+                      {
+                        label: "状态",
+                        value: "${status} (${job_status})"
+                      },
+                      {
+                        label: "错误",
+                        name: "last_error",
+                        placeholder: "-"
+                      },
+                      {
+                        label: "输出",
+                        value: "${output | json}"
+                      }
+                    ]
+                  }
+                }
+              }
+            }
+          ]
+        }
+      ]
+    },
+    actions: []
+  };
+};
+
 const schema = {
   type: "page",
+  toolbar: [
+    {
+      type: "button",
+      label: "工作流",
+      icon: "fas fa-diagram-project",
+      actionType: "drawer",
+      drawer: buildWorkflowDrawer()
+    }
+  ],
   body: {
     type: "service",
     api: endpoint,
@@ -1,3 +1,5 @@
+import { buildFilePicker } from "../../lib/file_picker";
+
 export {};
 
 const schema = {
@@ -14,7 +16,7 @@ const schema = {
       chunkApi: "/uploads/chunk",
       finishChunkApi: "/uploads/finish_chunk",
       maxSize: 200 * 1024 * 1024,
-      chunkSize: 2 * 1024 * 1024,
+      chunkSize: 8 * 1024 * 1024,
       useChunk: true,
       maxLength: 1,
       required: true,
@@ -38,6 +40,11 @@ const schema = {
       maxLength: 1,
       required: true,
     },
+    buildFilePicker({
+      name: "library_image",
+      label: "从文件库选择图片",
+      mimeType: "image/",
+    }),
   ],
 };
 window._j && window._j(schema);
@@ -6,12 +6,359 @@ import { buildCrudTable } from "../../lib/table";
 export { };
 
 const endpoint = "/system/files";
+const folderEndpoint = `${endpoint}/folders`;
+
+const shareEndpoint = `${endpoint}/shares`;
+const shareBase = `${window.location.origin}/s/`;
+
+const shareDialog = {
+  label: "分享链接",
+  icon: "fas fa-share-nodes",
+  type: "button",
+  actionType: "dialog",
+  dialog: {
+    title: "分享链接",
+    size: "xl",
+    actions: [],
+    body: {
+      ...buildCrudTable({
+        endpoint: shareEndpoint,
+        deletable: false,
+        showUpdatedAt: false,
+        itemCheckableOn: "!this.revoked",
+        filters: [
+          {
+            type: "input-text",
+            name: "file_name",
+            label: "文件名称",
+            placeholder: "请输入文件名称",
+            clearable: true,
+          },
+          {
+            type: "select",
+            name: "revoked",
+            label: "是否撤销",
+            placeholder: "请选择是否撤销",
+            clearable: true,
+            options: [
+              { label: "已撤销", value: true },
+              { label: "未撤销", value: false },
+            ],
+          },
+        ],
+        bulkActions: [
+          {
+            label: "撤销",
+            icon: "fas fa-ban",
+            level: "danger",
+            actionType: "ajax",
+            api: {
+              method: "post",
+              url: `${shareEndpoint}/batch/revoke`,
+              data: {
+                ids: "${ids | split}",
+              },
+            },
+            confirmText: "确定撤销选中的分享链接吗？",
+          },
+        ],
+        columns: [
+          {
+            name: "file_name",
+            label: "文件名称",
+            placeholder: "-",
+          },
+          {
+            type: "tpl",
+            name: "code",
+            label: "链接",
+            tpl: `${shareBase}\${code}`,
+            copyable: {
+              content: `${shareBase}\${code}`,
+            },
+          },
+          {
+            name: "has_password",
+            label: "密码",
+            type: "status",
+          },
+          {
+            name: "expires_at",
+            label: "过期时间",
+            type: "datetime",
+            placeholder: "永不过期",
+          },
+          {
+            type: "tpl",
+            label: "下载次数",
+            tpl: "${download_count} / ${max_downloads || '∞'}",
+          },
+          {
+            name: "last_downloaded_at",
+            label: "最后下载",
+            type: "datetime",
+            placeholder: "-",
+          },
+          {
+            name: "revoked",
+            label: "已撤销",
+            type: "status",
+          },
+          {
+            name: "creator_name",
+            label: "创建者",
+            placeholder: "-",
+          },
+        ],
+      }),
+      syncLocation: false,
+    },
+  },
+};
+
+const createShareDialog = {
+  icon: "fas fa-share-nodes",
+  tooltip: "分享",
+  type: "button",
+  align: "right",
+  actionType: "dialog",
+  level: "link",
+  dialog: {
+    title: "创建分享链接",
+    body: {
+      type: "form",
+      canAccessSuperData: false,
+      api: {
+        method: "post",
+        url: shareEndpoint,
+        data: {
+          file_id: "${id}",
+          expires_in: "${expires_in}",
+          password: "${password}",
+          max_downloads: "${max_downloads}",
+        },
+      },
+      data: {
+        id: "${id}",
+        expires_in: 7 * 24 * 3600,
+      },
+      body: [
+        {
+          type: "select",
+          name: "expires_in",
+          label: "有效期",
+          clearable: true,
+          placeholder: "永不过期",
+          clearValueOnEmpty: true,
+          options: [
+            { label: "1 小时", value: 3600 },
+            { label: "1 天", value: 24 * 3600 },
+            { label: "7 天", value: 7 * 24 * 3600 },
+            { label: "30 天", value: 30 * 24 * 3600 },
+          ],
+        },
+        {
+          type: "input-password",
+          name: "password",
+          label: "访问密码",
+          placeholder: "不填则无需密码",
+          clearValueOnEmpty: true,
+        },
+        {
+          type: "input-number",
+          name: "max_downloads",
+          label: "最大下载次数",
+          placeholder: "不填则不限次数",
+          min: 1,
+        },
+      ],
+    },
+  },
+};
+
+const folderSelect = (name: string, label: string) => ({
+  type: "tree-select",
+  name,
+  label,
+  source: folderEndpoint,
+  placeholder: "根目录",
+  clearable: true,
+  clearValueOnEmpty: true,
+  searchable: true,
+});
+
+const folderDialog = {
+  label: "目录管理",
+  icon: "fas fa-folder-tree",
+  type: "button",
+  actionType: "dialog",
+  dialog: {
+    title: "目录管理",
+    size: "md",
+    actions: [],
+    body: [
+      {
+        type: "input-tree",
+        name: "folder",
+        source: folderEndpoint,
+        heightAuto: true,
+        creatable: true,
+        removable: true,
+        editable: true,
+        showOutline: true,
+        addApi: `post:${folderEndpoint}`,
+        editApi: `put:${folderEndpoint}/\${id}`,
+        deleteApi: {
+          url: `${folderEndpoint}/batch/delete`,
+          method: "POST",
+          data: {
+            ids: ["\${id}"],
+          },
+        },
+        options: [],
+        addControls: [
+          {
+            label: "名称",
+            type: "input-text",
+            required: true,
+            name: "name",
+          },
+        ],
+        editControls: [
+          {
+            label: "ID",
+            type: "hidden",
+            required: true,
+            name: "id",
+          },
+          {
+            label: "名称",
+            type: "input-text",
+            required: true,
+            name: "name",
+            value: "${label}",
+          },
+        ],
+      },
+      {
+        type: "form",
+        title: "移动目录",
+        api: `put:${folderEndpoint}/\${id}/move`,
+        body: [
+          {
+            ...folderSelect("id", "目录"),
+            placeholder: "请选择要移动的目录",
+            clearable: false,
+            required: true,
+          },
+          folderSelect("parent_id", "移动到"),
+        ],
+      },
+    ],
+  },
+};
+
+const editDrawer = {
+  icon: "fas fa-edit",
+  tooltip: "编辑",
+  type: "button",
+  align: "right",
+  actionType: "drawer",
+  level: "link",
+  drawer: {
+    title: "编辑文件",
+    size: "md",
+    body: {
+      type: "form",
+      canAccessSuperData: false,
+      api: `put:${endpoint}/$id`,
+      data: {
+        id: "${id}",
+        name: "${name}",
+        description: "${description}",
+        tags: "${tags}",
+      },
+      body: [
+        {
+          type: "static-text",
+          name: "id",
+          label: "ID",
+          copyable: true,
+        },
+        {
+          type: "input-text",
+          name: "name",
+          label: "文件名称",
+          required: true,
+        },
+        {
+          type: "textarea",
+          name: "description",
+          label: "描述",
+        },
+        {
+          type: "input-tag",
+          name: "tags",
+          label: "标签",
+          joinValues: false,
+          extractValue: true,
+        },
+      ],
+    },
+  },
+};
+
+const usageDialog = {
+  label: "用户用量",
+  icon: "fas fa-chart-pie",
+  type: "button",
+  actionType: "dialog",
+  dialog: {
+    title: "用户存储用量",
+    size: "lg",
+    actions: [],
+    body: {
+      ...buildCrudTable({
+        endpoint: `${endpoint}/usages`,
+        deletable: false,
+        showCreatedAt: false,
+        showUpdatedAt: false,
+        filters: [
+          {
+            type: "input-text",
+            name: "uploader_name",
+            label: "上传者",
+            placeholder: "请输入上传者",
+            clearable: true,
+          },
+        ],
+        columns: [
+          {
+            name: "uploader_name",
+            label: "上传者",
+            placeholder: "-",
+          },
+          {
+            name: "file_count",
+            label: "文件数",
+          },
+          {
+            type: "pretty-bytes",
+            name: "size",
+            label: "已用空间",
+          },
+        ],
+      }),
+      syncLocation: false,
+    },
+  },
+};
 
 const schema = {
   type: "page",
   body: buildCrudTable({
     endpoint,
-    deletable: false,
+    deletable: _hasPermission(PERMISSIONS.SYSTEM.FILE.DELETE),
     filters: [
       {
         type: "input-text",
@@ -20,6 +367,55 @@ const schema = {
         placeholder: "请输入文件名称",
         clearable: true,
       },
+      folderSelect("folder_id", "目录"),
+      {
+        type: "input-text",
+        name: "tag",
+        label: "标签",
+        placeholder: "请输入标签",
+        clearable: true,
+      },
+      {
+        type: "select",
+        name: "mime_type",
+        label: "文件类型",
+        placeholder: "请选择文件类型",
+        clearable: true,
+        creatable: true,
+        options: [
+          { label: "图片", value: "image/" },
+          { label: "视频", value: "video/" },
+          { label: "音频", value: "audio/" },
+          { label: "文本", value: "text/" },
+          { label: "PDF", value: "application/pdf" },
+        ],
+      },
+      {
+        type: "input-text",
+        name: "uploader_name",
+        label: "上传者",
+        placeholder: "请输入上传者",
+        clearable: true,
+      },
+      {
+        type: "input-text",
+        name: "description",
+        label: "描述",
+        placeholder: "请输入描述",
+        clearable: true,
+      },
+      {
+        type: "input-number",
+        name: "min_width",
+        label: "最小宽度",
+        min: 1,
+      },
+      {
+        type: "input-number",
+        name: "min_height",
+        label: "最小高度",
+        min: 1,
+      },
       {
         type: "select",
         name: "used",
@@ -29,8 +425,72 @@ const schema = {
         options: usedStatuses,
       },
     ],
+    headerToolbar: [
+      usageDialog,
+      ...ifElementAuthorized(PERMISSIONS.SYSTEM.FILE.UPDATE, folderDialog),
+      ...ifElementAuthorized(PERMISSIONS.SYSTEM.FILE.SHARE, shareDialog),
+    ],
     bulkActions: [
-
+      ...ifElementAuthorized(PERMISSIONS.SYSTEM.FILE.UPDATE, {
+        label: "移动",
+        icon: "fas fa-folder-open",
+        actionType: "dialog",
+        dialog: {
+          title: "移动到目录",
+          body: {
+            type: "form",
+            api: {
+              method: "post",
+              url: `${endpoint}/batch/move`,
+              data: {
+                ids: "${ids | split}",
+                folder_id: "${folder_id}",
+              },
+            },
+            body: [folderSelect("folder_id", "目录")],
+          },
+        },
+      }),
+      ...ifElementAuthorized(PERMISSIONS.SYSTEM.FILE.UPDATE, {
+        label: "标签",
+        icon: "fas fa-tags",
+        actionType: "dialog",
+        dialog: {
+          title: "批量设置标签",
+          body: {
+            type: "form",
+            api: {
+              method: "post",
+              url: `${endpoint}/batch/tag`,
+              data: {
+                ids: "${ids | split}",
+                add: "${add}",
+                remove: "${remove}",
+              },
+            },
+            data: {
+              add: [],
+              remove: [],
+            },
+            body: [
+              {
+                type: "input-tag",
+                name: "add",
+                label: "添加标签",
+                joinValues: false,
+                extractValue: true,
+              },
+              {
+                type: "input-tag",
+                name: "remove",
+                label: "移除标签",
+                joinValues: false,
+                extractValue: true,
+              },
+            ],
+          },
+        },
+      }),
     ],
     showUpdatedAt: false,
     operations: [
@@ -44,22 +504,69 @@ const schema = {
         url: "/api/system/files/download/$path?access_token=" + getAccessToken(),
         blank: true,
       }),
-
+      ...ifElementAuthorized(PERMISSIONS.SYSTEM.FILE.UPDATE, editDrawer),
+      ...ifElementAuthorized(PERMISSIONS.SYSTEM.FILE.SHARE, createShareDialog),
     ],
     columns: [
       {
         name: "name",
         label: "文件名称",
       },
+      {
+        name: "folder_name",
+        label: "目录",
+        placeholder: "-",
+      },
+      {
+        name: "tags",
+        label: "标签",
+        type: "each",
+        placeholder: "-",
+        items: {
+          type: "tag",
+          label: "${item}",
+          displayMode: "rounded",
+        },
+      },
+      {
+        name: "mime_type",
+        label: "文件类型",
+        placeholder: "-",
+      },
+      {
+        type: "tpl",
+        label: "尺寸",
+        tpl: "${width ? width + ' × ' + height : '-'}",
+      },
       {
         type: "pretty-bytes",
         label: "文件大小",
         name: "size",
       },
       {
-        name: "used",
-        label: "使用中",
-        type: "status",
+        name: "reference_count",
+        label: "引用数",
+      },
+      {
+        name: "uploader_name",
+        label: "上传者",
+        placeholder: "-",
+      },
+      {
+        name: "scan_status",
+        label: "扫描状态",
+        type: "mapping",
+        map: {
+          unscanned: "<span class='label label-default'>未扫描</span>",
+          skipped: "<span class='label label-default'>未启用</span>",
+          clean: "<span class='label label-success'>安全</span>",
+          quarantined: "<span class='label label-danger'>已隔离</span>",
+        },
+      },
+      {
+        name: "scan_result",
+        label: "扫描结果",
+        placeholder: "-",
       },
       {
         name: "path",
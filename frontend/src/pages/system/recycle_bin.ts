@@ -0,0 +1,97 @@
+import { ifElementAuthorized } from "../../lib/auth";
+import { PERMISSIONS } from "../../lib/permissions";
+import { buildCrudTable } from "../../lib/table";
+
+export { };
+
+const endpoint = "/system/recycle_bin";
+
+const kindOptions = [
+  { label: "用户", value: "user" },
+  { label: "角色", value: "role" },
+  { label: "部门", value: "department" },
+  { label: "文件", value: "file" },
+];
+
+const schema = {
+  type: "page",
+  body: buildCrudTable({
+    endpoint,
+    filters: [
+      {
+        type: "select",
+        name: "kind",
+        label: "类型",
+        value: "user",
+        options: kindOptions,
+      },
+      {
+        type: "input-text",
+        name: "name",
+        label: "名称",
+        placeholder: "",
+        clearable: true,
+      },
+    ],
+    headerToolbar: [],
+    bulkActions: [
+      ...ifElementAuthorized(PERMISSIONS.SYSTEM.RECYCLE_BIN.RESTORE, {
+        label: "恢复",
+        icon: "fas fa-trash-arrow-up",
+        level: "primary",
+        actionType: "ajax",
+        api: {
+          method: "post",
+          url: `${endpoint}/batch/restore`,
+          data: {
+            kind: "${kind}",
+            ids: "${ids | split}",
+          },
+        },
+        confirmText: "确定恢复选中的项目吗？",
+      }),
+      ...ifElementAuthorized(PERMISSIONS.SYSTEM.RECYCLE_BIN.PURGE, {
+        label: "彻底删除",
+        icon: "fas fa-trash",
+        level: "danger",
+        actionType: "ajax",
+        api: {
+          method: "post",
+          url: `${endpoint}/batch/purge`,
+          data: {
+            kind: "${kind}",
+            ids: "${ids | split}",
+          },
+        },
+        confirmText: "彻底删除后无法恢复，确定继续吗？",
+      }),
+    ],
+    operations: [],
+    deletable: false,
+    showCreatedAt: false,
+    showUpdatedAt: false,
+    columns: [
+      {
+        name: "kind",
+        label: "类型",
+        type: "mapping",
+        map: Object.fromEntries(kindOptions.map(({ label, value }) => [value, label])),
+      },
+      {
+        name: "name",
+        label: "名称",
+      },
+      {
+        name: "detail",
+        label: "详情",
+        placeholder: "-",
+      },
+      {
+        name: "deleted_at",
+        label: "删除时间",
+        type: "datetime",
+      },
+    ],
+  }),
+};
+window._j && window._j(schema);
@@ -34,6 +34,20 @@ const buildRecordDrawer = () => {
             name: "result",
             label: "运行结果",
           },
+          {
+            name: "manual",
+            label: "手动运行",
+            type: "mapping",
+            map: {
+              true: "<span class='label label-info'>手动</span>",
+              false: "<span class='label label-default'>计划</span>",
+            },
+          },
+          {
+            name: "triggered_by_name",
+            label: "触发人",
+            placeholder: "-",
+          },
           {
             name: "run_at",
             label: "运行时间",
@@ -51,9 +65,161 @@ const buildRecordDrawer = () => {
   };
 };
 
+const buildRunButton = () => {
+  return {
+    icon: "fas fa-play",
+    align: "right",
+    tooltip: "立即运行",
+    level: "link",
+    actionType: "ajax",
+    confirmText: "确定立即运行 $name 吗？",
+    api: {
+      method: "post",
+      url: `${endpoint}/$key/run`,
+    },
+  };
+};
+
+const buildToggleButtons = () => {
+  return [
+    {
+      icon: "fas fa-pause",
+      align: "right",
+      tooltip: "暂停",
+      level: "link",
+      visibleOn: "${enabled}",
+      actionType: "ajax",
+      confirmText: "确定暂停 $name 吗？",
+      api: {
+        method: "post",
+        url: `${endpoint}/$key/pause`,
+      },
+    },
+    {
+      icon: "fas fa-circle-play",
+      align: "right",
+      tooltip: "恢复",
+      level: "link",
+      visibleOn: "${!enabled}",
+      actionType: "ajax",
+      api: {
+        method: "post",
+        url: `${endpoint}/$key/resume`,
+      },
+    },
+  ];
+};
+
+const buildNextRunsDialog = () => {
+  return {
+    icon: "fas fa-calendar-days",
+    align: "right",
+    tooltip: "未来运行时间",
+    level: "link",
+    actionType: "dialog",
+    dialog: {
+      title: "$name",
+      body: {
+        type: "service",
+        api: `${endpoint}/$key/next?count=10`,
+        body: {
+          type: "table",
+          source: "${items}",
+          placeholder: "任务已暂停",
+          columns: [
+            {
+              name: "run_at",
+              label: "运行时间",
+              type: "datetime",
+            },
+          ],
+        },
+      },
+      actions: [],
+    },
+  };
+};
+
+const buildEditDialog = () => {
+  return {
+    icon: "fas fa-pen-to-square",
+    align: "right",
+    tooltip: "编辑计划",
+    level: "link",
+    actionType: "dialog",
+    dialog: {
+      title: "$name",
+      size: "lg",
+      body: {
+        type: "form",
+        api: {
+          method: "put",
+          url: `${endpoint}/$key`,
+          data: {
+            key: "$key",
+            expr: "$expr",
+            timezone: "$timezone",
+            enabled: "$enabled",
+            params: "$params",
+          },
+        },
+        data: {
+          expr: "${expr === default_expr ? '' : expr}",
+        },
+        body: [
+          {
+            type: "input-text",
+            name: "expr",
+            label: "运行周期",
+            placeholder: "$default_expr",
+            description: "留空使用默认周期，如 at 01:01、every 30 minutes 或 cron 表达式",
+          },
+          {
+            type: "input-text",
+            name: "timezone",
+            label: "时区",
+            placeholder: "系统时区",
+            description: "如 Asia/Shanghai、UTC，留空使用系统时区",
+          },
+          {
+            type: "switch",
+            name: "enabled",
+            label: "启用",
+          },
+          {
+            type: "editor",
+            language: "json",
+            name: "params",
+            label: "运行参数",
+          },
+        ],
+      },
+    },
+  };
+};
+
+const buildLeaderPanel = () => {
+  return {
+    type: "service",
+    api: `${endpoint}/leader`,
+    interval: 15000,
+    silentPolling: true,
+    body: {
+      type: "tpl",
+      className: "block m-b-sm",
+      tpl: "<% if (data.holder) { %>"
+        + "<i class='fas fa-crown'></i> 当前主节点：<%= data.holder %>"
+        + "（令牌 <%= data.token %>，<%= data.acquired_at %> 起）"
+        + "<% if (data.alive) { %> <span class='label label-success'>在线</span>"
+        + "<% } else { %> <span class='label label-danger'>租约已过期</span><% } %>"
+        + "<% } else { %>暂无调度节点运行<% } %>",
+    },
+  };
+};
+
 const schema = {
   type: "page",
-  body: buildCrudTable({
+  body: [buildLeaderPanel(), buildCrudTable({
     endpoint,
     filters: [
 
@@ -63,6 +229,10 @@ const schema = {
 
     ],
     operations: [
+      ...ifElementAuthorized(PERMISSIONS.SYSTEM.SCHED.RUN, buildRunButton()),
+      ...ifElementAuthorized(PERMISSIONS.SYSTEM.SCHED.UPDATE, buildToggleButtons()),
+      ...ifElementAuthorized(PERMISSIONS.SYSTEM.SCHED.UPDATE, buildEditDialog()),
+      ...ifElementAuthorized(PERMISSIONS.SYSTEM.SCHED.READ, buildNextRunsDialog()),
       ...ifElementAuthorized(PERMISSIONS.SYSTEM.SCHED.READ, buildRecordDrawer()),
 
     ],
@@ -82,6 +252,16 @@ const schema = {
         name: "expr",
         label: "运行周期",
       },
+      {
+        name: "timezone",
+        label: "时区",
+        placeholder: "系统时区",
+      },
+      {
+        name: "enabled",
+        label: "启用",
+        type: "status",
+      },
       {
         name: "last_succeed",
         label: "上次运行状态",
@@ -107,6 +287,6 @@ const schema = {
         type: "datetime",
       },
     ],
-  }),
+  })],
 };
 window._j && window._j(schema);
\ No newline at end of file
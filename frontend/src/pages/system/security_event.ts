@@ -0,0 +1,97 @@
+import { buildCrudTable } from "../../lib/table";
+
+export { };
+
+const endpoint = "/system/security_events";
+
+const kindOptions = [
+  { label: "登录成功", value: "login_succeeded" },
+  { label: "登录失败", value: "login_failed" },
+  { label: "刷新令牌", value: "token_refreshed" },
+  { label: "退出登录", value: "logout_succeeded" },
+  { label: "下载分享文件", value: "file_share_downloaded" },
+];
+
+const schema = {
+  type: "page",
+  body: buildCrudTable({
+    endpoint,
+    filters: [
+      {
+        type: "input-text",
+        name: "account",
+        label: "登录账号",
+        placeholder: "",
+        clearable: true,
+      },
+      {
+        type: "select",
+        name: "kind",
+        label: "事件类型",
+        clearable: true,
+        options: kindOptions,
+      },
+      {
+        type: "select",
+        name: "anomalous",
+        label: "是否异常",
+        clearable: true,
+        options: [
+          { label: "是", value: true },
+          { label: "否", value: false },
+        ],
+      },
+    ],
+    headerToolbar: [],
+    bulkActions: [
+
+    ],
+    operations: [],
+    deletable: false,
+    showCreatedAt: false,
+    showUpdatedAt: false,
+    columns: [
+      {
+        "name": "user_name",
+        "label": "用户名称",
+      },
+      {
+        "name": "account",
+        "label": "登录账号",
+      },
+      {
+        "name": "kind",
+        "label": "事件类型",
+        "type": "mapping",
+        "map": Object.fromEntries(kindOptions.map(({ label, value }) => [value, label])),
+      },
+      {
+        "name": "succeed",
+        "label": "是否成功",
+        "type": "status",
+      },
+      {
+        "name": "reason",
+        "label": "失败原因",
+      },
+      {
+        "name": "ip",
+        "label": "IP地址",
+      },
+      {
+        "label": "位置",
+        "value": "${[country, region, city] | filter | join: ' '}",
+      },
+      {
+        "label": "异常",
+        "value": "${[new_device ? '新设备' : '', new_country ? '新国家/地区' : '', impossible_travel ? '异地登录' : ''] | filter | join: ', '}",
+      },
+      {
+        name: "occurred_at",
+        label: "发生时间",
+        type: "datetime",
+      }
+    ],
+  }),
+};
+window._j && window._j(schema);